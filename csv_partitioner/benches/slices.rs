@@ -0,0 +1,129 @@
+//! Loading and parsing a big vocabulary file, `cargo bench --features rayon`.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use csv::StringRecord;
+use csv_partitioner::{CsvSliceParser, EmptyPolicy, FromColumnSlice, ParseConfig, ParseError};
+
+/// rows in the generated file, enough that per-row work dominates
+const ROWS: usize = 100_000;
+
+/// slices (topics) side by side
+const SLICES: usize = 8;
+
+struct Word {
+    kana: String,
+    english: String,
+    kanji: String,
+}
+
+impl FromColumnSlice for Word {
+    const COLUMN_COUNT: usize = 3;
+
+    fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+        Ok(Word {
+            kana: record.get(start_col).unwrap_or("").to_string(),
+            english: record.get(start_col + 1).unwrap_or("").to_string(),
+            kanji: record.get(start_col + 2).unwrap_or("").to_string(),
+        })
+    }
+}
+
+/// a row that was kept, for timing everything but building the values
+struct Seen;
+
+impl FromColumnSlice for Seen {
+    const COLUMN_COUNT: usize = 3;
+
+    fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> {
+        Ok(Seen)
+    }
+}
+
+/// `SLICES` topics of kana, english, kanji, with topics running out at different rows
+/// like a real sheet, so most slices have a tail of empty rows to skip
+fn vocabulary() -> String {
+    let mut csv = (0..SLICES).map(|slice| format!("Topic {},,", slice)).collect::<Vec<_>>().join(",");
+    csv.push('\n');
+
+    for row in 0..ROWS {
+        let cells: Vec<String> = (0..SLICES)
+            .map(|slice| {
+                if row < ROWS / (slice + 1) && row % 7 != slice {
+                    format!("かな{row},english {row},漢字{row}")
+                } else {
+                    " , , ".to_string()
+                }
+            })
+            .collect();
+        csv.push_str(&cells.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+fn config(empty_row_policy: EmptyPolicy) -> ParseConfig {
+    ParseConfig { trim_fields: false, empty_row_policy, ..ParseConfig::default() }
+}
+
+fn load(c: &mut Criterion) {
+    let csv = vocabulary();
+    let mut group = c.benchmark_group("load");
+    group.throughput(Throughput::Bytes(csv.len() as u64));
+
+    group.bench_function("from_str", |b| {
+        b.iter(|| CsvSliceParser::from_str(black_box(&csv), ParseConfig::default()).unwrap())
+    });
+    group.finish();
+}
+
+fn parse(c: &mut Criterion) {
+    let csv = vocabulary();
+    let mut group = c.benchmark_group("parse");
+    group.throughput(Throughput::Elements((ROWS * SLICES) as u64));
+
+    for (name, policy) in [("all empty", EmptyPolicy::AllEmpty), ("any empty", EmptyPolicy::AnyEmpty)] {
+        let parser = CsvSliceParser::from_str(&csv, config(policy)).unwrap();
+
+        group.bench_function(format!("parse_slice each, {}", name), |b| {
+            b.iter(|| {
+                (0..parser.slice_count::<Word>())
+                    .map(|index| parser.parse_slice::<Word>(index).unwrap().len())
+                    .sum::<usize>()
+            })
+        });
+        group.bench_function(format!("parse_all_slices, {}", name), |b| {
+            b.iter(|| parser.parse_all_slices::<Word>().unwrap())
+        });
+        #[cfg(feature = "rayon")]
+        group.bench_function(format!("parse_all_slices_parallel, {}", name), |b| {
+            b.iter(|| parser.parse_all_slices_parallel::<Word>().unwrap())
+        });
+    }
+
+    // a type that costs nothing to build, leaving only the row skipping
+    let parser = CsvSliceParser::from_str(&csv, config(EmptyPolicy::AllEmpty)).unwrap();
+    group.bench_function("parse_slice each, skipping only", |b| {
+        b.iter(|| (0..SLICES).map(|index| parser.parse_slice::<Seen>(index).unwrap().len()).sum::<usize>())
+    });
+    group.bench_function("parse_all_slices, skipping only", |b| {
+        b.iter(|| parser.parse_all_slices::<Seen>().unwrap())
+    });
+
+    // the custom policy has no bitmap, as the baseline
+    let parser = CsvSliceParser::from_str(&csv, config(EmptyPolicy::Custom(|cells| cells.iter().all(|cell| cell.is_empty())))).unwrap();
+    group.bench_function("parse_all_slices, custom", |b| {
+        b.iter(|| parser.parse_all_slices::<Word>().unwrap())
+    });
+
+    // reading the words, so building them isn't optimised away
+    let words = parser.parse_all_slices::<Word>().unwrap();
+    black_box(words.iter().flatten().map(|word| word.kana.len() + word.english.len() + word.kanji.len()).sum::<usize>());
+
+    group.finish();
+}
+
+criterion_group!(benches, load, parse);
+criterion_main!(benches);
@@ -0,0 +1,67 @@
+//! The slice parser in a browser, without the file system.
+//!
+//! ```text
+//! cargo build --example wasm --target wasm32-unknown-unknown --no-default-features
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/debug/examples/wasm.wasm
+//! ```
+//!
+//! then from javascript:
+//!
+//! ```text
+//! import init, { Sheet } from "./pkg/wasm.js";
+//! await init();
+//! const sheet = new Sheet(await file.text());
+//! for (let i = 0; i < sheet.topicCount(); i++) console.log(sheet.topicName(i), sheet.words(i));
+//! ```
+
+use csv::StringRecord;
+use csv_partitioner::{sniff_format, CsvSliceParser, FromColumnSlice, ParseError, SNIFF_BYTES};
+use wasm_bindgen::prelude::*;
+
+/// kana, english, kanji, the layout csv-to-anki imports
+struct Word([String; 3]);
+
+impl FromColumnSlice for Word {
+    const COLUMN_COUNT: usize = 3;
+
+    fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+        let cell = |offset: usize| record.get(start_col + offset).unwrap_or("").to_string();
+        Ok(Word([cell(0), cell(1), cell(2)]))
+    }
+}
+
+/// A spreadsheet's text, split into its topics.
+#[wasm_bindgen]
+pub struct Sheet {
+    parser: CsvSliceParser,
+}
+
+#[wasm_bindgen]
+impl Sheet {
+    /// Read CSV or TSV text, the delimiter is sniffed.
+    #[wasm_bindgen(constructor)]
+    pub fn new(contents: &str) -> Result<Sheet, JsError> {
+        let sample = &contents.as_bytes()[..contents.len().min(SNIFF_BYTES)];
+        let parser = CsvSliceParser::from_str(contents, sniff_format(sample).config())?;
+
+        Ok(Sheet { parser })
+    }
+
+    #[wasm_bindgen(js_name = topicCount)]
+    pub fn topic_count(&self) -> usize {
+        self.parser.slice_count::<Word>()
+    }
+
+    #[wasm_bindgen(js_name = topicName)]
+    pub fn topic_name(&self, index: usize) -> Option<String> {
+        self.parser.slice_headers::<Word>(index).map(|headers| headers.first().to_string())
+    }
+
+    /// The topic's words, one `kana\tenglish\tkanji` string each.
+    pub fn words(&self, index: usize) -> Result<Vec<String>, JsError> {
+        let words = self.parser.parse_slice::<Word>(index)?;
+
+        Ok(words.into_iter().map(|Word(cells)| cells.join("\t")).collect())
+    }
+}
+
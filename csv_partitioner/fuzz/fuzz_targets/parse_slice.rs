@@ -0,0 +1,232 @@
+//! Feeds arbitrary bytes through `from_reader` + the slice parsing APIs.
+//!
+//! The first byte picks the slice index, the rest is treated as CSV data.
+//! Any error is fine, a panic is a bug.
+//!
+//! run with: `cargo fuzz run parse_slice` (from csv_partitioner/)
+
+#![no_main]
+
+use csv::StringRecord;
+use csv_partitioner::{ColumnMap, CsvSliceParser, CsvSliceStreamer, FromColumnSlice, FromColumnSliceRef, EmptyPolicy, InputEncoding, ParseConfig, ParseError, RaggedPolicy, RowSliceParser, SampleStrategy, SliceLayout, ToColumnSlice};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(PartialEq)]
+struct One(#[allow(dead_code)] String);
+
+impl FromColumnSlice for One {
+    const COLUMN_COUNT: usize = 1;
+
+    fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+        Ok(One(record.get(start_col).ok_or_else(|| ParseError::missing_field(start_col, "field"))?.to_string()))
+    }
+}
+
+impl ToColumnSlice for One {
+    fn to_fields(&self) -> Vec<String> {
+        vec![self.0.clone()]
+    }
+}
+
+#[derive(PartialEq)]
+struct Three(#[allow(dead_code)] Vec<String>);
+
+impl FromColumnSlice for Three {
+    const COLUMN_COUNT: usize = 3;
+
+    fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+        (start_col..start_col + 3)
+            .map(|i| record.get(i).map(str::to_string).ok_or_else(|| ParseError::missing_field(i, "field")))
+            .collect::<Result<_, _>>()
+            .map(Three)
+    }
+}
+
+impl ToColumnSlice for Three {
+    fn to_fields(&self) -> Vec<String> {
+        self.0.clone()
+    }
+}
+
+#[allow(dead_code)]
+enum Section {
+    One(Vec<One>),
+    Three(Vec<Three>),
+}
+
+impl From<Vec<One>> for Section {
+    fn from(rows: Vec<One>) -> Self {
+        Section::One(rows)
+    }
+}
+
+impl From<Vec<Three>> for Section {
+    fn from(rows: Vec<Three>) -> Self {
+        Section::Three(rows)
+    }
+}
+
+struct Borrowed<'a>(#[allow(dead_code)] &'a str);
+
+impl<'a> FromColumnSliceRef<'a> for Borrowed<'a> {
+    const COLUMN_COUNT: usize = 2;
+
+    fn from_record(record: &'a StringRecord, start_col: usize) -> Result<Self, ParseError> {
+        record.get(start_col + 1).map(Borrowed).ok_or_else(|| ParseError::missing_field(start_col + 1, "field"))
+    }
+}
+
+/// parse a slice, write it back with one row more and one row fewer, and serialise the result
+fn exercise_write<T: ToColumnSlice>(csv_bytes: &[u8], config: impl Fn() -> ParseConfig, slice_index: usize) {
+    let Ok(mut parser) = CsvSliceParser::from_reader(csv_bytes, config()) else {
+        return;
+    };
+    let Ok(mut values) = parser.parse_slice::<T>(slice_index) else {
+        return;
+    };
+
+    if let Some(first) = values.first().map(|value| T::from_record(&value.to_fields().into_iter().collect(), 0)) {
+        values.extend(first.ok());
+    }
+    let _ = parser.write_slice(slice_index, &values);
+    values.truncate(values.len().saturating_sub(2));
+    let _ = parser.write_slice(slice_index, &values);
+
+    let _ = parser.write_to(Vec::new());
+}
+
+fn exercise<T: FromColumnSlice + PartialEq>(parser: &CsvSliceParser, slice_index: usize) {
+    let _ = parser.slice_count::<T>();
+    let _ = parser.slices::<T>();
+    let _ = parser.infer_schema::<T>();
+    let _ = parser.validate_headers::<T>(&vec!["*"; T::COLUMN_COUNT]);
+
+    for index in [slice_index, usize::MAX, usize::MAX / T::COLUMN_COUNT] {
+        let _ = parser.parse_slice::<T>(index);
+        let _ = parser.parse_slice_lenient::<T>(index);
+        let _ = parser.slice_headers::<T>(index);
+        let _ = parser.parse_row::<T>(index, slice_index);
+        let _ = parser.parse_rows::<T>(index, slice_index / 2..slice_index);
+        let _ = parser.parse_slice_filtered::<T>(index, |_| slice_index % 2 == 0);
+        let _ = parser.query::<T>(index).filter(|_| true).map(|_| ()).take(slice_index).run();
+
+        // a single key pairs every row up with the row in the same position
+        let _ = parser.diff_slice::<T, _>(parser, index, |_| ());
+
+        if let Ok(iter) = parser.parse_slice_iter::<T>(index) {
+            iter.for_each(drop);
+        }
+
+        for chunk_size in [0, 1, slice_index, usize::MAX] {
+            if let Ok(chunks) = parser.parse_slice_chunks::<T>(index, chunk_size) {
+                chunks.for_each(drop);
+            }
+        }
+        let _ = parser.slice_stats::<T>(index);
+        let _ = parser.parse_row_across_slices::<T>(index);
+
+        for strategy in [SampleStrategy::Head, SampleStrategy::Random, SampleStrategy::Stride] {
+            for n in [0, 1, slice_index, usize::MAX] {
+                let _ = parser.sample_slice::<T>(index, n, strategy);
+            }
+        }
+    }
+
+    let _ = parser.parse_all_slices::<T>();
+    parser.parse_all_slices_iter::<T>().for_each(|rows| rows.for_each(drop));
+    parser.rows_across_slices::<T>().for_each(drop);
+}
+
+fn exercise_streaming<T: FromColumnSlice>(csv_bytes: &[u8], config: impl Fn() -> ParseConfig, slice_index: usize) {
+    if let Ok(streamer) = CsvSliceStreamer::from_reader(csv_bytes, config()) {
+        streamer.rows::<T>().for_each(drop);
+    }
+
+    if let Ok(streamer) = CsvSliceStreamer::from_reader(csv_bytes, config())
+        && let Ok(iter) = streamer.slice::<T>(slice_index)
+    {
+        iter.for_each(drop);
+    }
+}
+
+fn exercise_rows<T: FromColumnSlice>(csv_bytes: &[u8], config: ParseConfig) {
+    let Ok(parser) = RowSliceParser::from_reader(csv_bytes, config) else {
+        return;
+    };
+
+    for block in parser.blocks_by_marker("#") {
+        let _ = parser.parse_block::<T>(&block);
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&slice_index, csv_bytes)) = data.split_first() else {
+        return;
+    };
+
+    // the low bits pick a slice gap, including one big enough to overflow the column arithmetic
+    let slice_gap = match slice_index % 4 {
+        3 => usize::MAX,
+        gap => gap as usize,
+    };
+
+    // the next bit decides whether the first row is headers
+    let has_headers = slice_index & 4 == 0;
+
+    // decoding has to cope with truncated and invalid sequences
+    for encoding in [InputEncoding::Utf8Bom, InputEncoding::ShiftJis, InputEncoding::Utf16Le] {
+        let config = || ParseConfig { encoding, has_headers, ..ParseConfig::default() };
+
+        exercise_streaming::<One>(csv_bytes, config, slice_index as usize);
+        if let Ok(parser) = CsvSliceParser::from_reader(csv_bytes, config()) {
+            exercise::<Three>(&parser, slice_index as usize);
+        }
+    }
+
+    // padding and skipping have to agree with the slice bounds on rows of any length
+    for ragged_rows in [RaggedPolicy::PadEmpty, RaggedPolicy::Skip] {
+        let empty_row_policy = if has_headers { EmptyPolicy::AnyEmpty } else { EmptyPolicy::Custom(|cells| cells.len() % 2 == 0) };
+        let config = || ParseConfig { ragged_rows, empty_row_policy, slice_gap, has_headers, ..ParseConfig::default() };
+
+        exercise_streaming::<Three>(csv_bytes, config, slice_index as usize);
+        exercise_write::<Three>(csv_bytes, config, slice_index as usize);
+        exercise_rows::<Three>(csv_bytes, config());
+        if let Ok(parser) = CsvSliceParser::from_reader(csv_bytes, config()) {
+            exercise::<One>(&parser, slice_index as usize);
+            exercise::<Three>(&parser, slice_index as usize);
+
+            if let Ok(parser) = parser.with_column_map(&ColumnMap::reorder([2, 0, 1]).rename(1, "renamed")) {
+                exercise::<Three>(&parser, slice_index as usize);
+            }
+        }
+    }
+
+    for skip_empty_rows in [true, false] {
+        let config = || ParseConfig { skip_empty_rows, slice_gap, has_headers, ..ParseConfig::default() };
+
+        exercise_streaming::<One>(csv_bytes, config, slice_index as usize);
+        exercise_streaming::<Three>(csv_bytes, config, slice_index as usize);
+        exercise_write::<One>(csv_bytes, config, slice_index as usize);
+        exercise_write::<Three>(csv_bytes, config, slice_index as usize);
+        exercise_rows::<One>(csv_bytes, config());
+        exercise_rows::<Three>(csv_bytes, config());
+
+        let Ok(parser) = CsvSliceParser::from_reader(csv_bytes, config()) else {
+            return;
+        };
+
+        exercise::<One>(&parser, slice_index as usize);
+        exercise::<Three>(&parser, slice_index as usize);
+
+        for index in [slice_index as usize, usize::MAX, usize::MAX / 2] {
+            let _ = parser.parse_slice_ref::<Borrowed>(index);
+        }
+
+        let layout = SliceLayout::<Section>::new()
+            .slice::<Three>()
+            .slice_with_width::<One>(slice_index as usize % 4)
+            .slices::<One>(slice_index as usize % 3)
+            .slice_with_width::<Three>(usize::MAX);
+        let _ = parser.parse_layout(&layout);
+    }
+});
@@ -0,0 +1,109 @@
+//! Checks the slice arithmetic on arbitrary CSV: slices stay inside the headers, `from_record`
+//! is only called at a slice's first column, and skipped short rows never reach it.
+//!
+//! The first byte picks the config, the rest is treated as CSV data. Unlike the `parse_slice`
+//! target, which only looks for panics, this one asserts what the results have to be.
+//!
+//! run with: `cargo fuzz run slice_bounds` (from csv_partitioner/)
+
+#![no_main]
+
+use csv::StringRecord;
+use csv_partitioner::{CsvSliceParser, CsvSliceStreamer, EmptyPolicy, FromColumnSlice, ParseConfig, ParseError, RaggedPolicy};
+use libfuzzer_sys::fuzz_target;
+
+/// where `from_record` was called and how long the row was
+struct Spy<const W: usize, const MIN: usize> {
+    start_col: usize,
+    row_len: usize,
+}
+
+impl<const W: usize, const MIN: usize> FromColumnSlice for Spy<W, MIN> {
+    const COLUMN_COUNT: usize = W;
+    const MIN_COLUMN_COUNT: usize = MIN;
+
+    fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+        Ok(Spy { start_col, row_len: record.len() })
+    }
+}
+
+/// `slice_gap` and `skip_short` (`RaggedPolicy::Skip`) repeat the parser's config, it isn't public
+fn check<const W: usize, const MIN: usize>(parser: &CsvSliceParser, slice_gap: usize, skip_short: bool) {
+    let header_len = parser.headers().len();
+    let stride = W + slice_gap;
+    let count = parser.slice_count::<Spy<W, MIN>>();
+
+    let mut all_columns = Vec::with_capacity(count);
+    for index in 0..count {
+        let columns = parser.slice_columns::<Spy<W, MIN>>(index).expect("slices below slice_count exist");
+        assert_eq!(columns.start, index * stride, "slice {} starts off its stride", index);
+        assert!(columns.end <= header_len, "slice {} ends past the headers", index);
+        assert!((MIN..=W).contains(&columns.len()), "slice {} is {} columns wide", index, columns.len());
+        all_columns.push(columns);
+    }
+    assert!(parser.slice_columns::<Spy<W, MIN>>(count).is_none(), "a slice past slice_count exists");
+    assert!(count.saturating_mul(stride).saturating_add(MIN) > header_len, "another slice would fit");
+
+    for (index, columns) in all_columns.iter().enumerate() {
+        let Ok(values) = parser.parse_slice::<Spy<W, MIN>>(index) else {
+            continue;
+        };
+        for value in values {
+            assert_eq!(value.start_col, columns.start, "slice {} parsed at column {}", index, value.start_col);
+            assert!(!skip_short || value.row_len >= columns.end, "a short row reached slice {}", index);
+        }
+    }
+
+    if let Ok(slices) = parser.parse_all_slices::<Spy<W, MIN>>() {
+        assert_eq!(slices.len(), count);
+        for (values, columns) in slices.iter().zip(&all_columns) {
+            assert!(values.iter().all(|value| value.start_col == columns.start));
+        }
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&knobs, csv_bytes)) = data.split_first() else {
+        return;
+    };
+
+    let slice_gap = (knobs % 3) as usize;
+    let ragged_rows = match knobs / 3 % 3 {
+        0 => RaggedPolicy::Error,
+        1 => RaggedPolicy::PadEmpty,
+        _ => RaggedPolicy::Skip,
+    };
+    let empty_row_policy = if knobs & 16 == 0 { EmptyPolicy::AllEmpty } else { EmptyPolicy::AnyEmpty };
+    let header_row = (knobs >> 5 & 1) as usize;
+    let header_rows = (knobs >> 6) as usize;
+
+    let config = || ParseConfig {
+        slice_gap,
+        ragged_rows,
+        empty_row_policy,
+        header_row,
+        header_rows,
+        flexible: true,
+        ..ParseConfig::default()
+    };
+
+    let skip_short = ragged_rows == RaggedPolicy::Skip;
+
+    if let Ok(parser) = CsvSliceParser::from_reader(csv_bytes, config()) {
+        check::<1, 1>(&parser, slice_gap, skip_short);
+        check::<2, 2>(&parser, slice_gap, skip_short);
+        check::<3, 1>(&parser, slice_gap, skip_short);
+        check::<4, 3>(&parser, slice_gap, skip_short);
+    }
+
+    // streaming has to hand out the same slices
+    if let Ok(streamer) = CsvSliceStreamer::from_reader(csv_bytes, config()) {
+        let count = streamer.slice_count::<Spy<3, 1>>();
+        for row in streamer.rows::<Spy<3, 1>>().flatten() {
+            for (index, value) in row {
+                assert!(index < count);
+                assert_eq!(value.start_col, index * (3 + slice_gap));
+            }
+        }
+    }
+});
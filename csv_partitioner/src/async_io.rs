@@ -0,0 +1,118 @@
+//! Loading without blocking an async runtime, with the `async` feature.
+
+use std::path::Path;
+
+use csv::StringRecord;
+use csv_async::AsyncReaderBuilder;
+use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::{split_top_rows, CsvSliceParser, ParseConfig, ParseError, ProgressEvent, PROGRESS_INTERVAL};
+
+impl CsvSliceParser {
+    /// Load a CSV file with a default configuration, without blocking the runtime.
+    ///
+    /// Requires the `async` feature.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use csv_partitioner::CsvSliceParser;
+    /// # async fn example() -> Result<(), csv_partitioner::ParseError> {
+    /// let parser = CsvSliceParser::from_file_async("vocabulary.csv").await?;
+    /// println!("{} rows", parser.record_count());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn from_file_async<P: AsRef<Path>>(path: P) -> Result<Self, ParseError> {
+        Self::from_file_with_config_async(path, ParseConfig::default()).await
+    }
+
+    /// Load a CSV file with custom configuration, without blocking the runtime.
+    ///
+    /// Requires the `async` feature.
+    pub async fn from_file_with_config_async<P: AsRef<Path>>(path: P, config: ParseConfig) -> Result<Self, ParseError> {
+        Self::from_reader_async(File::open(path).await?, config).await
+    }
+
+    /// Load CSV data from any tokio reader, e.g. a request body, without blocking the runtime.
+    ///
+    /// Reads the same records as [`from_reader`](Self::from_reader) with the same config.
+    /// Encodings other than UTF-8 are read in full first and then decoded, decoding doesn't wait
+    /// on anything. Requires the `async` feature.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, ParseConfig};
+    /// # async fn example() -> Result<(), csv_partitioner::ParseError> {
+    /// let body: &[u8] = b"Food,Verbs\nrice,to eat\n";
+    /// let parser = CsvSliceParser::from_reader_async(body, ParseConfig::default()).await?;
+    ///
+    /// assert_eq!(parser.record_count(), 1);
+    /// # Ok(())
+    /// # }
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(example()).unwrap();
+    /// ```
+    pub async fn from_reader_async<R: AsyncRead + Unpin + Send>(
+        mut reader: R,
+        config: ParseConfig,
+    ) -> Result<Self, ParseError> {
+        if !config.encoding.is_utf8() {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            return Self::from_reader(bytes.as_slice(), config);
+        }
+
+        let mut reader = AsyncReaderBuilder::new()
+            .delimiter(config.delimiter)
+            .quote(config.quote)
+            .comment(config.comment)
+            .flexible(config.is_flexible())
+            .trim(if config.trim_fields { csv_async::Trim::All } else { csv_async::Trim::None })
+            .has_headers(false)
+            .create_reader(reader);
+
+        let mut record = csv_async::StringRecord::new();
+        let mut top_rows: Vec<StringRecord> = Vec::new();
+        let mut headers: Option<StringRecord> = None;
+        let mut records: Vec<StringRecord> = Vec::new();
+
+        while reader.read_record(&mut record).await? {
+            let mut converted = if headers.is_none() && top_rows.is_empty() { without_bom(&record) } else { record.iter().collect() };
+            if headers.is_none() && top_rows.len() < config.rows_above_data() {
+                top_rows.push(converted);
+                continue;
+            }
+
+            let headers = headers.get_or_insert_with(|| {
+                let (above, headers) = split_top_rows(&config, std::mem::take(&mut top_rows), Some(&converted));
+                top_rows = above;
+                headers
+            });
+            config.prepare(&mut converted, headers);
+            records.push(converted);
+
+            if records.len().is_multiple_of(PROGRESS_INTERVAL) {
+                config.report(|| ProgressEvent::Loading { records: records.len(), bytes: reader.position().byte() });
+            }
+        }
+        config.report(|| ProgressEvent::Loaded { records: records.len() });
+
+        let (top_rows, headers) = match headers {
+            Some(headers) => (top_rows, headers),
+            None => split_top_rows(&config, top_rows, None),
+        };
+        config.check_slice_gap(&headers)?;
+
+        Ok(CsvSliceParser { headers, records, config, top_rows })
+    }
+}
+
+/// `record` as a `csv` record, without the byte order mark the sync reader drops too
+fn without_bom(record: &csv_async::StringRecord) -> StringRecord {
+    record.iter()
+        .enumerate()
+        .map(|(i, field)| if i == 0 { field.trim_start_matches('\u{feff}') } else { field })
+        .collect()
+}
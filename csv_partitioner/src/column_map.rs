@@ -0,0 +1,156 @@
+//! Reordering and renaming the columns inside every slice.
+
+use csv::StringRecord;
+
+use crate::{CsvSliceParser, ParseError};
+
+/// A new order (and names) for the columns of each slice, attached with
+/// [`CsvSliceParser::with_column_map`].
+///
+/// For exports that put a slice's columns in another order than the one `from_record`
+/// reads, e.g. English, Japanese, Kanji instead of Japanese, English, Kanji. The map is as
+/// wide as the slices, `order[i]` is the slice column that moves to position `i`. The same
+/// map applies to every slice, gap columns and columns after the last whole slice stay put.
+///
+/// # Example
+///
+/// ```rust
+/// # use csv_partitioner::{ColumnMap, CsvSliceParser, ParseConfig};
+/// let data = "English,Japanese,Kanji,English,Japanese,Kanji\napple,りんご,林檎,water,みず,水\n";
+/// let parser = CsvSliceParser::from_str(data, ParseConfig::default()).unwrap();
+///
+/// let map = ColumnMap::reorder([1, 0, 2]).rename(2, "漢字");
+/// let parser = parser.with_column_map(&map).unwrap();
+///
+/// assert_eq!(parser.headers().iter().collect::<Vec<_>>(), vec!["Japanese", "English", "漢字", "Japanese", "English", "漢字"]);
+/// assert_eq!(parser.records()[0].iter().collect::<Vec<_>>(), vec!["りんご", "apple", "林檎", "みず", "water", "水"]);
+///
+/// // every column has to go somewhere
+/// assert!(parser.with_column_map(&ColumnMap::reorder([0, 0, 2])).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnMap {
+    order: Vec<usize>,
+    names: Vec<Option<String>>,
+}
+
+impl ColumnMap {
+    /// Move slice column `order[i]` to position `i`, `order` has to list each of
+    /// `0..order.len()` once.
+    pub fn reorder(order: impl IntoIterator<Item = usize>) -> Self {
+        let order: Vec<usize> = order.into_iter().collect();
+        let names = vec![None; order.len()];
+
+        ColumnMap { order, names }
+    }
+
+    /// Keep the columns where they are, e.g. to only rename some.
+    pub fn identity(width: usize) -> Self {
+        Self::reorder(0..width)
+    }
+
+    /// Give the header at `position` (after reordering) a new name in every slice.
+    ///
+    /// Renaming position 0 renames every slice the same, usually it's the topic name.
+    pub fn rename(mut self, position: usize, name: impl Into<String>) -> Self {
+        if position >= self.names.len() {
+            self.names.resize(position + 1, None);
+        }
+        self.names[position] = Some(name.into());
+        self
+    }
+
+    /// Width of the slices the map is for.
+    pub fn width(&self) -> usize {
+        self.order.len()
+    }
+
+    fn validate(&self) -> Result<(), ParseError> {
+        let mut seen = vec![false; self.order.len()];
+        for &column in &self.order {
+            match seen.get_mut(column) {
+                Some(seen) if !*seen => *seen = true,
+                _ => return Err(ParseError::Layout(format!(
+                    "Column map order {:?} has to list each of 0..{} once", self.order, self.order.len()
+                ))),
+            }
+        }
+
+        if self.order.is_empty() {
+            return Err(ParseError::Layout("Column map is empty".to_string()));
+        }
+        if self.names.len() > self.order.len() {
+            return Err(ParseError::Layout(format!(
+                "Column map renames position {}, it's only {} columns wide", self.names.len() - 1, self.order.len()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// `record` with the columns of every slice starting at `starts` reordered
+    fn apply(&self, record: &StringRecord, starts: &[usize]) -> StringRecord {
+        let mut fields: Vec<&str> = record.iter().collect();
+        let width = self.order.len();
+
+        for &start in starts {
+            if fields.len() <= start {
+                break;
+            }
+            // a short row is padded to the end of a slice it reaches into
+            if fields.len() < start + width {
+                fields.resize(start + width, "");
+            }
+
+            let reordered: Vec<&str> = self.order.iter().map(|&column| fields[start + column]).collect();
+            fields[start..start + width].copy_from_slice(&reordered);
+        }
+
+        let mut remapped = StringRecord::from(fields);
+        remapped.set_position(record.position().cloned());
+        remapped
+    }
+}
+
+impl CsvSliceParser {
+    /// Reorder and rename the columns of every slice, see [`ColumnMap`].
+    ///
+    /// Slices are `map.width()` columns wide with `slice_gap` columns between them. The
+    /// records are rewritten once, so everything after sees the new order, saving included.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CsvSliceParser)` - the parser with its columns moved
+    /// * `Err(ParseError::Layout)` - `map` isn't a reordering of its columns, or renames
+    ///   a position past its width
+    pub fn with_column_map(mut self, map: &ColumnMap) -> Result<Self, ParseError> {
+        map.validate()?;
+
+        let width = map.width();
+        let stride = width.saturating_add(self.config.slice_gap);
+        let starts: Vec<usize> = (0..)
+            .map_while(|index: usize| index.checked_mul(stride))
+            .take_while(|start| start.checked_add(width).is_some_and(|end| end <= self.headers.len()))
+            .collect();
+
+        let reordered = map.apply(&self.headers, &starts);
+        let mut headers: Vec<&str> = reordered.iter().collect();
+        for &start in &starts {
+            for (position, name) in map.names.iter().enumerate() {
+                if let Some(name) = name {
+                    headers[start + position] = name;
+                }
+            }
+        }
+
+        self.headers = StringRecord::from(headers);
+        self.records = self.records.iter().map(|record| map.apply(record, &starts)).collect();
+        // header rows stacked over the headers move with them, title rows above those don't
+        let title_rows = if self.config.has_headers { self.config.header_row } else { self.top_rows.len() };
+        for row in self.top_rows.iter_mut().skip(title_rows) {
+            *row = map.apply(row, &starts);
+        }
+
+        Ok(self)
+    }
+}
@@ -0,0 +1,86 @@
+//! Comparing two versions of a slice.
+
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+/// What changed between an older and a newer version of a slice.
+///
+/// Entries are matched by a key (see [`diff_by_key`]), an entry whose key is in both
+/// versions but whose value differs is `changed` rather than removed and added again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SliceDiff<T> {
+    /// Entries only in the newer version, in its order.
+    pub added: Vec<T>,
+
+    /// Entries only in the older version, in its order.
+    pub removed: Vec<T>,
+
+    /// `(older, newer)` pairs with the same key but different values, in the newer version's order.
+    pub changed: Vec<(T, T)>,
+}
+
+impl<T> SliceDiff<T> {
+    /// `true` when both versions hold the same entries.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl<T> Default for SliceDiff<T> {
+    fn default() -> Self {
+        SliceDiff { added: Vec::new(), removed: Vec::new(), changed: Vec::new() }
+    }
+}
+
+/// Compare two versions of a slice, matching entries by `key`.
+///
+/// Moving an entry to another row isn't a change. When several entries share a key they are
+/// paired up in order, the leftovers count as added or removed.
+///
+/// Works on anything, e.g. slices from two different files or slices found by header name.
+///
+/// # Example
+///
+/// ```rust
+/// use csv_partitioner::diff_by_key;
+///
+/// let old = vec![("apple", "りんご"), ("water", "みず"), ("cat", "ねこ")];
+/// let new = vec![("cat", "ねこ"), ("water", "お水"), ("dog", "いぬ")];
+///
+/// let diff = diff_by_key(old, new, |(english, _)| *english);
+///
+/// assert_eq!(diff.added, vec![("dog", "いぬ")]);
+/// assert_eq!(diff.removed, vec![("apple", "りんご")]);
+/// assert_eq!(diff.changed, vec![(("water", "みず"), ("water", "お水"))]);
+/// ```
+pub fn diff_by_key<T, K, F>(old: Vec<T>, new: Vec<T>, key: F) -> SliceDiff<T>
+where
+    T: PartialEq,
+    K: Eq + Hash,
+    F: Fn(&T) -> K,
+{
+    let mut old: Vec<Option<T>> = old.into_iter().map(Some).collect();
+
+    // positions of the older entries by key, taken from the front as newer entries claim them
+    let mut by_key: HashMap<K, VecDeque<usize>> = HashMap::new();
+    for (idx, entry) in old.iter().enumerate() {
+        if let Some(entry) = entry {
+            by_key.entry(key(entry)).or_default().push_back(idx);
+        }
+    }
+
+    let mut diff = SliceDiff::default();
+
+    for entry in new {
+        let matched = by_key.get_mut(&key(&entry)).and_then(VecDeque::pop_front);
+
+        match matched.and_then(|idx| old[idx].take()) {
+            Some(previous) if previous == entry => {},
+            Some(previous) => diff.changed.push((previous, entry)),
+            None => diff.added.push(entry),
+        }
+    }
+
+    diff.removed = old.into_iter().flatten().collect();
+    diff
+}
@@ -0,0 +1,57 @@
+//! Deciding when a row counts as empty in a slice.
+
+use csv::StringRecord;
+
+/// When a row counts as empty in a slice, set with `ParseConfig::empty_row_policy`.
+///
+/// Empty rows are only left out when `ParseConfig::skip_empty_rows` is on. Cells that are
+/// missing (a short row) or only whitespace are blank.
+///
+/// # Example
+///
+/// ```rust
+/// # use csv_partitioner::{CsvSliceParser, EmptyPolicy, FromColumnSlice, ParseConfig, ParseError};
+/// # use csv::StringRecord;
+/// # struct Pair;
+/// # impl FromColumnSlice for Pair {
+/// #     const COLUMN_COUNT: usize = 2;
+/// #     fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> { Ok(Pair) }
+/// # }
+/// let data = "Food,\nりんご,apple\nみず,\n,\n";
+/// let count = |empty_row_policy| {
+///     let config = ParseConfig { empty_row_policy, ..ParseConfig::default() };
+///     CsvSliceParser::from_str(data, config).unwrap().parse_slice::<Pair>(0).unwrap().len()
+/// };
+///
+/// assert_eq!(count(EmptyPolicy::AllEmpty), 2);
+/// // the row without a translation goes too
+/// assert_eq!(count(EmptyPolicy::AnyEmpty), 1);
+/// // only the first column matters
+/// assert_eq!(count(EmptyPolicy::Custom(|cells| cells[0].is_empty())), 2);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub enum EmptyPolicy {
+    /// Empty when every cell of the slice is blank.
+    #[default]
+    AllEmpty,
+
+    /// Empty when any cell of the slice is blank, for slices where every column is required.
+    AnyEmpty,
+
+    /// Empty when the function says so. It gets the slice's cells in column order, trimmed,
+    /// with `""` for missing ones.
+    Custom(fn(&[&str]) -> bool),
+}
+
+impl EmptyPolicy {
+    /// whether `record` is empty in the slice `start_col..end_col`
+    pub(crate) fn is_empty(self, record: &StringRecord, start_col: usize, end_col: usize) -> bool {
+        let cell = |i: usize| record.get(i).map_or("", str::trim);
+
+        match self {
+            EmptyPolicy::AllEmpty => (start_col..end_col).all(|i| cell(i).is_empty()),
+            EmptyPolicy::AnyEmpty => (start_col..end_col).any(|i| cell(i).is_empty()),
+            EmptyPolicy::Custom(is_empty) => is_empty(&(start_col..end_col).map(cell).collect::<Vec<_>>()),
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! Reading CSVs that aren't plain UTF-8, with the `encoding` feature.
+
+use std::fmt;
+use std::io::{self, Read};
+
+#[cfg(feature = "encoding")]
+use encoding_rs::{Decoder, DecoderResult, SHIFT_JIS, UTF_16LE, UTF_8};
+
+/// Text encoding of the input, set with `ParseConfig::encoding`.
+///
+/// Everything but [`Utf8`](Self::Utf8) is decoded to UTF-8 as it's read, so streaming
+/// stays streaming. Bytes that aren't valid in the encoding are an error, nothing is
+/// silently replaced. Without the `encoding` feature only `Utf8` is there.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "encoding")] {
+/// # use csv_partitioner::{CsvSliceParser, InputEncoding, ParseConfig};
+/// // "単語,意味" then "猫,cat", as excel on a japanese windows saves it
+/// let shift_jis: &[u8] = b"\x92\x50\x8c\xea,\x88\xd3\x96\xa1\n\x94\x4c,cat\n";
+///
+/// let config = ParseConfig::builder().encoding(InputEncoding::ShiftJis).build();
+/// let parser = CsvSliceParser::from_reader(shift_jis, config).unwrap();
+///
+/// assert_eq!(&parser.headers()[0], "単語");
+/// assert_eq!(&parser.records()[0][0], "猫");
+///
+/// // read as utf-8 it's not valid
+/// assert!(CsvSliceParser::from_reader(shift_jis, ParseConfig::default()).is_err());
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputEncoding {
+    /// UTF-8, read as it is.
+    #[default]
+    Utf8,
+
+    #[cfg(feature = "encoding")]
+    /// UTF-8 that may start with a byte order mark (Excel's "CSV UTF-8"), the mark is dropped.
+    Utf8Bom,
+
+    #[cfg(feature = "encoding")]
+    /// Shift_JIS, what Excel on a Japanese Windows saves plain CSV as.
+    ShiftJis,
+
+    #[cfg(feature = "encoding")]
+    /// UTF-16 little endian ("Unicode text" exports), a leading byte order mark is dropped.
+    Utf16Le,
+}
+
+impl InputEncoding {
+    /// `true` when the input is UTF-8 already, a BOM aside
+    #[cfg(any(feature = "mmap", feature = "async"))]
+    pub(crate) fn is_utf8(&self) -> bool {
+        match self {
+            InputEncoding::Utf8 => true,
+            #[cfg(feature = "encoding")]
+            InputEncoding::Utf8Bom => true,
+            #[cfg(feature = "encoding")]
+            InputEncoding::ShiftJis | InputEncoding::Utf16Le => false,
+        }
+    }
+
+    /// decoder to UTF-8, `None` when the bytes can be read as they are
+    #[cfg(feature = "encoding")]
+    fn decoder(&self) -> Option<Decoder> {
+        match self {
+            InputEncoding::Utf8 => None,
+            InputEncoding::Utf8Bom => Some(UTF_8.new_decoder_with_bom_removal()),
+            InputEncoding::ShiftJis => Some(SHIFT_JIS.new_decoder_without_bom_handling()),
+            InputEncoding::Utf16Le => Some(UTF_16LE.new_decoder_with_bom_removal()),
+        }
+    }
+}
+
+impl fmt::Display for InputEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            InputEncoding::Utf8 => "UTF-8",
+            #[cfg(feature = "encoding")]
+            InputEncoding::Utf8Bom => "UTF-8 (with BOM)",
+            #[cfg(feature = "encoding")]
+            InputEncoding::ShiftJis => "Shift_JIS",
+            #[cfg(feature = "encoding")]
+            InputEncoding::Utf16Le => "UTF-16LE",
+        };
+        f.pad(name)
+    }
+}
+
+/// size of the buffers between the reader, the decoder and the csv reader
+#[cfg(feature = "encoding")]
+const BUFFER_SIZE: usize = 8 * 1024;
+
+/// A reader handing out `inner`'s bytes as UTF-8.
+///
+/// Invalid input fails the read with `InvalidData`, saying at which byte.
+#[cfg(feature = "encoding")]
+pub(crate) struct DecodingReader<R> {
+    inner: R,
+    encoding: InputEncoding,
+    decoder: Option<Decoder>,
+
+    /// undecoded bytes from `inner`, `input[input_pos..]` is still to be decoded
+    input: Vec<u8>,
+    input_pos: usize,
+
+    /// decoded bytes, `output[output_pos..]` is still to be handed out
+    output: Vec<u8>,
+    output_pos: usize,
+
+    /// bytes of `inner` already decoded, for error messages
+    offset: usize,
+    eof: bool,
+    done: bool,
+}
+
+#[cfg(feature = "encoding")]
+impl<R: Read> DecodingReader<R> {
+    pub(crate) fn new(inner: R, encoding: InputEncoding) -> Self {
+        DecodingReader {
+            inner,
+            encoding,
+            decoder: encoding.decoder(),
+            input: Vec::new(),
+            input_pos: 0,
+            output: Vec::new(),
+            output_pos: 0,
+            offset: 0,
+            eof: false,
+            done: false,
+        }
+    }
+
+    /// decode the next chunk into `output`
+    fn fill(&mut self) -> io::Result<()> {
+        let Some(decoder) = self.decoder.as_mut() else {
+            return Ok(());
+        };
+
+        if self.input_pos == self.input.len() && !self.eof {
+            self.input.resize(BUFFER_SIZE, 0);
+            let read = self.inner.read(&mut self.input)?;
+            self.input.truncate(read);
+            self.input_pos = 0;
+            self.eof = read == 0;
+        }
+
+        self.output.resize(BUFFER_SIZE, 0);
+        let (result, read, written) = decoder.decode_to_utf8_without_replacement(
+            &self.input[self.input_pos..], &mut self.output, self.eof
+        );
+        self.input_pos += read;
+        self.offset += read;
+        self.output.truncate(written);
+        self.output_pos = 0;
+
+        match result {
+            DecoderResult::InputEmpty => self.done = self.eof,
+            DecoderResult::OutputFull => {},
+            DecoderResult::Malformed(bad, after) => {
+                let at = self.offset - usize::from(after) - usize::from(bad);
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("input isn't valid {} (byte {})", self.encoding, at),
+                ));
+            },
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "encoding")]
+impl<R: Read> Read for DecodingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.decoder.is_none() {
+            return self.inner.read(buf);
+        }
+
+        while self.output_pos == self.output.len() && !self.done && !buf.is_empty() {
+            self.fill()?;
+        }
+
+        let n = buf.len().min(self.output.len() - self.output_pos);
+        buf[..n].copy_from_slice(&self.output[self.output_pos..self.output_pos + n]);
+        self.output_pos += n;
+
+        Ok(n)
+    }
+}
+
+/// Without the `encoding` feature the input is always UTF-8 and read as it is.
+#[cfg(not(feature = "encoding"))]
+pub(crate) struct DecodingReader<R>(R);
+
+#[cfg(not(feature = "encoding"))]
+impl<R: Read> DecodingReader<R> {
+    pub(crate) fn new(inner: R, _encoding: InputEncoding) -> Self {
+        DecodingReader(inner)
+    }
+}
+
+#[cfg(not(feature = "encoding"))]
+impl<R: Read> Read for DecodingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
@@ -0,0 +1,212 @@
+//! Error type returned by every parser.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use crate::HeaderMismatch;
+
+/// Everything that can go wrong while loading or slicing a CSV.
+///
+/// Match on it to tell a bad request (out of bounds, unusable layout) from bad data
+/// (missing fields, malformed CSV).
+///
+/// Rows and columns are stored zero-based, [`Display`](fmt::Display) numbers them from 1.
+/// Rows count data rows only, the header row isn't one.
+///
+/// # Example
+///
+/// ```rust
+/// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+/// # use csv::StringRecord;
+/// struct Pair { word: String, translation: String }
+///
+/// impl FromColumnSlice for Pair {
+///     const COLUMN_COUNT: usize = 2;
+///
+///     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+///         let field = |offset: usize, name: &str| record.get(start_col + offset)
+///             .filter(|value| !value.is_empty())
+///             .map(str::to_string)
+///             .ok_or_else(|| ParseError::missing_field(start_col + offset, name));
+///
+///         Ok(Pair { word: field(0, "word")?, translation: field(1, "translation")? })
+///     }
+/// }
+///
+/// let data = "A,B\nhello,hola\nbye,\n";
+/// let parser = CsvSliceParser::from_reader(data.as_bytes(), ParseConfig::default()).unwrap();
+///
+/// match parser.parse_slice::<Pair>(0) {
+///     Err(ParseError::MissingField { row, col, name }) => {
+///         assert_eq!((row, col, name.as_str()), (Some(1), 1, "translation"));
+///     },
+///     _ => unreachable!(),
+/// }
+///
+/// assert!(matches!(parser.parse_slice::<Pair>(5), Err(ParseError::SliceOutOfBounds { .. })));
+/// ```
+#[derive(Debug)]
+pub enum ParseError {
+    /// Asked for a slice past the last one.
+    SliceOutOfBounds { slice_index: usize, slice_count: usize },
+
+    /// Asked for a data row past the last one.
+    RowOutOfBounds { row_index: usize, row_count: usize },
+
+    /// A required field is missing or empty.
+    ///
+    /// `from_record` leaves `row` as `None` (it doesn't know it), the parsers fill it in.
+    MissingField { row: Option<usize>, col: usize, name: String },
+
+    /// The CSV's shape doesn't fit the request, e.g. a type wider than the header row
+    /// or a header pattern that isn't there.
+    Layout(String),
+
+    /// Header names that aren't what was expected, from header validation.
+    HeaderMismatch(Vec<HeaderMismatch>),
+
+    /// Reading the input failed.
+    Io(io::Error),
+
+    /// The input isn't valid CSV.
+    Csv(csv::Error),
+
+    /// Anything else, usually raised by a `from_record` implementation.
+    Custom(Box<dyn Error + Send + Sync>),
+}
+
+impl ParseError {
+    /// A missing field error for `from_record` implementations, the parser adds the row.
+    pub fn missing_field(col: usize, name: impl Into<String>) -> Self {
+        ParseError::MissingField { row: None, col, name: name.into() }
+    }
+
+    /// Wrap any error, e.g. from parsing a number inside `from_record`.
+    pub fn custom(error: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        ParseError::Custom(error.into())
+    }
+
+    /// Record which data row a `MissingField` came from, for code calling `from_record` itself.
+    ///
+    /// Rows already set are kept.
+    pub fn at_row(self, row: usize) -> Self {
+        match self {
+            ParseError::MissingField { row: None, col, name } => ParseError::MissingField { row: Some(row), col, name },
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::SliceOutOfBounds { slice_index, slice_count } => write!(
+                f, "Slice {} out of bounds ({} slices available)", slice_index, slice_count
+            ),
+            ParseError::RowOutOfBounds { row_index, row_count } => write!(
+                f, "Row {} out of bounds ({} rows available)", row_index + 1, row_count
+            ),
+            ParseError::MissingField { row: Some(row), col, name } => write!(
+                f, "Missing {} (column {}) in row {}", name, col + 1, row + 1
+            ),
+            ParseError::MissingField { row: None, col, name } => write!(f, "Missing {} (column {})", name, col + 1),
+            ParseError::Layout(message) => write!(f, "{}", message),
+            ParseError::HeaderMismatch(mismatches) => {
+                write!(f, "Headers don't match: ")?;
+                for (idx, mismatch) in mismatches.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", mismatch)?;
+                }
+                Ok(())
+            },
+            ParseError::Io(e) => write!(f, "I/O error: {}", e),
+            ParseError::Csv(e) => write!(f, "CSV error: {}", e),
+            ParseError::Custom(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::Io(e) => Some(e),
+            ParseError::Csv(e) => Some(e),
+            // Custom displays as the wrapped error, so its cause is the next link
+            ParseError::Custom(e) => e.source(),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(error: io::Error) -> Self {
+        ParseError::Io(error)
+    }
+}
+
+impl From<csv::Error> for ParseError {
+    fn from(error: csv::Error) -> Self {
+        // failed reads (including input that isn't valid in `ParseConfig::encoding`) aren't bad CSV
+        if error.is_io_error() {
+            match error.into_kind() {
+                csv::ErrorKind::Io(error) => return ParseError::Io(error),
+                _ => unreachable!("is_io_error checked the kind"),
+            }
+        }
+        ParseError::Csv(error)
+    }
+}
+
+#[cfg(feature = "async")]
+impl From<csv_async::Error> for ParseError {
+    fn from(error: csv_async::Error) -> Self {
+        if error.is_io_error() {
+            match error.into_kind() {
+                csv_async::ErrorKind::Io(error) => return ParseError::Io(error),
+                _ => unreachable!("is_io_error checked the kind"),
+            }
+        }
+        // `Csv` holds the sync crate's error, this one can only be passed on as it is
+        ParseError::Custom(Box::new(error))
+    }
+}
+
+impl From<String> for ParseError {
+    fn from(message: String) -> Self {
+        ParseError::Custom(message.into())
+    }
+}
+
+impl From<&str> for ParseError {
+    fn from(message: &str) -> Self {
+        ParseError::Custom(message.into())
+    }
+}
+
+/// A row [`CsvSliceParser::parse_slice_lenient`](crate::CsvSliceParser::parse_slice_lenient) left out.
+///
+/// `row` is zero-based and counts data rows only, like the rows in [`ParseError`].
+#[derive(Debug)]
+pub struct RowError {
+    pub row: usize,
+    pub error: ParseError,
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.error {
+            // already says which row
+            ParseError::MissingField { row: Some(_), .. } => write!(f, "{}", self.error),
+            error => write!(f, "Row {}: {}", self.row + 1, error),
+        }
+    }
+}
+
+impl Error for RowError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
@@ -0,0 +1,76 @@
+//! Which cells of every row are filled in, worked out once for all slices.
+
+use csv::StringRecord;
+
+use crate::{EmptyPolicy, ParseConfig};
+
+/// One bit per cell, set when the cell isn't blank, `words` u64s per row.
+///
+/// Checking whether a row is empty in a slice is then a few masked compares instead of
+/// trimming every cell again, for each slice that parses the row.
+pub(crate) struct FilledCells {
+    words: usize,
+    bits: Vec<u64>,
+    /// `EmptyPolicy::AnyEmpty`, otherwise `AllEmpty`
+    any_empty: bool,
+}
+
+impl FilledCells {
+    /// The bitmap of `records`, `None` when `config` never asks whether a row is empty or
+    /// asks a custom policy, which needs the cells themselves.
+    pub(crate) fn new(records: &[StringRecord], config: &ParseConfig) -> Option<Self> {
+        let any_empty = match config.empty_row_policy {
+            _ if !config.skip_empty_rows => return None,
+            EmptyPolicy::AllEmpty => false,
+            EmptyPolicy::AnyEmpty => true,
+            EmptyPolicy::Custom(_) => return None,
+        };
+
+        let words = records.iter().map(StringRecord::len).max().unwrap_or(0).div_ceil(64);
+        let mut bits = vec![0u64; words * records.len()];
+
+        for (row, record) in records.iter().enumerate() {
+            let row_bits = &mut bits[row * words..(row + 1) * words];
+            for (column, cell) in record.iter().enumerate() {
+                if is_filled(cell) {
+                    row_bits[column / 64] |= 1 << (column % 64);
+                }
+            }
+        }
+
+        Some(FilledCells { words, bits, any_empty })
+    }
+
+    /// whether `row` counts as empty in the slice `start_col..end_col`, the same answer as
+    /// `EmptyPolicy::is_empty` on the record
+    pub(crate) fn is_empty(&self, row: usize, start_col: usize, end_col: usize) -> bool {
+        let row_bits = &self.bits[row * self.words..(row + 1) * self.words];
+        // columns past the widest row are never filled
+        let word = |i: usize| row_bits.get(i).copied().unwrap_or(0);
+
+        let mut all = true;
+        let mut any = false;
+        let mut column = start_col;
+        while column < end_col {
+            let offset = column % 64;
+            let span = (end_col - column).min(64 - offset);
+            let mask = if span == 64 { u64::MAX } else { ((1u64 << span) - 1) << offset };
+            let filled = word(column / 64) & mask;
+
+            all &= filled == mask;
+            any |= filled != 0;
+            column += span;
+        }
+
+        if self.any_empty { !all } else { !any }
+    }
+}
+
+/// `!cell.trim().is_empty()`, deciding on the first character when it isn't whitespace
+fn is_filled(cell: &str) -> bool {
+    match cell.chars().next() {
+        None => false,
+        Some(first) if !first.is_whitespace() => true,
+        Some(_) => !cell.trim().is_empty(),
+    }
+}
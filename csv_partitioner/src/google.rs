@@ -0,0 +1,252 @@
+//! Reading a shared Google Sheet through its CSV export, with the `google-sheets` feature.
+
+use std::io;
+
+use crate::{CsvSliceParser, ParseConfig, ParseError};
+
+const SHEETS_PREFIX: &str = "https://docs.google.com/spreadsheets/d/";
+
+/// The CSV export link for a Google Sheets link or spreadsheet id.
+///
+/// Takes the link from the address bar or the share dialog (`.../d/<id>/edit#gid=<tab>`),
+/// a "publish to the web" link (`.../d/e/<id>/pubhtml`) or a bare id. The tab in `gid`
+/// is kept, without one the export is the first tab. Requires the `google-sheets` feature.
+///
+/// # Example
+///
+/// ```rust
+/// use csv_partitioner::google_sheet_csv_url;
+///
+/// let link = "https://docs.google.com/spreadsheets/d/1AbC_dEf-123456789012345/edit?usp=sharing#gid=42";
+/// assert_eq!(
+///     google_sheet_csv_url(link).unwrap(),
+///     "https://docs.google.com/spreadsheets/d/1AbC_dEf-123456789012345/export?format=csv&gid=42",
+/// );
+///
+/// assert_eq!(
+///     google_sheet_csv_url("1AbC_dEf-123456789012345").unwrap(),
+///     "https://docs.google.com/spreadsheets/d/1AbC_dEf-123456789012345/export?format=csv",
+/// );
+///
+/// assert!(google_sheet_csv_url("vocab.csv").is_err());
+/// ```
+pub fn google_sheet_csv_url(url_or_id: &str) -> Result<String, ParseError> {
+    let url_or_id = url_or_id.trim();
+    let gid = url_or_id.split(['?', '#', '&'])
+        .find_map(|part| part.strip_prefix("gid="))
+        .filter(|gid| !gid.is_empty() && gid.bytes().all(|b| b.is_ascii_digit()))
+        .map_or_else(String::new, |gid| format!("&gid={}", gid));
+
+    let path = url_or_id.strip_prefix(SHEETS_PREFIX).or_else(|| url_or_id.strip_prefix(&SHEETS_PREFIX[8..]));
+    let (published, id) = match path.map(|path| path.split(['/', '?', '#']).collect::<Vec<_>>()) {
+        Some(parts) if parts.first() == Some(&"e") => (true, parts.get(1).copied().unwrap_or("")),
+        Some(parts) => (false, parts[0]),
+        None => (false, url_or_id),
+    };
+
+    let is_id = id.len() >= 20 && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+    if !is_id {
+        return Err(ParseError::Layout(format!("'{}' isn't a Google Sheets link or spreadsheet id", url_or_id)));
+    }
+
+    Ok(if published {
+        format!("{}e/{}/pub?output=csv{}", SHEETS_PREFIX, id, gid)
+    } else {
+        format!("{}{}/export?format=csv{}", SHEETS_PREFIX, id, gid)
+    })
+}
+
+/// A downloaded Google Sheet, see [`download_google_sheet`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoogleSheet {
+    /// the tab as CSV
+    pub csv: String,
+    /// the name Google gives the download, `<spreadsheet> - <tab>` without the `.csv`,
+    /// `None` when the response didn't name it
+    pub title: Option<String>,
+}
+
+/// Download a Google Sheet as CSV text, see [`google_sheet_csv_url`] for the links it takes.
+///
+/// The sheet has to be readable without signing in ("Anyone with the link can view", or
+/// published to the web). Requires the `google-sheets` feature.
+///
+/// # Returns
+///
+/// * `Ok(String)` - the tab as CSV
+/// * `Err(ParseError::Io)` - the download failed
+/// * `Err(ParseError)` - `Layout` for something that isn't a sheet link, `Custom` when
+///   Google refused (not shared, no such sheet or tab)
+pub fn fetch_google_sheet(url_or_id: &str) -> Result<String, ParseError> {
+    download_google_sheet(url_or_id).map(|sheet| sheet.csv)
+}
+
+/// Download a Google Sheet as CSV along with its title, like [`fetch_google_sheet`].
+///
+/// The title comes from the file name of the export, for naming what's made from the sheet.
+/// Requires the `google-sheets` feature.
+pub fn download_google_sheet(url_or_id: &str) -> Result<GoogleSheet, ParseError> {
+    download(&google_sheet_csv_url(url_or_id)?, url_or_id)
+}
+
+/// download the csv at the export `url` of the sheet `link`
+fn download(url: &str, link: &str) -> Result<GoogleSheet, ParseError> {
+    let response = reqwest::blocking::get(url).map_err(io::Error::other)?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(ParseError::custom(format!(
+            "Google Sheets answered {} for {}, check the link and that the tab still exists", status, link
+        )));
+    }
+
+    // sheets that aren't shared redirect to the sign-in page instead of failing
+    let header = |name| response.headers().get(name).and_then(|value| value.to_str().ok());
+    if header(reqwest::header::CONTENT_TYPE).is_some_and(|value| value.starts_with("text/html")) {
+        return Err(ParseError::custom(format!(
+            "{} isn't shared, set it to \"Anyone with the link can view\" (or publish it to the web)", link
+        )));
+    }
+
+    let title = header(reqwest::header::CONTENT_DISPOSITION)
+        .and_then(attachment_name)
+        .map(|name| name.strip_suffix(".csv").map(str::to_string).unwrap_or(name))
+        .filter(|title| !title.trim().is_empty());
+
+    Ok(GoogleSheet { csv: response.text().map_err(io::Error::other)?, title })
+}
+
+/// the file name in a `Content-Disposition` header, `filename*` (percent encoded utf-8)
+/// over the plain `filename`
+fn attachment_name(disposition: &str) -> Option<String> {
+    let mut plain = None;
+
+    for (key, value) in disposition.split(';').filter_map(|param| param.split_once('=')) {
+        match key.trim().to_ascii_lowercase().as_str() {
+            "filename*" => {
+                let value = value.trim();
+                let encoded = value.get(..7)
+                    .filter(|charset| charset.eq_ignore_ascii_case("utf-8''"))
+                    .map(|_| &value[7..]);
+                if let Some(name) = encoded.and_then(percent_decode) {
+                    return Some(name);
+                }
+            },
+            "filename" => plain = Some(value.trim().trim_matches('"').to_string()),
+            _ => {},
+        }
+    }
+
+    plain
+}
+
+fn percent_decode(encoded: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut rest = encoded.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+impl CsvSliceParser {
+    /// Load a shared Google Sheet with a default configuration.
+    ///
+    /// Requires the `google-sheets` feature.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use csv_partitioner::CsvSliceParser;
+    /// # use std::error::Error;
+    /// # fn example() -> Result<(), Box<dyn Error>> {
+    /// let parser = CsvSliceParser::from_google_sheet(
+    ///     "https://docs.google.com/spreadsheets/d/1AbC_dEf-123456789012345/edit#gid=0"
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_google_sheet(url_or_id: &str) -> Result<Self, ParseError> {
+        Self::from_google_sheet_with_config(url_or_id, ParseConfig::default())
+    }
+
+    /// Load a shared Google Sheet with custom configuration, see [`fetch_google_sheet`].
+    ///
+    /// The export is always comma separated UTF-8, `delimiter` and `encoding` are ignored.
+    pub fn from_google_sheet_with_config(url_or_id: &str, config: ParseConfig) -> Result<Self, ParseError> {
+        let csv = fetch_google_sheet(url_or_id)?;
+        let delimiter = config.delimiter;
+
+        let mut parser = Self::from_str(&csv, ParseConfig { delimiter: b',', ..config })?;
+        parser.config.delimiter = delimiter;
+
+        Ok(parser)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use super::*;
+
+    /// serve `response` to one request, returns the url to ask for
+    fn serve_once(response: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/export?format=csv", listener.local_addr().unwrap());
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 4096];
+            let _ = stream.read(&mut request);
+            let _ = stream.write_all(response.as_bytes());
+        });
+
+        url
+    }
+
+    #[test]
+    fn downloads_the_csv_and_its_title() {
+        let url = serve_once(
+            "HTTP/1.1 200 OK\r\n\
+             Content-Type: text/csv\r\n\
+             Content-Disposition: attachment; filename=\"Vocab - Sheet1.csv\"; filename*=UTF-8''%E5%8D%98%E8%AA%9E%20-%20Sheet1.csv\r\n\
+             Connection: close\r\n\r\n\
+             Food,,\r\nあと,,\r\n",
+        );
+
+        let sheet = download(&url, "the link").unwrap();
+
+        assert_eq!(sheet.csv, "Food,,\r\nあと,,\r\n");
+        assert_eq!(sheet.title.as_deref(), Some("単語 - Sheet1"));
+    }
+
+    #[test]
+    fn refuses_the_sign_in_page_and_error_statuses() {
+        let sign_in = serve_once("HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nConnection: close\r\n\r\n<html>");
+        let missing = serve_once("HTTP/1.1 404 Not Found\r\nConnection: close\r\n\r\n");
+
+        assert!(download(&sign_in, "the link").unwrap_err().to_string().contains("isn't shared"));
+        assert!(download(&missing, "the link").unwrap_err().to_string().contains("404"));
+    }
+
+    #[test]
+    fn reads_the_plain_file_name_without_an_encoded_one() {
+        assert_eq!(attachment_name("attachment; filename=\"Vocab - Sheet1.csv\"").as_deref(), Some("Vocab - Sheet1.csv"));
+        assert_eq!(attachment_name("attachment; filename*=utf-8''a%2Fb.csv").as_deref(), Some("a/b.csv"));
+        assert_eq!(attachment_name("attachment"), None);
+    }
+}
@@ -0,0 +1,198 @@
+//! Header names: how they are matched, and the headers of a slice.
+
+use std::fmt;
+use std::ops::Range;
+
+#[cfg(feature = "nfkc")]
+use unicode_normalization::UnicodeNormalization;
+
+use crate::ParseError;
+
+/// How header names are compared by header-driven features
+/// (topic lookup, header validation, ...).
+///
+/// # Example
+///
+/// ```rust
+/// use csv_partitioner::HeaderMatching;
+///
+/// assert!(HeaderMatching::Normalized.matches("English", " english "));
+/// assert!(!HeaderMatching::Exact.matches("English", "english"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HeaderMatching {
+    /// Byte-for-byte comparison.
+    Exact,
+
+    /// Compare after [`normalize_header`]: NFKC, trimmed, whitespace collapsed, lowercased.
+    #[default]
+    Normalized,
+}
+
+impl HeaderMatching {
+    /// Check whether two header names refer to the same column.
+    pub fn matches(&self, a: &str, b: &str) -> bool {
+        match self {
+            HeaderMatching::Exact => a == b,
+            HeaderMatching::Normalized => normalize_header(a) == normalize_header(b),
+        }
+    }
+}
+
+/// Normalise a header name for comparison.
+///
+/// Applies Unicode NFKC with the `nfkc` feature (so full-width `ＥＮＧＬＩＳＨ` becomes
+/// `ENGLISH`), trims and collapses whitespace, then lowercases.
+///
+/// # Example
+///
+/// ```rust
+/// use csv_partitioner::normalize_header;
+///
+/// assert_eq!(normalize_header("  Japanese  Words "), "japanese words");
+///
+/// # #[cfg(feature = "nfkc")]
+/// assert_eq!(normalize_header("Ｊａｐａｎｅｓｅ"), "japanese");
+/// ```
+pub fn normalize_header(header: &str) -> String {
+    #[cfg(feature = "nfkc")]
+    let header = header.nfkc().collect::<String>();
+
+    header.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// One header that isn't what it should be, see [`ParseError::HeaderMismatch`].
+///
+/// `column` is zero-based, [`Display`](fmt::Display) numbers it from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderMismatch {
+    pub slice_index: usize,
+    pub column: usize,
+    pub found: String,
+    pub expected: String,
+}
+
+impl fmt::Display for HeaderMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f, "column {} (slice {}) is '{}', expected '{}'",
+            self.column + 1, self.slice_index, self.found, self.expected
+        )
+    }
+}
+
+/// The header names of one slice, from [`CsvSliceParser::slice_headers`](crate::CsvSliceParser::slice_headers).
+///
+/// Owned, so it can outlive the parser. Names are indexed by their offset in the slice
+/// (0 is the slice's first column), blank headers are `""`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SliceHeaders {
+    index: usize,
+    columns: Range<usize>,
+    names: Vec<String>,
+    matching: HeaderMatching,
+}
+
+impl SliceHeaders {
+    pub(crate) fn new(index: usize, columns: Range<usize>, names: Vec<String>, matching: HeaderMatching) -> Self {
+        SliceHeaders { index, columns, names, matching }
+    }
+
+    /// Index of the slice these headers belong to.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Columns of the CSV the headers were read from.
+    pub fn columns(&self) -> Range<usize> {
+        self.columns.clone()
+    }
+
+    /// Every name, in column order.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// The name at `offset` within the slice.
+    pub fn get(&self, offset: usize) -> Option<&str> {
+        self.names.get(offset).map(String::as_str)
+    }
+
+    /// The first name, usually what the slice is called.
+    pub fn first(&self) -> &str {
+        self.get(0).unwrap_or("")
+    }
+
+    /// Number of columns in the slice.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// `true` for a slice without columns, which the parser never returns.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Check the names against what the columns should be called.
+    ///
+    /// Names are compared with the parser's [`HeaderMatching`], `expected` must name every column.
+    /// `"*"` accepts any name, e.g. `["*", "", ""]` for a named slice with blank headers after it.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - every name matches
+    /// * `Err(ParseError::HeaderMismatch)` - one entry per column that doesn't
+    /// * `Err(ParseError::Layout)` - `expected` has the wrong length
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # struct Entry;
+    /// # impl FromColumnSlice for Entry {
+    /// #     const COLUMN_COUNT: usize = 2;
+    /// #     fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> { Ok(Entry) }
+    /// # }
+    /// let parser = CsvSliceParser::from_str("English,spanish,English,Spansh\n", ParseConfig::default()).unwrap();
+    ///
+    /// let first = parser.slice_headers::<Entry>(0).unwrap();
+    /// assert!(first.expected_names(&["English", "Spanish"]).is_ok());
+    ///
+    /// let second = parser.slice_headers::<Entry>(1).unwrap();
+    /// let error = second.expected_names(&["English", "Spanish"]).unwrap_err();
+    /// assert_eq!(error.to_string(), "Headers don't match: column 4 (slice 1) is 'Spansh', expected 'Spanish'");
+    /// ```
+    pub fn expected_names(&self, expected: &[&str]) -> Result<(), ParseError> {
+        if expected.len() != self.names.len() {
+            return Err(ParseError::Layout(format!(
+                "Slice {} has {} columns, but {} names were expected", self.index, self.names.len(), expected.len()
+            )));
+        }
+
+        let mismatches = self.mismatches(expected);
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(ParseError::HeaderMismatch(mismatches))
+        }
+    }
+
+    /// columns whose name doesn't match `expected`, which is as long as the slice
+    pub(crate) fn mismatches(&self, expected: &[&str]) -> Vec<HeaderMismatch> {
+        self.columns.clone()
+            .zip(self.names.iter().zip(expected))
+            .filter(|(_, (name, expected))| **expected != "*" && !self.matching.matches(name, expected))
+            .map(|(column, (name, expected))| HeaderMismatch {
+                slice_index: self.index,
+                column,
+                found: name.clone(),
+                expected: expected.to_string(),
+            })
+            .collect()
+    }
+}
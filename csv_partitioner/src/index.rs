@@ -0,0 +1,155 @@
+//! Looking values of a slice up by a key instead of scanning for them.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::{CsvSliceParser, FromColumnSlice, ParseError};
+
+/// The values of a slice with a hash index on a key, from [`CsvSliceParser::build_index`].
+///
+/// Values keep their file order. Keys don't have to be unique, lookups return the first
+/// value with a key and [`duplicates`](Self::duplicates) lists the keys that repeat.
+#[derive(Debug, Clone)]
+pub struct SliceIndex<K, T> {
+    values: Vec<T>,
+    rows: Vec<usize>,
+    positions: HashMap<K, Vec<usize>>,
+}
+
+impl<K: Hash + Eq, T> SliceIndex<K, T> {
+    /// The first value with `key`.
+    pub fn get<Q>(&self, key: &Q) -> Option<&T>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.positions.get(key).map(|positions| &self.values[positions[0]])
+    }
+
+    /// Every value with `key`, in file order.
+    pub fn get_all<Q>(&self, key: &Q) -> impl Iterator<Item = &T>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.positions.get(key).into_iter().flatten().map(|&position| &self.values[position])
+    }
+
+    /// Data row (like the rows in [`ParseError`]) of the first value with `key`.
+    pub fn row<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.positions.get(key).map(|positions| self.rows[positions[0]])
+    }
+
+    /// `true` when some value has `key`.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.positions.contains_key(key)
+    }
+
+    /// Keys shared by more than one value, with those values, in the order the keys first appear.
+    pub fn duplicates(&self) -> Vec<(&K, Vec<&T>)> {
+        let mut duplicates: Vec<(&K, &Vec<usize>)> = self.positions.iter()
+            .filter(|(_, positions)| positions.len() > 1)
+            .collect();
+        duplicates.sort_by_key(|(_, positions)| positions[0]);
+
+        duplicates.into_iter()
+            .map(|(key, positions)| (key, positions.iter().map(|&position| &self.values[position]).collect()))
+            .collect()
+    }
+
+    /// Number of distinct keys.
+    pub fn key_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Number of values, duplicates included.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// `true` when the slice had no rows to index.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// All values, in file order.
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Drop the index and keep the values, in file order.
+    pub fn into_values(self) -> Vec<T> {
+        self.values
+    }
+}
+
+impl CsvSliceParser {
+    /// Parse a slice and index it by `key`, for lookups that don't scan every value.
+    ///
+    /// Rows are skipped as with [`parse_slice`](Self::parse_slice). Build an index of an
+    /// existing export once, then check each new value against it, e.g. to leave out words
+    /// that are already there.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SliceIndex)` - the slice's values, looked up by `key`
+    /// * `Err(ParseError)` - `SliceOutOfBounds`, or whatever `from_record` returned (with its row)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// struct Word { reading: String, meaning: String }
+    ///
+    /// impl FromColumnSlice for Word {
+    ///     const COLUMN_COUNT: usize = 2;
+    ///
+    ///     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    ///         let cell = |offset| record.get(start_col + offset).unwrap_or("").to_string();
+    ///         Ok(Word { reading: cell(0), meaning: cell(1) })
+    ///     }
+    /// }
+    ///
+    /// let export = CsvSliceParser::from_str("Food,\nりんご,apple\nみず,water\nりんご,apple (fruit)\n", ParseConfig::default()).unwrap();
+    /// let index = export.build_index::<Word, _>(0, |word| word.reading.clone()).unwrap();
+    ///
+    /// assert_eq!(index.get("みず").map(|word| word.meaning.as_str()), Some("water"));
+    /// assert_eq!(index.row("みず"), Some(1));
+    /// assert!(!index.contains_key("いぬ"));
+    ///
+    /// let duplicates = index.duplicates();
+    /// assert_eq!(duplicates.len(), 1);
+    /// assert_eq!((duplicates[0].0.as_str(), duplicates[0].1.len()), ("りんご", 2));
+    /// ```
+    pub fn build_index<T, K>(&self, slice_index: usize, mut key: impl FnMut(&T) -> K) -> Result<SliceIndex<K, T>, ParseError>
+    where
+        T: FromColumnSlice,
+        K: Hash + Eq,
+    {
+        let (start_col, end_col) = self.validate_slice_index::<T>(slice_index)?;
+        let mut index = SliceIndex { values: Vec::new(), rows: Vec::new(), positions: HashMap::new() };
+
+        for (row, record) in self.records.iter().enumerate() {
+            if self.is_skipped(start_col, end_col, record) {
+                continue;
+            }
+
+            let value = T::from_record(record, start_col).map_err(|e| e.at_row(row))?;
+            index.positions.entry(key(&value)).or_insert_with(Vec::new).push(index.values.len());
+            index.values.push(value);
+            index.rows.push(row);
+        }
+
+        Ok(index)
+    }
+}
@@ -0,0 +1,33 @@
+//! Describing slices before parsing them.
+
+use std::ops::Range;
+
+/// Where a slice is and what's in it, from [`CsvSliceParser::slices`](crate::CsvSliceParser::slices).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SliceInfo {
+    /// Index to pass to [`parse_slice`](crate::CsvSliceParser::parse_slice) and friends.
+    pub index: usize,
+
+    /// Columns the slice covers, gaps from `ParseConfig::slice_gap` excluded.
+    pub columns: Range<usize>,
+
+    /// Header of each column in the slice, blank headers included as `""`.
+    pub headers: Vec<String>,
+
+    /// Data rows with at least one non-blank cell in the slice.
+    ///
+    /// Rows left out by `ParseConfig::skip_marker` still count.
+    pub non_empty_rows: usize,
+}
+
+impl SliceInfo {
+    /// The first header, usually the slice's name.
+    pub fn name(&self) -> &str {
+        self.headers.first().map_or("", String::as_str)
+    }
+
+    /// `true` when every data row is blank in this slice.
+    pub fn is_empty(&self) -> bool {
+        self.non_empty_rows == 0
+    }
+}
@@ -0,0 +1,155 @@
+//! Files whose slices aren't all the same type.
+
+use std::ops::Range;
+
+use crate::{CsvSliceParser, FromColumnSlice, ParseError};
+
+/// The slices of a file from left to right, each with its own type and width.
+///
+/// For sheets that mix layouts, say a block of words next to a block of phrases. Every
+/// registered type turns into `E` through `From<Vec<T>>`, usually an enum with one variant
+/// per kind of slice. Slices follow each other with `ParseConfig::slice_gap` columns in
+/// between, columns after the last one are ignored.
+///
+/// Parse with [`CsvSliceParser::parse_layout`].
+///
+/// # Example
+///
+/// ```rust
+/// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError, SliceLayout};
+/// # use csv::StringRecord;
+/// struct Word { kana: String, english: String }
+/// struct Phrase { japanese: String }
+///
+/// impl FromColumnSlice for Word {
+///     const COLUMN_COUNT: usize = 2;
+///
+///     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+///         let cell = |offset: usize| record.get(start_col + offset).unwrap_or("").to_string();
+///         Ok(Word { kana: cell(0), english: cell(1) })
+///     }
+/// }
+///
+/// impl FromColumnSlice for Phrase {
+///     const COLUMN_COUNT: usize = 1;
+///
+///     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+///         Ok(Phrase { japanese: record.get(start_col).unwrap_or("").to_string() })
+///     }
+/// }
+///
+/// enum Section {
+///     Words(Vec<Word>),
+///     Phrases(Vec<Phrase>),
+/// }
+///
+/// impl From<Vec<Word>> for Section {
+///     fn from(words: Vec<Word>) -> Self { Section::Words(words) }
+/// }
+///
+/// impl From<Vec<Phrase>> for Section {
+///     fn from(phrases: Vec<Phrase>) -> Self { Section::Phrases(phrases) }
+/// }
+///
+/// let data = "Food,,Greetings,Notes\nりんご,apple,おはよう,morning\nみず,water,,\n";
+/// let parser = CsvSliceParser::from_str(data, ParseConfig::default()).unwrap();
+///
+/// // the phrases block has a notes column Phrase doesn't read
+/// let layout = SliceLayout::new().slice::<Word>().slice_with_width::<Phrase>(2);
+/// let sections: Vec<Section> = parser.parse_layout(&layout).unwrap();
+///
+/// match &sections[..] {
+///     [Section::Words(words), Section::Phrases(phrases)] => {
+///         assert_eq!(words.len(), 2);
+///         assert_eq!(phrases[0].japanese, "おはよう");
+///     },
+///     _ => unreachable!(),
+/// }
+/// ```
+pub struct SliceLayout<E> {
+    slices: Vec<LayoutSlice<E>>,
+}
+
+struct LayoutSlice<E> {
+    width: usize,
+    parse: fn(&CsvSliceParser, Range<usize>) -> Result<E, ParseError>,
+}
+
+impl<E> SliceLayout<E> {
+    /// An empty layout, add slices in column order.
+    pub fn new() -> Self {
+        SliceLayout { slices: Vec::new() }
+    }
+
+    /// Add a slice of `T`, `T::COLUMN_COUNT` columns wide.
+    pub fn slice<T: FromColumnSlice>(self) -> Self
+    where
+        E: From<Vec<T>>,
+    {
+        self.slice_with_width::<T>(T::COLUMN_COUNT)
+    }
+
+    /// Add `count` slices of `T` in a row.
+    pub fn slices<T: FromColumnSlice>(self, count: usize) -> Self
+    where
+        E: From<Vec<T>>,
+    {
+        (0..count).fold(self, |layout, _| layout.slice::<T>())
+    }
+
+    /// Add a slice of `T` that is `width` columns wide, for blocks with extra columns
+    /// (notes, ticks) `T` doesn't read. `T` is read from the slice's first column,
+    /// a `width` below `T::MIN_COLUMN_COUNT` fails when parsing.
+    pub fn slice_with_width<T: FromColumnSlice>(mut self, width: usize) -> Self
+    where
+        E: From<Vec<T>>,
+    {
+        self.slices.push(LayoutSlice { width, parse: parse_as::<T, E> });
+        self
+    }
+
+    /// Number of slices in the layout.
+    pub fn len(&self) -> usize {
+        self.slices.len()
+    }
+
+    /// `true` when no slice has been added.
+    pub fn is_empty(&self) -> bool {
+        self.slices.is_empty()
+    }
+
+    /// Columns of every slice, `None` if the arithmetic overflows.
+    fn columns(&self, slice_gap: usize) -> Option<Vec<Range<usize>>> {
+        let mut start = 0usize;
+
+        self.slices.iter()
+            .map(|slice| {
+                let end = start.checked_add(slice.width)?;
+                let columns = start..end;
+                // only the next slice's end can overflow, the last slice needs no gap after it
+                start = end.saturating_add(slice_gap);
+                Some(columns)
+            })
+            .collect()
+    }
+
+    pub(crate) fn parse(&self, parser: &CsvSliceParser, slice_gap: usize) -> Result<Vec<E>, ParseError> {
+        let columns = self.columns(slice_gap)
+            .ok_or_else(|| ParseError::Layout("The layout is wider than any CSV could be".to_string()))?;
+
+        self.slices.iter()
+            .zip(columns)
+            .map(|(slice, columns)| (slice.parse)(parser, columns))
+            .collect()
+    }
+}
+
+impl<E> Default for SliceLayout<E> {
+    fn default() -> Self {
+        SliceLayout::new()
+    }
+}
+
+fn parse_as<T: FromColumnSlice, E: From<Vec<T>>>(parser: &CsvSliceParser, columns: Range<usize>) -> Result<E, ParseError> {
+    parser.parse_slice_columns::<T>(columns).map(E::from)
+}
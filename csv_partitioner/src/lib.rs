@@ -7,15 +7,52 @@
 //! ## Features
 //!
 //! - **Type-safe deserialisation** into custom structs
+//! - **Zero-copy** parsing into types borrowing `&str` fields ([`FromColumnSliceRef`], [`CsvSliceParser::parse_slice_ref`])
+//! - **Typed errors** ([`ParseError`]) that say which row and column failed
+//! - **Lenient** parsing that sets bad rows aside instead of stopping ([`CsvSliceParser::parse_slice_lenient`])
 //! - **Configurable parsing** behaviour
-//!
-//! TODO:
-//! - parallel processing support
+//! - **Cell transforms** applied to every cell in one place, e.g. normalisation ([`ParseConfig::field_transform`])
+//! - **Empty rows** skipped when all, any or chosen cells are blank ([`EmptyPolicy`])
+//! - **Ragged rows** padded or skipped instead of failing ([`RaggedPolicy`])
+//! - **Merging** files with the same layout into one parser ([`CsvSliceParser::from_files`], [`CsvSliceParser::merge`])
+//! - **Delimiter sniffing** for CSV, TSV and semicolon exports, header row included ([`CsvSliceParser::from_file_auto`])
+//! - **Encodings** other than UTF-8 (Shift_JIS, UTF-16LE, BOMs) decoded while reading, with the `encoding` feature ([`InputEncoding`])
+//! - **Batches** of a fixed number of rows for batch APIs ([`CsvSliceParser::parse_slice_chunks`])
+//! - **Filtering** rows while parsing, with queries that map and stop early ([`CsvSliceParser::parse_slice_filtered`], [`SliceQuery`])
+//! - **Random access** to single rows or pages of a slice ([`CsvSliceParser::parse_row`], [`CsvSliceParser::parse_rows`])
+//! - **Row-aligned** access to one row across every slice ([`CsvSliceParser::parse_row_across_slices`])
+//! - **Write-back** of edited slices and saving the CSV ([`ToColumnSlice`], [`CsvSliceParser::write_slice`])
+//! - **Exporting** a single slice to a CSV of its own ([`CsvSliceParser::export_slice_csv`])
+//! - **Progress** reports while loading and parsing big files ([`ProgressEvent`])
+//! - **Streaming** row-by-row parsing for files too big for memory ([`CsvSliceStreamer`])
+//! - **Slice metadata** (columns, headers, row counts) to pick slices before parsing ([`CsvSliceParser::slices`])
+//! - **Schema inference** (column types, blanks, duplicates) to sanity-check a file ([`CsvSliceParser::infer_schema`])
+//! - **Fill statistics** (empty cells, field lengths, skipped rows) to spot half-filled columns ([`CsvSliceParser::slice_stats`])
+//! - **Header validation** against the names every slice should have ([`CsvSliceParser::validate_headers`])
+//! - **Mixed layouts** with a different type per slice ([`SliceLayout`], [`CsvSliceParser::parse_layout`])
+//! - **Column remapping** inside every slice for exports in another order ([`ColumnMap`], [`CsvSliceParser::with_column_map`])
+//! - **Header-driven** slicing for layouts that don't repeat at a fixed width ([`CsvSliceParser::slices_by_header_pattern`])
+//! - **Row-major** blocks for data that repeats downwards instead of across ([`RowSliceParser`])
+//! - **De-duplication** of repeated rows within a slice ([`CsvSliceParser::dedup_slice`])
+//! - **Key lookups** on a slice indexed by a column, e.g. to check for duplicates ([`CsvSliceParser::build_index`])
+//! - **Sorting and grouping** a slice by a key while parsing ([`CsvSliceParser::parse_slice_sorted_by`], [`CsvSliceParser::parse_slice_grouped_by`])
+//! - **Diffing** two versions of a slice ([`CsvSliceParser::diff_slice`], [`diff_by_key`])
+//! - **Schema diffs** of two versions of a file: added, removed and changed slices ([`CsvSliceParser::schema_diff`])
+//! - **Row diffs** of two versions of a file, keyed by a column, for delta imports ([`CsvSliceParser::diff_records`])
+//! - **Sampling** a few rows of a slice for previews ([`CsvSliceParser::sample_slice`])
+//! - **Spreadsheets** (`.xlsx`, `.xls`, `.ods`) read directly with the `xlsx` feature ([`CsvSliceParser::from_xlsx`])
+//! - **Memory-mapped** files parsed in place, without loading every record, with the `mmap` feature ([`CsvSliceParser::from_file_mmap`])
+//! - **Google Sheets** links read through the CSV export with the `google-sheets` feature ([`CsvSliceParser::from_google_sheet`])
+//! - **Async** loading on tokio that doesn't block the runtime, with the `async` feature ([`CsvSliceParser::from_reader_async`])
+//! - **Serde** deserialisation of slices with the `serde` feature ([`CsvSliceParser::parse_slice_serde`])
+//! - **Sharing** one parser between threads, a cheap view per slice ([`CsvSliceParser::into_shared`], [`CsvSliceParser::par_slices`])
+//! - **Parallel** slice parsing with the `rayon` feature ([`CsvSliceParser::parse_all_slices_parallel`])
+//! - **WASM** builds for `wasm32-unknown-unknown` without the default `fs` feature, loading from strings and readers only (see `examples/wasm.rs`)
 //!
 //! ## Quick Start
 //!
 //! ```rust
-//! use csv_slice_parser::{CsvSliceParser, FromColumnSlice};
+//! use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseError};
 //! use csv::StringRecord;
 //! use std::error::Error;
 //!
@@ -31,16 +68,16 @@
 //! impl FromColumnSlice for VocabEntry {
 //!     const COLUMN_COUNT: usize = 3;
 //!
-//!     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, Box<dyn Error>> {
+//!     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
 //!         Ok(VocabEntry {
 //!             word: record.get(start_col)
-//!                 .ok_or("Missing word")?
+//!                 .ok_or_else(|| ParseError::missing_field(start_col, "word"))?
 //!                 .to_string(),
 //!             translation: record.get(start_col + 1)
-//!                 .ok_or("Missing translation")?
+//!                 .ok_or_else(|| ParseError::missing_field(start_col + 1, "translation"))?
 //!                 .to_string(),
 //!             example: record.get(start_col + 2)
-//!                 .ok_or("Missing example")?
+//!                 .ok_or_else(|| ParseError::missing_field(start_col + 2, "example"))?
 //!                 .to_string(),
 //!         })
 //!     }
@@ -48,10 +85,10 @@
 //!
 //! // 3. parse your CSV
 //! # fn example() -> Result<(), Box<dyn Error>> {
-//!     let parser = CsvSliceParser::from_file("vocabulary.csv")?;
-//!     let slice1_entries = Vec<VocabEntry> = parser.parse_slice(0)?;
-//!     Ok(())
-//! }
+//! let parser = CsvSliceParser::from_file("vocabulary.csv")?;
+//! let slice1_entries: Vec<VocabEntry> = parser.parse_slice(0)?;
+//! # Ok(())
+//! # }
 //! ```
 //!
 //! ## CSV structure example
@@ -63,11 +100,79 @@
 //! ```
 
 
-use csv::{ReaderBuilder, StringRecord};
-use std::error::Error;
-use std::fs::{File};
+#[cfg(feature = "async")]
+mod async_io;
+mod column_map;
+mod diff;
+mod empty;
+mod encoding;
+mod error;
+mod filled;
+#[cfg(feature = "google-sheets")]
+mod google;
+mod headers;
+mod index;
+mod info;
+mod layout;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod rows;
+mod progress;
+mod query;
+mod ragged;
+mod record_diff;
+mod sample;
+mod schema;
+mod schema_diff;
+mod shared;
+mod sniff;
+mod stats;
+mod streaming;
+mod transform;
+#[cfg(feature = "xlsx")]
+mod xlsx;
+
+pub use column_map::ColumnMap;
+pub use diff::{diff_by_key, SliceDiff};
+pub use empty::EmptyPolicy;
+pub use encoding::InputEncoding;
+pub use error::{ParseError, RowError};
+#[cfg(feature = "google-sheets")]
+pub use google::{download_google_sheet, fetch_google_sheet, google_sheet_csv_url, GoogleSheet};
+pub use headers::{normalize_header, HeaderMatching, HeaderMismatch, SliceHeaders};
+pub use index::SliceIndex;
+pub use info::SliceInfo;
+pub use layout::SliceLayout;
+#[cfg(feature = "mmap")]
+pub use mmap::MmapSliceParser;
+pub use rows::{RowBlock, RowSliceParser};
+pub use progress::{ProgressEvent, ProgressHook};
+pub use query::SliceQuery;
+pub use ragged::RaggedPolicy;
+pub use record_diff::{RecordDiff, SliceRecordDiff};
+pub use sample::SampleStrategy;
+pub use schema::{ColumnSchema, ColumnType, SliceSchema};
+pub use schema_diff::{SchemaDiff, SliceChange};
+pub use shared::SliceView;
+pub use sniff::{sniff_format, SniffedFormat, SNIFF_BYTES};
+pub use stats::{ColumnStats, SliceStats};
+pub use streaming::{CsvSliceStreamer, SliceRows};
+pub use transform::{ColumnRef, FieldTransform};
+
+use csv::{Reader, ReaderBuilder, StringRecord, WriterBuilder};
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "fs")]
+use std::fs::File;
+use std::io::{Read, Write};
+use std::ops::Range;
+#[cfg(feature = "fs")]
 use std::path::Path;
 
+use encoding::DecodingReader;
+use filled::FilledCells;
+use progress::PROGRESS_INTERVAL;
+use schema::ColumnScan;
+
 /// Trait for types that can be deserialized from a slice of CSV columns.
 ///
 /// Implement this trait to define how your struct maps to CSV columns.
@@ -78,6 +183,74 @@ pub trait FromColumnSlice: Sized {
     /// this should be 3.
     const COLUMN_COUNT: usize;
 
+    /// The fewest columns a slice can have, for types whose trailing columns are optional.
+    ///
+    /// When the columns after the last whole slice are at least this many, they make one more,
+    /// narrower slice, e.g. a topic without a kanji column at the end of a kana, English, kanji
+    /// sheet. `from_record` then gets `None` from `record.get` for the missing columns (rows
+    /// longer than the headers still have their extra fields there). Slices in the middle are
+    /// always `COLUMN_COUNT` wide. Default: `COLUMN_COUNT`, no narrower slices.
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// struct Word { kana: String, english: String, kanji: Option<String> }
+    ///
+    /// impl FromColumnSlice for Word {
+    ///     const COLUMN_COUNT: usize = 3;
+    ///     const MIN_COLUMN_COUNT: usize = 2;
+    ///
+    ///     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    ///         let field = |offset| record.get(start_col + offset).unwrap_or("").to_string();
+    ///         let kanji = record.get(start_col + 2).filter(|kanji| !kanji.is_empty()).map(str::to_string);
+    ///         Ok(Word { kana: field(0), english: field(1), kanji })
+    ///     }
+    /// }
+    ///
+    /// let data = "Food,,,Colours,\nりんご,apple,林檎,あか,red\n";
+    /// let parser = CsvSliceParser::from_str(data, ParseConfig::default()).unwrap();
+    ///
+    /// assert_eq!(parser.slice_count::<Word>(), 2);
+    /// assert_eq!(parser.slice_columns::<Word>(1), Some(3..5));
+    /// assert_eq!(parser.parse_slice::<Word>(1).unwrap()[0].kanji, None);
+    /// ```
+    const MIN_COLUMN_COUNT: usize = Self::COLUMN_COUNT;
+
+    /// Compile-time guard against zero width slices.
+    ///
+    /// Evaluated whenever the parser is used with `Self`, so a `COLUMN_COUNT` of 0, or a
+    /// `MIN_COLUMN_COUNT` outside of `1..=COLUMN_COUNT`, fails the build instead of
+    /// panicking at runtime.
+    ///
+    /// ```rust,compile_fail
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # use std::error::Error;
+    /// struct Empty;
+    ///
+    /// impl FromColumnSlice for Empty {
+    ///     const COLUMN_COUNT: usize = 0;
+    ///     fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> { Ok(Empty) }
+    /// }
+    ///
+    /// let parser = CsvSliceParser::from_records(StringRecord::new(), vec![], ParseConfig::default());
+    /// parser.slice_count::<Empty>(); // error: COLUMN_COUNT must be greater than 0
+    /// ```
+    #[doc(hidden)]
+    const NON_ZERO_WIDTH: () = {
+        assert!(Self::COLUMN_COUNT > 0, "FromColumnSlice::COLUMN_COUNT must be greater than 0");
+        assert!(
+            Self::MIN_COLUMN_COUNT > 0 && Self::MIN_COLUMN_COUNT <= Self::COLUMN_COUNT,
+            "FromColumnSlice::MIN_COLUMN_COUNT must be between 1 and COLUMN_COUNT"
+        );
+    };
+
+    /// Header names every slice should have, one per column (`"*"` for any name).
+    ///
+    /// Checked by [`CsvSliceParser::validate_declared_headers`]. Empty (the default)
+    /// means the headers aren't checked.
+    const EXPECTED_HEADERS: &'static [&'static str] = &[];
+
 
     /// Deserialise from a 'StringRecord' starting at the given column index
     ///
@@ -89,33 +262,93 @@ pub trait FromColumnSlice: Sized {
     /// # Returns
     ///
     /// * 'Ok(Self)' - successfully returns parsed struct
-    /// * 'Err(Box<dyn Error>)' - parsing error with description
+    /// * 'Err(ParseError)' - usually [`ParseError::missing_field`], the parser fills in the row
     ///
     /// # Example
     ///
     /// ```rust
-    /// # use csv_slice_parser::FromColumnSlice;
+    /// # use csv_partitioner::{FromColumnSlice, ParseError};
     /// # use csv::StringRecord;
     /// # use std::error::Error;
     /// # struct MyStruct { field1: String, field2: String }
     /// # impl FromColumnSlice for MyStruct {
     /// #     const COLUMN_COUNT: usize = 2;
-    /// fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, Box<dyn Error>> {
+    /// fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
     ///     // Validate column exists before parsing
     ///     let field1 = record.get(start_col)
-    ///         .ok_or("Missing first column")?
+    ///         .ok_or_else(|| ParseError::missing_field(start_col, "first column"))?
     ///         .to_string();
     ///
-    ///     // Parse numeric types with error handling
+    ///     // Other failures (e.g. parsing numbers) can be wrapped with ParseError::custom
     ///     let field2 = record.get(start_col + 1)
-    ///         .ok_or("Missing second column")?
+    ///         .ok_or_else(|| ParseError::missing_field(start_col + 1, "second column"))?
     ///         .to_string();
     ///
     ///     Ok(MyStruct { field1, field2 })
     /// }
     /// # }
     /// ```
-    fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, Box<dyn Error>>;
+    fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError>;
+}
+
+
+/// Trait for types that can be written back into a slice of CSV columns,
+/// the other direction of [`FromColumnSlice`].
+///
+/// Used by [`CsvSliceParser::write_slice`] to put edited values back before saving.
+pub trait ToColumnSlice: FromColumnSlice {
+    /// The value's cells, in column order, exactly `COLUMN_COUNT` of them.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{FromColumnSlice, ParseError, ToColumnSlice};
+    /// # use csv::StringRecord;
+    /// # struct Pair { word: String, translation: String }
+    /// # impl FromColumnSlice for Pair {
+    /// #     const COLUMN_COUNT: usize = 2;
+    /// #     fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> { unimplemented!() }
+    /// # }
+    /// impl ToColumnSlice for Pair {
+    ///     fn to_fields(&self) -> Vec<String> {
+    ///         vec![self.word.clone(), self.translation.clone()]
+    ///     }
+    /// }
+    /// ```
+    fn to_fields(&self) -> Vec<String>;
+}
+
+
+/// Borrowing counterpart of [`FromColumnSlice`], for types that keep `&str` fields
+/// pointing into the parser's records instead of copying them into `String`s.
+///
+/// Used by [`CsvSliceParser::parse_slice_ref`]. The values can't outlive the parser,
+/// which makes it a fit for read-only passes (counting, searching, checking) over big files.
+///
+/// # Example
+///
+/// ```rust
+/// # use csv_partitioner::{FromColumnSliceRef, ParseError};
+/// # use csv::StringRecord;
+/// struct Pair<'a> { word: &'a str, translation: &'a str }
+///
+/// impl<'a> FromColumnSliceRef<'a> for Pair<'a> {
+///     const COLUMN_COUNT: usize = 2;
+///
+///     fn from_record(record: &'a StringRecord, start_col: usize) -> Result<Self, ParseError> {
+///         Ok(Pair {
+///             word: record.get(start_col).ok_or_else(|| ParseError::missing_field(start_col, "word"))?,
+///             translation: record.get(start_col + 1).unwrap_or(""),
+///         })
+///     }
+/// }
+/// ```
+pub trait FromColumnSliceRef<'a>: Sized {
+    /// The number of columns this type contains, as for [`FromColumnSlice::COLUMN_COUNT`]
+    const COLUMN_COUNT: usize;
+
+    /// Borrow the fields from `record` starting at `start_col`
+    fn from_record(record: &'a StringRecord, start_col: usize) -> Result<Self, ParseError>;
 }
 
 
@@ -127,21 +360,39 @@ pub trait FromColumnSlice: Sized {
 ///
 /// ```rust
 /// use csv_partitioner::ParseConfig;
-/// use csv_slice_parser::ParseConfig;
+/// use csv_partitioner::HeaderMatching;
 ///
 /// let config = ParseConfig {
-///     skip_empty_rows = true,
-///     reserve_capacity = true,
-///     trim_fields = true,
-/// }
+///     skip_empty_rows: true,
+///     reserve_capacity: true,
+///     trim_fields: true,
+///     header_matching: HeaderMatching::Normalized,
+///     skip_marker: Some("!skip".to_string()),
+///     slice_gap: 0,
+///     ..ParseConfig::default()
+/// };
+///
+/// // or with the builder, e.g. for a semicolon separated export
+/// let config = ParseConfig::builder()
+///     .delimiter(b';')
+///     .quote(b'\'')
+///     .comment(Some(b'#'))
+///     .has_headers(false)
+///     .build();
 /// ```
 pub struct ParseConfig {
-    /// Skip rows where all columns in the slice are empty.
+    /// Skip rows that are empty in the slice, see `empty_row_policy` for what counts as empty.
     ///
     /// When `true`, rows like `"", "", ""` are filtered out.
     /// Default: `true`
     pub skip_empty_rows: bool,
 
+    /// When a row counts as empty for `skip_empty_rows`.
+    ///
+    /// `AnyEmpty` also skips rows missing only some of the slice's cells.
+    /// Default: `EmptyPolicy::AllEmpty`
+    pub empty_row_policy: EmptyPolicy,
+
     /// Pre-allocate capacity in result vectors based on record count.
     ///
     /// When `true`. reduces reallocations but uses more memory upfront.
@@ -153,24 +404,289 @@ pub struct ParseConfig {
     /// When `true`, `" hello "` becomes `"hello"`.
     /// Default: `true`
     pub trim_fields: bool,
+
+    /// How header names are compared by header-driven lookups like [`CsvSliceParser::find_header`].
+    ///
+    /// When `Normalized`, `"English"`, `"english "` and `"ＥＮＧＬＩＳＨ"` (with the `nfkc` feature) all match.
+    /// Default: `HeaderMatching::Normalized`
+    pub header_matching: HeaderMatching,
+
+    /// Skip rows where any column in the slice holds exactly this value (after trimming).
+    ///
+    /// Lets a spreadsheet keep rows that aren't ready yet, e.g. `!skip` in a spare column.
+    /// Only the slice's own columns are checked, so other slices on the same row are unaffected.
+    /// Default: `None`
+    pub skip_marker: Option<String>,
+
+    /// Number of separator columns between consecutive slices.
+    ///
+    /// For spreadsheets with an empty column between topic blocks: with a gap of 1 and
+    /// 3 column slices, slices start at columns 0, 4, 8, ... The gap columns are never read.
+    /// Default: `0`
+    pub slice_gap: usize,
+
+    /// Field delimiter, e.g. `b'\t'` for TSV or `b';'` for spreadsheets from locales with decimal commas.
+    /// Default: `b','`
+    pub delimiter: u8,
+
+    /// Quote character around fields that contain delimiters or newlines.
+    /// Default: `b'"'`
+    pub quote: u8,
+
+    /// Lines starting with this byte are ignored entirely.
+    /// Default: `None`
+    pub comment: Option<u8>,
+
+    /// Whether the first row holds headers.
+    ///
+    /// When `false` the first row is data, and headers are blank (one per column of the first row).
+    /// Default: `true`
+    pub has_headers: bool,
+
+    /// Rows to skip above the header row, e.g. a title row over the table.
+    ///
+    /// Without headers, the rows skipped above the data. They're kept for saving, but no
+    /// slice sees them. With `ragged_rows: Error` they have to be as wide as the rest,
+    /// as they are in spreadsheet exports, set `flexible` for shorter ones.
+    /// Default: `0`
+    pub header_row: usize,
+
+    /// Number of header rows stacked on top of each other, 0 counts as 1.
+    ///
+    /// E.g. 2 for topic names on one row and field names on the row below. The last one is
+    /// [`CsvSliceParser::headers`], the row slices are named by and validated against, all
+    /// of them are in [`CsvSliceParser::header_rows`]. Ignored without headers.
+    /// Default: `1`
+    pub header_rows: usize,
+
+    /// Text encoding of the input, decoded to UTF-8 while reading.
+    ///
+    /// Ignored by `from_str` (the text is already decoded), saving always writes UTF-8.
+    /// Default: `InputEncoding::Utf8`
+    pub encoding: InputEncoding,
+
+    /// What to do with rows that have fewer fields than the headers.
+    ///
+    /// `PadEmpty` fills them up with empty fields, `Skip` leaves them out of the slices
+    /// they don't reach.
+    /// Default: `RaggedPolicy::Error`
+    pub ragged_rows: RaggedPolicy,
+
+    /// Read rows with more or fewer fields than the headers as they are.
+    ///
+    /// Sets `ReaderBuilder::flexible`, so with `RaggedPolicy::Error` a row of another
+    /// width isn't a CSV error. Neither padded nor skipped, a short row's missing cells are
+    /// `None` to `from_record`. The other ragged policies read flexibly anyway.
+    /// Default: `false`
+    pub flexible: bool,
+
+    /// Called with a [`ProgressEvent`] while records load and while slices parse.
+    ///
+    /// Only loading (into a [`CsvSliceParser`], or mapping a file) and the methods that parse whole slices
+    /// (`parse_slice`, `parse_slice_lenient`, `parse_all_slices`, layouts) report.
+    /// Default: `None`
+    pub progress: Option<ProgressHook>,
+
+    /// Rewrites every cell of every row as it's loaded, before any slice sees it.
+    ///
+    /// For clean-ups every struct would otherwise repeat, e.g. NFC normalisation,
+    /// full-width to half-width, or stripping furigana brackets. Runs after `trim_fields`
+    /// and `ragged_rows`, headers are left as they are. Saving writes the rewritten cells.
+    /// Default: `None`
+    pub field_transform: Option<FieldTransform>,
 }
 
 impl Default for ParseConfig {
     fn default() -> Self {
         ParseConfig {
             skip_empty_rows: true,
+            empty_row_policy: EmptyPolicy::AllEmpty,
             reserve_capacity: true,
             trim_fields: true,
+            header_matching: HeaderMatching::default(),
+            skip_marker: None,
+            slice_gap: 0,
+            delimiter: b',',
+            quote: b'"',
+            comment: None,
+            has_headers: true,
+            header_row: 0,
+            header_rows: 1,
+            encoding: InputEncoding::Utf8,
+            ragged_rows: RaggedPolicy::Error,
+            flexible: false,
+            progress: None,
+            field_transform: None,
+        }
+    }
+}
+
+impl ParseConfig {
+    /// Start from the defaults and change only what's needed.
+    pub fn builder() -> ParseConfigBuilder {
+        ParseConfigBuilder { config: ParseConfig::default() }
+    }
+
+    /// hand `event` to the progress hook, if there is one
+    fn report(&self, event: impl FnOnce() -> ProgressEvent) {
+        if let Some(progress) = &self.progress {
+            progress(event());
+        }
+    }
+
+    /// title rows and header rows, the rows above the data
+    fn rows_above_data(&self) -> usize {
+        self.header_row + if self.has_headers { self.header_rows.max(1) } else { 0 }
+    }
+
+    /// whether the csv reader has to accept rows of any length
+    fn is_flexible(&self) -> bool {
+        self.flexible || self.ragged_rows.is_flexible()
+    }
+
+    /// pad and rewrite a freshly read `record` as `ragged_rows` and `field_transform` say
+    fn prepare(&self, record: &mut StringRecord, headers: &StringRecord) {
+        self.ragged_rows.apply(record, headers.len());
+        if let Some(transform) = &self.field_transform {
+            *record = transform::transform_record(transform, record, headers);
+        }
+    }
+
+    /// refuse a `slice_gap` too big to add to the width of `headers`, slice counting would
+    /// have nowhere to go but clamp it
+    fn check_slice_gap(&self, headers: &StringRecord) -> Result<(), ParseError> {
+        match headers.len().checked_add(self.slice_gap) {
+            Some(_) => Ok(()),
+            None => Err(ParseError::Layout(format!("slice_gap {} is too big for {} columns", self.slice_gap, headers.len()))),
         }
     }
 }
 
+/// Builder for [`ParseConfig`], every method sets the field of the same name.
+///
+/// # Example
+///
+/// ```rust
+/// # use csv_partitioner::{CsvSliceParser, ParseConfig};
+/// let data = "Food\tVerbs\n# not ready yet\nりんご\tいく\n";
+/// let config = ParseConfig::builder().delimiter(b'\t').comment(Some(b'#')).build();
+///
+/// let parser = CsvSliceParser::from_reader(data.as_bytes(), config).unwrap();
+/// assert_eq!(parser.headers().len(), 2);
+/// assert_eq!(parser.record_count(), 1);
+///
+/// // without a header row every line is data
+/// let config = ParseConfig::builder().delimiter(b';').has_headers(false).build();
+/// let parser = CsvSliceParser::from_reader("りんご;apple\nいく;to go\n".as_bytes(), config).unwrap();
+/// assert_eq!(parser.record_count(), 2);
+/// assert_eq!(parser.headers().iter().collect::<Vec<_>>(), vec!["", ""]);
+/// ```
+pub struct ParseConfigBuilder {
+    config: ParseConfig,
+}
+
+impl ParseConfigBuilder {
+    pub fn skip_empty_rows(mut self, skip_empty_rows: bool) -> Self {
+        self.config.skip_empty_rows = skip_empty_rows;
+        self
+    }
+
+    pub fn empty_row_policy(mut self, empty_row_policy: EmptyPolicy) -> Self {
+        self.config.empty_row_policy = empty_row_policy;
+        self
+    }
+
+    pub fn reserve_capacity(mut self, reserve_capacity: bool) -> Self {
+        self.config.reserve_capacity = reserve_capacity;
+        self
+    }
+
+    pub fn trim_fields(mut self, trim_fields: bool) -> Self {
+        self.config.trim_fields = trim_fields;
+        self
+    }
+
+    pub fn header_matching(mut self, header_matching: HeaderMatching) -> Self {
+        self.config.header_matching = header_matching;
+        self
+    }
+
+    pub fn skip_marker(mut self, skip_marker: Option<String>) -> Self {
+        self.config.skip_marker = skip_marker;
+        self
+    }
+
+    pub fn slice_gap(mut self, slice_gap: usize) -> Self {
+        self.config.slice_gap = slice_gap;
+        self
+    }
+
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.config.delimiter = delimiter;
+        self
+    }
+
+    pub fn quote(mut self, quote: u8) -> Self {
+        self.config.quote = quote;
+        self
+    }
+
+    pub fn comment(mut self, comment: Option<u8>) -> Self {
+        self.config.comment = comment;
+        self
+    }
+
+    pub fn has_headers(mut self, has_headers: bool) -> Self {
+        self.config.has_headers = has_headers;
+        self
+    }
+
+    pub fn header_row(mut self, header_row: usize) -> Self {
+        self.config.header_row = header_row;
+        self
+    }
+
+    pub fn header_rows(mut self, header_rows: usize) -> Self {
+        self.config.header_rows = header_rows;
+        self
+    }
+
+    pub fn encoding(mut self, encoding: InputEncoding) -> Self {
+        self.config.encoding = encoding;
+        self
+    }
+
+    pub fn ragged_rows(mut self, ragged_rows: RaggedPolicy) -> Self {
+        self.config.ragged_rows = ragged_rows;
+        self
+    }
+
+    pub fn flexible(mut self, flexible: bool) -> Self {
+        self.config.flexible = flexible;
+        self
+    }
+
+    pub fn progress(mut self, progress: impl Fn(ProgressEvent) + Send + Sync + 'static) -> Self {
+        self.config.progress = Some(Box::new(progress));
+        self
+    }
+
+    pub fn field_transform(mut self, field_transform: impl Fn(&str, ColumnRef<'_>) -> String + Send + Sync + 'static) -> Self {
+        self.config.field_transform = Some(Box::new(field_transform));
+        self
+    }
+
+    pub fn build(self) -> ParseConfig {
+        self.config
+    }
+}
+
 /// Main parser
 /// 
 /// # Example
 ///
 /// ```rust
-/// use csv_slice_parser::{CsvSliceParser, FromColumnSlice, ParseConfig};
+/// use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
 /// use csv::StringRecord;
 /// use std::error::Error;
 ///
@@ -184,7 +700,7 @@ impl Default for ParseConfig {
 /// impl FromColumnSlice for Entry {
 ///     const COLUMN_COUNT: usize = 3;
 ///     
-///     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, Box<dyn Error>> {
+///     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
 ///         Ok(Entry {
 ///             field1: record.get(start_col).unwrap_or("").to_string(),
 ///             field2: record.get(start_col + 1).unwrap_or("").to_string(),
@@ -215,6 +731,8 @@ pub struct CsvSliceParser {
     headers: StringRecord,
     records: Vec<StringRecord>,
     config: ParseConfig,
+    /// title rows, then the header rows over `headers`, as they were in the file
+    top_rows: Vec<StringRecord>,
 }
 
 impl CsvSliceParser {
@@ -227,18 +745,19 @@ impl CsvSliceParser {
     /// # Returns
     ///
     /// * `Ok(CsvSliceParser)` - Successfully loaded parser
-    /// * `Err(Box<dyn Error>)` - I/O or parsing error
+    /// * `Err(ParseError)` - `Io` (including bytes that aren't valid in `ParseConfig::encoding`) or `Csv` error
     /// # Example
     ///
     /// ```rust,no_run
-    /// # use csv_slice_parser::CsvSliceParser;
+    /// # use csv_partitioner::CsvSliceParser;
     /// # use std::error::Error;
     /// # fn example() -> Result<(), Box<dyn Error>> {
     /// let parser = CsvSliceParser::from_file("vocabulary.csv")?;
     /// # Ok(())
     /// # }
     /// ```
-    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+    #[cfg(feature = "fs")]
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ParseError> {
         Self::from_file_with_config(path, ParseConfig::default())
     }
 
@@ -252,47 +771,106 @@ impl CsvSliceParser {
     /// # Example
     ///
     /// ```rust,no_run
-    /// # use csv_slice_parser::{CsvSliceParser, ParseConfig};
+    /// # use csv_partitioner::{CsvSliceParser, ParseConfig};
     /// # use std::error::Error;
     /// # fn example() -> Result<(), Box<dyn Error>> {
     /// let config = ParseConfig {
     ///     skip_empty_rows: false,  // Keep all rows
     ///     reserve_capacity: true,
     ///     trim_fields: false,      // Keep whitespace
+    ///     ..ParseConfig::default()
     /// };
     /// let parser = CsvSliceParser::from_file_with_config("data.csv", config)?;
     /// # Ok(())
     /// # }
     /// ```
+    #[cfg(feature = "fs")]
     pub fn from_file_with_config<P: AsRef<Path>>(
         path: P,
         config: ParseConfig
-    ) -> Result<Self, Box<dyn Error>> {
+    ) -> Result<Self, ParseError> {
         let file = File::open(path)?;
-        let mut reader = ReaderBuilder::new()
-            .has_headers(true)
-            .trim(csv::Trim::All)
-            .from_reader(file);
+        Self::from_reader(file, config)
+    }
 
-        let headers = reader.headers()?.clone();
+    /// Load CSV data from any reader with custom configuration.
+    ///
+    /// Useful for data that doesn't live on disk (network responses, embedded strings).
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Source of CSV bytes
+    /// * `config` - Custom parsing configuration
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, ParseConfig};
+    /// # use std::error::Error;
+    /// # fn example() -> Result<(), Box<dyn Error>> {
+    /// let data = "A,B,C\n1,2,3\n";
+    /// let parser = CsvSliceParser::from_reader(data.as_bytes(), ParseConfig::default())?;
+    /// assert_eq!(parser.record_count(), 1);
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn from_reader<R: Read>(
+        reader: R,
+        config: ParseConfig
+    ) -> Result<Self, ParseError> {
+        let mut reader = csv_reader(reader, &config);
+        let (top_rows, headers, first) = read_headers(&mut reader, &config)?;
 
         let mut records: Vec<StringRecord> = if config.reserve_capacity {
             Vec::with_capacity(headers.len())
         } else {
             Vec::new()
         };
+        if let Some(mut first) = first {
+            config.prepare(&mut first, &headers);
+            records.push(first);
+        }
 
-        for result in reader.records() {
-            records.push(result?);
+        let mut record = StringRecord::new();
+        while reader.read_record(&mut record)? {
+            config.prepare(&mut record, &headers);
+            records.push(std::mem::take(&mut record));
+
+            if records.len().is_multiple_of(PROGRESS_INTERVAL) {
+                config.report(|| ProgressEvent::Loading { records: records.len(), bytes: reader.position().byte() });
+            }
         }
+        config.report(|| ProgressEvent::Loaded { records: records.len() });
 
         if config.reserve_capacity {
             records.shrink_to_fit();
         }
 
-        Ok(CsvSliceParser { headers, records, config })
+        Ok(CsvSliceParser { headers, records, config, top_rows })
     }
 
+    /// Load CSV data held in a string with custom configuration.
+    ///
+    /// Shorthand for [`from_reader`](Self::from_reader) on the string's bytes, handy for
+    /// tests and for data that was already downloaded or decoded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, ParseConfig};
+    /// let parser = CsvSliceParser::from_str("A,B,C\n1,2,3\n4,5,6\n", ParseConfig::default()).unwrap();
+    /// assert_eq!(parser.record_count(), 2);
+    /// assert_eq!(parser.find_header("B"), Some(1));
+    /// ```
+    pub fn from_str(data: &str, config: ParseConfig) -> Result<Self, ParseError> {
+        // already text, decoding it again would garble it
+        let encoding = config.encoding;
+        let mut parser = Self::from_reader(data.as_bytes(), ParseConfig { encoding: InputEncoding::Utf8, ..config })?;
+        parser.config.encoding = encoding;
+
+        Ok(parser)
+    }
 
     /// Create a parser from in-memory `StringRecord` data.
     ///
@@ -301,7 +879,7 @@ impl CsvSliceParser {
     /// # Example
     ///
     /// ```rust
-    /// # use csv_slice_parser::{CsvSliceParser, ParseConfig};
+    /// # use csv_partitioner::{CsvSliceParser, ParseConfig};
     /// # use csv::StringRecord;
     /// let mut headers = StringRecord::new();
     /// headers.push_field("A");
@@ -321,270 +899,1951 @@ impl CsvSliceParser {
     /// ```
     pub fn from_records(
         headers: StringRecord,
-        records: Vec<StringRecord>,
+        mut records: Vec<StringRecord>,
         config: ParseConfig,
     ) -> Self {
-        CsvSliceParser { headers, records, config }
+        for record in &mut records {
+            config.prepare(record, &headers);
+        }
+
+        CsvSliceParser { headers, records, config, top_rows: Vec::new() }
     }
 
-    /// Get the number of column slices available for a given type.
+    /// Create a parser from plain rows of cells, without building `StringRecord`s.
+    ///
+    /// Rows can be ragged (shorter or longer than the headers), short ones are padded when
+    /// `ragged_rows` is `PadEmpty`. Meant for tests and for data that didn't come from a file.
     ///
     /// # Example
     ///
     /// ```rust
-    /// # use csv_slice_parser::{CsvSliceParser, FromColumnSlice};
-    /// # use csv::StringRecord;
-    /// # use std::error::Error;
-    /// # struct MyType;
-    /// # impl FromColumnSlice for MyType {
-    /// #     const COLUMN_COUNT: usize = 3;
-    /// #     fn from_record(_: &StringRecord, _: usize) -> Result<Self, Box<dyn Error>> { Ok(MyType) }
-    /// # }
-    /// # fn example() -> Result<(), Box<dyn Error>> {
-    /// # let parser = CsvSliceParser::from_file("data.csv")?;
-    /// let count = parser.slice_count::<MyType>();
-    /// println!("Found {} slices of {} columns each", count, MyType::COLUMN_COUNT);
-    /// # Ok(())
-    /// # }
+    /// # use csv_partitioner::{CsvSliceParser, ParseConfig};
+    /// let parser = CsvSliceParser::from_rows(
+    ///     ["Spanish", "English"],
+    ///     [vec!["hola", "hello"], vec!["adiós"]],
+    ///     ParseConfig::default(),
+    /// );
+    ///
+    /// assert_eq!(parser.record_count(), 2);
+    /// assert_eq!(parser.records()[1].len(), 1);
     /// ```
-    #[inline]
-    pub fn slice_count<T: FromColumnSlice>(&self) -> usize {
-        self.headers.len() / T::COLUMN_COUNT
+    pub fn from_rows<H, R, S>(
+        headers: impl IntoIterator<Item = H>,
+        rows: impl IntoIterator<Item = R>,
+        config: ParseConfig,
+    ) -> Self
+    where
+        H: AsRef<str>,
+        R: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let headers = headers.into_iter().collect();
+        let records = rows.into_iter().map(|row| row.into_iter().collect()).collect();
+
+        Self::from_records(headers, records, config)
     }
 
-    /// Get the total number of records (rows) in the CSV.
+    /// Load several CSV files with the same layout as one, with a default configuration.
+    ///
+    /// See [`from_files_with_config`](Self::from_files_with_config).
+    #[cfg(feature = "fs")]
+    pub fn from_files<P: AsRef<Path>>(paths: &[P]) -> Result<Self, ParseError> {
+        Self::from_files_with_config(paths, ParseConfig::default())
+    }
+
+    /// Load several CSV files with the same layout as one, e.g. one file per semester.
+    ///
+    /// Records follow each other in the order of `paths`, so row numbers in errors count
+    /// through all of them. Every file needs the first one's headers, compared with
+    /// `header_matching`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CsvSliceParser)` - the records of every file
+    /// * `Err(ParseError::Layout)` - `paths` is empty, or a file's headers don't match (the
+    ///   message names the file)
+    /// * `Err(ParseError)` - `Io` or `Csv` when a file can't be read
     ///
     /// # Example
     ///
-    /// ```rust
-    /// # use csv_slice_parser::CsvSliceParser;
+    /// ```rust,no_run
+    /// # use csv_partitioner::CsvSliceParser;
     /// # use std::error::Error;
     /// # fn example() -> Result<(), Box<dyn Error>> {
-    /// # let parser = CsvSliceParser::from_file("data.csv")?;
-    /// println!("CSV has {} rows", parser.record_count());
+    /// let parser = CsvSliceParser::from_files(&["semester1.csv", "semester2.csv"])?;
     /// # Ok(())
     /// # }
     /// ```
-    #[inline]
-    pub fn record_count(&self) -> usize {
-        self.records.len()
-    }
+    #[cfg(feature = "fs")]
+    pub fn from_files_with_config<P: AsRef<Path>>(paths: &[P], config: ParseConfig) -> Result<Self, ParseError> {
+        let Some((first, rest)) = paths.split_first() else {
+            return Err(ParseError::Layout("No files to load".to_string()));
+        };
 
-    fn validate_slice_index<T: FromColumnSlice>(&self, slice_index: usize) -> Result<(usize, usize), Box<dyn Error>>{
-        let start_col = slice_index * T::COLUMN_COUNT;
-        let end_col = start_col + T::COLUMN_COUNT;
+        let mut merged = Self::from_file_with_config(first, config)?;
 
-        if end_col > self.headers.len() {
-            return Err(format!(
-                "Slice {} out of bounds (columns {}-{} requested, but only {} columns available)",
-                slice_index, start_col, end_col, self.headers.len()
-            ).into());
-        }
+        for path in rest {
+            // the config isn't Clone (it can hold a progress hook), so it's handed along
+            let config = std::mem::take(&mut merged.config);
+            let mut next = Self::from_file_with_config(path, config)?;
+            merged.config = std::mem::take(&mut next.config);
 
-        Ok((start_col, end_col))
-    }
+            merged.merge(next).map_err(|e| ParseError::Layout(format!("{}: {}", path.as_ref().display(), e)))?;
+        }
 
-    fn has_empty_fields(&self, start_col: usize, end_col: usize, record: &StringRecord) -> bool {
-        (start_col..end_col)
-            .all(|i| record.get(i).map_or(true, |s| s.trim().is_empty()))
+        Ok(merged)
     }
 
-    /// Parse a specific column slice into a vector of structs.
+    /// Append the records of a parser with the same headers, e.g. a second file of the same layout.
     ///
-    /// This is the main parsing method. It deserializes all rows for a given
-    /// column slice into your custom struct type.
+    /// Headers are compared with this parser's `header_matching`, `other`'s config is dropped.
     ///
-    /// # Arguments
+    /// # Returns
     ///
-    /// * `slice_index` - Zero-based index of the slice to parse
+    /// * `Ok(())` - `other`'s records come after this parser's
+    /// * `Err(ParseError::Layout)` - the headers don't match, nothing was appended
     ///
-    /// # Returns
+    /// # Example
     ///
-    /// * `Ok(Vec<T>)` - Successfully parsed entries
-    /// * `Err(Box<dyn Error>)` - Slice out of bounds or parsing error
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, ParseConfig};
+    /// let mut spring = CsvSliceParser::from_str("Food,\nりんご,apple\n", ParseConfig::default()).unwrap();
+    /// let autumn = CsvSliceParser::from_str("food ,\nくり,chestnut\n", ParseConfig::default()).unwrap();
+    ///
+    /// spring.merge(autumn).unwrap();
+    /// assert_eq!(spring.record_count(), 2);
+    ///
+    /// let verbs = CsvSliceParser::from_str("Verbs,\nいく,to go\n", ParseConfig::default()).unwrap();
+    /// assert_eq!(
+    ///     spring.merge(verbs).unwrap_err().to_string(),
+    ///     "Headers don't match: column 1 is 'Verbs', expected 'Food'",
+    /// );
+    /// ```
+    pub fn merge(&mut self, other: CsvSliceParser) -> Result<(), ParseError> {
+        if other.headers.len() != self.headers.len() {
+            return Err(ParseError::Layout(format!(
+                "Headers don't match: {} columns, expected {}", other.headers.len(), self.headers.len()
+            )));
+        }
+
+        let mismatches: Vec<String> = self.headers.iter()
+            .zip(other.headers.iter())
+            .enumerate()
+            .filter(|(_, (expected, found))| !self.config.header_matching.matches(expected, found))
+            .map(|(column, (expected, found))| format!("column {} is '{}', expected '{}'", column + 1, found, expected))
+            .collect();
+        if !mismatches.is_empty() {
+            return Err(ParseError::Layout(format!("Headers don't match: {}", mismatches.join(", "))));
+        }
+
+        self.records.extend(other.records);
+
+        Ok(())
+    }
+
+    /// Get the number of column slices available for a given type.
     ///
     /// # Example
     ///
     /// ```rust
-    /// # use csv_slice_parser::{CsvSliceParser, FromColumnSlice};
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseError};
     /// # use csv::StringRecord;
     /// # use std::error::Error;
-    /// # #[derive(Debug)]
-    /// # struct VocabEntry { word: String, translation: String, example: String }
-    /// # impl FromColumnSlice for VocabEntry {
+    /// # struct MyType;
+    /// # impl FromColumnSlice for MyType {
     /// #     const COLUMN_COUNT: usize = 3;
-    /// #     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, Box<dyn Error>> {
-    /// #         Ok(VocabEntry {
-    /// #             word: record.get(start_col).unwrap_or("").to_string(),
-    /// #             translation: record.get(start_col + 1).unwrap_or("").to_string(),
-    /// #             example: record.get(start_col + 2).unwrap_or("").to_string(),
-    /// #         })
-    /// #     }
+    /// #     fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> { Ok(MyType) }
     /// # }
     /// # fn example() -> Result<(), Box<dyn Error>> {
-    /// # let parser = CsvSliceParser::from_file("vocab.csv")?;
-    /// // Parse columns 0-2 (first slice)
-    /// let first_language: Vec<VocabEntry> = parser.parse_slice(0)?;
+    /// # let parser = CsvSliceParser::from_file("data.csv")?;
+    /// let count = parser.slice_count::<MyType>();
+    /// println!("Found {} slices of {} columns each", count, MyType::COLUMN_COUNT);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn slice_count<T: FromColumnSlice>(&self) -> usize {
+        let () = T::NON_ZERO_WIDTH;
+        count_slices::<T>(&self.config, self.headers.len())
+    }
+
+    /// Get the columns covered by a slice, `None` if it's out of bounds.
+    ///
+    /// Accounts for `ParseConfig::slice_gap`, so use this rather than
+    /// `slice_index * COLUMN_COUNT` to find e.g. a slice's header column.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # use std::error::Error;
+    /// # struct Entry;
+    /// # impl FromColumnSlice for Entry {
+    /// #     const COLUMN_COUNT: usize = 3;
+    /// #     fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> { Ok(Entry) }
+    /// # }
+    /// // an empty separator column between the two topics
+    /// let headers = StringRecord::from(vec!["Food", "", "", "", "Verbs", "", ""]);
+    /// let config = ParseConfig { slice_gap: 1, ..ParseConfig::default() };
+    /// let parser = CsvSliceParser::from_records(headers, vec![], config);
+    ///
+    /// assert_eq!(parser.slice_count::<Entry>(), 2);
+    /// assert_eq!(parser.slice_columns::<Entry>(1), Some(4..7));
+    /// assert_eq!(parser.slice_columns::<Entry>(2), None);
+    /// ```
+    pub fn slice_columns<T: FromColumnSlice>(&self, slice_index: usize) -> Option<Range<usize>> {
+        let () = T::NON_ZERO_WIDTH;
+        slice_columns::<T>(&self.config, slice_index, self.headers.len())
+            .map(|(start_col, end_col)| start_col..end_col)
+    }
+
+    /// Describe every slice `T` splits the CSV into, in column order.
+    ///
+    /// Handy for listing or filtering slices (by header, by size) before parsing any of them.
+    /// Reads every row once to count the non-empty ones, nothing is deserialised.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # struct Pair;
+    /// # impl FromColumnSlice for Pair {
+    /// #     const COLUMN_COUNT: usize = 2;
+    /// #     fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> { Ok(Pair) }
+    /// # }
+    /// let data = "Food,,,Verbs,\nrice,ご飯,,to eat,食べる\nbread,パン,,,\n";
+    /// let config = ParseConfig { slice_gap: 1, ..ParseConfig::default() };
+    /// let parser = CsvSliceParser::from_str(data, config).unwrap();
+    ///
+    /// let slices = parser.slices::<Pair>();
+    ///
+    /// assert_eq!(slices.len(), 2);
+    /// assert_eq!((slices[1].index, slices[1].columns.clone()), (1, 3..5));
+    /// assert_eq!(slices[1].headers, vec!["Verbs", ""]);
+    /// assert_eq!(slices[0].non_empty_rows, 2);
+    /// assert_eq!(slices[1].non_empty_rows, 1);
+    ///
+    /// let verbs = slices.iter().find(|slice| slice.name() == "Verbs").unwrap();
+    /// assert_eq!(parser.parse_slice::<Pair>(verbs.index).unwrap().len(), 1);
+    /// ```
+    pub fn slices<T: FromColumnSlice>(&self) -> Vec<SliceInfo> {
+        let mut slices: Vec<SliceInfo> = (0..self.slice_count::<T>())
+            .filter_map(|index| self.slice_columns::<T>(index).map(|columns| (index, columns)))
+            .map(|(index, columns)| SliceInfo {
+                index,
+                headers: columns.clone().map(|i| self.headers.get(i).unwrap_or("").to_string()).collect(),
+                columns,
+                non_empty_rows: 0,
+            })
+            .collect();
+
+        // one pass over the rows for all slices, rows are the long side
+        for record in &self.records {
+            for slice in &mut slices {
+                if !is_blank(record, slice.columns.start, slice.columns.end) {
+                    slice.non_empty_rows += 1;
+                }
+            }
+        }
+
+        slices
+    }
+
+    /// Guess the type of every column of every slice, with blank and distinct counts.
+    ///
+    /// Meant for a quick look at a file someone else filled in: a number column with a
+    /// stray word in it comes out as `String`, a column that should be a key shows up as
+    /// not unique. Only rows parsing would keep are counted, nothing is deserialised.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{ColumnType, CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # struct Entry;
+    /// # impl FromColumnSlice for Entry {
+    /// #     const COLUMN_COUNT: usize = 3;
+    /// #     fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> { Ok(Entry) }
+    /// # }
+    /// let data = "word,lesson,added,word,lesson,added\n\
+    ///             hola,1,2024-03-01,bonjour,1,\n\
+    ///             adiós,2,2024-03-08,bonjour,two,\n";
+    /// let parser = CsvSliceParser::from_str(data, ParseConfig::default()).unwrap();
+    ///
+    /// let schema = parser.infer_schema::<Entry>();
+    ///
+    /// let types: Vec<ColumnType> = schema[0].column_schemas.iter().map(|c| c.column_type).collect();
+    /// assert_eq!(types, vec![ColumnType::String, ColumnType::Integer, ColumnType::Date]);
+    ///
+    /// let french = &schema[1].column_schemas;
+    /// assert!(!french[0].unique);
+    /// assert_eq!(french[1].column_type, ColumnType::String);
+    /// assert_eq!((french[2].column_type, french[2].nulls), (ColumnType::Empty, 2));
+    /// ```
+    pub fn infer_schema<T: FromColumnSlice>(&self) -> Vec<SliceSchema> {
+        (0..self.slice_count::<T>())
+            .filter_map(|index| self.slice_columns::<T>(index).map(|columns| (index, columns)))
+            .map(|(index, columns)| {
+                let mut scans: Vec<ColumnScan> = columns.clone()
+                    .map(|i| ColumnScan::new(i, self.headers.get(i).unwrap_or("")))
+                    .collect();
+                let mut rows = 0;
+
+                for record in &self.records {
+                    if self.is_skipped(columns.start, columns.end, record) {
+                        continue
+                    }
+
+                    rows += 1;
+                    for scan in &mut scans {
+                        scan.add(record.get(scan.column()));
+                    }
+                }
+
+                SliceSchema { index, columns, rows, column_schemas: scans.into_iter().map(ColumnScan::finish).collect() }
+            })
+            .collect()
+    }
+
+    /// Count empty cells, field lengths and skipped rows in a slice.
+    ///
+    /// For spotting half-filled columns (a topic whose translations stop halfway down)
+    /// before parsing. Cells are counted over the rows parsing would keep, the rows it
+    /// leaves out are counted on their own. Nothing is deserialised.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SliceStats)` - the counts
+    /// * `Err(ParseError)` - `SliceOutOfBounds` or `Layout`, as for [`parse_slice`](Self::parse_slice)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # struct Entry;
+    /// # impl FromColumnSlice for Entry {
+    /// #     const COLUMN_COUNT: usize = 2;
+    /// #     fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> { Ok(Entry) }
+    /// # }
+    /// let data = "Food,\nりんご,apple\nみず,\n,\nごはん,\n";
+    /// let parser = CsvSliceParser::from_str(data, ParseConfig::default()).unwrap();
+    ///
+    /// let stats = parser.slice_stats::<Entry>(0).unwrap();
+    ///
+    /// assert_eq!((stats.rows, stats.skipped_empty), (3, 1));
+    /// assert_eq!(stats.column_stats[1].empty, 2);
+    /// assert_eq!((stats.column_stats[0].shortest, stats.column_stats[0].longest), (Some(2), Some(3)));
+    /// assert!(stats.column_stats[1].fill_rate() < 0.5);
+    /// ```
+    pub fn slice_stats<T: FromColumnSlice>(&self, slice_index: usize) -> Result<SliceStats, ParseError> {
+        let (start_col, end_col) = self.validate_slice_index::<T>(slice_index)?;
+        let mut stats = SliceStats::new(slice_index, start_col..end_col, &self.headers);
+
+        for record in &self.records {
+            if !self.is_skipped(start_col, end_col, record) {
+                stats.add(record);
+            } else if self.config.skip_empty_rows && self.config.empty_row_policy.is_empty(record, start_col, end_col) {
+                stats.skipped_empty += 1;
+            } else {
+                stats.skipped_other += 1;
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// Get the total number of records (rows) in the CSV.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::CsvSliceParser;
+    /// # use std::error::Error;
+    /// # fn example() -> Result<(), Box<dyn Error>> {
+    /// # let parser = CsvSliceParser::from_file("data.csv")?;
+    /// println!("CSV has {} rows", parser.record_count());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn record_count(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Check that `T` can slice this CSV at all.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - at least one full slice of `T` fits in the headers
+    /// * `Err(ParseError::Layout)` - `T` is wider than the CSV (a zero width `T` doesn't compile)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # use std::error::Error;
+    /// # struct Wide;
+    /// # impl FromColumnSlice for Wide {
+    /// #     const COLUMN_COUNT: usize = 4;
+    /// #     fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> { Ok(Wide) }
+    /// # }
+    /// let headers = StringRecord::from(vec!["A", "B", "C"]);
+    /// let parser = CsvSliceParser::from_records(headers, vec![], ParseConfig::default());
+    ///
+    /// // 4 columns wide, but only 3 header columns
+    /// assert!(parser.validate_layout::<Wide>().is_err());
+    /// ```
+    pub fn validate_layout<T: FromColumnSlice>(&self) -> Result<(), ParseError> {
+        let () = T::NON_ZERO_WIDTH;
+
+        if T::MIN_COLUMN_COUNT > self.headers.len() {
+            return Err(ParseError::Layout(format!(
+                "{} spans {} columns, but the CSV only has {} header columns",
+                std::any::type_name::<T>(), T::MIN_COLUMN_COUNT, self.headers.len()
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn validate_slice_index<T: FromColumnSlice>(&self, slice_index: usize) -> Result<(usize, usize), ParseError>{
+        self.validate_layout::<T>()?;
+
+        self.slice_columns::<T>(slice_index)
+            .map(|columns| (columns.start, columns.end))
+            .ok_or(ParseError::SliceOutOfBounds { slice_index, slice_count: self.slice_count::<T>() })
+    }
+
+    fn is_skipped(&self, start_col: usize, end_col: usize, record: &StringRecord) -> bool {
+        is_skipped(&self.config, record, start_col, end_col)
+    }
+
+    /// `is_skipped` for `row` by `config`'s rules, asking `filled` whether it's empty when there's one
+    fn is_row_skipped(&self, config: &ParseConfig, filled: Option<&FilledCells>, row: usize, start_col: usize, end_col: usize) -> bool {
+        let record = &self.records[row];
+        match filled {
+            Some(filled) => is_marked_or_ragged(config, record, start_col, end_col) || filled.is_empty(row, start_col, end_col),
+            None => is_skipped(config, record, start_col, end_col),
+        }
+    }
+
+    /// report `row` rows of `columns` done, every `PROGRESS_INTERVAL` rows and at the end
+    fn report_parsing(&self, columns: Range<usize>, row: usize) {
+        let rows = self.records.len();
+        if (row > 0 && row.is_multiple_of(PROGRESS_INTERVAL)) || row == rows {
+            self.config.report(|| ProgressEvent::Parsing { columns, row, rows });
+        }
+    }
+
+    /// Parse a specific column slice into a vector of structs.
+    ///
+    /// This is the main parsing method. It deserializes all rows for a given
+    /// column slice into your custom struct type.
+    ///
+    /// # Arguments
+    ///
+    /// * `slice_index` - Zero-based index of the slice to parse
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<T>)` - Successfully parsed entries
+    /// * `Err(ParseError)` - `SliceOutOfBounds`, or whatever `from_record` returned (with its row)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseError};
+    /// # use csv::StringRecord;
+    /// # use std::error::Error;
+    /// # #[derive(Debug)]
+    /// # struct VocabEntry { word: String, translation: String, example: String }
+    /// # impl FromColumnSlice for VocabEntry {
+    /// #     const COLUMN_COUNT: usize = 3;
+    /// #     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    /// #         Ok(VocabEntry {
+    /// #             word: record.get(start_col).unwrap_or("").to_string(),
+    /// #             translation: record.get(start_col + 1).unwrap_or("").to_string(),
+    /// #             example: record.get(start_col + 2).unwrap_or("").to_string(),
+    /// #         })
+    /// #     }
+    /// # }
+    /// # fn example() -> Result<(), Box<dyn Error>> {
+    /// # let parser = CsvSliceParser::from_file("vocab.csv")?;
+    /// // Parse columns 0-2 (first slice)
+    /// let first_language: Vec<VocabEntry> = parser.parse_slice(0)?;
+    ///
+    /// // Parse columns 3-5 (second slice)
+    /// let second_language: Vec<VocabEntry> = parser.parse_slice(1)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse_slice<T: FromColumnSlice>(&self, slice_index: usize) -> Result<Vec<T>, ParseError> {
+        let (start_col, end_col) = self.validate_slice_index::<T>(slice_index)?;
+
+        self.parse_columns(&self.config, start_col, end_col, None)
+    }
+
+    /// Parse a slice with other rules for which rows to leave out than the parser's config.
+    ///
+    /// For slices that need them, e.g. `skip_empty_rows` off for an example sentence slice
+    /// where a blank row means something, while the vocab slices around it skip them. Only
+    /// the options that pick rows are taken from `config`: `skip_empty_rows`,
+    /// `empty_row_policy`, `skip_marker`, `ragged_rows` (whether short rows are skipped) and
+    /// `reserve_capacity`. Everything else was used to read the file and stays as loaded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # struct Cell(String);
+    /// # impl FromColumnSlice for Cell {
+    /// #     const COLUMN_COUNT: usize = 1;
+    /// #     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    /// #         Ok(Cell(record.get(start_col).unwrap_or("").to_string()))
+    /// #     }
+    /// # }
+    /// let parser = CsvSliceParser::from_str("Vocab,Examples\nねこ,猫がいる\nいぬ,\n,犬がいる\n", ParseConfig::default()).unwrap();
+    ///
+    /// assert_eq!(parser.parse_slice::<Cell>(1).unwrap().len(), 2);
+    ///
+    /// // one example per vocab row, blanks included
+    /// let keep_blanks = ParseConfig { skip_empty_rows: false, ..ParseConfig::default() };
+    /// let examples = parser.parse_slice_with_config::<Cell>(1, &keep_blanks).unwrap();
+    /// assert_eq!(examples.iter().map(|Cell(s)| s.as_str()).collect::<Vec<_>>(), vec!["猫がいる", "", "犬がいる"]);
+    /// ```
+    pub fn parse_slice_with_config<T: FromColumnSlice>(&self, slice_index: usize, config: &ParseConfig) -> Result<Vec<T>, ParseError> {
+        let (start_col, end_col) = self.validate_slice_index::<T>(slice_index)?;
+
+        self.parse_columns(config, start_col, end_col, None)
+    }
+
+    /// Parse a slice, setting aside rows that fail instead of stopping at the first one.
+    ///
+    /// Returns the rows that parsed and a [`RowError`] for each row that didn't, both in file
+    /// order. Only a bad `slice_index` (or layout) is still an `Err`. Use it when one malformed
+    /// row shouldn't cost the other few thousand, and report the errors afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// struct Number(u32);
+    ///
+    /// impl FromColumnSlice for Number {
+    ///     const COLUMN_COUNT: usize = 1;
+    ///
+    ///     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    ///         let cell = record.get(start_col).unwrap_or("");
+    ///         cell.parse().map(Number).map_err(ParseError::custom)
+    ///     }
+    /// }
+    ///
+    /// let parser = CsvSliceParser::from_str("Numbers\n1\ntwo\n3\n", ParseConfig::default()).unwrap();
+    ///
+    /// let (numbers, errors) = parser.parse_slice_lenient::<Number>(0).unwrap();
+    ///
+    /// assert_eq!(numbers.iter().map(|Number(n)| *n).collect::<Vec<_>>(), vec![1, 3]);
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(errors[0].row, 1);
+    /// assert_eq!(errors[0].to_string(), "Row 2: invalid digit found in string");
+    /// ```
+    pub fn parse_slice_lenient<T: FromColumnSlice>(
+        &self,
+        slice_index: usize,
+    ) -> Result<(Vec<T>, Vec<RowError>), ParseError> {
+        let (start_col, end_col) = self.validate_slice_index::<T>(slice_index)?;
+
+        let mut parsed = if self.config.reserve_capacity {
+            Vec::with_capacity(self.records.len())
+        } else {
+            Vec::new()
+        };
+        let mut errors = Vec::new();
+
+        for (row, record) in self.records.iter().enumerate() {
+            self.report_parsing(start_col..end_col, row);
+            if self.is_skipped(start_col, end_col, record) {
+                continue
+            }
+
+            match T::from_record(record, start_col) {
+                Ok(value) => parsed.push(value),
+                Err(error) => errors.push(RowError { row, error: error.at_row(row) }),
+            }
+        }
+        self.report_parsing(start_col..end_col, self.records.len());
+
+        parsed.shrink_to_fit();
+
+        Ok((parsed, errors))
+    }
+
+    /// Parse a slice into values borrowing from the parser, no field is copied.
+    ///
+    /// Same rows and errors as [`parse_slice`](Self::parse_slice), for types implementing
+    /// [`FromColumnSliceRef`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSliceRef, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// struct Word<'a>(&'a str);
+    ///
+    /// impl<'a> FromColumnSliceRef<'a> for Word<'a> {
+    ///     const COLUMN_COUNT: usize = 2;
+    ///
+    ///     fn from_record(record: &'a StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    ///         record.get(start_col).map(Word).ok_or_else(|| ParseError::missing_field(start_col, "word"))
+    ///     }
+    /// }
+    ///
+    /// let data = "Spanish,English,French,English\nhola,hello,bonjour,hello\nadiós,bye,,\n";
+    /// let parser = CsvSliceParser::from_str(data, ParseConfig::default()).unwrap();
+    ///
+    /// let french: Vec<Word> = parser.parse_slice_ref(1).unwrap();
+    /// assert_eq!(french.iter().map(|Word(word)| *word).collect::<Vec<_>>(), vec!["bonjour"]);
+    ///
+    /// assert!(parser.parse_slice_ref::<Word>(2).is_err());
+    /// ```
+    pub fn parse_slice_ref<'a, T: FromColumnSliceRef<'a>>(&'a self, slice_index: usize) -> Result<Vec<T>, ParseError> {
+        let (start_col, end_col) = self.validate_slice_index::<Borrowed<T>>(slice_index)?;
+
+        let mut results = if self.config.reserve_capacity {
+            Vec::with_capacity(self.records.len())
+        } else {
+            Vec::new()
+        };
+
+        for (row, record) in self.records.iter().enumerate() {
+            self.report_parsing(start_col..end_col, row);
+            if self.is_skipped(start_col, end_col, record) {
+                continue
+            }
+            results.push(T::from_record(record, start_col).map_err(|e| e.at_row(row))?);
+        }
+        self.report_parsing(start_col..end_col, self.records.len());
+
+        results.shrink_to_fit();
+
+        Ok(results)
+    }
+
+    /// parse `start_col..end_col` of every row `config` keeps, `filled` is the bitmap when one was
+    /// built for several slices
+    fn parse_columns<T: FromColumnSlice>(
+        &self,
+        config: &ParseConfig,
+        start_col: usize,
+        end_col: usize,
+        filled: Option<&FilledCells>,
+    ) -> Result<Vec<T>, ParseError> {
+        let mut results = if config.reserve_capacity {
+            Vec::with_capacity(self.records.len())
+        } else {
+            Vec::new()
+        };
+
+        for (row, record) in self.records.iter().enumerate() {
+            self.report_parsing(start_col..end_col, row);
+            if self.is_row_skipped(config, filled, row, start_col, end_col) {
+                continue
+            }
+            results.push(T::from_record(record, start_col).map_err(|e| e.at_row(row))?);
+        }
+        self.report_parsing(start_col..end_col, self.records.len());
+
+        results.shrink_to_fit();
+
+        Ok(results)
+    }
+
+    /// Parse a slice lazily with an iterator.
+    ///
+    /// This provides memory-efficient processing by parsing records on-demand
+    /// instead of allocating all results upfront.
+    ///
+    /// # Arguments
+    ///
+    /// * `slice_index` - Zero-based index of the slice to parse
+    ///
+    /// # Returns
+    ///
+    /// An iterator that yields `Result<T, ParseError>` for each row.
+    /// The slice index is checked up front, an out of bounds slice is an `Err` before any row is read.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseError};
+    /// # use csv::StringRecord;
+    /// # use std::error::Error;
+    /// # #[derive(Debug)]
+    /// # struct Entry { field: String }
+    /// # impl FromColumnSlice for Entry {
+    /// #     const COLUMN_COUNT: usize = 1;
+    /// #     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    /// #         Ok(Entry { field: record.get(start_col).unwrap_or("").to_string() })
+    /// #     }
+    /// # }
+    /// # fn example() -> Result<(), Box<dyn Error>> {
+    /// # let parser = CsvSliceParser::from_file("data.csv")?;
+    /// // Process only the first 100 entries
+    /// for result in parser.parse_slice_iter::<Entry>(0)?.take(100) {
+    ///     let entry = result?;
+    ///     println!("{:?}", entry);
+    /// }
+    ///
+    /// // Filter while parsing
+    /// let filtered: Vec<Entry> = parser.parse_slice_iter::<Entry>(0)?
+    ///     .filter_map(Result::ok)
+    ///     .filter(|e| e.field.len() > 5)
+    ///     .collect();
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Rows are only parsed as the iterator reaches them, so a bad row further down
+    /// doesn't stop the rows before it:
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// struct Number(u32);
+    ///
+    /// impl FromColumnSlice for Number {
+    ///     const COLUMN_COUNT: usize = 1;
+    ///
+    ///     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    ///         let cell = record.get(start_col).unwrap_or("");
+    ///         cell.parse().map(Number).map_err(ParseError::custom)
+    ///     }
+    /// }
+    ///
+    /// let data = "Numbers\n1\n2\nthree\n";
+    /// let parser = CsvSliceParser::from_reader(data.as_bytes(), ParseConfig::default()).unwrap();
+    ///
+    /// let first_two: Vec<u32> = parser.parse_slice_iter::<Number>(0).unwrap()
+    ///     .take(2)
+    ///     .map(|number| number.map(|Number(n)| n))
+    ///     .collect::<Result<_, _>>()
+    ///     .unwrap();
+    /// assert_eq!(first_two, vec![1, 2]);
+    ///
+    /// assert!(parser.parse_slice_iter::<Number>(0).unwrap().nth(2).unwrap().is_err());
+    ///
+    /// // the slice index is checked before iterating
+    /// assert!(matches!(parser.parse_slice_iter::<Number>(1), Err(ParseError::SliceOutOfBounds { .. })));
+    /// ```
+    pub fn parse_slice_iter<'a, T: FromColumnSlice + 'a>(
+        &'a self,
+        slice_index: usize
+    ) -> Result<impl Iterator<Item = Result<T, ParseError>> + 'a, ParseError> {
+        let (start_col, end_col) = self.validate_slice_index::<T>(slice_index)?;
+
+        Ok(self.iter_columns(start_col, end_col))
+    }
+
+    /// Parse a slice lazily in batches of `chunk_size` rows, for APIs that take a batch at a time.
+    ///
+    /// Every batch but the last is full, skipped rows don't count. A row that fails ends
+    /// the iteration with its error, the rows before it in the batch are dropped.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(impl Iterator)` - the batches, in file order
+    /// * `Err(ParseError::Layout)` - `chunk_size` is 0
+    /// * `Err(ParseError)` - `SliceOutOfBounds`, as for [`parse_slice`](Self::parse_slice)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # struct Word(String);
+    /// # impl FromColumnSlice for Word {
+    /// #     const COLUMN_COUNT: usize = 1;
+    /// #     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    /// #         Ok(Word(record.get(start_col).unwrap_or("").to_string()))
+    /// #     }
+    /// # }
+    /// let data = "Food\nrice\nbread\n\nmilk\nfish\negg\n";
+    /// let parser = CsvSliceParser::from_str(data, ParseConfig::default()).unwrap();
+    ///
+    /// let sizes: Vec<usize> = parser.parse_slice_chunks::<Word>(0, 2).unwrap()
+    ///     .map(|chunk| chunk.map(|words| words.len()))
+    ///     .collect::<Result<_, _>>()
+    ///     .unwrap();
+    /// assert_eq!(sizes, vec![2, 2, 1]);
+    ///
+    /// assert!(parser.parse_slice_chunks::<Word>(0, 0).is_err());
+    /// ```
+    pub fn parse_slice_chunks<'a, T: FromColumnSlice + 'a>(
+        &'a self,
+        slice_index: usize,
+        chunk_size: usize,
+    ) -> Result<impl Iterator<Item = Result<Vec<T>, ParseError>> + 'a, ParseError> {
+        let (start_col, end_col) = self.validate_slice_index::<T>(slice_index)?;
+        if chunk_size == 0 {
+            return Err(ParseError::Layout("chunk_size has to be at least 1".to_string()));
+        }
+
+        let mut rows = self.iter_columns::<T>(start_col, end_col);
+        let mut failed = false;
+
+        Ok(std::iter::from_fn(move || {
+            if failed {
+                return None;
+            }
+
+            let mut chunk = Vec::with_capacity(chunk_size.min(self.records.len()));
+            for row in rows.by_ref() {
+                match row {
+                    Ok(value) => chunk.push(value),
+                    Err(e) => {
+                        failed = true;
+                        return Some(Err(e));
+                    },
+                }
+                if chunk.len() == chunk_size {
+                    break;
+                }
+            }
+
+            (!chunk.is_empty()).then_some(Ok(chunk))
+        }))
+    }
+
+    /// Parse a slice, keeping only the rows `predicate` accepts.
+    ///
+    /// Rows are tested as they're parsed, the ones left out are never collected.
+    /// Errors are the same as for [`parse_slice`](Self::parse_slice), rejected rows included.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// struct Word(String);
+    ///
+    /// impl FromColumnSlice for Word {
+    ///     const COLUMN_COUNT: usize = 1;
+    ///
+    ///     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    ///         Ok(Word(record.get(start_col).unwrap_or("").to_string()))
+    ///     }
+    /// }
+    ///
+    /// let parser = CsvSliceParser::from_str("Words\nhola\nadiós\nhasta luego\n", ParseConfig::default()).unwrap();
+    ///
+    /// let phrases = parser.parse_slice_filtered::<Word>(0, |Word(word)| word.contains(' ')).unwrap();
+    /// assert_eq!(phrases.len(), 1);
+    /// ```
+    pub fn parse_slice_filtered<T: FromColumnSlice>(
+        &self,
+        slice_index: usize,
+        mut predicate: impl FnMut(&T) -> bool,
+    ) -> Result<Vec<T>, ParseError> {
+        let (start_col, end_col) = self.validate_slice_index::<T>(slice_index)?;
+
+        self.iter_columns(start_col, end_col)
+            .filter(|row| match row {
+                Ok(value) => predicate(value),
+                Err(_) => true,
+            })
+            .collect()
+    }
+
+    /// Start a [`SliceQuery`] on a slice, to filter, transform and limit its rows while parsing.
+    ///
+    /// A bad `slice_index` is reported when the query runs.
+    pub fn query<'a, T: FromColumnSlice + 'a>(&'a self, slice_index: usize) -> SliceQuery<'a, T> {
+        SliceQuery::new(self.parse_slice_iter(slice_index))
+    }
+
+    fn iter_columns<'a, T: FromColumnSlice + 'a>(
+        &'a self,
+        start_col: usize,
+        end_col: usize
+    ) -> impl Iterator<Item = Result<T, ParseError>> + 'a {
+        self.records.iter().enumerate().filter_map(move |(row, record)| {
+            if self.is_skipped(start_col, end_col, record) {
+                return None;
+            }
+            Some(T::from_record(record, start_col).map_err(|e| e.at_row(row)))
+        })
+    }
+
+    /// Lazily parse every slice, one row iterator per slice in column order.
+    ///
+    /// Nothing is parsed until the inner iterators are advanced, and each one only parses
+    /// its own slice, so slices can be consumed (or skipped) independently.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # struct Entry { field: String }
+    /// # impl FromColumnSlice for Entry {
+    /// #     const COLUMN_COUNT: usize = 1;
+    /// #     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    /// #         Ok(Entry { field: record.get(start_col).unwrap_or("").to_string() })
+    /// #     }
+    /// # }
+    /// let data = "Fruit,Animals\napple,cat\npear,\n";
+    /// let parser = CsvSliceParser::from_reader(data.as_bytes(), ParseConfig::default()).unwrap();
+    ///
+    /// let slices: Vec<Vec<String>> = parser.parse_all_slices_iter::<Entry>()
+    ///     .map(|rows| rows.map(|entry| entry.map(|e| e.field)).collect::<Result<_, _>>())
+    ///     .collect::<Result<_, _>>()
+    ///     .unwrap();
+    ///
+    /// // blank cells are skipped per slice, the same as parse_all_slices
+    /// assert_eq!(slices, vec![vec!["apple", "pear"], vec!["cat"]]);
+    /// ```
+    pub fn parse_all_slices_iter<'a, T: FromColumnSlice + 'a>(
+        &'a self
+    ) -> impl Iterator<Item = impl Iterator<Item = Result<T, ParseError>> + 'a> + 'a {
+        (0..self.slice_count::<T>())
+            .filter_map(move |slice_index| self.slice_columns::<T>(slice_index))
+            .map(move |columns| self.iter_columns(columns.start, columns.end))
+    }
+
+    /// Parse one row of a slice, without touching the others.
+    ///
+    /// `row_index` counts data rows from 0, like the rows in [`ParseError`]. The row is
+    /// parsed even if the config would skip it (blank, or holding the skip marker),
+    /// it was asked for by position.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(T)` - the parsed row
+    /// * `Err(ParseError::RowOutOfBounds)` - there aren't that many rows
+    /// * `Err(ParseError)` - `SliceOutOfBounds`, or whatever `from_record` returned (with its row)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # struct Entry { field: String }
+    /// # impl FromColumnSlice for Entry {
+    /// #     const COLUMN_COUNT: usize = 1;
+    /// #     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    /// #         Ok(Entry { field: record.get(start_col).unwrap_or("").to_string() })
+    /// #     }
+    /// # }
+    /// let parser = CsvSliceParser::from_str("Fruit,Animal\napple,cat\npear,dog\n", ParseConfig::default()).unwrap();
+    ///
+    /// assert_eq!(parser.parse_row::<Entry>(1, 1).unwrap().field, "dog");
+    /// assert!(matches!(parser.parse_row::<Entry>(0, 2), Err(ParseError::RowOutOfBounds { row_count: 2, .. })));
+    /// ```
+    pub fn parse_row<T: FromColumnSlice>(&self, slice_index: usize, row_index: usize) -> Result<T, ParseError> {
+        let (start_col, _) = self.validate_slice_index::<T>(slice_index)?;
+
+        let record = self.records.get(row_index)
+            .ok_or(ParseError::RowOutOfBounds { row_index, row_count: self.records.len() })?;
+
+        T::from_record(record, start_col).map_err(|e| e.at_row(row_index))
+    }
+
+    /// Parse the rows of a slice within `rows`, e.g. one page of a long list.
+    ///
+    /// `rows` counts data rows from 0. Rows the config skips are left out as in
+    /// [`parse_slice`](Self::parse_slice), so a page can come back shorter than its range.
+    /// Only rows in the range are deserialised.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<T>)` - the parsed rows in file order, empty for an empty range
+    /// * `Err(ParseError::RowOutOfBounds)` - the range ends past the last row
+    /// * `Err(ParseError)` - `SliceOutOfBounds`, or whatever `from_record` returned (with its row)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # struct Entry { field: String }
+    /// # impl FromColumnSlice for Entry {
+    /// #     const COLUMN_COUNT: usize = 1;
+    /// #     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    /// #         Ok(Entry { field: record.get(start_col).unwrap_or("").to_string() })
+    /// #     }
+    /// # }
+    /// let parser = CsvSliceParser::from_str("Numbers\n1\n2\n\"\"\n4\n5\n", ParseConfig::default()).unwrap();
+    ///
+    /// let page: Vec<String> = parser.parse_rows::<Entry>(0, 1..4).unwrap().into_iter().map(|e| e.field).collect();
+    /// // the blank row is skipped, so the page is two rows long
+    /// assert_eq!(page, vec!["2", "4"]);
+    ///
+    /// assert!(matches!(parser.parse_rows::<Entry>(0, 3..9), Err(ParseError::RowOutOfBounds { row_index: 8, .. })));
+    /// ```
+    pub fn parse_rows<T: FromColumnSlice>(&self, slice_index: usize, rows: Range<usize>) -> Result<Vec<T>, ParseError> {
+        let (start_col, end_col) = self.validate_slice_index::<T>(slice_index)?;
+
+        if rows.end > self.records.len() {
+            return Err(ParseError::RowOutOfBounds { row_index: rows.end - 1, row_count: self.records.len() });
+        }
+
+        let first = rows.start;
+        self.records.get(rows).unwrap_or_default()
+            .iter()
+            .enumerate()
+            .filter(|(_, record)| !self.is_skipped(start_col, end_col, record))
+            .map(|(offset, record)| T::from_record(record, start_col).map_err(|e| e.at_row(first + offset)))
+            .collect()
+    }
+
+    /// Parse one row in every slice, e.g. the same word in each language.
+    ///
+    /// Returns one entry per slice in column order, `None` where the config skips that slice's
+    /// part of the row (blank, or holding the skip marker), so positions line up with slice indices.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<Option<T>>)` - the row in each slice, empty when `T` doesn't fit the headers
+    /// * `Err(ParseError::RowOutOfBounds)` - there aren't that many rows
+    /// * `Err(ParseError)` - whatever `from_record` returned (with its row)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # struct Word(String);
+    /// # impl FromColumnSlice for Word {
+    /// #     const COLUMN_COUNT: usize = 2;
+    /// #     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    /// #         Ok(Word(record.get(start_col).unwrap_or("").to_string()))
+    /// #     }
+    /// # }
+    /// let data = "Spanish,,French,,German,\nhola,hello,bonjour,hello,hallo,hello\nadiós,bye,,,tschüss,bye\n";
+    /// let parser = CsvSliceParser::from_str(data, ParseConfig::default()).unwrap();
+    ///
+    /// let bye = parser.parse_row_across_slices::<Word>(1).unwrap();
+    /// let words: Vec<Option<&str>> = bye.iter().map(|word| word.as_ref().map(|Word(w)| w.as_str())).collect();
+    /// assert_eq!(words, vec![Some("adiós"), None, Some("tschüss")]);
+    ///
+    /// // or every row in turn
+    /// assert_eq!(parser.rows_across_slices::<Word>().count(), 2);
+    /// ```
+    pub fn parse_row_across_slices<T: FromColumnSlice>(&self, row_index: usize) -> Result<Vec<Option<T>>, ParseError> {
+        let record = self.records.get(row_index)
+            .ok_or(ParseError::RowOutOfBounds { row_index, row_count: self.records.len() })?;
+
+        self.across_slices(row_index, record)
+    }
+
+    /// Lazily parse every row across all slices, see [`parse_row_across_slices`](Self::parse_row_across_slices).
+    ///
+    /// Yields one `Vec` per data row in file order, rows are only parsed as the iterator advances.
+    pub fn rows_across_slices<'a, T: FromColumnSlice + 'a>(
+        &'a self,
+    ) -> impl Iterator<Item = Result<Vec<Option<T>>, ParseError>> + 'a {
+        self.records.iter()
+            .enumerate()
+            .map(move |(row, record)| self.across_slices(row, record))
+    }
+
+    fn across_slices<T: FromColumnSlice>(&self, row: usize, record: &StringRecord) -> Result<Vec<Option<T>>, ParseError> {
+        (0..self.slice_count::<T>())
+            .map_while(|slice_index| self.slice_columns::<T>(slice_index))
+            .map(|columns| {
+                if self.is_skipped(columns.start, columns.end, record) {
+                    return Ok(None);
+                }
+                T::from_record(record, columns.start).map(Some).map_err(|e| e.at_row(row))
+            })
+            .collect()
+    }
+
+    /// Parse `n` rows of a slice, for previewing a file without parsing all of it.
+    ///
+    /// Rows are chosen by `strategy` among the rows [`parse_slice`](Self::parse_slice) would
+    /// return (skipped rows never show up), and come back in file order. Only the chosen rows
+    /// are deserialised, so an error in a row that wasn't picked goes unnoticed.
+    ///
+    /// Returns every row when the slice has `n` or fewer.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError, SampleStrategy};
+    /// # use csv::StringRecord;
+    /// # struct Entry { field: String }
+    /// # impl FromColumnSlice for Entry {
+    /// #     const COLUMN_COUNT: usize = 1;
+    /// #     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    /// #         Ok(Entry { field: record.get(start_col).unwrap_or("").to_string() })
+    /// #     }
+    /// # }
+    /// let data = "Numbers\n1\n2\n3\n4\n5\n6\n7\n8\n";
+    /// let parser = CsvSliceParser::from_reader(data.as_bytes(), ParseConfig::default()).unwrap();
+    ///
+    /// let fields = |sample: Vec<Entry>| sample.into_iter().map(|e| e.field).collect::<Vec<_>>();
+    ///
+    /// let head = parser.sample_slice::<Entry>(0, 3, SampleStrategy::Head).unwrap();
+    /// assert_eq!(fields(head), vec!["1", "2", "3"]);
+    ///
+    /// let stride = parser.sample_slice::<Entry>(0, 4, SampleStrategy::Stride).unwrap();
+    /// assert_eq!(fields(stride), vec!["1", "3", "5", "7"]);
+    ///
+    /// let random = parser.sample_slice::<Entry>(0, 3, SampleStrategy::Random).unwrap();
+    /// assert_eq!(random.len(), 3);
+    /// ```
+    pub fn sample_slice<T: FromColumnSlice>(
+        &self,
+        slice_index: usize,
+        n: usize,
+        strategy: SampleStrategy,
+    ) -> Result<Vec<T>, ParseError> {
+        let (start_col, end_col) = self.validate_slice_index::<T>(slice_index)?;
+
+        let rows = self.records.iter()
+            .enumerate()
+            .filter(|(_, record)| !self.is_skipped(start_col, end_col, record))
+            .map(|(row, _)| row);
+
+        // head can stop reading early, the others need to know how many rows there are
+        let rows: Vec<usize> = match strategy {
+            SampleStrategy::Head => rows.take(n).collect(),
+            _ => {
+                let rows: Vec<usize> = rows.collect();
+                strategy.pick(rows.len(), n).into_iter().map(|i| rows[i]).collect()
+            },
+        };
+
+        rows.into_iter()
+            .map(|row| T::from_record(&self.records[row], start_col).map_err(|e| e.at_row(row)))
+            .collect()
+    }
+
+    /// Compare a slice with the same slice of a newer version of the file.
+    ///
+    /// Both slices are parsed in full and matched by `key`, see [`diff_by_key`] for how
+    /// entries pair up. Fails if either parse fails, including the slice not existing in `newer`.
+    /// For slices that moved (a topic added to the left of it), parse both and call
+    /// [`diff_by_key`] directly.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// #[derive(Debug, PartialEq)]
+    /// struct Pair { word: String, translation: String }
+    ///
+    /// impl FromColumnSlice for Pair {
+    ///     const COLUMN_COUNT: usize = 2;
+    ///
+    ///     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    ///         let field = |offset| record.get(start_col + offset).unwrap_or("").to_string();
+    ///         Ok(Pair { word: field(0), translation: field(1) })
+    ///     }
+    /// }
+    ///
+    /// let week1 = CsvSliceParser::from_str("Food,\nrice,ご飯\nbread,パン\n", ParseConfig::default()).unwrap();
+    /// let week2 = CsvSliceParser::from_str("Food,\nrice,米\nmilk,牛乳\n", ParseConfig::default()).unwrap();
+    ///
+    /// let diff = week1.diff_slice::<Pair, _>(&week2, 0, |pair| pair.word.clone()).unwrap();
+    ///
+    /// assert_eq!(diff.added[0].word, "milk");
+    /// assert_eq!(diff.removed[0].word, "bread");
+    /// assert_eq!((diff.changed[0].0.translation.as_str(), diff.changed[0].1.translation.as_str()), ("ご飯", "米"));
+    /// ```
+    pub fn diff_slice<T, K>(
+        &self,
+        newer: &CsvSliceParser,
+        slice_index: usize,
+        key: impl Fn(&T) -> K,
+    ) -> Result<SliceDiff<T>, ParseError>
+    where
+        T: FromColumnSlice + PartialEq,
+        K: Eq + std::hash::Hash,
+    {
+        Ok(diff_by_key(self.parse_slice(slice_index)?, newer.parse_slice(slice_index)?, key))
+    }
+
+    /// Parse a slice, dropping rows identical to an earlier row of the same slice.
+    ///
+    /// Returns the first copy of every row, in file order, and how many repeats were dropped.
+    /// Rows count as identical when their parsed values are equal, so with trimmed fields
+    /// `"cat "` and `"cat"` are the same row. Only the slice's own columns are compared.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// #[derive(PartialEq, Eq, Hash)]
+    /// struct Pair { word: String, translation: String }
+    ///
+    /// impl FromColumnSlice for Pair {
+    ///     const COLUMN_COUNT: usize = 2;
+    ///
+    ///     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    ///         let field = |offset| record.get(start_col + offset).unwrap_or("").to_string();
+    ///         Ok(Pair { word: field(0), translation: field(1) })
+    ///     }
+    /// }
+    ///
+    /// let data = "Food,\nrice,ご飯\nbread,パン\nrice,ご飯\nrice,米\n";
+    /// let parser = CsvSliceParser::from_str(data, ParseConfig::default()).unwrap();
+    ///
+    /// let (pairs, dropped) = parser.dedup_slice::<Pair>(0).unwrap();
+    ///
+    /// assert_eq!(pairs.iter().map(|pair| pair.translation.as_str()).collect::<Vec<_>>(), vec!["ご飯", "パン", "米"]);
+    /// assert_eq!(dropped, 1);
+    /// ```
+    pub fn dedup_slice<T>(&self, slice_index: usize) -> Result<(Vec<T>, usize), ParseError>
+    where
+        T: FromColumnSlice + Eq + std::hash::Hash,
+    {
+        let mut values = self.parse_slice::<T>(slice_index)?;
+        let dropped = dedup_in_order(&mut values);
+
+        Ok((values, dropped))
+    }
+
+    /// Parse a slice sorted by `key`, e.g. alphabetically by reading.
+    ///
+    /// The sort is stable, rows with equal keys keep their file order. `key` is called once
+    /// per row, so building an owned key (a `String`, a normalised reading) is cheap enough.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// struct Word { kana: String, english: String }
+    ///
+    /// impl FromColumnSlice for Word {
+    ///     const COLUMN_COUNT: usize = 2;
+    ///
+    ///     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    ///         let field = |offset| record.get(start_col + offset).unwrap_or("").to_string();
+    ///         Ok(Word { kana: field(0), english: field(1) })
+    ///     }
+    /// }
+    ///
+    /// let data = "Food,\nりんご,apple\nごはん,rice\nいちご,strawberry\n";
+    /// let parser = CsvSliceParser::from_str(data, ParseConfig::default()).unwrap();
+    ///
+    /// let words = parser.parse_slice_sorted_by::<Word, _>(0, |word| word.kana.clone()).unwrap();
+    /// assert_eq!(words.iter().map(|word| word.english.as_str()).collect::<Vec<_>>(), vec!["strawberry", "rice", "apple"]);
+    /// ```
+    pub fn parse_slice_sorted_by<T, K>(&self, slice_index: usize, key: impl FnMut(&T) -> K) -> Result<Vec<T>, ParseError>
+    where
+        T: FromColumnSlice,
+        K: Ord,
+    {
+        let mut values = self.parse_slice::<T>(slice_index)?;
+        values.sort_by_cached_key(key);
+
+        Ok(values)
+    }
+
+    /// Parse a slice into groups by `key`, e.g. by a JLPT level column.
+    ///
+    /// Each group holds its rows in file order. Rows are grouped as they're parsed, the
+    /// slice is never collected into one `Vec` first. Stops at the first row that fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// struct Word { kanji: String, level: String }
+    ///
+    /// impl FromColumnSlice for Word {
+    ///     const COLUMN_COUNT: usize = 2;
+    ///
+    ///     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    ///         let field = |offset| record.get(start_col + offset).unwrap_or("").to_string();
+    ///         Ok(Word { kanji: field(0), level: field(1) })
+    ///     }
+    /// }
+    ///
+    /// let data = "Kanji,JLPT\n水,N5\n猫,N5\n鍵,N3\n";
+    /// let parser = CsvSliceParser::from_str(data, ParseConfig::default()).unwrap();
+    ///
+    /// let levels = parser.parse_slice_grouped_by::<Word, _>(0, |word| word.level.clone()).unwrap();
+    /// assert_eq!(levels["N5"].iter().map(|word| word.kanji.as_str()).collect::<Vec<_>>(), vec!["水", "猫"]);
+    /// assert_eq!(levels["N3"].len(), 1);
+    /// ```
+    pub fn parse_slice_grouped_by<T, K>(
+        &self,
+        slice_index: usize,
+        mut key: impl FnMut(&T) -> K,
+    ) -> Result<HashMap<K, Vec<T>>, ParseError>
+    where
+        T: FromColumnSlice,
+        K: Eq + std::hash::Hash,
+    {
+        let (start_col, end_col) = self.validate_slice_index::<T>(slice_index)?;
+        let mut groups: HashMap<K, Vec<T>> = HashMap::new();
+
+        for value in self.iter_columns::<T>(start_col, end_col) {
+            let value = value?;
+            groups.entry(key(&value)).or_default().push(value);
+        }
+
+        Ok(groups)
+    }
+
+    /// Parse all slices into separate vectors.
+    ///
+    /// Convenience method to parse every available slice in one call. Which cells are blank
+    /// is worked out once for every row instead of once per slice, so skipping empty rows
+    /// costs less than calling [`parse_slice`](Self::parse_slice) for each slice.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseError};
+    /// # use csv::StringRecord;
+    /// # use std::error::Error;
+    /// # #[derive(Debug)]
+    /// # struct Entry { field: String }
+    /// # impl FromColumnSlice for Entry {
+    /// #     const COLUMN_COUNT: usize = 3;
+    /// #     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    /// #         Ok(Entry { field: "".into() })
+    /// #     }
+    /// # }
+    /// # fn example() -> Result<(), Box<dyn Error>> {
+    /// # let parser = CsvSliceParser::from_file("data.csv")?;
+    /// let all_slices: Vec<Vec<Entry>> = parser.parse_all_slices()?;
+    ///
+    /// for (i, slice) in all_slices.iter().enumerate() {
+    ///     println!("Slice {} has {} entries", i, slice.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse_all_slices<T: FromColumnSlice>(&self) -> Result<Vec<Vec<T>>, ParseError> {
+        let slice_count = self.slice_count::<T>();
+        let mut all_slices: Vec<Vec<T>> = Vec::with_capacity(slice_count);
+        // every slice asks whether the same rows are empty, so that's worked out once
+        let filled = FilledCells::new(&self.records, &self.config);
+
+        for i in 0..slice_count {
+            let (start_col, end_col) = self.validate_slice_index::<T>(i)?;
+            all_slices.push(self.parse_columns(&self.config, start_col, end_col, filled.as_ref())?)
+        }
+
+        Ok(all_slices)
+    }
+
+    /// Parse all slices at once, one slice per thread.
+    ///
+    /// Same output as [`parse_all_slices`](Self::parse_all_slices), slices stay in column order.
+    /// Worth it for wide files (lots of slices) with many rows, for small files the thread
+    /// overhead costs more than it saves.
+    ///
+    /// Requires the `rayon` feature.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseError};
+    /// # use csv::StringRecord;
+    /// # use std::error::Error;
+    /// # struct Entry { field: String }
+    /// # impl FromColumnSlice for Entry {
+    /// #     const COLUMN_COUNT: usize = 3;
+    /// #     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    /// #         Ok(Entry { field: record.get(start_col).unwrap_or("").to_string() })
+    /// #     }
+    /// # }
+    /// # fn example() -> Result<(), Box<dyn Error>> {
+    /// let parser = CsvSliceParser::from_file("forty_topics.csv")?;
+    /// let all_slices: Vec<Vec<Entry>> = parser.parse_all_slices_parallel()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn parse_all_slices_parallel<T: FromColumnSlice + Send>(&self) -> Result<Vec<Vec<T>>, ParseError> {
+        use rayon::prelude::*;
+
+        let filled = FilledCells::new(&self.records, &self.config);
+
+        (0..self.slice_count::<T>())
+            .into_par_iter()
+            .map(|i| {
+                let (start_col, end_col) = self.validate_slice_index::<T>(i)?;
+                self.parse_columns(&self.config, start_col, end_col, filled.as_ref())
+            })
+            .collect()
+    }
+
+    /// Parse a slice `WIDTH` columns wide with serde instead of [`FromColumnSlice`].
+    ///
+    /// Each row's slice becomes a record of its own, with the slice's headers as field names,
+    /// and is handed to `csv`'s deserializer. Numbers, `Option`s, enums, `#[serde(rename)]`
+    /// and friends work like they do with `csv` directly. Tuples and tuple structs are filled
+    /// by position, for slices whose headers aren't field names (`Food,,`).
+    ///
+    /// Slices are laid out like any `FromColumnSlice` type with a `COLUMN_COUNT` of `WIDTH`,
+    /// `slice_gap` and row skipping apply the same way.
+    ///
+    /// Requires the `serde` feature.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<T>)` - every row that wasn't skipped, deserialised
+    /// * `Err(ParseError::Csv)` - a row didn't deserialise, the error says which record and line
+    /// * `Err(ParseError)` - `SliceOutOfBounds` or `Layout`, as for [`parse_slice`](Self::parse_slice)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use csv_partitioner::{CsvSliceParser, ParseConfig};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize)]
+    /// #[serde(rename_all = "lowercase")]
+    /// enum Level { Easy, Hard }
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize)]
+    /// struct Word {
+    ///     word: String,
+    ///     lesson: u32,
+    ///     level: Option<Level>,
+    /// }
+    ///
+    /// let data = "word,lesson,level,word,lesson,level\nhola,1,easy,bonjour,2,\nadiós,3,hard,,,\n";
+    /// let parser = CsvSliceParser::from_str(data, ParseConfig::default()).unwrap();
+    ///
+    /// let french: Vec<Word> = parser.parse_slice_serde::<Word, 3>(1).unwrap();
+    /// assert_eq!(french, vec![Word { word: "bonjour".to_string(), lesson: 2, level: None }]);
+    ///
+    /// // by position, headers are ignored
+    /// let spanish: Vec<(String, u32, String)> = parser.parse_slice_serde::<_, 3>(0).unwrap();
+    /// assert_eq!(spanish[1], ("adiós".to_string(), 3, "hard".to_string()));
+    ///
+    /// let bad = CsvSliceParser::from_str("word,lesson\nhola,one\n", ParseConfig::default()).unwrap();
+    /// assert!(bad.parse_slice_serde::<(String, u32), 2>(0).is_err());
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn parse_slice_serde<T: serde::de::DeserializeOwned, const WIDTH: usize>(
+        &self,
+        slice_index: usize,
+    ) -> Result<Vec<T>, ParseError> {
+        let (start_col, end_col) = self.validate_slice_index::<Columns<WIDTH>>(slice_index)?;
+
+        // without a header row the headers are blank, and no field would match them
+        let headers: Option<StringRecord> = self.config.has_headers
+            .then(|| (start_col..end_col).map(|i| self.headers.get(i).unwrap_or("")).collect());
+
+        let mut results = if self.config.reserve_capacity {
+            Vec::with_capacity(self.records.len())
+        } else {
+            Vec::new()
+        };
+
+        for record in &self.records {
+            if self.is_skipped(start_col, end_col, record) {
+                continue
+            }
+
+            let mut slice: StringRecord = (start_col..end_col).map(|i| record.get(i).unwrap_or("")).collect();
+            // keeps the record and line in deserialize errors
+            slice.set_position(record.position().cloned());
+
+            results.push(slice.deserialize(headers.as_ref())?);
+        }
+
+        results.shrink_to_fit();
+
+        Ok(results)
+    }
+
+    /// Find slice boundaries from a repeating header pattern instead of `COLUMN_COUNT`.
+    ///
+    /// A new slice starts at every header matching `pattern[0]` and runs up to the next one
+    /// (or the last column), so slices can differ in width and extra columns like notes
+    /// are fine. Every slice must contain the rest of the pattern, in order.
+    /// Columns before the first match are ignored. Headers are compared according to
+    /// `ParseConfig::header_matching`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<Range<usize>>)` - column range of each slice, left to right
+    /// * `Err(ParseError::Layout)` - empty pattern, or a slice missing part of the pattern
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, ParseConfig};
+    /// # use csv::StringRecord;
+    /// let headers = StringRecord::from(vec![
+    ///     "Japanese", "English", "Kanji",
+    ///     "Japanese", "English", "Kanji", "Notes",
+    /// ]);
+    /// let parser = CsvSliceParser::from_records(headers, vec![], ParseConfig::default());
+    ///
+    /// let slices = parser.slices_by_header_pattern(&["Japanese", "English", "Kanji"]).unwrap();
+    /// assert_eq!(slices, vec![0..3, 3..7]);
+    /// ```
+    pub fn slices_by_header_pattern(&self, pattern: &[&str]) -> Result<Vec<Range<usize>>, ParseError> {
+        let first = pattern.first()
+            .ok_or_else(|| ParseError::Layout("Header pattern must not be empty".to_string()))?;
+        let matching = self.config.header_matching;
+
+        let starts: Vec<usize> = self.headers
+            .iter()
+            .enumerate()
+            .filter(|(_, header)| matching.matches(header, first))
+            .map(|(i, _)| i)
+            .collect();
+
+        let ends = starts.iter().skip(1).copied().chain(std::iter::once(self.headers.len()));
+
+        starts.iter().zip(ends)
+            .map(|(&start, end)| {
+                let mut headers = self.headers.iter().take(end).skip(start + 1);
+
+                for expected in &pattern[1..] {
+                    if !headers.any(|header| matching.matches(header, expected)) {
+                        return Err(ParseError::Layout(format!(
+                            "Slice starting at column {} ('{}') has no '{}' column",
+                            start, first, expected
+                        )));
+                    }
+                }
+
+                Ok(start..end)
+            })
+            .collect()
+    }
+
+    /// Parse the slice covering `columns`, e.g. one returned by
+    /// [`slices_by_header_pattern`](Self::slices_by_header_pattern).
+    ///
+    /// `T::from_record` is called with `columns.start`, the range must be at least
+    /// `T::MIN_COLUMN_COUNT` wide. Empty rows are judged over the whole range.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # use std::error::Error;
+    /// # struct Entry { word: String }
+    /// # impl FromColumnSlice for Entry {
+    /// #     const COLUMN_COUNT: usize = 2;
+    /// #     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    /// #         Ok(Entry { word: record.get(start_col).unwrap_or("").to_string() })
+    /// #     }
+    /// # }
+    /// # fn example() -> Result<(), Box<dyn Error>> {
+    /// let data = "Japanese,English,Japanese,English,Notes\nいぬ,dog,ねこ,cat,meow\n";
+    /// let parser = CsvSliceParser::from_reader(data.as_bytes(), ParseConfig::default())?;
     ///
-    /// // Parse columns 3-5 (second slice)
-    /// let second_language: Vec<VocabEntry> = parser.parse_slice(1)?;
+    /// let mut words = Vec::new();
+    /// for columns in parser.slices_by_header_pattern(&["Japanese", "English"])? {
+    ///     let entries: Vec<Entry> = parser.parse_slice_columns(columns)?;
+    ///     words.extend(entries.into_iter().map(|e| e.word));
+    /// }
+    /// assert_eq!(words, vec!["いぬ", "ねこ"]);
     /// # Ok(())
     /// # }
+    /// # example().unwrap();
     /// ```
-    pub fn parse_slice<T: FromColumnSlice>(&self, slice_index: usize) -> Result<Vec<T>, Box<dyn Error>> {
-        let (start_col, end_col) = self.validate_slice_index::<T>(slice_index)?;
+    pub fn parse_slice_columns<T: FromColumnSlice>(&self, columns: Range<usize>) -> Result<Vec<T>, ParseError> {
+        let () = T::NON_ZERO_WIDTH;
 
-        let mut results = if self.config.reserve_capacity {
-            Vec::with_capacity(self.records.len())
-        } else {
-            Vec::new()
-        };
-
-        for record in &self.records {
-            if self.config.skip_empty_rows {
-                if self.has_empty_fields(start_col, end_col, record) {
-                    continue
-                }
-            }
-            results.push(T::from_record(record, start_col)?);
+        if columns.end > self.headers.len() {
+            return Err(ParseError::Layout(format!(
+                "Columns {}-{} out of bounds, only {} columns available",
+                columns.start, columns.end, self.headers.len()
+            )));
         }
 
-        results.shrink_to_fit();
+        if columns.len() < T::MIN_COLUMN_COUNT {
+            return Err(ParseError::Layout(format!(
+                "{} spans {} columns, but columns {}-{} are only {} wide",
+                std::any::type_name::<T>(), T::MIN_COLUMN_COUNT, columns.start, columns.end, columns.len()
+            )));
+        }
 
-        Ok(results)
+        self.parse_columns(&self.config, columns.start, columns.end, None)
     }
 
-    /// Parse a slice lazily with an iterator.
+    /// Parse every slice of a [`SliceLayout`], for files whose slices have different types.
     ///
-    /// This provides memory-efficient processing by parsing records on-demand
-    /// instead of allocating all results upfront.
+    /// # Returns
     ///
-    /// # Arguments
+    /// * `Ok(Vec<E>)` - one value per slice of the layout, in column order
+    /// * `Err(ParseError::Layout)` - the layout is wider than the CSV, or a slice narrower than its type
+    /// * `Err(ParseError)` - whatever a `from_record` returned (with its row)
+    pub fn parse_layout<E>(&self, layout: &SliceLayout<E>) -> Result<Vec<E>, ParseError> {
+        layout.parse(self, self.config.slice_gap)
+    }
+
+    /// Check that every slice's headers follow the same pattern.
     ///
-    /// * `slice_index` - Zero-based index of the slice to parse
+    /// `expected` names each column of a slice (`"*"` accepts any name) and is compared with
+    /// every slice using the config's [`HeaderMatching`]. Run it before parsing to catch a
+    /// mistyped header instead of importing under the wrong name.
     ///
     /// # Returns
     ///
-    /// An iterator that yields `Result<T, Box<dyn Error>>` for each row.
+    /// * `Ok(())` - every slice matches
+    /// * `Err(ParseError::HeaderMismatch)` - every column that doesn't, across all slices
+    /// * `Err(ParseError::Layout)` - `expected` isn't `COLUMN_COUNT` long, or `T` doesn't fit the CSV
     ///
     /// # Example
     ///
     /// ```rust
-    /// # use csv_slice_parser::{CsvSliceParser, FromColumnSlice};
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
     /// # use csv::StringRecord;
-    /// # use std::error::Error;
-    /// # #[derive(Debug)]
-    /// # struct Entry { field: String }
+    /// # struct Entry;
     /// # impl FromColumnSlice for Entry {
-    /// #     const COLUMN_COUNT: usize = 1;
-    /// #     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, Box<dyn Error>> {
-    /// #         Ok(Entry { field: record.get(start_col).unwrap_or("").to_string() })
-    /// #     }
+    /// #     const COLUMN_COUNT: usize = 3;
+    /// #     fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> { Ok(Entry) }
     /// # }
-    /// # fn example() -> Result<(), Box<dyn Error>> {
-    /// # let parser = CsvSliceParser::from_file("data.csv")?;
-    /// // Process only the first 100 entries
-    /// for result in parser.parse_slice_iter::<Entry>(0)?.take(100) {
-    ///     let entry = result?;
-    ///     println!("{:?}", entry);
+    /// let data = "Food,Reading,English,Verbs,Raeding,English\n";
+    /// let parser = CsvSliceParser::from_str(data, ParseConfig::default()).unwrap();
+    ///
+    /// match parser.validate_headers::<Entry>(&["*", "Reading", "English"]) {
+    ///     Err(ParseError::HeaderMismatch(mismatches)) => {
+    ///         assert_eq!(mismatches.len(), 1);
+    ///         assert_eq!((mismatches[0].slice_index, mismatches[0].column), (1, 4));
+    ///         assert_eq!(mismatches[0].found, "Raeding");
+    ///     },
+    ///     _ => unreachable!(),
     /// }
+    /// ```
+    pub fn validate_headers<T: FromColumnSlice>(&self, expected: &[&str]) -> Result<(), ParseError> {
+        self.validate_layout::<T>()?;
+
+        if expected.len() != T::COLUMN_COUNT {
+            return Err(ParseError::Layout(format!(
+                "{} spans {} columns, but {} header names were expected",
+                std::any::type_name::<T>(), T::COLUMN_COUNT, expected.len()
+            )));
+        }
+
+        let mismatches: Vec<HeaderMismatch> = (0..self.slice_count::<T>())
+            .filter_map(|index| self.slice_headers::<T>(index))
+            .flat_map(|headers| headers.mismatches(expected))
+            .collect();
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(ParseError::HeaderMismatch(mismatches))
+        }
+    }
+
+    /// [`validate_headers`](Self::validate_headers) against `T::EXPECTED_HEADERS`.
     ///
-    /// // Filter while parsing
-    /// let filtered: Vec<Entry> = parser.parse_slice_iter::<Entry>(0)?
-    ///     .filter_map(Result::ok)
-    ///     .filter(|e| e.field.len() > 5)
-    ///     .collect();
-    /// # Ok(())
-    /// # }
+    /// Always `Ok` for types that don't declare any.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// struct Pair;
+    ///
+    /// impl FromColumnSlice for Pair {
+    ///     const COLUMN_COUNT: usize = 2;
+    ///     const EXPECTED_HEADERS: &'static [&'static str] = &["Word", "Translation"];
+    ///
+    ///     fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> { Ok(Pair) }
+    /// }
+    ///
+    /// let parser = CsvSliceParser::from_str("word,translation,Word,Transl\n", ParseConfig::default()).unwrap();
+    /// let error = parser.validate_declared_headers::<Pair>().unwrap_err();
+    /// assert_eq!(error.to_string(), "Headers don't match: column 4 (slice 1) is 'Transl', expected 'Translation'");
     /// ```
-    pub fn parse_slice_iter<'a, T: FromColumnSlice + 'a>(
-        &'a self,
-        slice_index: usize
-    ) -> Result<impl Iterator<Item = Result<T, Box<dyn Error>>> + 'a, Box<dyn Error>> {
-        let (start_col, end_col) = self.validate_slice_index::<T>(slice_index)?;
+    pub fn validate_declared_headers<T: FromColumnSlice>(&self) -> Result<(), ParseError> {
+        if T::EXPECTED_HEADERS.is_empty() {
+            return Ok(());
+        }
 
-        Ok(self.records.iter().filter_map(move |record| {
-            if self.config.skip_empty_rows {
-                if self.has_empty_fields(start_col, end_col, record) {
-                    return None;
-                }
-            }
-            Some(T::from_record(record, start_col))
-        }))
+        self.validate_headers::<T>(T::EXPECTED_HEADERS)
     }
 
-    /// Parse all slices into separate vectors.
+    /// Get the column headers for a specific slice, `None` if it's out of bounds.
     ///
-    /// Convenience method to parse every available slice in one call.
+    /// The headers are owned (they don't borrow the parser) and can check themselves
+    /// against the expected names with [`SliceHeaders::expected_names`].
     ///
     /// # Example
     ///
     /// ```rust
-    /// # use csv_slice_parser::{CsvSliceParser, FromColumnSlice};
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseError};
     /// # use csv::StringRecord;
     /// # use std::error::Error;
-    /// # #[derive(Debug)]
-    /// # struct Entry { field: String }
+    /// # struct Entry;
     /// # impl FromColumnSlice for Entry {
     /// #     const COLUMN_COUNT: usize = 3;
-    /// #     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, Box<dyn Error>> {
-    /// #         Ok(Entry { field: "".into() })
-    /// #     }
+    /// #     fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> { Ok(Entry) }
     /// # }
     /// # fn example() -> Result<(), Box<dyn Error>> {
     /// # let parser = CsvSliceParser::from_file("data.csv")?;
-    /// let all_slices: Vec<Vec<Entry>> = parser.parse_all_slices()?;
-    ///
-    /// for (i, slice) in all_slices.iter().enumerate() {
-    ///     println!("Slice {} has {} entries", i, slice.len());
+    /// if let Some(headers) = parser.slice_headers::<Entry>(0) {
+    ///     println!("Slice 0 is '{}', headers: {:?}", headers.first(), headers.names());
     /// }
     /// # Ok(())
     /// # }
     /// ```
-    pub fn parse_all_slices<T: FromColumnSlice>(&self) -> Result<Vec<Vec<T>>, Box<dyn Error>> {
-        let slice_count = self.slice_count::<T>();
-        let mut all_slices: Vec<Vec<T>> = Vec::with_capacity(slice_count);
+    ///
+    /// Slices account for `ParseConfig::slice_gap`:
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # struct Entry;
+    /// # impl FromColumnSlice for Entry {
+    /// #     const COLUMN_COUNT: usize = 2;
+    /// #     fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> { Ok(Entry) }
+    /// # }
+    /// let config = ParseConfig { slice_gap: 1, ..ParseConfig::default() };
+    /// let parser = CsvSliceParser::from_str("Food,,,Verbs,Notes\n", config).unwrap();
+    ///
+    /// let verbs = parser.slice_headers::<Entry>(1).unwrap();
+    /// assert_eq!((verbs.columns(), verbs.first(), verbs.get(1)), (3..5, "Verbs", Some("Notes")));
+    /// assert!(parser.slice_headers::<Entry>(2).is_none());
+    /// ```
+    pub fn slice_headers<T: FromColumnSlice>(&self, slice_index: usize) -> Option<SliceHeaders> {
+        self.slice_columns::<T>(slice_index).map(|columns| {
+            let names = columns.clone().map(|i| self.headers.get(i).unwrap_or("").to_string()).collect();
+            SliceHeaders::new(slice_index, columns, names, self.config.header_matching)
+        })
+    }
 
-        for i in 0..slice_count {
-            all_slices.push(self.parse_slice(i)?)
+    /// Replace the rows of a slice with `values`, to save the file afterwards.
+    ///
+    /// `values` line up with the rows [`parse_slice`](Self::parse_slice) returns, so
+    /// parse, edit, write back is a round trip. Extra values go into the first rows below
+    /// the slice's last row where the slice is blank, then into new rows at the end. With
+    /// fewer values the slice's remaining rows are cleared. Other slices aren't touched.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - the records now hold the values
+    /// * `Err(ParseError::Layout)` - a value's `to_fields` isn't `COLUMN_COUNT` long, or a narrower
+    ///   last slice has no columns for a value's trailing fields (that aren't blank or already
+    ///   in the row), nothing is written
+    /// * `Err(ParseError::SliceOutOfBounds)` - no such slice
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError, ToColumnSlice};
+    /// # use csv::StringRecord;
+    /// struct Word(String);
+    ///
+    /// impl FromColumnSlice for Word {
+    ///     const COLUMN_COUNT: usize = 1;
+    ///     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    ///         Ok(Word(record.get(start_col).unwrap_or("").to_string()))
+    ///     }
+    /// }
+    ///
+    /// impl ToColumnSlice for Word {
+    ///     fn to_fields(&self) -> Vec<String> {
+    ///         vec![self.0.clone()]
+    ///     }
+    /// }
+    ///
+    /// let mut parser = CsvSliceParser::from_str("Food,Verbs\nApple,eat\nPEAR,\n", ParseConfig::default()).unwrap();
+    ///
+    /// let mut food: Vec<Word> = parser.parse_slice(0).unwrap();
+    /// food.iter_mut().for_each(|word| word.0 = word.0.to_lowercase());
+    /// food.push(Word("plum".to_string()));
+    /// parser.write_slice(0, &food).unwrap();
+    ///
+    /// let mut csv = Vec::new();
+    /// parser.write_to(&mut csv).unwrap();
+    /// assert_eq!(String::from_utf8(csv).unwrap(), "Food,Verbs\napple,eat\npear,\nplum,\n");
+    /// ```
+    pub fn write_slice<T: ToColumnSlice>(&mut self, slice_index: usize, values: &[T]) -> Result<(), ParseError> {
+        let (start_col, end_col) = self.validate_slice_index::<T>(slice_index)?;
+
+        let fields: Vec<Vec<String>> = values.iter().map(ToColumnSlice::to_fields).collect();
+        if let Some(bad) = fields.iter().find(|fields| fields.len() != T::COLUMN_COUNT) {
+            return Err(ParseError::Layout(format!(
+                "{}::to_fields returned {} fields, COLUMN_COUNT is {}",
+                std::any::type_name::<T>(), bad.len(), T::COLUMN_COUNT
+            )));
         }
 
-        Ok(all_slices)
+        // the rows parse_slice reads from, then the blank ones below them, then new rows
+        let parsed: Vec<usize> = (0..self.records.len())
+            .filter(|&row| !self.is_skipped(start_col, end_col, &self.records[row]))
+            .collect();
+        let after = parsed.last().map_or(0, |last| last + 1);
+        let blank = (after..self.records.len()).filter(|&row| is_blank(&self.records[row], start_col, end_col));
+        let targets: Vec<usize> = parsed.iter().copied().chain(blank).take(fields.len()).collect();
+
+        // a narrower last slice only has room for its first cells, the rest have to be blank
+        // or already in the row (when it's longer than the headers)
+        let width = end_col - start_col;
+        for (idx, cells) in fields.iter().enumerate() {
+            let record = targets.get(idx).map(|&row| &self.records[row]);
+            let lost = cells.iter().enumerate().skip(width).find(|&(offset, cell)| {
+                !cell.trim().is_empty() && record.and_then(|record| record.get(start_col + offset)) != Some(cell.as_str())
+            });
+
+            if let Some((offset, cell)) = lost {
+                return Err(ParseError::Layout(format!(
+                    "Slice {} has {} columns, value {} would lose '{}' from its field {}",
+                    slice_index, width, idx, cell, offset
+                )));
+            }
+        }
+
+        for (idx, cells) in fields.iter().enumerate() {
+            let row = match targets.get(idx) {
+                Some(&row) => row,
+                None => {
+                    self.records.push(std::iter::repeat_n("", self.headers.len()).collect());
+                    self.records.len() - 1
+                },
+            };
+            set_cells(&mut self.records[row], start_col, &cells[..width]);
+        }
+
+        let empty = vec![String::new(); width];
+        for &row in parsed.iter().skip(fields.len()) {
+            set_cells(&mut self.records[row], start_col, &empty);
+        }
+
+        Ok(())
+    }
+
+    /// Write the title and header rows (unless the config has none) and every record as CSV.
+    ///
+    /// Uses the config's delimiter and quote. Comment lines from the original aren't kept.
+    pub fn write_to<W: Write>(&self, writer: W) -> Result<(), ParseError> {
+        let mut writer = WriterBuilder::new()
+            .delimiter(self.config.delimiter)
+            .quote(self.config.quote)
+            .flexible(true)
+            .from_writer(writer);
+
+        for record in &self.top_rows {
+            writer.write_record(record)?;
+        }
+        if self.config.has_headers {
+            writer.write_record(&self.headers)?;
+        }
+        for record in &self.records {
+            writer.write_record(record)?;
+        }
+
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Save the CSV to `path`, see [`write_to`](Self::write_to).
+    ///
+    /// ```rust,no_run
+    /// # use csv_partitioner::CsvSliceParser;
+    /// # use std::error::Error;
+    /// # fn example() -> Result<(), Box<dyn Error>> {
+    /// let parser = CsvSliceParser::from_file("vocabulary.csv")?;
+    /// // ... write_slice edits ...
+    /// parser.save_to_file("vocabulary.csv")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), ParseError> {
+        self.write_to(File::create(path)?)
     }
 
-    /// Get the column headers for a specific slice.
+    /// Write one slice as a CSV of its own: its headers (unless the config has none), then
+    /// its cells of every row [`parse_slice`](Self::parse_slice) keeps.
+    ///
+    /// Rows aren't deserialised, `T` only gives the slice's columns. Uses the config's
+    /// delimiter and quote, like [`write_to`](Self::write_to).
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - the slice was written
+    /// * `Err(ParseError)` - `SliceOutOfBounds`, `Layout`, or a `Csv` error from writing
     ///
     /// # Example
     ///
     /// ```rust
-    /// # use csv_slice_parser::{CsvSliceParser, FromColumnSlice};
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # struct Word;
+    /// # impl FromColumnSlice for Word {
+    /// #     const COLUMN_COUNT: usize = 2;
+    /// #     fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> { Ok(Word) }
+    /// # }
+    /// let parser = CsvSliceParser::from_str("Food,,Verbs,\nりんご,apple,いく,to go\n,,\"たべる, くう\",to eat\n", ParseConfig::default()).unwrap();
+    ///
+    /// let mut verbs = Vec::new();
+    /// parser.write_slice_to::<Word, _>(1, &mut verbs).unwrap();
+    ///
+    /// assert_eq!(String::from_utf8(verbs).unwrap(), "Verbs,\nいく,to go\n\"たべる, くう\",to eat\n");
+    /// ```
+    pub fn write_slice_to<T: FromColumnSlice, W: Write>(&self, slice_index: usize, writer: W) -> Result<(), ParseError> {
+        let (start_col, end_col) = self.validate_slice_index::<T>(slice_index)?;
+        let mut writer = WriterBuilder::new()
+            .delimiter(self.config.delimiter)
+            .quote(self.config.quote)
+            .from_writer(writer);
+
+        let cells = |record: &StringRecord| (start_col..end_col).map(|i| record.get(i).unwrap_or("").to_string()).collect::<Vec<_>>();
+
+        if self.config.has_headers {
+            writer.write_record(cells(&self.headers))?;
+        }
+        for record in self.records.iter().filter(|record| !self.is_skipped(start_col, end_col, record)) {
+            writer.write_record(cells(record))?;
+        }
+
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Save one slice to a CSV file of its own, see [`write_slice_to`](Self::write_slice_to).
+    ///
+    /// For tools that take one topic per file, like Anki's own CSV import.
+    ///
+    /// ```rust,no_run
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseError};
     /// # use csv::StringRecord;
     /// # use std::error::Error;
-    /// # struct Entry;
-    /// # impl FromColumnSlice for Entry {
+    /// # struct Word;
+    /// # impl FromColumnSlice for Word {
     /// #     const COLUMN_COUNT: usize = 3;
-    /// #     fn from_record(_: &StringRecord, _: usize) -> Result<Self, Box<dyn Error>> { Ok(Entry) }
+    /// #     fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> { Ok(Word) }
     /// # }
     /// # fn example() -> Result<(), Box<dyn Error>> {
-    /// # let parser = CsvSliceParser::from_file("data.csv")?;
-    /// if let Some(headers) = parser.slice_headers::<Entry>(0) {
-    ///     println!("Slice 0 headers: {:?}", headers);
+    /// let parser = CsvSliceParser::from_file("vocabulary.csv")?;
+    /// for slice in parser.slices::<Word>() {
+    ///     parser.export_slice_csv::<Word, _>(slice.index, format!("{}.csv", slice.name()))?;
     /// }
     /// # Ok(())
     /// # }
     /// ```
-    #[inline]
-    pub fn slice_headers<T: FromColumnSlice>(&self, slice_index: usize) -> Option<Vec<&str>> {
-        let start_col = slice_index * T::COLUMN_COUNT;
-        let end_col = slice_index + T::COLUMN_COUNT;
+    #[cfg(feature = "fs")]
+    pub fn export_slice_csv<T: FromColumnSlice, P: AsRef<Path>>(&self, slice_index: usize, path: P) -> Result<(), ParseError> {
+        // checked before the file is created, so a bad index doesn't leave an empty file behind
+        self.validate_slice_index::<T>(slice_index)?;
 
-        if end_col > self.headers.len() {
-            None
-        } else {
-            Some((start_col..end_col)
-                .filter_map(|i| self.headers.get(i))
-                .collect())
-        }
+        self.write_slice_to::<T, _>(slice_index, File::create(path)?)
     }
 
     /// Access the underlying CSV records for custom processing.
@@ -592,7 +2851,7 @@ impl CsvSliceParser {
     /// # Example
     ///
     /// ```rust
-    /// # use csv_slice_parser::CsvSliceParser;
+    /// # use csv_partitioner::CsvSliceParser;
     /// # use std::error::Error;
     /// # fn example() -> Result<(), Box<dyn Error>> {
     /// # let parser = CsvSliceParser::from_file("data.csv")?;
@@ -608,12 +2867,34 @@ impl CsvSliceParser {
         &self.records
     }
 
+    /// Find the index of the first header matching `name`,
+    /// compared according to `ParseConfig::header_matching`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, ParseConfig};
+    /// # use csv::StringRecord;
+    /// let headers = StringRecord::from(vec!["Japanese", "English", "Kanji"]);
+    /// let parser = CsvSliceParser::from_records(headers, vec![], ParseConfig::default());
+    ///
+    /// assert_eq!(parser.find_header(" english"), Some(1));
+    /// # #[cfg(feature = "nfkc")]
+    /// assert_eq!(parser.find_header("ｅｎｇｌｉｓｈ"), Some(1));
+    /// assert_eq!(parser.find_header("French"), None);
+    /// ```
+    pub fn find_header(&self, name: &str) -> Option<usize> {
+        self.headers
+            .iter()
+            .position(|header| self.config.header_matching.matches(header, name))
+    }
+
     /// Access the CSV headers.
     ///
     /// # Example
     ///
     /// ```rust
-    /// # use csv_slice_parser::CsvSliceParser;
+    /// # use csv_partitioner::CsvSliceParser;
     /// # use std::error::Error;
     /// # fn example() -> Result<(), Box<dyn Error>> {
     /// # let parser = CsvSliceParser::from_file("data.csv")?;
@@ -626,4 +2907,211 @@ impl CsvSliceParser {
     pub fn headers(&self) -> &StringRecord {
         &self.headers
     }
-}
\ No newline at end of file
+
+    /// Every header row, top to bottom, the last one being [`headers`](Self::headers).
+    ///
+    /// More than one with `ParseConfig::header_rows`, title rows above them
+    /// (`header_row`) aren't included. Empty when the config has no headers.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, ParseConfig};
+    /// let data = "JLPT N5 vocabulary,,,\nFood,,Verbs,\nkana,english,kana,english\nりんご,apple,いく,to go\n";
+    /// let config = ParseConfig::builder().header_row(1).header_rows(2).build();
+    /// let parser = CsvSliceParser::from_str(data, config).unwrap();
+    ///
+    /// let rows = parser.header_rows();
+    /// assert_eq!(rows.len(), 2);
+    /// assert_eq!(rows[0].get(2), Some("Verbs"));
+    /// assert_eq!(parser.headers().get(2), Some("kana"));
+    /// assert_eq!(parser.record_count(), 1);
+    /// ```
+    pub fn header_rows(&self) -> Vec<&StringRecord> {
+        let above = if self.config.has_headers { self.top_rows.get(self.config.header_row..).unwrap_or_default() } else { &[] };
+
+        above.iter().chain(self.config.has_headers.then_some(&self.headers)).collect()
+    }
+
+    /// The headers of a slice on every header row, top to bottom, `None` if it's out of bounds.
+    ///
+    /// With two header rows, e.g. the topic name is `[0].first()` and the field names are in `[1]`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # struct Word;
+    /// # impl FromColumnSlice for Word {
+    /// #     const COLUMN_COUNT: usize = 2;
+    /// #     fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> { Ok(Word) }
+    /// # }
+    /// let data = "Food,,Verbs,\nkana,english,kana,english\nりんご,apple,いく,to go\n";
+    /// let parser = CsvSliceParser::from_str(data, ParseConfig::builder().header_rows(2).build()).unwrap();
+    ///
+    /// let verbs = parser.slice_header_rows::<Word>(1).unwrap();
+    /// assert_eq!(verbs[0].first(), "Verbs");
+    /// assert_eq!(verbs[1].names(), ["kana", "english"]);
+    /// ```
+    pub fn slice_header_rows<T: FromColumnSlice>(&self, slice_index: usize) -> Option<Vec<SliceHeaders>> {
+        let columns = self.slice_columns::<T>(slice_index)?;
+
+        Some(self.header_rows().into_iter().map(|row| {
+            let names = columns.clone().map(|i| row.get(i).unwrap_or("").to_string()).collect();
+            SliceHeaders::new(slice_index, columns.clone(), names, self.config.header_matching)
+        }).collect())
+    }
+}
+
+/// stands in for a `FromColumnSlice` type `WIDTH` columns wide, to lay out serde slices
+#[cfg(feature = "serde")]
+struct Columns<const WIDTH: usize>;
+
+#[cfg(feature = "serde")]
+impl<const WIDTH: usize> FromColumnSlice for Columns<WIDTH> {
+    const COLUMN_COUNT: usize = WIDTH;
+
+    fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> {
+        Ok(Columns)
+    }
+}
+
+/// stands in for a `FromColumnSliceRef` type to lay out its slices, never parsed itself
+struct Borrowed<T>(std::marker::PhantomData<T>);
+
+impl<'a, T: FromColumnSliceRef<'a>> FromColumnSlice for Borrowed<T> {
+    const COLUMN_COUNT: usize = T::COLUMN_COUNT;
+
+    fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> {
+        Ok(Borrowed(std::marker::PhantomData))
+    }
+}
+
+/// csv reader shared by the in-memory and streaming parsers, `read_headers` takes the rows above the data
+fn csv_reader<R: Read>(reader: R, config: &ParseConfig) -> Reader<DecodingReader<R>> {
+    reader_builder(config)
+        .has_headers(false)
+        .from_reader(DecodingReader::new(reader, config.encoding))
+}
+
+/// `ReaderBuilder` with the config's dialect (delimiter, quoting, comments, trimming, ragged rows)
+fn reader_builder(config: &ParseConfig) -> ReaderBuilder {
+    let mut builder = ReaderBuilder::new();
+    builder
+        .delimiter(config.delimiter)
+        .quote(config.quote)
+        .comment(config.comment)
+        .flexible(config.is_flexible())
+        .trim(if config.trim_fields { csv::Trim::All } else { csv::Trim::None });
+    builder
+}
+
+/// read the rows above the data, see `split_top_rows`. Without headers the first data row
+/// is read too, to size the blank headers, and handed back to be parsed
+fn read_headers<R: Read>(
+    reader: &mut Reader<R>,
+    config: &ParseConfig,
+) -> Result<(Vec<StringRecord>, StringRecord, Option<StringRecord>), ParseError> {
+    let mut top_rows = Vec::new();
+    let mut record = StringRecord::new();
+    while top_rows.len() < config.rows_above_data() && reader.read_record(&mut record)? {
+        top_rows.push(std::mem::take(&mut record));
+    }
+
+    let first = if config.has_headers { None } else { reader.read_record(&mut record)?.then_some(record) };
+    let (top_rows, headers) = split_top_rows(config, top_rows, first.as_ref());
+    config.check_slice_gap(&headers)?;
+
+    Ok((top_rows, headers, first))
+}
+
+/// the rows above the data without the header row, and the header row. Without headers
+/// that's blank headers as wide as `first_data`
+fn split_top_rows(
+    config: &ParseConfig,
+    mut top_rows: Vec<StringRecord>,
+    first_data: Option<&StringRecord>,
+) -> (Vec<StringRecord>, StringRecord) {
+    let headers = if !config.has_headers {
+        first_data.map(|record| record.iter().map(|_| "").collect()).unwrap_or_default()
+    } else if top_rows.len() > config.header_row {
+        top_rows.pop().unwrap_or_default()
+    } else {
+        StringRecord::new()
+    };
+
+    (top_rows, headers)
+}
+
+/// `(start_col, end_col)` of a slice, `None` if the arithmetic overflows
+fn slice_columns<T: FromColumnSlice>(config: &ParseConfig, slice_index: usize, column_count: usize) -> Option<(usize, usize)> {
+    let stride = T::COLUMN_COUNT.checked_add(config.slice_gap)?;
+    let start_col = slice_index.checked_mul(stride)?;
+    // only the last slice can be cut short, the ones before it have a whole slice after them
+    let end_col = start_col.checked_add(T::COLUMN_COUNT)?.min(column_count);
+
+    (end_col >= start_col.checked_add(T::MIN_COLUMN_COUNT)?).then_some((start_col, end_col))
+}
+
+/// number of slices in `column_count` columns, the last slice needs no trailing gap and can
+/// be as narrow as `MIN_COLUMN_COUNT`
+fn count_slices<T: FromColumnSlice>(config: &ParseConfig, column_count: usize) -> usize {
+    // every whole slice after the first has a gap in front of it
+    let whole = match (column_count.checked_sub(T::COLUMN_COUNT), T::COLUMN_COUNT.checked_add(config.slice_gap)) {
+        (None, _) | (_, Some(0)) => 0,
+        (Some(rest), Some(stride)) => 1 + rest / stride,
+        // a gap that big leaves no room for a second slice
+        (Some(_), None) => 1,
+    };
+
+    whole + usize::from(T::MIN_COLUMN_COUNT < T::COLUMN_COUNT && slice_columns::<T>(config, whole, column_count).is_some())
+}
+
+/// put `cells` into the record from `start_col` on, padding short records with empty fields
+fn set_cells(record: &mut StringRecord, start_col: usize, cells: &[String]) {
+    let mut fields: Vec<String> = record.iter().map(str::to_string).collect();
+    if fields.len() < start_col + cells.len() {
+        fields.resize(start_col + cells.len(), String::new());
+    }
+    fields[start_col..start_col + cells.len()].clone_from_slice(cells);
+
+    let position = record.position().cloned();
+    *record = StringRecord::from(fields);
+    record.set_position(position);
+}
+
+/// drop values equal to an earlier one, keeping the order, returns how many were dropped
+fn dedup_in_order<T: Eq + std::hash::Hash>(values: &mut Vec<T>) -> usize {
+    let keep: Vec<bool> = {
+        let mut seen = HashSet::with_capacity(values.len());
+        values.iter().map(|value| seen.insert(value)).collect()
+    };
+
+    let before = values.len();
+    let mut keep = keep.into_iter();
+    values.retain(|_| keep.next().unwrap_or(true));
+
+    before - values.len()
+}
+
+/// true when every column in `start_col..end_col` is missing or whitespace
+fn is_blank(record: &StringRecord, start_col: usize, end_col: usize) -> bool {
+    (start_col..end_col)
+        .all(|i| record.get(i).is_none_or(|s| s.trim().is_empty()))
+}
+
+/// true when `config` says the row should be left out of the slice `start_col..end_col`
+fn is_skipped(config: &ParseConfig, record: &StringRecord, start_col: usize, end_col: usize) -> bool {
+    is_marked_or_ragged(config, record, start_col, end_col)
+        || (config.skip_empty_rows && config.empty_row_policy.is_empty(record, start_col, end_col))
+}
+
+/// the reasons besides being empty to leave a row out of the slice `start_col..end_col`
+fn is_marked_or_ragged(config: &ParseConfig, record: &StringRecord, start_col: usize, end_col: usize) -> bool {
+    let marked = config.skip_marker.as_deref().is_some_and(|marker| {
+        (start_col..end_col).any(|i| record.get(i).is_some_and(|s| s.trim() == marker))
+    });
+
+    marked || (config.ragged_rows == RaggedPolicy::Skip && record.len() < end_col)
+}
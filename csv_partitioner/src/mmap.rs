@@ -0,0 +1,230 @@
+//! Parsing slices in place from a memory-mapped file, with the `mmap` feature.
+
+use std::fs::File;
+use std::path::Path;
+
+use csv::{Reader, StringRecord};
+use memmap2::Mmap;
+
+use crate::{
+    count_slices, is_skipped, reader_builder, slice_columns, split_top_rows, CsvSliceParser, FromColumnSlice,
+    ParseConfig, ParseError, ProgressEvent, PROGRESS_INTERVAL,
+};
+
+impl CsvSliceParser {
+    /// Map a CSV file into memory instead of loading its records, see [`MmapSliceParser`].
+    ///
+    /// Requires the `mmap` feature.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # use std::error::Error;
+    /// # struct Entry { word: String }
+    /// # impl FromColumnSlice for Entry {
+    /// #     const COLUMN_COUNT: usize = 3;
+    /// #     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    /// #         Ok(Entry { word: record.get(start_col).unwrap_or("").to_string() })
+    /// #     }
+    /// # }
+    /// # fn example() -> Result<(), Box<dyn Error>> {
+    /// let parser = CsvSliceParser::from_file_mmap("huge.csv", ParseConfig::default())?;
+    ///
+    /// for slice_index in 0..parser.slice_count::<Entry>() {
+    ///     let entries: Vec<Entry> = parser.parse_slice(slice_index)?;
+    ///     println!("slice {}: {} entries", slice_index, entries.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_file_mmap<P: AsRef<Path>>(path: P, config: ParseConfig) -> Result<MmapSliceParser, ParseError> {
+        MmapSliceParser::open(path, config)
+    }
+}
+
+/// A CSV file parsed in place, for inputs too big to copy into memory.
+///
+/// Opening maps the file and reads through it once, keeping only where each row starts
+/// (8 bytes a row). Rows are parsed from the mapped bytes when they're asked for, so
+/// memory use is what gets returned rather than the whole file. Unlike
+/// [`CsvSliceStreamer`](crate::CsvSliceStreamer) any number of slices and single rows can
+/// be parsed, in any order.
+///
+/// The file has to be UTF-8 (`InputEncoding::Utf8` or `Utf8Bom`), and mustn't be written
+/// to while it's mapped. Requires the `mmap` feature.
+pub struct MmapSliceParser {
+    mmap: Mmap,
+    headers: StringRecord,
+
+    /// byte offset of every data row in `mmap`
+    offsets: Vec<u64>,
+    config: ParseConfig,
+}
+
+impl MmapSliceParser {
+    /// Map `path` and index its rows, reporting progress like
+    /// [`CsvSliceParser::from_reader`] does.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(MmapSliceParser)` - the file, ready to slice
+    /// * `Err(ParseError::Io)` - the file can't be opened or mapped
+    /// * `Err(ParseError::Csv)` - the file isn't valid CSV or UTF-8
+    /// * `Err(ParseError::Custom)` - the config asks for an encoding other than UTF-8
+    pub fn open<P: AsRef<Path>>(path: P, config: ParseConfig) -> Result<Self, ParseError> {
+        if !config.encoding.is_utf8() {
+            return Err(ParseError::custom(format!(
+                "Memory-mapped files have to be UTF-8, load {} files with from_file_with_config", config.encoding
+            )));
+        }
+
+        let file = File::open(path)?;
+        // SAFETY: the map is only ever read. Another process writing to the file while it's
+        // mapped is undefined behaviour, which is why the docs rule it out.
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        let mut reader = reader_builder(&config).has_headers(false).from_reader(&mmap[..]);
+        let mut record = StringRecord::new();
+        let mut top_rows: Vec<StringRecord> = Vec::new();
+        let mut headers: Option<StringRecord> = None;
+        let mut offsets = Vec::new();
+
+        loop {
+            let offset = reader.position().byte();
+            if !reader.read_record(&mut record)? {
+                break;
+            }
+
+            if headers.is_none() {
+                if top_rows.len() < config.rows_above_data() {
+                    top_rows.push(record.clone());
+                    continue;
+                }
+                headers = Some(split_top_rows(&config, std::mem::take(&mut top_rows), Some(&record)).1);
+            }
+
+            offsets.push(offset);
+            if offsets.len().is_multiple_of(PROGRESS_INTERVAL) {
+                config.report(|| ProgressEvent::Loading { records: offsets.len(), bytes: reader.position().byte() });
+            }
+        }
+        config.report(|| ProgressEvent::Loaded { records: offsets.len() });
+
+        drop(reader);
+        let headers = headers.unwrap_or_else(|| split_top_rows(&config, top_rows, None).1);
+        config.check_slice_gap(&headers)?;
+
+        Ok(MmapSliceParser { mmap, headers, offsets, config })
+    }
+
+    /// The header row.
+    pub fn headers(&self) -> &StringRecord {
+        &self.headers
+    }
+
+    /// Number of data rows.
+    pub fn record_count(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Number of column slices available for a given type (same as the in-memory parser).
+    #[inline]
+    pub fn slice_count<T: FromColumnSlice>(&self) -> usize {
+        let () = T::NON_ZERO_WIDTH;
+        count_slices::<T>(&self.config, self.headers.len())
+    }
+
+    /// One data row, read from the file.
+    ///
+    /// Short rows are padded when `ragged_rows` is `PadEmpty`, like loaded records are.
+    pub fn record(&self, row_index: usize) -> Result<StringRecord, ParseError> {
+        if row_index >= self.offsets.len() {
+            return Err(ParseError::RowOutOfBounds { row_index, row_count: self.offsets.len() });
+        }
+
+        let mut record = StringRecord::new();
+        self.read_next(&mut self.reader_at(row_index), &mut record)?;
+
+        Ok(record)
+    }
+
+    /// Parse a slice, the same rows and errors as [`CsvSliceParser::parse_slice`].
+    pub fn parse_slice<T: FromColumnSlice>(&self, slice_index: usize) -> Result<Vec<T>, ParseError> {
+        let (start_col, end_col) = self.validate_slice_index::<T>(slice_index)?;
+        let rows = self.offsets.len();
+
+        let mut results = if self.config.reserve_capacity { Vec::with_capacity(rows) } else { Vec::new() };
+        let mut reader = self.reader_at(0);
+        let mut record = StringRecord::new();
+
+        for row in 0..rows {
+            if row > 0 && row.is_multiple_of(PROGRESS_INTERVAL) {
+                self.config.report(|| ProgressEvent::Parsing { columns: start_col..end_col, row, rows });
+            }
+
+            self.read_next(&mut reader, &mut record)?;
+            if is_skipped(&self.config, &record, start_col, end_col) {
+                continue;
+            }
+            results.push(T::from_record(&record, start_col).map_err(|e| e.at_row(row))?);
+        }
+        self.config.report(|| ProgressEvent::Parsing { columns: start_col..end_col, row: rows, rows });
+
+        results.shrink_to_fit();
+
+        Ok(results)
+    }
+
+    /// Parse a slice lazily, one row at a time, like [`CsvSliceParser::parse_slice_iter`].
+    ///
+    /// Only the row being parsed is held in memory.
+    pub fn parse_slice_iter<'a, T: FromColumnSlice + 'a>(
+        &'a self,
+        slice_index: usize,
+    ) -> Result<impl Iterator<Item = Result<T, ParseError>> + 'a, ParseError> {
+        let (start_col, end_col) = self.validate_slice_index::<T>(slice_index)?;
+        let mut reader = self.reader_at(0);
+        let mut record = StringRecord::new();
+
+        Ok((0..self.offsets.len()).filter_map(move |row| {
+            if let Err(e) = self.read_next(&mut reader, &mut record) {
+                return Some(Err(e));
+            }
+            if is_skipped(&self.config, &record, start_col, end_col) {
+                return None;
+            }
+            Some(T::from_record(&record, start_col).map_err(|e| e.at_row(row)))
+        }))
+    }
+
+    /// Parse one row of a slice, like [`CsvSliceParser::parse_row`]. Only that row is read.
+    pub fn parse_row<T: FromColumnSlice>(&self, slice_index: usize, row_index: usize) -> Result<T, ParseError> {
+        let (start_col, _) = self.validate_slice_index::<T>(slice_index)?;
+
+        T::from_record(&self.record(row_index)?, start_col).map_err(|e| e.at_row(row_index))
+    }
+
+    fn validate_slice_index<T: FromColumnSlice>(&self, slice_index: usize) -> Result<(usize, usize), ParseError> {
+        let slice_count = self.slice_count::<T>();
+
+        slice_columns::<T>(&self.config, slice_index, self.headers.len())
+            .ok_or(ParseError::SliceOutOfBounds { slice_index, slice_count })
+    }
+
+    /// csv reader starting at data row `row_index`
+    fn reader_at(&self, row_index: usize) -> Reader<&[u8]> {
+        let start = self.offsets.get(row_index).map_or(self.mmap.len(), |&offset| offset as usize);
+
+        reader_builder(&self.config).has_headers(false).from_reader(&self.mmap[start..])
+    }
+
+    /// read the next row into `record`, every row was read once already so it's there
+    fn read_next(&self, reader: &mut Reader<&[u8]>, record: &mut StringRecord) -> Result<(), ParseError> {
+        reader.read_record(record)?;
+        self.config.prepare(record, &self.headers);
+
+        Ok(())
+    }
+}
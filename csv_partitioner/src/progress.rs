@@ -0,0 +1,52 @@
+//! Progress reporting for big files.
+
+use std::ops::Range;
+
+/// What [`ParseConfig::progress`](crate::ParseConfig::progress) is called with.
+///
+/// Events come every few thousand rows and once at the end of each step, cheap enough to
+/// print or to move a progress bar with.
+///
+/// # Example
+///
+/// ```rust
+/// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError, ProgressEvent};
+/// # use csv::StringRecord;
+/// # use std::sync::{Arc, Mutex};
+/// # struct Word;
+/// # impl FromColumnSlice for Word {
+/// #     const COLUMN_COUNT: usize = 1;
+/// #     fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> { Ok(Word) }
+/// # }
+/// let events = Arc::new(Mutex::new(Vec::new()));
+/// let seen = Arc::clone(&events);
+/// let config = ParseConfig::builder()
+///     .progress(move |event| seen.lock().unwrap().push(event))
+///     .build();
+///
+/// let parser = CsvSliceParser::from_str("Food\nrice\nbread\n", config).unwrap();
+/// parser.parse_slice::<Word>(0).unwrap();
+///
+/// assert_eq!(events.lock().unwrap()[..], [
+///     ProgressEvent::Loaded { records: 2 },
+///     ProgressEvent::Parsing { columns: 0..1, row: 2, rows: 2 },
+/// ]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// Still reading the input, `records` rows so far, `bytes` into the text.
+    Loading { records: usize, bytes: u64 },
+
+    /// The whole input is read.
+    Loaded { records: usize },
+
+    /// Parsing the slice in `columns`, `row` of `rows` done. The last event of a slice has
+    /// `row == rows`, a slice that fails stops early.
+    Parsing { columns: Range<usize>, row: usize, rows: usize },
+}
+
+/// Callback for [`ProgressEvent`]s, shared by every thread that parses with the config.
+pub type ProgressHook = Box<dyn Fn(ProgressEvent) + Send + Sync>;
+
+/// rows between two events
+pub(crate) const PROGRESS_INTERVAL: usize = 4096;
@@ -0,0 +1,79 @@
+//! Filtering and transforming a slice while it's parsed.
+
+use crate::ParseError;
+
+/// A pipeline over the rows of one slice, from [`CsvSliceParser::query`](crate::CsvSliceParser::query).
+///
+/// Steps run row by row as the slice is parsed, so nothing is collected until
+/// [`run`](Self::run) and parsing stops as soon as [`take`](Self::take) has enough.
+/// A row that fails to parse ends the query with its error, whatever the steps are.
+///
+/// # Example
+///
+/// ```rust
+/// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+/// # use csv::StringRecord;
+/// struct Word { word: String, lesson: u32 }
+///
+/// impl FromColumnSlice for Word {
+///     const COLUMN_COUNT: usize = 2;
+///
+///     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+///         Ok(Word {
+///             word: record.get(start_col).unwrap_or("").to_string(),
+///             lesson: record.get(start_col + 1).unwrap_or("").parse().map_err(ParseError::custom)?,
+///         })
+///     }
+/// }
+///
+/// let data = "word,lesson\nhola,1\nadiós,2\ngracias,2\nperro,3\n";
+/// let parser = CsvSliceParser::from_str(data, ParseConfig::default()).unwrap();
+///
+/// let lesson_two = parser.query::<Word>(0)
+///     .filter(|word| word.lesson == 2)
+///     .map(|word| word.word)
+///     .take(5)
+///     .run()
+///     .unwrap();
+/// assert_eq!(lesson_two, vec!["adiós", "gracias"]);
+///
+/// // a bad slice index only comes out when the query runs
+/// assert!(matches!(parser.query::<Word>(1).run(), Err(ParseError::SliceOutOfBounds { .. })));
+/// ```
+pub struct SliceQuery<'a, T> {
+    rows: Result<Box<dyn Iterator<Item = Result<T, ParseError>> + 'a>, ParseError>,
+}
+
+impl<'a, T: 'a> SliceQuery<'a, T> {
+    pub(crate) fn new(rows: Result<impl Iterator<Item = Result<T, ParseError>> + 'a, ParseError>) -> Self {
+        SliceQuery { rows: rows.map(|rows| Box::new(rows) as Box<dyn Iterator<Item = _>>) }
+    }
+
+    /// Keep only the rows `predicate` accepts.
+    pub fn filter(self, mut predicate: impl FnMut(&T) -> bool + 'a) -> Self {
+        SliceQuery::new(self.rows.map(|rows| rows.filter(move |row| match row {
+            Ok(value) => predicate(value),
+            Err(_) => true,
+        })))
+    }
+
+    /// Turn every row into something else, e.g. pick out one field.
+    pub fn map<U: 'a>(self, mut f: impl FnMut(T) -> U + 'a) -> SliceQuery<'a, U> {
+        SliceQuery::new(self.rows.map(|rows| rows.map(move |row| row.map(&mut f))))
+    }
+
+    /// Stop after `n` rows, the rest of the slice isn't parsed.
+    pub fn take(self, n: usize) -> Self {
+        SliceQuery::new(self.rows.map(|rows| rows.take(n)))
+    }
+
+    /// Run the query and collect what's left.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<T>)` - the rows that made it through, in file order
+    /// * `Err(ParseError)` - a bad slice index, or the first row that failed to parse
+    pub fn run(self) -> Result<Vec<T>, ParseError> {
+        self.rows?.collect()
+    }
+}
@@ -0,0 +1,67 @@
+//! Rows with fewer fields than the header row.
+
+use csv::StringRecord;
+
+/// What to do with rows shorter than the header row, set with `ParseConfig::ragged_rows`.
+///
+/// Hand-edited CSVs often drop the trailing commas of a row whose last cells are empty.
+/// Rows longer than the headers are read as they are with `PadEmpty` and `Skip`, the
+/// extra fields belong to no slice.
+///
+/// # Example
+///
+/// ```rust
+/// # use csv_partitioner::{CsvSliceParser, ParseConfig, RaggedPolicy};
+/// let data = "Spanish,English,Notes\nhola,hello,greeting\nadiós,bye\n";
+///
+/// // the default is a CSV error on the short row
+/// assert!(CsvSliceParser::from_str(data, ParseConfig::default()).is_err());
+///
+/// let config = ParseConfig::builder().ragged_rows(RaggedPolicy::PadEmpty).build();
+/// let parser = CsvSliceParser::from_str(data, config).unwrap();
+/// assert_eq!(parser.records()[1].iter().collect::<Vec<_>>(), vec!["adiós", "bye", ""]);
+///
+/// // or leave the short row out of the slices it doesn't fill
+/// let config = ParseConfig::builder().ragged_rows(RaggedPolicy::Skip).build();
+/// let parser = CsvSliceParser::from_str(data, config).unwrap();
+/// assert_eq!(parser.parse_slice::<Cells<3>>(0).unwrap().len(), 1);
+/// assert_eq!(parser.parse_slice::<Cells<2>>(0).unwrap().len(), 2);
+/// # struct Cells<const W: usize>;
+/// # impl<const W: usize> csv_partitioner::FromColumnSlice for Cells<W> {
+/// #     const COLUMN_COUNT: usize = W;
+/// #     fn from_record(record: &csv::StringRecord, start_col: usize) -> Result<Self, csv_partitioner::ParseError> {
+/// #         record.get(start_col + W - 1).map(|_| Cells).ok_or_else(|| csv_partitioner::ParseError::missing_field(start_col + W - 1, "cell"))
+/// #     }
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RaggedPolicy {
+    /// Reading a CSV with a short row fails with a CSV error that names the line.
+    ///
+    /// Records handed to `from_records` or `from_rows` aren't checked.
+    #[default]
+    Error,
+
+    /// Short rows are padded with empty fields up to the width of the headers.
+    PadEmpty,
+
+    /// Rows that end before a slice's last column are left out of that slice, slices
+    /// they fill on the same row are still parsed. Row numbers in errors don't shift.
+    Skip,
+}
+
+impl RaggedPolicy {
+    /// whether the csv reader has to accept rows of any length
+    pub(crate) fn is_flexible(self) -> bool {
+        self != RaggedPolicy::Error
+    }
+
+    /// pad `record` to `width` fields when the policy says so
+    pub(crate) fn apply(self, record: &mut StringRecord, width: usize) {
+        if self == RaggedPolicy::PadEmpty {
+            for _ in record.len()..width {
+                record.push_field("");
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Which rows changed between two versions of a file, slice by slice.
+
+use std::ops::Range;
+
+use crate::{diff_by_key, CsvSliceParser, FromColumnSlice, ParseError, SliceInfo};
+
+/// Changed rows of one slice, from [`RecordDiff::slices`].
+///
+/// Rows are data row indices (like the rows in [`ParseError`]) into the older or newer
+/// parser, pass them to [`parse_row`](CsvSliceParser::parse_row) to get the values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SliceRecordDiff {
+    /// Name of the slice, its first header in the newer file (the older one when it was removed).
+    pub name: String,
+
+    /// Index of the slice in the older file, `None` when it was added.
+    pub older_index: Option<usize>,
+
+    /// Index of the slice in the newer file, `None` when it was removed.
+    pub newer_index: Option<usize>,
+
+    /// Rows of the newer file whose key isn't in the older one.
+    pub added: Vec<usize>,
+
+    /// Rows of the older file whose key isn't in the newer one.
+    pub removed: Vec<usize>,
+
+    /// `(older row, newer row)` pairs with the same key but different cells.
+    pub modified: Vec<(usize, usize)>,
+}
+
+impl SliceRecordDiff {
+    /// `true` when no row of the slice changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Changed rows between two versions of a file, from [`CsvSliceParser::diff_records`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RecordDiff {
+    /// One entry per slice in either file, matched like [`SchemaDiff`](crate::SchemaDiff)
+    /// matches them. Matched slices come in the newer file's order, removed ones last.
+    pub slices: Vec<SliceRecordDiff>,
+}
+
+impl RecordDiff {
+    /// `true` when no row of any slice changed.
+    pub fn is_empty(&self) -> bool {
+        self.slices.iter().all(SliceRecordDiff::is_empty)
+    }
+
+    /// The slices where some row changed.
+    pub fn changed(&self) -> impl Iterator<Item = &SliceRecordDiff> {
+        self.slices.iter().filter(|slice| !slice.is_empty())
+    }
+}
+
+/// a kept row's cells in a slice, equal when the cells are whatever row it's on
+struct Row<'a> {
+    row: usize,
+    cells: Vec<&'a str>,
+}
+
+impl PartialEq for Row<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cells == other.cells
+    }
+}
+
+impl CsvSliceParser {
+    /// Compare the rows of every slice with a newer version of the file, for delta imports.
+    ///
+    /// Slices are matched by name (see [`schema_diff`](Self::schema_diff)), rows by the
+    /// cell in their slice's `key_column` (0 for the first column), trimmed. Rows with the
+    /// same key pair up in order, rows either parser would skip (blank, marked) are left
+    /// out. All rows of an added slice are added, all rows of a removed one removed. Nothing
+    /// is deserialised, only the cells are compared.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(RecordDiff)` - the changed rows of every slice
+    /// * `Err(ParseError::Layout)` - `key_column` isn't a column of `T`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # struct Word;
+    /// # impl FromColumnSlice for Word {
+    /// #     const COLUMN_COUNT: usize = 2;
+    /// #     fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> { Ok(Word) }
+    /// # }
+    /// let week1 = CsvSliceParser::from_str("Food,,Verbs,\nりんご,apple,いく,to go\nみず,water,,\n", ParseConfig::default()).unwrap();
+    /// let week2 = CsvSliceParser::from_str("Food,,Verbs,\nみず,water,いく,to go\nりんご,apple (fruit),みる,to see\n", ParseConfig::default()).unwrap();
+    ///
+    /// // keyed by the kana
+    /// let diff = week1.diff_records::<Word>(&week2, 0).unwrap();
+    ///
+    /// let food = &diff.slices[0];
+    /// assert_eq!(food.modified, vec![(0, 1)]);
+    /// assert!(food.added.is_empty() && food.removed.is_empty());
+    ///
+    /// let verbs = &diff.slices[1];
+    /// assert_eq!(verbs.added, vec![1]);
+    /// ```
+    pub fn diff_records<T: FromColumnSlice>(&self, newer: &CsvSliceParser, key_column: usize) -> Result<RecordDiff, ParseError> {
+        if key_column >= T::COLUMN_COUNT {
+            return Err(ParseError::Layout(format!(
+                "Key column {} is outside of {}, it's {} columns wide",
+                key_column, std::any::type_name::<T>(), T::COLUMN_COUNT
+            )));
+        }
+
+        let schema = self.schema_diff::<T>(newer);
+        let mut slices = Vec::with_capacity(schema.matched.len() + schema.added.len() + schema.removed.len());
+
+        // every slice of the newer file, in its order, with what it was before if anything
+        let mut in_newer: Vec<(Option<&SliceInfo>, &SliceInfo)> = schema.matched.iter()
+            .map(|change| (Some(&change.older), &change.newer))
+            .chain(schema.added.iter().map(|slice| (None, slice)))
+            .collect();
+        in_newer.sort_by_key(|(_, newer)| newer.index);
+
+        for (older, newer_slice) in in_newer {
+            let older_rows = older.map_or_else(Vec::new, |older| self.kept_rows(older.columns.clone()));
+            let diff = diff_by_key(older_rows, newer.kept_rows(newer_slice.columns.clone()), |row| {
+                row.cells.get(key_column).copied().unwrap_or("").trim()
+            });
+
+            slices.push(SliceRecordDiff {
+                name: newer_slice.name().to_string(),
+                older_index: older.map(|older| older.index),
+                newer_index: Some(newer_slice.index),
+                added: diff.added.iter().map(|row| row.row).collect(),
+                removed: diff.removed.iter().map(|row| row.row).collect(),
+                modified: diff.changed.iter().map(|(older, newer)| (older.row, newer.row)).collect(),
+            });
+        }
+
+        for removed in &schema.removed {
+            slices.push(SliceRecordDiff {
+                name: removed.name().to_string(),
+                older_index: Some(removed.index),
+                newer_index: None,
+                added: Vec::new(),
+                removed: self.kept_rows(removed.columns.clone()).iter().map(|row| row.row).collect(),
+                modified: Vec::new(),
+            });
+        }
+
+        Ok(RecordDiff { slices })
+    }
+
+    /// the rows parsing would keep in `columns`, with their cells
+    fn kept_rows(&self, columns: Range<usize>) -> Vec<Row<'_>> {
+        self.records.iter()
+            .enumerate()
+            .filter(|(_, record)| !self.is_skipped(columns.start, columns.end, record))
+            .map(|(row, record)| Row { row, cells: columns.clone().map(|i| record.get(i).unwrap_or("")).collect() })
+            .collect()
+    }
+}
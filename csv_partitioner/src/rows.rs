@@ -0,0 +1,179 @@
+//! Row-major slicing, for CSVs that repeat vertically instead of horizontally.
+
+#[cfg(feature = "fs")]
+use std::fs::File;
+use std::io::Read;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use csv::StringRecord;
+
+use crate::encoding::DecodingReader;
+use crate::{is_skipped, reader_builder, transform, FromColumnSlice, ParseConfig, ParseError};
+
+/// Parser for CSVs made of blocks of rows, each block introduced by a header row.
+///
+/// ```csv
+/// # Food
+/// りんご,apple,
+/// さかな,fish,魚
+/// # Verbs
+/// いく,to go,行く
+/// ```
+///
+/// Rows are deserialised with the same [`FromColumnSlice`] trait as the column parser,
+/// always starting at column 0. There is no header line at the top, every row is data
+/// until a header row says otherwise, and rows may differ in length.
+/// The config's delimiter, quoting, comment, trimming and `field_transform` apply (the
+/// transform sees every row, header rows included, with blank headers), `has_headers` doesn't.
+///
+/// # Example
+///
+/// ```rust
+/// # use csv_partitioner::{FromColumnSlice, ParseConfig, ParseError, RowSliceParser};
+/// # use csv::StringRecord;
+/// # use std::error::Error;
+/// # struct Entry { word: String }
+/// # impl FromColumnSlice for Entry {
+/// #     const COLUMN_COUNT: usize = 2;
+/// #     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+/// #         Ok(Entry { word: record.get(start_col).unwrap_or("").to_string() })
+/// #     }
+/// # }
+/// # fn example() -> Result<(), Box<dyn Error>> {
+/// let data = "# Food\nりんご,apple\nさかな,fish\n# Verbs\nいく,to go\n";
+/// let parser = RowSliceParser::from_reader(data.as_bytes(), ParseConfig::default())?;
+///
+/// let mut topics = Vec::new();
+/// for block in parser.blocks_by_marker("#") {
+///     let entries: Vec<Entry> = parser.parse_block(&block)?;
+///     topics.push((block.title().to_string(), entries.len()));
+/// }
+/// assert_eq!(topics, vec![("Food".to_string(), 2), ("Verbs".to_string(), 1)]);
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+pub struct RowSliceParser {
+    records: Vec<StringRecord>,
+    config: ParseConfig,
+}
+
+/// One block of a [`RowSliceParser`]: its header row and the data rows under it.
+#[derive(Debug, Clone, Copy)]
+pub struct RowBlock<'a> {
+    title: &'a str,
+    first_row: usize,
+    header: &'a StringRecord,
+    rows: &'a [StringRecord],
+}
+
+impl<'a> RowBlock<'a> {
+    /// Block name, the header row's first cell (without the marker for [`RowSliceParser::blocks_by_marker`]).
+    pub fn title(&self) -> &'a str {
+        self.title
+    }
+
+    /// The whole header row.
+    pub fn header(&self) -> &'a StringRecord {
+        self.header
+    }
+
+    /// Data rows, unparsed.
+    pub fn rows(&self) -> &'a [StringRecord] {
+        self.rows
+    }
+}
+
+impl RowSliceParser {
+    /// Load a CSV file.
+    #[cfg(feature = "fs")]
+    pub fn from_file<P: AsRef<Path>>(path: P, config: ParseConfig) -> Result<Self, ParseError> {
+        Self::from_reader(File::open(path)?, config)
+    }
+
+    /// Load CSV data from any reader.
+    pub fn from_reader<R: Read>(reader: R, config: ParseConfig) -> Result<Self, ParseError> {
+        let mut reader = reader_builder(&config)
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(DecodingReader::new(reader, config.encoding));
+
+        let mut records = reader.records().collect::<Result<Vec<_>, _>>()?;
+        if let Some(transform) = &config.field_transform {
+            for record in &mut records {
+                *record = transform::transform_record(transform, record, &StringRecord::new());
+            }
+        }
+
+        Ok(RowSliceParser { records, config })
+    }
+
+    /// Split into blocks, a block starts at every row for which `is_header` returns true.
+    ///
+    /// Rows before the first header row belong to no block and are ignored.
+    /// The block title is the header row's first cell.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{ParseConfig, RowSliceParser};
+    /// // header rows have a name in the first cell and nothing else
+    /// let data = "Food,,\nりんご,apple,\nVerbs,,\nいく,to go,行く\n";
+    /// let parser = RowSliceParser::from_reader(data.as_bytes(), ParseConfig::default()).unwrap();
+    ///
+    /// let blocks = parser.blocks_by(|row| row.iter().skip(1).all(str::is_empty));
+    /// let titles: Vec<&str> = blocks.iter().map(|block| block.title()).collect();
+    /// assert_eq!(titles, vec!["Food", "Verbs"]);
+    /// ```
+    pub fn blocks_by(&self, is_header: impl Fn(&StringRecord) -> bool) -> Vec<RowBlock<'_>> {
+        self.blocks(|record| is_header(record).then(|| record.get(0).unwrap_or("")))
+    }
+
+    /// Split into blocks at rows whose first cell starts with `marker` (e.g. `#` for `# Food`).
+    ///
+    /// The block title is the rest of that cell, trimmed.
+    pub fn blocks_by_marker(&self, marker: &str) -> Vec<RowBlock<'_>> {
+        self.blocks(|record| {
+            record.get(0)
+                .and_then(|cell| cell.strip_prefix(marker))
+                .map(str::trim)
+        })
+    }
+
+    fn blocks<'a>(&'a self, title_of: impl Fn(&'a StringRecord) -> Option<&'a str>) -> Vec<RowBlock<'a>> {
+        let starts: Vec<(usize, &str)> = self.records.iter()
+            .enumerate()
+            .filter_map(|(i, record)| title_of(record).map(|title| (i, title)))
+            .collect();
+
+        let ends = starts.iter().skip(1).map(|(i, _)| *i).chain(std::iter::once(self.records.len()));
+
+        starts.iter().zip(ends)
+            .map(|(&(start, title), end)| RowBlock {
+                title,
+                first_row: start + 1,
+                header: &self.records[start],
+                rows: &self.records[start + 1..end],
+            })
+            .collect()
+    }
+
+    /// Parse a block's data rows, skipping empty/marked rows per the `ParseConfig`.
+    ///
+    /// There's no header line, so error rows are positions in the whole file (header rows included).
+    pub fn parse_block<T: FromColumnSlice>(&self, block: &RowBlock) -> Result<Vec<T>, ParseError> {
+        let () = T::NON_ZERO_WIDTH;
+
+        block.rows.iter()
+            .enumerate()
+            .filter(|(_, record)| !is_skipped(&self.config, record, 0, T::COLUMN_COUNT))
+            .map(|(i, record)| T::from_record(record, 0).map_err(|e| e.at_row(block.first_row + i)))
+            .collect()
+    }
+
+    /// Number of rows in the file, header rows included.
+    pub fn record_count(&self) -> usize {
+        self.records.len()
+    }
+}
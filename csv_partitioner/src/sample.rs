@@ -0,0 +1,70 @@
+//! Picking a few rows out of a slice, for previews.
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::hash::BuildHasher;
+
+/// Which rows [`CsvSliceParser::sample_slice`](crate::CsvSliceParser::sample_slice) returns.
+///
+/// Every strategy returns rows in file order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SampleStrategy {
+    /// The first rows.
+    #[default]
+    Head,
+
+    /// Rows picked at random, a different pick on every call.
+    Random,
+
+    /// Rows spread evenly from the first row to the end of the slice.
+    Stride,
+}
+
+impl SampleStrategy {
+    /// Pick `n` of the positions `0..len`, sorted and without repeats.
+    pub(crate) fn pick(&self, len: usize, n: usize) -> Vec<usize> {
+        if n >= len {
+            return (0..len).collect();
+        }
+
+        match self {
+            SampleStrategy::Head => (0..n).collect(),
+            // widened so i * len can't overflow on huge slices
+            SampleStrategy::Stride => (0..n).map(|i| (i as u128 * len as u128 / n as u128) as usize).collect(),
+            SampleStrategy::Random => {
+                // Floyd's algorithm, n draws no matter how big len is
+                let mut rng = XorShift::new();
+                let mut picked = HashSet::with_capacity(n);
+
+                for upper in len - n..len {
+                    let candidate = rng.below(upper + 1);
+                    if !picked.insert(candidate) {
+                        picked.insert(upper);
+                    }
+                }
+
+                let mut picked: Vec<usize> = picked.into_iter().collect();
+                picked.sort_unstable();
+                picked
+            },
+        }
+    }
+}
+
+/// Small non-cryptographic generator, previews don't need more and it saves a dependency.
+struct XorShift(u64);
+
+impl XorShift {
+    fn new() -> Self {
+        // RandomState is seeded randomly per process and per instance
+        XorShift(RandomState::new().hash_one(0u8) | 1)
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+
+        (self.0 % bound as u64) as usize
+    }
+}
@@ -0,0 +1,186 @@
+//! Guessing what each column holds, to sanity-check a CSV before using it.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Range;
+
+/// What the non-blank cells of a column look like, from the narrowest type that fits them all.
+///
+/// Integers also fit [`Float`](ColumnType::Float), anything that fits nothing else is a
+/// [`String`](ColumnType::String).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    /// Every cell is blank.
+    Empty,
+
+    /// Whole numbers, e.g. `42` or `-3`.
+    Integer,
+
+    /// Numbers, e.g. `1.5` or `2e3`, integers included.
+    Float,
+
+    /// Dates as `2024-03-01`, `2024/03/01` or `01/03/2024`.
+    Date,
+
+    /// Anything else.
+    String,
+}
+
+impl ColumnType {
+    /// The type of a single non-blank cell.
+    fn of(cell: &str) -> Self {
+        if cell.parse::<i64>().is_ok() {
+            ColumnType::Integer
+        } else if cell.parse::<f64>().is_ok_and(f64::is_finite) {
+            ColumnType::Float
+        } else if is_date(cell) {
+            ColumnType::Date
+        } else {
+            ColumnType::String
+        }
+    }
+
+    /// The narrowest type both fit in.
+    fn widen(self, other: Self) -> Self {
+        use ColumnType::*;
+
+        match (self, other) {
+            (Empty, other) | (other, Empty) => other,
+            (a, b) if a == b => a,
+            (Integer, Float) | (Float, Integer) => Float,
+            _ => String,
+        }
+    }
+}
+
+impl fmt::Display for ColumnType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ColumnType::Empty => "empty",
+            ColumnType::Integer => "integer",
+            ColumnType::Float => "float",
+            ColumnType::Date => "date",
+            ColumnType::String => "string",
+        };
+        // pad, so the names line up in tables
+        f.pad(name)
+    }
+}
+
+/// One column of a [`SliceSchema`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnSchema {
+    /// Column in the CSV, zero-based.
+    pub column: usize,
+
+    /// Its header, `""` when blank.
+    pub header: String,
+
+    /// Narrowest type every non-blank cell fits.
+    pub column_type: ColumnType,
+
+    /// Blank (or whitespace only) cells.
+    pub nulls: usize,
+
+    /// Different non-blank values, compared after trimming.
+    pub distinct: usize,
+
+    /// No non-blank value appears twice, e.g. a column that could be a key.
+    pub unique: bool,
+}
+
+/// The inferred columns of one slice, from [`CsvSliceParser::infer_schema`](crate::CsvSliceParser::infer_schema).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SliceSchema {
+    /// Index of the slice.
+    pub index: usize,
+
+    /// Columns the slice covers.
+    pub columns: Range<usize>,
+
+    /// Rows looked at, the ones parsing would keep (`skip_empty_rows` and `skip_marker` apply).
+    pub rows: usize,
+
+    /// One entry per column, in order.
+    pub column_schemas: Vec<ColumnSchema>,
+}
+
+impl SliceSchema {
+    /// The first header, usually the slice's name.
+    pub fn name(&self) -> &str {
+        self.column_schemas.first().map_or("", |column| column.header.as_str())
+    }
+}
+
+/// Accumulates one column while the rows are scanned.
+pub(crate) struct ColumnScan {
+    column: usize,
+    header: String,
+    column_type: ColumnType,
+    nulls: usize,
+    non_null: usize,
+    values: HashSet<String>,
+}
+
+impl ColumnScan {
+    pub(crate) fn new(column: usize, header: &str) -> Self {
+        ColumnScan { column, header: header.to_string(), column_type: ColumnType::Empty, nulls: 0, non_null: 0, values: HashSet::new() }
+    }
+
+    pub(crate) fn column(&self) -> usize {
+        self.column
+    }
+
+    pub(crate) fn add(&mut self, cell: Option<&str>) {
+        match cell.map(str::trim).filter(|cell| !cell.is_empty()) {
+            Some(cell) => {
+                self.column_type = self.column_type.widen(ColumnType::of(cell));
+                self.non_null += 1;
+                if !self.values.contains(cell) {
+                    self.values.insert(cell.to_string());
+                }
+            },
+            None => self.nulls += 1,
+        }
+    }
+
+    pub(crate) fn finish(self) -> ColumnSchema {
+        ColumnSchema {
+            column: self.column,
+            header: self.header,
+            column_type: self.column_type,
+            nulls: self.nulls,
+            distinct: self.values.len(),
+            unique: self.values.len() == self.non_null,
+        }
+    }
+}
+
+/// `yyyy-mm-dd`, `yyyy/mm/dd`, `dd/mm/yyyy` or `mm/dd/yyyy`
+fn is_date(cell: &str) -> bool {
+    let parts: Vec<&str> = cell.split(['-', '/']).collect();
+    let [a, b, c] = parts.as_slice() else {
+        return false;
+    };
+
+    let number = |part: &str, digits: Range<usize>| -> Option<u32> {
+        if digits.contains(&part.len()) && part.chars().all(|c| c.is_ascii_digit()) {
+            part.parse().ok()
+        } else {
+            None
+        }
+    };
+
+    let (year_first, year_last) = (number(a, 4..5), number(c, 4..5));
+    let (month, day) = match (year_first, year_last) {
+        (Some(_), None) => (number(b, 1..3), number(c, 1..3)),
+        // day or month first, either is fine as long as one order makes sense
+        (None, Some(_)) => match (number(a, 1..3), number(b, 1..3)) {
+            (Some(a), Some(b)) if a <= 12 => (Some(a), Some(b)),
+            (a, b) => (b, a),
+        },
+        _ => return false,
+    };
+
+    month.is_some_and(|month| (1..=12).contains(&month)) && day.is_some_and(|day| (1..=31).contains(&day))
+}
@@ -0,0 +1,169 @@
+//! Comparing the slices of two versions of a file, before comparing what's in them.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use crate::{normalize_header, CsvSliceParser, FromColumnSlice, HeaderMatching, SliceInfo};
+
+/// One slice found in both versions, from [`SchemaDiff::matched`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SliceChange {
+    /// The slice in the older file.
+    pub older: SliceInfo,
+
+    /// The same slice in the newer file.
+    pub newer: SliceInfo,
+}
+
+impl SliceChange {
+    /// `true` when any header of the slice reads differently, exactly.
+    pub fn headers_changed(&self) -> bool {
+        self.older.headers != self.newer.headers
+    }
+
+    /// Non-empty rows in the newer version minus those in the older, negative when rows went.
+    pub fn row_delta(&self) -> i64 {
+        self.newer.non_empty_rows as i64 - self.older.non_empty_rows as i64
+    }
+
+    /// `true` when the slice is at another position, e.g. because one was added before it.
+    pub fn moved(&self) -> bool {
+        self.older.index != self.newer.index
+    }
+
+    /// `true` when nothing about the slice changed, its position included.
+    pub fn is_unchanged(&self) -> bool {
+        !self.headers_changed() && self.row_delta() == 0 && !self.moved()
+    }
+}
+
+/// How the slices of two versions of a file differ, from [`CsvSliceParser::schema_diff`].
+///
+/// Slices are matched by name (the first header) with the older file's `header_matching`,
+/// slices with the same name pair up in order. Rows are only counted, use
+/// [`CsvSliceParser::diff_slice`] on a matched pair to see which rows changed.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SchemaDiff {
+    /// Slices only in the newer file, in its order.
+    pub added: Vec<SliceInfo>,
+
+    /// Slices only in the older file, in its order.
+    pub removed: Vec<SliceInfo>,
+
+    /// Slices in both, changed or not, in the newer file's order.
+    pub matched: Vec<SliceChange>,
+}
+
+impl SchemaDiff {
+    /// The matched slices where something changed.
+    pub fn changed(&self) -> impl Iterator<Item = &SliceChange> {
+        self.matched.iter().filter(|change| !change.is_unchanged())
+    }
+
+    /// `true` when both files have the same slices, with the same headers and row counts.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed().next().is_none()
+    }
+
+    fn between(older: Vec<SliceInfo>, newer: Vec<SliceInfo>, matching: HeaderMatching) -> Self {
+        let key = |slice: &SliceInfo| match matching {
+            HeaderMatching::Exact => slice.name().to_string(),
+            HeaderMatching::Normalized => normalize_header(slice.name()),
+        };
+
+        let mut older: Vec<Option<SliceInfo>> = older.into_iter().map(Some).collect();
+        let mut by_name: HashMap<String, VecDeque<usize>> = HashMap::new();
+        for (idx, slice) in older.iter().enumerate() {
+            if let Some(slice) = slice {
+                by_name.entry(key(slice)).or_default().push_back(idx);
+            }
+        }
+
+        let mut diff = SchemaDiff::default();
+        for slice in newer {
+            let matched = by_name.get_mut(&key(&slice)).and_then(VecDeque::pop_front);
+
+            match matched.and_then(|idx| older[idx].take()) {
+                Some(previous) => diff.matched.push(SliceChange { older: previous, newer: slice }),
+                None => diff.added.push(slice),
+            }
+        }
+
+        diff.removed = older.into_iter().flatten().collect();
+        diff
+    }
+}
+
+/// One line per added, removed or changed slice, e.g. `~ Verbs: 12 -> 15 rows`.
+impl fmt::Display for SchemaDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for slice in &self.added {
+            writeln!(f, "+ {} ({} rows)", name(slice), slice.non_empty_rows)?;
+        }
+        for slice in &self.removed {
+            writeln!(f, "- {} ({} rows)", name(slice), slice.non_empty_rows)?;
+        }
+        for change in self.changed() {
+            let mut parts = Vec::new();
+            if change.moved() {
+                parts.push(format!("moved from slice {} to {}", change.older.index, change.newer.index));
+            }
+            if change.headers_changed() {
+                parts.push(format!("headers [{}] -> [{}]", change.older.headers.join(", "), change.newer.headers.join(", ")));
+            }
+            if change.row_delta() != 0 {
+                parts.push(format!("{} -> {} rows", change.older.non_empty_rows, change.newer.non_empty_rows));
+            }
+            writeln!(f, "~ {}: {}", name(&change.newer), parts.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn name(slice: &SliceInfo) -> &str {
+    if slice.name().trim().is_empty() { "(unnamed)" } else { slice.name() }
+}
+
+impl CsvSliceParser {
+    /// Compare the slices of this file with those of a newer version, see [`SchemaDiff`].
+    ///
+    /// Both files are split into `T`s with their own configs, a newer file with another
+    /// `slice_gap` still matches up.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # struct Pair;
+    /// # impl FromColumnSlice for Pair {
+    /// #     const COLUMN_COUNT: usize = 2;
+    /// #     fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> { Ok(Pair) }
+    /// # }
+    /// let last_week = CsvSliceParser::from_str("Food,,Verbs,\nrice,ご飯,iku,行く\n", ParseConfig::default()).unwrap();
+    /// let this_week = CsvSliceParser::from_str(
+    ///     "Verbs,,Colours,,food,\niku,行く,aka,赤,rice,ご飯\nmiru,見る,,,bread,パン\n",
+    ///     ParseConfig::default(),
+    /// ).unwrap();
+    ///
+    /// let diff = last_week.schema_diff::<Pair>(&this_week);
+    ///
+    /// assert_eq!(diff.added.iter().map(|slice| slice.name()).collect::<Vec<_>>(), vec!["Colours"]);
+    /// assert!(diff.removed.is_empty());
+    ///
+    /// let verbs = &diff.matched[0];
+    /// assert_eq!((verbs.older.index, verbs.newer.index, verbs.row_delta()), (1, 0, 1));
+    /// // "Food" and "food" are the same slice with normalized matching
+    /// assert!(diff.matched[1].headers_changed());
+    ///
+    /// assert_eq!(diff.to_string(), "\
+    /// + Colours (1 rows)
+    /// ~ Verbs: moved from slice 1 to 0, 1 -> 2 rows
+    /// ~ food: moved from slice 0 to 2, headers [Food, ] -> [food, ], 1 -> 2 rows
+    /// ");
+    /// ```
+    pub fn schema_diff<T: FromColumnSlice>(&self, newer: &CsvSliceParser) -> SchemaDiff {
+        SchemaDiff::between(self.slices::<T>(), newer.slices::<T>(), self.config.header_matching)
+    }
+}
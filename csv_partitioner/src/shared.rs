@@ -0,0 +1,109 @@
+//! Sharing one loaded parser between threads, a slice per thread.
+
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::{CsvSliceParser, FromColumnSlice, ParseError, SliceHeaders};
+
+// a parser is only ever read after loading, so sharing one needs no locks
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<CsvSliceParser>();
+};
+
+/// One slice of a shared parser, from [`CsvSliceParser::par_slices`].
+///
+/// Holds an `Arc` of the parser, so it's cheap to clone and can be moved to another thread
+/// (it's `Send + Sync` whatever `T` is). Nothing is parsed until asked for.
+pub struct SliceView<T> {
+    parser: Arc<CsvSliceParser>,
+    index: usize,
+    columns: Range<usize>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for SliceView<T> {
+    fn clone(&self) -> Self {
+        SliceView { parser: Arc::clone(&self.parser), index: self.index, columns: self.columns.clone(), _marker: PhantomData }
+    }
+}
+
+impl<T> std::fmt::Debug for SliceView<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SliceView").field("index", &self.index).field("columns", &self.columns).finish()
+    }
+}
+
+impl<T: FromColumnSlice> SliceView<T> {
+    /// Position of the slice, as passed to the parser's methods.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Columns the slice covers.
+    pub fn columns(&self) -> Range<usize> {
+        self.columns.clone()
+    }
+
+    /// The slice's headers, see [`CsvSliceParser::slice_headers`].
+    pub fn headers(&self) -> SliceHeaders {
+        self.parser.slice_headers::<T>(self.index).expect("a view's slice is in bounds")
+    }
+
+    /// The parser the slice belongs to.
+    pub fn parser(&self) -> &CsvSliceParser {
+        &self.parser
+    }
+
+    /// Parse the slice, see [`CsvSliceParser::parse_slice`].
+    pub fn parse(&self) -> Result<Vec<T>, ParseError> {
+        self.parser.parse_slice(self.index)
+    }
+
+    /// Parse one row of the slice, see [`CsvSliceParser::parse_row`].
+    pub fn parse_row(&self, row_index: usize) -> Result<T, ParseError> {
+        self.parser.parse_row(self.index, row_index)
+    }
+}
+
+impl CsvSliceParser {
+    /// Move the parser into an `Arc`, to share it between threads or iterators.
+    ///
+    /// The parser is `Send + Sync`, so that's all sharing one takes.
+    pub fn into_shared(self) -> Arc<Self> {
+        Arc::new(self)
+    }
+
+    /// A view of every slice of a shared parser, each one cheap to move to a thread of its own.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # struct Word(String);
+    /// # impl FromColumnSlice for Word {
+    /// #     const COLUMN_COUNT: usize = 2;
+    /// #     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    /// #         Ok(Word(record.get(start_col).unwrap_or("").to_string()))
+    /// #     }
+    /// # }
+    /// let parser = CsvSliceParser::from_str("Food,,Verbs,\nりんご,apple,いく,to go\n,,たべる,to eat\n", ParseConfig::default())
+    ///     .unwrap()
+    ///     .into_shared();
+    ///
+    /// let handles: Vec<_> = parser.par_slices::<Word>().into_iter()
+    ///     .map(|slice| std::thread::spawn(move || (slice.headers().first().to_string(), slice.parse().unwrap().len())))
+    ///     .collect();
+    /// let counts: Vec<(String, usize)> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+    ///
+    /// assert_eq!(counts, vec![("Food".to_string(), 1), ("Verbs".to_string(), 2)]);
+    /// ```
+    pub fn par_slices<T: FromColumnSlice>(self: &Arc<Self>) -> Vec<SliceView<T>> {
+        (0..self.slice_count::<T>())
+            .filter_map(|index| self.slice_columns::<T>(index).map(|columns| (index, columns)))
+            .map(|(index, columns)| SliceView { parser: Arc::clone(self), index, columns, _marker: PhantomData })
+            .collect()
+    }
+}
@@ -0,0 +1,170 @@
+//! Guessing the delimiter and header row of a file from its first few KB.
+
+#[cfg(feature = "fs")]
+use std::fs::File;
+#[cfg(feature = "fs")]
+use std::io::Read;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use csv::{ByteRecord, ReaderBuilder};
+
+use crate::ParseConfig;
+#[cfg(feature = "fs")]
+use crate::{CsvSliceParser, ParseError};
+
+/// Bytes [`CsvSliceParser::from_file_auto`] reads to sniff the format.
+pub const SNIFF_BYTES: usize = 8 * 1024;
+
+/// Delimiters [`sniff_format`] picks from, in the order ties are broken.
+const DELIMITERS: [u8; 3] = [b',', b'\t', b';'];
+
+/// Rows looked at, past the header
+const SNIFF_ROWS: usize = 50;
+
+/// What [`sniff_format`] makes of the start of a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SniffedFormat {
+    /// Comma, tab or semicolon.
+    pub delimiter: u8,
+
+    /// Whether the first row looks like headers.
+    pub has_headers: bool,
+}
+
+impl SniffedFormat {
+    /// A default config reading with the sniffed delimiter and header row.
+    pub fn config(self) -> ParseConfig {
+        ParseConfig { delimiter: self.delimiter, has_headers: self.has_headers, ..ParseConfig::default() }
+    }
+}
+
+/// Guess the delimiter and whether there's a header row from the start of a file.
+///
+/// The delimiter is the one of `,`, `\t` and `;` that splits the most rows into as many
+/// fields as the first row, wider splits winning ties. Text without any of them is read with
+/// commas. The first row counts as headers unless it looks like data: numbers where the rows
+/// below have numbers too. A first row that's blank over columns filled in below (the
+/// topic-name-then-blanks layout) is always headers.
+///
+/// `sample` can end mid-row, the cut off row is ignored.
+///
+/// # Example
+///
+/// ```rust
+/// # use csv_partitioner::sniff_format;
+/// // a jisho export, the commas are inside the meanings
+/// let tsv = "Kanji\tReading\tMeaning\n水\tみず\twater, fluid\n猫\tねこ\tcat\n";
+/// let format = sniff_format(tsv.as_bytes());
+///
+/// assert_eq!(format.delimiter, b'\t');
+/// assert!(format.has_headers);
+///
+/// let scores = "1;85;90\n2;70;65\n3;92;88\n";
+/// assert_eq!(sniff_format(scores.as_bytes()).delimiter, b';');
+/// assert!(!sniff_format(scores.as_bytes()).has_headers);
+/// ```
+pub fn sniff_format(sample: &[u8]) -> SniffedFormat {
+    let sample = sample.strip_prefix(b"\xef\xbb\xbf").unwrap_or(sample);
+    // a full buffer most likely ends inside a row
+    let sample = match sample.iter().rposition(|&byte| byte == b'\n') {
+        Some(end) if sample.len() >= SNIFF_BYTES => &sample[..=end],
+        _ => sample,
+    };
+
+    let best = DELIMITERS.iter()
+        .map(|&delimiter| (delimiter, sample_rows(sample, delimiter)))
+        .filter(|(_, rows)| rows.first().is_some_and(|first| first.len() > 1))
+        .map(|(delimiter, rows)| {
+            let width = rows[0].len();
+            let consistent = rows.iter().filter(|row| row.len() == width).count();
+            (delimiter, rows, consistent, width)
+        })
+        // max_by keeps the last of equals, so go through the candidates backwards
+        .rev()
+        .max_by_key(|&(_, _, consistent, width)| (consistent, width));
+
+    match best {
+        Some((delimiter, rows, _, _)) => SniffedFormat { delimiter, has_headers: looks_like_headers(&rows) },
+        None => SniffedFormat { delimiter: b',', has_headers: true },
+    }
+}
+
+/// the first rows of `sample` split on `delimiter`, rows that don't parse end the sample
+fn sample_rows(sample: &[u8], delimiter: u8) -> Vec<ByteRecord> {
+    let mut reader = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(sample);
+
+    reader.byte_records()
+        .take(SNIFF_ROWS + 1)
+        .map_while(Result::ok)
+        .collect()
+}
+
+/// whether the first of `rows` reads as headers, see [`sniff_format`]
+fn looks_like_headers(rows: &[ByteRecord]) -> bool {
+    let Some((first, data)) = rows.split_first() else {
+        return true;
+    };
+    if data.is_empty() {
+        return true;
+    }
+
+    let mut votes = 0i32;
+    for (column, header) in first.iter().enumerate() {
+        let cells: Vec<&[u8]> = data.iter().filter_map(|row| row.get(column)).filter(|cell| !cell.is_empty()).collect();
+        // columns mostly blank below say nothing
+        if cells.len() * 2 <= data.len() {
+            continue;
+        }
+
+        let numbers = cells.iter().filter(|cell| is_number(cell)).count();
+        if header.is_empty() {
+            votes += 1;
+        } else if numbers * 2 > cells.len() {
+            votes += if is_number(header) { -1 } else { 1 };
+        }
+    }
+
+    votes >= 0
+}
+
+fn is_number(cell: &[u8]) -> bool {
+    std::str::from_utf8(cell).is_ok_and(|cell| cell.parse::<f64>().is_ok())
+}
+
+#[cfg(feature = "fs")]
+impl CsvSliceParser {
+    /// Load a CSV or TSV file, sniffing its delimiter and header row first.
+    ///
+    /// The first [`SNIFF_BYTES`] bytes go through [`sniff_format`], the rest of the config is
+    /// the default. For files that have to be read another way, sniff them and change the
+    /// config from [`SniffedFormat::config`] instead.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CsvSliceParser)` - the file read with the sniffed delimiter
+    /// * `Err(ParseError)` - `Io` or `Csv` error, as with [`from_file`](Self::from_file)
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use csv_partitioner::CsvSliceParser;
+    /// # fn example() -> Result<(), csv_partitioner::ParseError> {
+    /// // tab separated, without having to say so
+    /// let parser = CsvSliceParser::from_file_auto("jisho-export.tsv")?;
+    /// println!("{} columns", parser.headers().len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_file_auto<P: AsRef<Path>>(path: P) -> Result<Self, ParseError> {
+        let mut sample = Vec::with_capacity(SNIFF_BYTES);
+        File::open(path.as_ref())?.take(SNIFF_BYTES as u64).read_to_end(&mut sample)?;
+
+        Self::from_file_with_config(path, sniff_format(&sample).config())
+    }
+}
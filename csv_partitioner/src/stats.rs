@@ -0,0 +1,107 @@
+//! Counting how well a slice is filled in.
+
+use std::ops::Range;
+
+/// Fill counts for one column of a [`SliceStats`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnStats {
+    /// Column in the CSV, zero-based.
+    pub column: usize,
+
+    /// Its header, `""` when blank.
+    pub header: String,
+
+    /// Blank (or whitespace only) cells in the rows parsing keeps, missing cells included.
+    pub empty: usize,
+
+    /// Non-blank cells in the rows parsing keeps.
+    pub filled: usize,
+
+    /// Length of the shortest non-blank cell in characters, `None` when there's none.
+    pub shortest: Option<usize>,
+
+    /// Length of the longest non-blank cell in characters, `None` when there's none.
+    pub longest: Option<usize>,
+}
+
+impl ColumnStats {
+    fn new(column: usize, header: &str) -> Self {
+        ColumnStats { column, header: header.to_string(), empty: 0, filled: 0, shortest: None, longest: None }
+    }
+
+    fn add(&mut self, cell: Option<&str>) {
+        match cell.map(str::trim).filter(|cell| !cell.is_empty()) {
+            Some(cell) => {
+                let length = cell.chars().count();
+                self.filled += 1;
+                self.shortest = Some(self.shortest.map_or(length, |shortest| shortest.min(length)));
+                self.longest = Some(self.longest.map_or(length, |longest| longest.max(length)));
+            },
+            None => self.empty += 1,
+        }
+    }
+
+    /// Share of cells that are filled in, from 0 to 1. A column with no rows counts as full.
+    pub fn fill_rate(&self) -> f64 {
+        fill_rate(self.filled, self.empty)
+    }
+}
+
+/// How well a slice is filled in, from [`CsvSliceParser::slice_stats`](crate::CsvSliceParser::slice_stats).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SliceStats {
+    /// Index of the slice.
+    pub index: usize,
+
+    /// Columns the slice covers.
+    pub columns: Range<usize>,
+
+    /// Rows parsing keeps, the ones the column counts are over.
+    pub rows: usize,
+
+    /// Rows left out because they count as empty in the slice (`skip_empty_rows`, `empty_row_policy`).
+    pub skipped_empty: usize,
+
+    /// Rows left out for any other reason (`skip_marker`, or `RaggedPolicy::Skip`).
+    pub skipped_other: usize,
+
+    /// One entry per column, in order.
+    pub column_stats: Vec<ColumnStats>,
+}
+
+impl SliceStats {
+    /// The first header, usually the slice's name.
+    pub fn name(&self) -> &str {
+        self.column_stats.first().map_or("", |column| column.header.as_str())
+    }
+
+    /// Share of the slice's cells that are filled in, over every column.
+    pub fn fill_rate(&self) -> f64 {
+        let filled = self.column_stats.iter().map(|column| column.filled).sum();
+        let empty = self.column_stats.iter().map(|column| column.empty).sum();
+
+        fill_rate(filled, empty)
+    }
+
+    pub(crate) fn new(index: usize, columns: Range<usize>, headers: &csv::StringRecord) -> Self {
+        let column_stats = columns.clone().map(|i| ColumnStats::new(i, headers.get(i).unwrap_or(""))).collect();
+
+        SliceStats { index, columns, rows: 0, skipped_empty: 0, skipped_other: 0, column_stats }
+    }
+
+    /// count a row parsing keeps
+    pub(crate) fn add(&mut self, record: &csv::StringRecord) {
+        self.rows += 1;
+        for column in &mut self.column_stats {
+            column.add(record.get(column.column));
+        }
+    }
+}
+
+fn fill_rate(filled: usize, empty: usize) -> f64 {
+    if filled + empty == 0 {
+        1.0
+    } else {
+        filled as f64 / (filled + empty) as f64
+    }
+}
@@ -0,0 +1,223 @@
+//! Row-by-row parsing for CSVs too big to hold in memory.
+
+#[cfg(feature = "fs")]
+use std::fs::File;
+use std::io::Read;
+use std::marker::PhantomData;
+use std::ops::Range;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use csv::{Reader, StringRecord};
+
+use crate::encoding::DecodingReader;
+use crate::{count_slices, csv_reader, is_skipped, read_headers, slice_columns, FromColumnSlice, ParseConfig, ParseError};
+
+/// Streaming counterpart of [`CsvSliceParser`](crate::CsvSliceParser).
+///
+/// Keeps the underlying `csv::Reader` open and parses one row at a time, so memory use
+/// stays flat no matter how big the file is. The trade-off is that it can only be read
+/// once, front to back.
+///
+/// # Example
+///
+/// ```rust
+/// # use csv_partitioner::{CsvSliceStreamer, FromColumnSlice, ParseConfig, ParseError};
+/// # use csv::StringRecord;
+/// # use std::error::Error;
+/// # struct Entry { word: String }
+/// # impl FromColumnSlice for Entry {
+/// #     const COLUMN_COUNT: usize = 2;
+/// #     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+/// #         Ok(Entry { word: record.get(start_col).unwrap_or("").to_string() })
+/// #     }
+/// # }
+/// # fn example() -> Result<(), Box<dyn Error>> {
+/// let data = "Food,,Verbs,\nりんご,apple,いく,to go\n,,たべる,to eat\n";
+/// let streamer = CsvSliceStreamer::from_reader(data.as_bytes(), ParseConfig::default())?;
+///
+/// let mut words = Vec::new();
+/// for row in streamer.rows::<Entry>() {
+///     for (slice_index, entry) in row? {
+///         words.push((slice_index, entry.word));
+///     }
+/// }
+///
+/// assert_eq!(words, vec![
+///     (0, "りんご".to_string()),
+///     (1, "いく".to_string()),
+///     (1, "たべる".to_string()),
+/// ]);
+/// # Ok(())
+/// # }
+/// # example().unwrap();
+/// ```
+pub struct CsvSliceStreamer<R: Read> {
+    reader: Reader<DecodingReader<R>>,
+    headers: StringRecord,
+    config: ParseConfig,
+    /// the first data row, read early to size blank headers
+    first: Option<StringRecord>,
+}
+
+#[cfg(feature = "fs")]
+impl CsvSliceStreamer<File> {
+    /// Open a CSV file for streaming, only the header row is read up front.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use csv_partitioner::{CsvSliceStreamer, ParseConfig};
+    /// # use std::error::Error;
+    /// # fn example() -> Result<(), Box<dyn Error>> {
+    /// let streamer = CsvSliceStreamer::open("huge.csv", ParseConfig::default())?;
+    /// println!("{} columns", streamer.headers().len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn open<P: AsRef<Path>>(path: P, config: ParseConfig) -> Result<Self, ParseError> {
+        Self::from_reader(File::open(path)?, config)
+    }
+}
+
+impl<R: Read> CsvSliceStreamer<R> {
+    /// Stream CSV data from any reader, only the header row is read up front.
+    pub fn from_reader(reader: R, config: ParseConfig) -> Result<Self, ParseError> {
+        let mut reader = csv_reader(reader, &config);
+        let (_, headers, first) = read_headers(&mut reader, &config)?;
+
+        Ok(CsvSliceStreamer { reader, headers, config, first })
+    }
+
+    /// The header row.
+    pub fn headers(&self) -> &StringRecord {
+        &self.headers
+    }
+
+    /// Number of column slices available for a given type (same as the in-memory parser).
+    #[inline]
+    pub fn slice_count<T: FromColumnSlice>(&self) -> usize {
+        let () = T::NON_ZERO_WIDTH;
+        count_slices::<T>(&self.config, self.headers.len())
+    }
+
+    /// Iterate rows, each yielding the parsed `(slice_index, value)` pairs of that row.
+    ///
+    /// Empty slices (with `skip_empty_rows`) and marked ones (`skip_marker`) are left out of a row,
+    /// so a row can be empty.
+    pub fn rows<T: FromColumnSlice>(self) -> SliceRows<R, T> {
+        SliceRows {
+            slices: 0..self.slice_count::<T>(),
+            streamer: self,
+            record: StringRecord::new(),
+            row_index: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Iterate the values of a single slice, reading the whole file once.
+    ///
+    /// Only that slice's columns are parsed, a cell the other slices can't parse doesn't stop it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `slice_index` is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use csv_partitioner::{CsvSliceStreamer, FromColumnSlice, ParseConfig, ParseError};
+    /// # use csv::StringRecord;
+    /// # use std::error::Error;
+    /// # struct Entry { word: String }
+    /// # impl FromColumnSlice for Entry {
+    /// #     const COLUMN_COUNT: usize = 2;
+    /// #     fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+    /// #         Ok(Entry { word: record.get(start_col).unwrap_or("").to_string() })
+    /// #     }
+    /// # }
+    /// # fn example() -> Result<(), Box<dyn Error>> {
+    /// let data = "Food,,Verbs,\nりんご,apple,いく,to go\n,,たべる,to eat\n";
+    /// let streamer = CsvSliceStreamer::from_reader(data.as_bytes(), ParseConfig::default())?;
+    ///
+    /// let verbs: Vec<String> = streamer.slice::<Entry>(1)?
+    ///     .map(|entry| entry.map(|e| e.word))
+    ///     .collect::<Result<_, _>>()?;
+    /// assert_eq!(verbs, vec!["いく", "たべる"]);
+    /// # Ok(())
+    /// # }
+    /// # example().unwrap();
+    /// ```
+    pub fn slice<T: FromColumnSlice>(self, slice_index: usize)
+        -> Result<impl Iterator<Item = Result<T, ParseError>>, ParseError>
+    {
+        let slice_count = self.slice_count::<T>();
+        if slice_index >= slice_count {
+            return Err(ParseError::SliceOutOfBounds { slice_index, slice_count });
+        }
+
+        let rows = SliceRows::<R, T> {
+            slices: slice_index..slice_index + 1,
+            streamer: self,
+            record: StringRecord::new(),
+            row_index: 0,
+            _marker: PhantomData,
+        };
+
+        Ok(rows.filter_map(|row| match row {
+            Ok(row) => row.into_iter().next().map(|(_, value)| Ok(value)),
+            Err(e) => Some(Err(e)),
+        }))
+    }
+}
+
+/// Iterator returned by [`CsvSliceStreamer::rows`].
+pub struct SliceRows<R: Read, T> {
+    streamer: CsvSliceStreamer<R>,
+    record: StringRecord,
+    /// slices parsed from each row, all of them or the one `slice` asked for
+    slices: Range<usize>,
+    row_index: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<R: Read, T: FromColumnSlice> Iterator for SliceRows<R, T> {
+    type Item = Result<Vec<(usize, T)>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // the record buffer is reused, so only one row is ever held in memory
+        if let Some(first) = self.streamer.first.take() {
+            self.record = first;
+        } else {
+            match self.streamer.reader.read_record(&mut self.record) {
+                Ok(false) => return None,
+                Ok(true) => {},
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+        self.streamer.config.prepare(&mut self.record, &self.streamer.headers);
+
+        let row_index = self.row_index;
+        self.row_index += 1;
+
+        let mut row = Vec::with_capacity(self.slices.len());
+
+        for slice_index in self.slices.clone() {
+            // slice_count only counts slices that fit in the headers, so this is always there
+            let Some((start_col, end_col)) = slice_columns::<T>(&self.streamer.config, slice_index, self.streamer.headers.len()) else {
+                break;
+            };
+
+            if is_skipped(&self.streamer.config, &self.record, start_col, end_col) {
+                continue;
+            }
+
+            match T::from_record(&self.record, start_col) {
+                Ok(value) => row.push((slice_index, value)),
+                Err(e) => return Some(Err(e.at_row(row_index))),
+            }
+        }
+
+        Some(Ok(row))
+    }
+}
@@ -0,0 +1,42 @@
+//! Rewriting every cell in one place before slices see it.
+
+use csv::StringRecord;
+
+/// The column a cell handed to [`ParseConfig::field_transform`](crate::ParseConfig::field_transform) is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnRef<'a> {
+    /// Column in the CSV, zero-based.
+    pub index: usize,
+
+    /// Its header, `""` when blank or past the last header.
+    pub header: &'a str,
+}
+
+/// Rewrites a cell, see [`ParseConfig::field_transform`](crate::ParseConfig::field_transform).
+///
+/// # Example
+///
+/// ```rust
+/// # use csv_partitioner::{ColumnRef, CsvSliceParser, ParseConfig};
+/// // 【】 hold the furigana in the Kanji column, nowhere else
+/// let config = ParseConfig::builder()
+///     .field_transform(|cell: &str, column: ColumnRef| match column.header {
+///         "Kanji" => cell.split('【').next().unwrap_or(cell).to_string(),
+///         _ => cell.to_string(),
+///     })
+///     .build();
+///
+/// let parser = CsvSliceParser::from_str("English,Kanji\nwater,水【みず】\n", config).unwrap();
+/// assert_eq!(parser.records()[0].iter().collect::<Vec<_>>(), vec!["water", "水"]);
+/// ```
+pub type FieldTransform = Box<dyn Fn(&str, ColumnRef<'_>) -> String + Send + Sync>;
+
+/// `record` with every cell rewritten by `transform`, keeping its position
+pub(crate) fn transform_record(transform: &FieldTransform, record: &StringRecord, headers: &StringRecord) -> StringRecord {
+    let mut transformed: StringRecord = record.iter()
+        .enumerate()
+        .map(|(index, cell)| transform(cell, ColumnRef { index, header: headers.get(index).unwrap_or("") }))
+        .collect();
+    transformed.set_position(record.position().cloned());
+    transformed
+}
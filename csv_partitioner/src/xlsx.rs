@@ -0,0 +1,102 @@
+//! Reading slices straight from spreadsheet files, with the `xlsx` feature.
+
+use std::path::Path;
+
+use calamine::{open_workbook_auto, Data, Reader};
+use csv::StringRecord;
+
+use crate::{split_top_rows, CsvSliceParser, ParseConfig, ParseError};
+
+impl CsvSliceParser {
+    /// Load one sheet of a spreadsheet file with a default configuration.
+    ///
+    /// Works for `.xlsx`, `.xlsm`, `.xls`, `.xlsb` and `.ods`. Requires the `xlsx` feature.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use csv_partitioner::CsvSliceParser;
+    /// # use std::error::Error;
+    /// # fn example() -> Result<(), Box<dyn Error>> {
+    /// for sheet in CsvSliceParser::xlsx_sheet_names("vocabulary.xlsx")? {
+    ///     let parser = CsvSliceParser::from_xlsx("vocabulary.xlsx", &sheet)?;
+    ///     println!("{}: {} rows", sheet, parser.record_count());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_xlsx<P: AsRef<Path>>(path: P, sheet_name: &str) -> Result<Self, ParseError> {
+        Self::from_xlsx_with_config(path, sheet_name, ParseConfig::default())
+    }
+
+    /// Load one sheet of a spreadsheet file with custom configuration.
+    ///
+    /// The sheet reads like its CSV export: columns and rows keep their position even when
+    /// the first ones are empty, numbers come out as written (`3`, `2.5`) and dates as
+    /// `2024-03-01` (with the time when there is one). `has_headers` and `trim_fields` apply,
+    /// the CSV dialect options (`delimiter`, `quote`, `comment`, `encoding`) don't.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CsvSliceParser)` - the sheet, ready to slice
+    /// * `Err(ParseError::Layout)` - there's no sheet called `sheet_name`
+    /// * `Err(ParseError)` - `Custom` when the file can't be read as a spreadsheet
+    pub fn from_xlsx_with_config<P: AsRef<Path>>(
+        path: P,
+        sheet_name: &str,
+        config: ParseConfig,
+    ) -> Result<Self, ParseError> {
+        let mut workbook = open_workbook_auto(path).map_err(ParseError::custom)?;
+
+        if !workbook.sheet_names().iter().any(|name| name == sheet_name) {
+            return Err(ParseError::Layout(format!(
+                "No sheet named '{}', the workbook has: {}", sheet_name, workbook.sheet_names().join(", ")
+            )));
+        }
+
+        let range = workbook.worksheet_range(sheet_name).map_err(ParseError::custom)?;
+        let (first_row, first_col) = range.start().unwrap_or((0, 0));
+
+        // empty rows and columns before the used range are still there in a CSV export
+        let leading_cells = std::iter::repeat_n(String::new(), first_col as usize);
+        let empty_row = vec![String::new(); first_col as usize + range.width()];
+        let mut rows = std::iter::repeat_n(empty_row, first_row as usize)
+            .chain(range.rows().map(|row| {
+                leading_cells.clone().chain(row.iter().map(|cell| cell_text(cell, config.trim_fields))).collect()
+            }));
+
+        let top_rows: Vec<StringRecord> = rows.by_ref().take(config.rows_above_data()).map(|row| row.iter().collect()).collect();
+        let records: Vec<StringRecord> = rows.map(|row| row.iter().collect()).collect();
+        let (top_rows, headers) = split_top_rows(&config, top_rows, records.first());
+        config.check_slice_gap(&headers)?;
+
+        let mut parser = CsvSliceParser::from_records(headers, records, config);
+        parser.top_rows = top_rows;
+
+        Ok(parser)
+    }
+
+    /// Names of the sheets in a spreadsheet file, in workbook order.
+    ///
+    /// Requires the `xlsx` feature.
+    pub fn xlsx_sheet_names<P: AsRef<Path>>(path: P) -> Result<Vec<String>, ParseError> {
+        Ok(open_workbook_auto(path).map_err(ParseError::custom)?.sheet_names())
+    }
+}
+
+/// a cell the way a CSV export writes it
+fn cell_text(cell: &Data, trim: bool) -> String {
+    let text = match cell {
+        Data::DateTime(datetime) if datetime.is_datetime() => {
+            let (year, month, day, hour, minute, second, _) = datetime.to_ymd_hms_milli();
+            if (hour, minute, second) == (0, 0, 0) {
+                format!("{:04}-{:02}-{:02}", year, month, day)
+            } else {
+                format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", year, month, day, hour, minute, second)
+            }
+        },
+        cell => cell.to_string(),
+    };
+
+    if trim { text.trim().to_string() } else { text }
+}
@@ -0,0 +1,86 @@
+//! Loading asynchronously has to give what loading synchronously does.
+
+#![cfg(feature = "async")]
+
+use csv_partitioner::{ColumnRef, CsvSliceParser, ParseConfig, RaggedPolicy};
+#[cfg(feature = "encoding")]
+use csv_partitioner::InputEncoding;
+
+const FILES: [&[u8]; 5] = [
+    b"Food,,Verbs,\nrice,\xe3\x81\x94\xe9\xa3\xaf,iku,to go\n,,taberu,to eat\n",
+    b"\xef\xbb\xbfFood,,Verbs,\n  rice ,gohan,iku,to go\n",
+    b"Food,,Verbs,\n\"rice, cooked\",\"gohan\nmeshi\",iku,to go\n\n# later\nmilk,gyuunyuu",
+    b"Spanish,English\nhola,hello\nadios\n",
+    b"",
+];
+
+fn rows(parser: &CsvSliceParser) -> Vec<Vec<String>> {
+    std::iter::once(parser.headers())
+        .chain(parser.records())
+        .map(|record| record.iter().map(str::to_string).collect())
+        .collect()
+}
+
+#[tokio::test]
+async fn records_match_the_sync_reader() {
+    for contents in FILES {
+        for (has_headers, trim_fields, ragged_rows) in [
+            (true, true, RaggedPolicy::PadEmpty),
+            (false, false, RaggedPolicy::Skip),
+            (true, true, RaggedPolicy::Error),
+        ] {
+            let config = || ParseConfig { has_headers, trim_fields, ragged_rows, comment: Some(b'#'), ..ParseConfig::default() };
+
+            let sync = CsvSliceParser::from_reader(contents, config());
+            let not_sync = CsvSliceParser::from_reader_async(contents, config()).await;
+
+            match (sync, not_sync) {
+                (Ok(sync), Ok(not_sync)) => assert_eq!(rows(&sync), rows(&not_sync)),
+                (Err(_), Err(_)) => {},
+                (sync, not_sync) => panic!("{:?}: sync ok {}, async ok {}", contents, sync.is_ok(), not_sync.is_ok()),
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn cells_are_transformed_like_the_sync_reader() {
+    let config = || ParseConfig::builder()
+        .field_transform(|cell: &str, column: ColumnRef| format!("{}:{}", column.index, cell.to_uppercase()))
+        .build();
+
+    for contents in FILES {
+        let sync = CsvSliceParser::from_reader(contents, config());
+        let not_sync = CsvSliceParser::from_reader_async(contents, config()).await;
+
+        match (sync, not_sync) {
+            (Ok(sync), Ok(not_sync)) => assert_eq!(rows(&sync), rows(&not_sync)),
+            (Err(_), Err(_)) => {},
+            (sync, not_sync) => panic!("{:?}: sync ok {}, async ok {}", contents, sync.is_ok(), not_sync.is_ok()),
+        }
+    }
+}
+
+#[tokio::test]
+#[cfg(feature = "encoding")]
+async fn other_encodings_are_decoded() {
+    let shift_jis: &[u8] = b"\x92\x50\x8c\xea,\x88\xd3\x96\xa1\n\x94\x4c,cat\n";
+    let config = ParseConfig::builder().encoding(InputEncoding::ShiftJis).build();
+
+    let parser = CsvSliceParser::from_reader_async(shift_jis, config).await.unwrap();
+
+    assert_eq!(&parser.headers()[0], "単語");
+    assert_eq!(&parser.records()[0][0], "猫");
+}
+
+#[tokio::test]
+async fn files_are_read_through_tokio() {
+    let path = std::env::temp_dir().join(format!("csv-partitioner-async-{}.csv", std::process::id()));
+    std::fs::write(&path, FILES[0]).unwrap();
+
+    let parser = CsvSliceParser::from_file_async(&path).await.unwrap();
+    assert_eq!(rows(&parser), rows(&CsvSliceParser::from_file(&path).unwrap()));
+
+    std::fs::remove_file(&path).unwrap();
+    assert!(CsvSliceParser::from_file_async(&path).await.is_err());
+}
@@ -0,0 +1,29 @@
+//! Fixtures shared by the integration tests, each test file uses only some of them.
+
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::PathBuf;
+
+use csv::StringRecord;
+use csv_partitioner::{FromColumnSlice, ParseError};
+
+/// a two-column slice read as is, missing cells are empty
+#[derive(Debug, PartialEq)]
+pub struct Word(pub String, pub String);
+
+impl FromColumnSlice for Word {
+    const COLUMN_COUNT: usize = 2;
+
+    fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+        let cell = |offset: usize| record.get(start_col + offset).unwrap_or("").to_string();
+        Ok(Word(cell(0), cell(1)))
+    }
+}
+
+/// write `contents` to a file of its own in the temp dir
+pub fn temp_csv(name: &str, contents: impl AsRef<[u8]>) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("csv-partitioner-{}-{}.csv", std::process::id(), name));
+    fs::write(&path, contents).unwrap();
+    path
+}
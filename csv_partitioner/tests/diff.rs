@@ -0,0 +1,50 @@
+//! Comparing two versions of a file, slices first and then their rows.
+
+use csv::StringRecord;
+use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+
+struct Word;
+
+impl FromColumnSlice for Word {
+    const COLUMN_COUNT: usize = 2;
+
+    fn from_record(_: &StringRecord, _: usize) -> Result<Self, ParseError> {
+        Ok(Word)
+    }
+}
+
+fn parser(data: &str) -> CsvSliceParser {
+    CsvSliceParser::from_str(data, ParseConfig::default()).unwrap()
+}
+
+#[test]
+fn rows_of_added_and_removed_slices_all_count() {
+    let week1 = parser("Food,,Verbs,\nりんご,apple,いく,to go\nみず,water,みる,to see\n");
+    let week2 = parser("Colours,,Food,\nあか,red,りんご,apple\n,,みず,water\n");
+
+    let schema = week1.schema_diff::<Word>(&week2);
+    assert_eq!(schema.added.len(), 1);
+    assert_eq!(schema.removed.len(), 1);
+    assert!(!schema.is_empty());
+
+    let diff = week1.diff_records::<Word>(&week2, 0).unwrap();
+    let names: Vec<(&str, Option<usize>, Option<usize>)> = diff.slices.iter()
+        .map(|slice| (slice.name.as_str(), slice.older_index, slice.newer_index))
+        .collect();
+    assert_eq!(names, vec![("Colours", None, Some(0)), ("Food", Some(0), Some(1)), ("Verbs", Some(1), None)]);
+
+    // the blank row of the new slice is skipped, like parsing would
+    assert_eq!(diff.slices[0].added, vec![0]);
+    assert!(diff.slices[1].is_empty());
+    assert_eq!(diff.slices[2].removed, vec![0, 1]);
+    assert_eq!(diff.changed().count(), 2);
+}
+
+#[test]
+fn the_same_file_has_no_changes() {
+    let data = "Food,,Verbs,\nりんご,apple,いく,to go\nりんご,apple,,\n";
+
+    assert!(parser(data).schema_diff::<Word>(&parser(data)).is_empty());
+    assert!(parser(data).diff_records::<Word>(&parser(data), 1).unwrap().is_empty());
+    assert!(matches!(parser(data).diff_records::<Word>(&parser(data), 2), Err(ParseError::Layout(_))));
+}
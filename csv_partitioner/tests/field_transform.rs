@@ -0,0 +1,28 @@
+//! `ParseConfig::field_transform` on the row-major parser.
+
+mod common;
+
+use common::Word;
+use csv_partitioner::{ColumnRef, ParseConfig, RowSliceParser};
+
+/// drops the furigana in 【】 and says which column the cell was in
+fn config() -> ParseConfig {
+    ParseConfig::builder()
+        .field_transform(|cell: &str, column: ColumnRef| {
+            format!("{}{}", column.index, cell.split('【').next().unwrap_or(cell))
+        })
+        .build()
+}
+
+#[test]
+fn row_blocks_are_transformed() {
+    let data = "# Food\n水【みず】,water\n# Verbs\n行く【いく】,to go\n";
+    let parser = RowSliceParser::from_reader(data.as_bytes(), config()).unwrap();
+
+    let blocks = parser.blocks_by_marker("0#");
+    let titles: Vec<&str> = blocks.iter().map(|block| block.title()).collect();
+    assert_eq!(titles, vec!["Food", "Verbs"]);
+
+    let verbs: Vec<Word> = parser.parse_block(&blocks[1]).unwrap();
+    assert_eq!((verbs[0].0.as_str(), verbs[0].1.as_str()), ("0行く", "1to go"));
+}
@@ -0,0 +1,121 @@
+//! Title rows and stacked header rows above the data, read the same by every loader.
+
+mod common;
+
+use common::Word;
+use csv_partitioner::{ColumnMap, CsvSliceParser, CsvSliceStreamer, ParseConfig};
+
+/// a title row, topic names, field names, then the words
+const SHEET: &str = "\
+JLPT N5,,,
+Food,,Verbs,
+kana,english,kana,english
+りんご,apple,いく,to go
+,,たべる,to eat
+";
+
+fn config() -> ParseConfig {
+    ParseConfig::builder().header_row(1).header_rows(2).build()
+}
+
+fn words(parser: &CsvSliceParser) -> Vec<Vec<Word>> {
+    parser.parse_all_slices().unwrap()
+}
+
+#[test]
+fn topic_names_and_field_names_come_from_their_own_rows() {
+    let parser = CsvSliceParser::from_str(SHEET, config()).unwrap();
+
+    assert_eq!(parser.headers().iter().collect::<Vec<_>>(), vec!["kana", "english", "kana", "english"]);
+    assert_eq!(parser.header_rows().len(), 2);
+    assert_eq!(parser.find_header("english"), Some(1));
+
+    let topics: Vec<String> = (0..parser.slice_count::<Word>())
+        .map(|index| parser.slice_header_rows::<Word>(index).unwrap()[0].first().to_string())
+        .collect();
+    assert_eq!(topics, vec!["Food", "Verbs"]);
+    assert!(parser.slice_header_rows::<Word>(2).is_none());
+
+    assert_eq!(words(&parser), vec![
+        vec![Word("りんご".into(), "apple".into())],
+        vec![Word("いく".into(), "to go".into()), Word("たべる".into(), "to eat".into())],
+    ]);
+}
+
+#[test]
+fn title_rows_above_data_without_headers() {
+    let config = ParseConfig::builder().header_row(3).has_headers(false).build();
+    let parser = CsvSliceParser::from_str(SHEET, config).unwrap();
+
+    assert_eq!(parser.headers().iter().collect::<Vec<_>>(), vec!["", "", "", ""]);
+    assert!(parser.header_rows().is_empty());
+    assert_eq!(parser.record_count(), 2);
+}
+
+#[test]
+fn a_file_that_ends_in_its_header_rows_has_no_data() {
+    let parser = CsvSliceParser::from_str("JLPT N5,\nFood,\n", config()).unwrap();
+    assert_eq!(parser.headers().iter().collect::<Vec<_>>(), vec!["Food", ""]);
+    assert_eq!(parser.record_count(), 0);
+
+    let parser = CsvSliceParser::from_str("JLPT N5,\n", config()).unwrap();
+    assert!(parser.headers().is_empty());
+}
+
+#[test]
+fn saving_keeps_the_rows_above_the_data() {
+    let parser = CsvSliceParser::from_str(SHEET, config()).unwrap();
+
+    let mut saved = Vec::new();
+    parser.write_to(&mut saved).unwrap();
+
+    assert_eq!(String::from_utf8(saved).unwrap(), SHEET);
+}
+
+#[test]
+fn a_column_map_moves_the_stacked_headers_too() {
+    let parser = CsvSliceParser::from_str(SHEET, config()).unwrap()
+        .with_column_map(&ColumnMap::reorder([1, 0]))
+        .unwrap();
+
+    let rows = parser.header_rows();
+    assert_eq!(rows[0].iter().collect::<Vec<_>>(), vec!["", "Food", "", "Verbs"]);
+    assert_eq!(rows[1].iter().collect::<Vec<_>>(), vec!["english", "kana", "english", "kana"]);
+}
+
+#[test]
+fn streaming_skips_the_same_rows() {
+    let streamer = CsvSliceStreamer::from_reader(SHEET.as_bytes(), config()).unwrap();
+    assert_eq!(streamer.headers().get(0), Some("kana"));
+    let verbs: Vec<Word> = streamer.slice::<Word>(1).unwrap().collect::<Result<_, _>>().unwrap();
+    assert_eq!(verbs.len(), 2);
+
+    let config = ParseConfig::builder().header_row(3).has_headers(false).build();
+    let streamer = CsvSliceStreamer::from_reader(SHEET.as_bytes(), config).unwrap();
+    let rows: Vec<_> = streamer.rows::<Word>().collect::<Result<_, _>>().unwrap();
+    assert_eq!(rows.iter().map(Vec::len).collect::<Vec<_>>(), vec![2, 1]);
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn async_loading_skips_the_same_rows() {
+    let loaded = CsvSliceParser::from_str(SHEET, config()).unwrap();
+    let parser = CsvSliceParser::from_reader_async(SHEET.as_bytes(), config()).await.unwrap();
+
+    assert_eq!(parser.header_rows(), loaded.header_rows());
+    assert_eq!(words(&parser), words(&loaded));
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn mapped_files_skip_the_same_rows() {
+    let path = std::env::temp_dir().join(format!("csv-partitioner-header-rows-{}.csv", std::process::id()));
+    std::fs::write(&path, SHEET).unwrap();
+
+    let mapped = CsvSliceParser::from_file_mmap(&path, config()).unwrap();
+    let loaded = CsvSliceParser::from_str(SHEET, config()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(mapped.headers(), loaded.headers());
+    assert_eq!(mapped.parse_slice::<Word>(1).unwrap(), loaded.parse_slice::<Word>(1).unwrap());
+}
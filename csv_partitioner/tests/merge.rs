@@ -0,0 +1,72 @@
+//! Loading several files with the same layout as one.
+
+#![cfg(feature = "fs")]
+
+mod common;
+
+use std::fs;
+
+use common::temp_csv;
+use csv::StringRecord;
+use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+
+struct Word(String);
+
+impl FromColumnSlice for Word {
+    const COLUMN_COUNT: usize = 2;
+
+    fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+        record.get(start_col + 1)
+            .filter(|english| !english.is_empty())
+            .ok_or_else(|| ParseError::missing_field(start_col + 1, "english"))?;
+        Ok(Word(record[start_col].to_string()))
+    }
+}
+
+#[test]
+fn records_follow_each_other_in_file_order() {
+    let spring = temp_csv("spring", "Food,,Verbs,\nりんご,apple,いく,to go\n");
+    let autumn = temp_csv("autumn", "food,,verbs,\nくり,chestnut,,\nなし,,みる,to see\n");
+
+    let parser = CsvSliceParser::from_files(&[&spring, &autumn]).unwrap();
+    assert_eq!(parser.record_count(), 3);
+
+    let verbs: Vec<String> = parser.parse_slice::<Word>(1).unwrap().into_iter().map(|Word(word)| word).collect();
+    assert_eq!(verbs, vec!["いく", "みる"]);
+
+    // rows count on through the second file
+    match parser.parse_slice::<Word>(0) {
+        Err(ParseError::MissingField { row, .. }) => assert_eq!(row, Some(2)),
+        _ => panic!("なし has no translation"),
+    }
+
+    fs::remove_file(spring).unwrap();
+    fs::remove_file(autumn).unwrap();
+}
+
+#[test]
+fn files_with_other_headers_are_named() {
+    let spring = temp_csv("words", "Food,,Verbs,\nりんご,apple,いく,to go\n");
+    let phrases = temp_csv("phrases", "Greetings,,Verbs,\nおはよう,good morning,,\n");
+
+    let Err(error) = CsvSliceParser::from_files(&[&spring, &phrases]) else {
+        panic!("the headers differ");
+    };
+    assert!(matches!(error, ParseError::Layout(_)));
+    assert_eq!(
+        error.to_string(),
+        format!("{}: Headers don't match: column 1 is 'Greetings', expected 'Food'", phrases.display()),
+    );
+
+    let config = ParseConfig { has_headers: false, ..ParseConfig::default() };
+    assert!(CsvSliceParser::from_files_with_config(&[&spring, &phrases], config).is_ok());
+
+    fs::remove_file(spring).unwrap();
+    fs::remove_file(phrases).unwrap();
+}
+
+#[test]
+fn there_has_to_be_a_file() {
+    let no_paths: [&str; 0] = [];
+    assert!(matches!(CsvSliceParser::from_files(&no_paths), Err(ParseError::Layout(_))));
+}
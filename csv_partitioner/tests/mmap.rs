@@ -0,0 +1,79 @@
+//! Parsing a memory-mapped file has to give what loading it does.
+
+#![cfg(feature = "mmap")]
+
+mod common;
+
+use std::fs;
+
+use common::{temp_csv, Word};
+use csv_partitioner::{CsvSliceParser, ParseConfig, ParseError, RaggedPolicy};
+#[cfg(feature = "encoding")]
+use csv_partitioner::InputEncoding;
+
+#[test]
+#[cfg(feature = "encoding")]
+fn slices_match_the_loaded_parser() {
+    let files: [(&str, &str); 4] = [
+        ("plain", "Food,,Verbs,\nrice,ご飯,いく,to go\n,,たべる,to eat\nbread,パン,,\n"),
+        ("bom", "\u{feff}Food,,Verbs,\nrice,¥,いく,to go\n"),
+        ("quoted", "Food,,Verbs,\n\"rice, cooked\",\"ご飯\nごはん\",いく,to go\n\n# later\nmilk,牛乳,,"),
+        ("empty", ""),
+    ];
+
+    for (name, contents) in files {
+        let path = temp_csv(name, contents);
+
+        for has_headers in [true, false] {
+            let config = || ParseConfig { has_headers, comment: Some(b'#'), encoding: InputEncoding::Utf8Bom, ..ParseConfig::default() };
+            let loaded = CsvSliceParser::from_file_with_config(&path, config()).unwrap();
+            let mapped = CsvSliceParser::from_file_mmap(&path, config()).unwrap();
+
+            assert_eq!(mapped.headers(), loaded.headers(), "{}", name);
+            assert_eq!(mapped.record_count(), loaded.record_count(), "{}", name);
+            assert_eq!(mapped.slice_count::<Word>(), loaded.slice_count::<Word>(), "{}", name);
+
+            for slice_index in 0..loaded.slice_count::<Word>() {
+                assert_eq!(mapped.parse_slice::<Word>(slice_index).unwrap(), loaded.parse_slice::<Word>(slice_index).unwrap());
+                let lazily: Vec<Word> = mapped.parse_slice_iter(slice_index).unwrap().collect::<Result<_, _>>().unwrap();
+                assert_eq!(lazily, loaded.parse_slice::<Word>(slice_index).unwrap());
+            }
+            for row in 0..loaded.record_count() {
+                assert_eq!(&mapped.record(row).unwrap(), &loaded.records()[row]);
+            }
+        }
+
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn rows_are_read_by_position() {
+    let path = temp_csv("rows", "Spanish,English\nhola,hello\nadiós\nbuenas,good evening\n");
+    let config = ParseConfig::builder().ragged_rows(RaggedPolicy::PadEmpty).build();
+    let parser = CsvSliceParser::from_file_mmap(&path, config).unwrap();
+
+    assert_eq!(parser.parse_row::<Word>(0, 2).unwrap(), Word("buenas".to_string(), "good evening".to_string()));
+    assert_eq!(parser.parse_row::<Word>(0, 1).unwrap(), Word("adiós".to_string(), String::new()));
+    assert!(matches!(parser.parse_row::<Word>(0, 3), Err(ParseError::RowOutOfBounds { row_index: 3, row_count: 3 })));
+    assert!(matches!(parser.parse_row::<Word>(1, 0), Err(ParseError::SliceOutOfBounds { slice_index: 1, slice_count: 1 })));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+#[cfg(feature = "encoding")]
+fn other_encodings_are_refused() {
+    let path = temp_csv("shift-jis", b"\x92\x50\x8c\xea,\x88\xd3\x96\xa1\n");
+    let config = ParseConfig::builder().encoding(InputEncoding::ShiftJis).build();
+
+    let Err(error) = CsvSliceParser::from_file_mmap(&path, config) else {
+        panic!("Shift_JIS can't be parsed in place");
+    };
+    assert_eq!(error.to_string(), "Memory-mapped files have to be UTF-8, load Shift_JIS files with from_file_with_config");
+
+    // and bytes that aren't UTF-8 fail when the file is opened, not later
+    assert!(CsvSliceParser::from_file_mmap(&path, ParseConfig::default()).is_err());
+
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,122 @@
+//! Quoted fields with newlines and quotes in them, e.g. example sentences, have to stay in
+//! their row and slice however the parser reads them.
+
+use csv::StringRecord;
+use csv_partitioner::{sniff_format, CsvSliceParser, CsvSliceStreamer, FromColumnSlice, ParseConfig, ParseError};
+
+#[derive(Debug, PartialEq)]
+struct Example {
+    word: String,
+    sentence: Option<String>,
+}
+
+impl FromColumnSlice for Example {
+    const COLUMN_COUNT: usize = 2;
+
+    fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+        let word = record.get(start_col).ok_or_else(|| ParseError::missing_field(start_col, "word"))?;
+        if word == "!" {
+            return Err(ParseError::custom("not a word"));
+        }
+
+        Ok(Example { word: word.to_string(), sentence: record.get(start_col + 1).map(str::to_string) })
+    }
+}
+
+fn example(word: &str, sentence: &str) -> Example {
+    Example { word: word.to_string(), sentence: Some(sentence.to_string()) }
+}
+
+/// two slices, the sentences span lines and hold quotes, commas and a `\r\n`
+const SENTENCES: &str = "\
+Animals,,Verbs,
+ねこ,\"猫がいる。
+Neko ga iru.\",たべる,\"「\"\"食べる\"\"」, to eat\"
+いぬ,\"犬が
+\r
+走る。\",,
+\"\"\"quoted\"\"\",\"
+trimmed
+\",みる,見る
+";
+
+#[test]
+fn multiline_cells_stay_in_their_row_and_slice() {
+    let parser = CsvSliceParser::from_str(SENTENCES, ParseConfig::default()).unwrap();
+
+    assert_eq!(parser.record_count(), 3);
+    assert_eq!(parser.parse_slice::<Example>(0).unwrap(), vec![
+        example("ねこ", "猫がいる。\nNeko ga iru."),
+        example("いぬ", "犬が\n\r\n走る。"),
+        // trimming takes the newlines around a cell, not those inside it
+        example("\"quoted\"", "trimmed"),
+    ]);
+    assert_eq!(parser.parse_slice::<Example>(1).unwrap(), vec![
+        example("たべる", "「\"食べる\"」, to eat"),
+        example("みる", "見る"),
+    ]);
+}
+
+#[test]
+fn untrimmed_cells_keep_their_newlines() {
+    let config = ParseConfig::builder().trim_fields(false).build();
+    let parser = CsvSliceParser::from_str(SENTENCES, config).unwrap();
+
+    assert_eq!(parser.parse_row::<Example>(0, 2).unwrap(), example("\"quoted\"", "\ntrimmed\n"));
+}
+
+#[test]
+fn errors_count_rows_not_lines() {
+    let data = "Words,\n\"a\nb\nc\",x\n!,y\n";
+    let parser = CsvSliceParser::from_str(data, ParseConfig::default()).unwrap();
+
+    let (words, errors) = parser.parse_slice_lenient::<Example>(0).unwrap();
+    assert_eq!(words, vec![example("a\nb\nc", "x")]);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].to_string(), "Row 2: not a word");
+}
+
+#[test]
+fn streaming_reads_the_same_rows() {
+    let parser = CsvSliceParser::from_str(SENTENCES, ParseConfig::default()).unwrap();
+    let streamer = CsvSliceStreamer::from_reader(SENTENCES.as_bytes(), ParseConfig::default()).unwrap();
+
+    let streamed: Vec<Example> = streamer.slice::<Example>(0).unwrap().collect::<Result<_, _>>().unwrap();
+    assert_eq!(streamed, parser.parse_slice::<Example>(0).unwrap());
+}
+
+#[test]
+fn saving_quotes_multiline_cells_again() {
+    let parser = CsvSliceParser::from_str(SENTENCES, ParseConfig::default()).unwrap();
+
+    let mut saved = Vec::new();
+    parser.write_to(&mut saved).unwrap();
+    let reloaded = CsvSliceParser::from_reader(saved.as_slice(), ParseConfig::default()).unwrap();
+
+    assert_eq!(reloaded.headers(), parser.headers());
+    assert_eq!(reloaded.records(), parser.records());
+}
+
+#[test]
+fn short_and_long_rows_need_flexible() {
+    // the row after the multiline one stops after its word, the last has a note past the headers
+    let data = "Animals,\nねこ,\"猫が\nいる\"\nいぬ\ntori,鳥,note\n";
+    assert!(matches!(CsvSliceParser::from_str(data, ParseConfig::default()), Err(ParseError::Csv(_))));
+
+    let config = ParseConfig::builder().flexible(true).build();
+    let parser = CsvSliceParser::from_str(data, config).unwrap();
+
+    assert_eq!(parser.records().iter().map(StringRecord::len).collect::<Vec<_>>(), vec![2, 1, 3]);
+    assert_eq!(parser.parse_slice::<Example>(0).unwrap(), vec![
+        example("ねこ", "猫が\nいる"),
+        Example { word: "いぬ".to_string(), sentence: None },
+        example("tori", "鳥"),
+    ]);
+}
+
+#[test]
+fn sniffing_looks_past_newlines_in_quotes() {
+    let data = "Word\tSentence\nねこ\t\"猫が, いる\n猫が, いた\"\nいぬ\t\"犬, 犬\"\n";
+
+    assert_eq!(sniff_format(data.as_bytes()).delimiter, b'\t');
+}
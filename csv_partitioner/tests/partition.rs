@@ -0,0 +1,414 @@
+//! Property tests for how slices partition the columns of a CSV.
+//!
+//! Every generated cell is either blank or unique (`r{row}c{col}`), so comparing the cells
+//! the parser returns with the cells that should be there shows both missing and repeated ones.
+
+use csv::StringRecord;
+use csv_partitioner::{CsvSliceParser, EmptyPolicy, FromColumnSlice, ParseConfig, ParseError, RaggedPolicy, ToColumnSlice};
+use proptest::prelude::*;
+
+/// the non-blank cells of a `W` column slice, in column order
+struct Cells<const W: usize>(Vec<String>);
+
+impl<const W: usize> FromColumnSlice for Cells<W> {
+    const COLUMN_COUNT: usize = W;
+
+    fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+        Ok(Cells((start_col..start_col + W)
+            .filter_map(|col| record.get(col))
+            .filter(|cell| !cell.trim().is_empty())
+            .map(str::to_string)
+            .collect()))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Case {
+    width: usize,
+    gap: usize,
+    skip_empty_rows: bool,
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Case {
+    fn parser(&self) -> CsvSliceParser {
+        let config = ParseConfig { slice_gap: self.gap, skip_empty_rows: self.skip_empty_rows, ..ParseConfig::default() };
+        CsvSliceParser::from_rows(&self.headers, &self.rows, config)
+    }
+
+    /// slices that fit in the headers, counted one by one instead of by division
+    fn slice_count(&self) -> usize {
+        let mut count = 0;
+        while (count + 1) * self.width + count * self.gap <= self.headers.len() {
+            count += 1;
+        }
+        count
+    }
+
+    fn columns(&self, slice_index: usize) -> std::ops::Range<usize> {
+        let start = slice_index * (self.width + self.gap);
+        start..start + self.width
+    }
+
+    /// non-blank cells of `row` inside the slice
+    fn cells(&self, row: &[String], slice_index: usize) -> Vec<String> {
+        self.columns(slice_index)
+            .filter_map(|col| row.get(col))
+            .filter(|cell| !cell.trim().is_empty())
+            .cloned()
+            .collect()
+    }
+}
+
+/// blank, whitespace only or a unique value, with rows shorter and longer than the headers
+fn case() -> impl Strategy<Value = Case> {
+    (1..=4usize, 0..=3usize, any::<bool>(), 0..=12usize, prop::collection::vec(prop::collection::vec(0..3u8, 0..=14), 0..=8))
+        .prop_map(|(width, gap, skip_empty_rows, header_count, kinds)| {
+            let headers = (0..header_count).map(|col| format!("h{}", col)).collect();
+            let rows = kinds.iter().enumerate()
+                .map(|(row, kinds)| kinds.iter().enumerate().map(|(col, kind)| match kind {
+                    0 => String::new(),
+                    1 => " ".to_string(),
+                    _ => format!("r{}c{}", row, col),
+                }).collect())
+                .collect();
+
+            Case { width, gap, skip_empty_rows, headers, rows }
+        })
+}
+
+/// all `W` cells of a slice, missing ones as `""`, to write back what was read
+#[derive(Debug, PartialEq)]
+struct Raw<const W: usize>(Vec<String>);
+
+impl<const W: usize> FromColumnSlice for Raw<W> {
+    const COLUMN_COUNT: usize = W;
+
+    fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+        Ok(Raw((start_col..start_col + W).map(|col| record.get(col).unwrap_or("").to_string()).collect()))
+    }
+}
+
+impl<const W: usize> ToColumnSlice for Raw<W> {
+    fn to_fields(&self) -> Vec<String> {
+        self.0.clone()
+    }
+}
+
+fn check_round_trip<const W: usize>(case: &Case) -> Result<(), TestCaseError> {
+    let mut parser = case.parser();
+    let Ok(before) = parser.parse_all_slices::<Raw<W>>() else {
+        return Ok(());
+    };
+
+    for (index, values) in before.iter().enumerate() {
+        parser.write_slice(index, values).unwrap();
+    }
+    prop_assert_eq!(parser.parse_all_slices::<Raw<W>>().unwrap(), before);
+
+    Ok(())
+}
+
+fn check_partition<const W: usize>(case: &Case) -> Result<(), TestCaseError> {
+    let parser = case.parser();
+    let count = case.slice_count();
+
+    if W > case.headers.len() {
+        prop_assert!(matches!(parser.parse_slice::<Cells<W>>(0), Err(ParseError::Layout(_))));
+        prop_assert!(parser.parse_all_slices::<Cells<W>>().unwrap().is_empty());
+        return Ok(());
+    }
+
+    prop_assert_eq!(parser.slice_count::<Cells<W>>(), count);
+
+    for index in 0..count {
+        prop_assert_eq!(parser.slice_columns::<Cells<W>>(index), Some(case.columns(index)));
+        let headers = parser.slice_headers::<Cells<W>>(index).unwrap();
+        prop_assert_eq!(headers.columns(), case.columns(index));
+        prop_assert_eq!(headers.first(), case.headers[case.columns(index).start].as_str());
+    }
+
+    prop_assert_eq!(parser.slice_columns::<Cells<W>>(count), None);
+    prop_assert!(parser.slice_headers::<Cells<W>>(count).is_none());
+    match parser.parse_slice::<Cells<W>>(count).map(|_| ()) {
+        Err(ParseError::SliceOutOfBounds { slice_index, slice_count }) => {
+            prop_assert_eq!((slice_index, slice_count), (count, count))
+        },
+        other => prop_assert!(false, "expected slice {} to be out of bounds, got {:?}", count, other),
+    }
+
+    // every non-blank cell inside a slice comes back exactly once, cells in gaps and past
+    // the last whole slice never do
+    let slices = parser.parse_all_slices::<Cells<W>>().unwrap();
+    let mut parsed: Vec<String> = slices.iter().flatten().flat_map(|cells| cells.0.clone()).collect();
+    let mut expected: Vec<String> = (0..count)
+        .flat_map(|index| case.rows.iter().flat_map(move |row| case.cells(row, index)))
+        .collect();
+    parsed.sort();
+    expected.sort();
+    prop_assert_eq!(parsed, expected);
+
+    let info = parser.slices::<Cells<W>>();
+    prop_assert_eq!(info.len(), count);
+
+    for (index, rows) in slices.iter().enumerate() {
+        let kept = if case.skip_empty_rows { info[index].non_empty_rows } else { case.rows.len() };
+        prop_assert_eq!(rows.len(), kept);
+
+        // single rows ignore the skip rules, so they line up with the input
+        for (row, cells) in case.rows.iter().enumerate() {
+            prop_assert_eq!(parser.parse_row::<Cells<W>>(index, row).unwrap().0, case.cells(cells, index));
+        }
+        prop_assert!(parser.parse_row::<Cells<W>>(index, case.rows.len()).is_err());
+
+        let paged = parser.parse_rows::<Cells<W>>(index, 0..case.rows.len()).unwrap();
+        prop_assert_eq!(paged.len(), rows.len());
+
+        // the stats count the same rows and cells parsing returns
+        let stats = parser.slice_stats::<Cells<W>>(index).unwrap();
+        prop_assert_eq!(stats.rows, rows.len());
+        prop_assert_eq!(stats.rows + stats.skipped_empty + stats.skipped_other, case.rows.len());
+        let filled: usize = stats.column_stats.iter().map(|column| column.filled).sum();
+        prop_assert_eq!(filled, rows.iter().map(|cells| cells.0.len()).sum::<usize>());
+    }
+
+    // reading one row across the slices gives the same cells, transposed
+    let across: Vec<Vec<Option<Cells<W>>>> = parser.rows_across_slices().collect::<Result<_, _>>().unwrap();
+    prop_assert_eq!(across.len(), case.rows.len());
+    prop_assert!(across.iter().all(|row| row.len() == count));
+
+    for (index, rows) in slices.iter().enumerate() {
+        let column: Vec<&Vec<String>> = across.iter().filter_map(|row| row[index].as_ref()).map(|cells| &cells.0).collect();
+        prop_assert_eq!(column, rows.iter().map(|cells| &cells.0).collect::<Vec<_>>());
+    }
+
+    Ok(())
+}
+
+/// all `W` cells of a slice like [`Raw`], but the last slice can be as narrow as one column
+#[derive(Debug, PartialEq)]
+struct Trailing<const W: usize>(Vec<String>);
+
+impl<const W: usize> FromColumnSlice for Trailing<W> {
+    const COLUMN_COUNT: usize = W;
+    const MIN_COLUMN_COUNT: usize = 1;
+
+    fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+        Raw::<W>::from_record(record, start_col).map(|raw| Trailing(raw.0))
+    }
+}
+
+impl<const W: usize> ToColumnSlice for Trailing<W> {
+    fn to_fields(&self) -> Vec<String> {
+        self.0.clone()
+    }
+}
+
+fn check_trailing<const W: usize>(case: &Case) -> Result<(), TestCaseError> {
+    let mut parser = case.parser();
+    // whole slices, then one more if any header column is left after the gap
+    let whole = case.slice_count();
+    let count = whole + usize::from(whole * (W + case.gap) < case.headers.len());
+
+    prop_assert_eq!(parser.slice_count::<Trailing<W>>(), count);
+    for index in 0..count {
+        let columns = case.columns(index);
+        prop_assert_eq!(parser.slice_columns::<Trailing<W>>(index), Some(columns.start..columns.end.min(case.headers.len())));
+    }
+    prop_assert_eq!(parser.slice_columns::<Trailing<W>>(count), None);
+
+    // the narrow slice reads the cells of its missing columns as blank, or as they are on
+    // rows longer than the headers, but only its own columns make a row empty
+    let before = parser.parse_all_slices::<Trailing<W>>().unwrap();
+    prop_assert_eq!(before.len(), count);
+    for (index, rows) in before.iter().enumerate() {
+        let columns = case.columns(index);
+        let kept = |row: &&Vec<String>| {
+            !case.skip_empty_rows || row.iter().take(columns.end.min(case.headers.len())).skip(columns.start).any(|cell| !cell.trim().is_empty())
+        };
+        let parsed: Vec<String> = rows.iter().flat_map(|cells| cells.0.iter().filter(|cell| !cell.trim().is_empty()).cloned()).collect();
+        let expected: Vec<String> = case.rows.iter().filter(kept).flat_map(|row| case.cells(row, index)).collect();
+        prop_assert_eq!(parsed, expected);
+    }
+
+    for (index, values) in before.iter().enumerate() {
+        parser.write_slice(index, values).unwrap();
+    }
+    prop_assert_eq!(parser.parse_all_slices::<Trailing<W>>().unwrap(), before);
+
+    Ok(())
+}
+
+/// where `from_record` was called and what it saw: the slice's `W` cells and the row's length
+#[derive(Debug, PartialEq)]
+struct Spy<const W: usize, const MIN: usize> {
+    start_col: usize,
+    cells: Vec<String>,
+    row_len: usize,
+}
+
+impl<const W: usize, const MIN: usize> FromColumnSlice for Spy<W, MIN> {
+    const COLUMN_COUNT: usize = W;
+    const MIN_COLUMN_COUNT: usize = MIN;
+
+    fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+        let cells = (start_col..start_col + W).map(|col| record.get(col).unwrap_or("").to_string()).collect();
+        Ok(Spy { start_col, cells, row_len: record.len() })
+    }
+}
+
+/// a [`Case`] with ragged rows padded or skipped, and either empty row policy
+fn bounds_case() -> impl Strategy<Value = (Case, RaggedPolicy, EmptyPolicy)> {
+    (case(), prop_oneof![Just(RaggedPolicy::Error), Just(RaggedPolicy::PadEmpty), Just(RaggedPolicy::Skip)], any::<bool>())
+        .prop_map(|(case, ragged_rows, any_empty)| {
+            (case, ragged_rows, if any_empty { EmptyPolicy::AnyEmpty } else { EmptyPolicy::AllEmpty })
+        })
+}
+
+fn check_bounds<const W: usize, const MIN: usize>(case: &Case, ragged_rows: RaggedPolicy, empty_row_policy: EmptyPolicy) -> Result<(), TestCaseError> {
+    let config = || ParseConfig {
+        slice_gap: case.gap,
+        skip_empty_rows: case.skip_empty_rows,
+        ragged_rows,
+        empty_row_policy,
+        ..ParseConfig::default()
+    };
+    let parser = CsvSliceParser::from_rows(&case.headers, &case.rows, config());
+    let header_len = case.headers.len();
+    let stride = W + case.gap;
+    let count = parser.slice_count::<Spy<W, MIN>>();
+
+    // slices start a stride apart, end inside the headers, and nothing fits after the last
+    for index in 0..count {
+        let columns = parser.slice_columns::<Spy<W, MIN>>(index).unwrap();
+        prop_assert_eq!(columns.start, index * stride);
+        prop_assert!(columns.end <= header_len);
+        prop_assert!((MIN..=W).contains(&columns.len()));
+    }
+    prop_assert_eq!(parser.slice_columns::<Spy<W, MIN>>(count), None);
+    prop_assert!(count * stride + MIN > header_len);
+    if MIN == W {
+        prop_assert!(count * W + count.saturating_sub(1) * case.gap <= header_len);
+    }
+
+    for index in 0..count {
+        let columns = parser.slice_columns::<Spy<W, MIN>>(index).unwrap();
+        let values = parser.parse_slice::<Spy<W, MIN>>(index).unwrap();
+
+        for value in &values {
+            prop_assert_eq!(value.start_col, columns.start);
+            if ragged_rows == RaggedPolicy::Skip {
+                prop_assert!(value.row_len >= columns.end);
+            }
+        }
+
+        // whatever is outside the slice, the slice parses the same
+        let read = columns.start..columns.start + W;
+        let scrambled: Vec<Vec<String>> = case.rows.iter()
+            .map(|row| row.iter().enumerate().map(|(col, cell)| if read.contains(&col) { cell.clone() } else { format!("x{}", col) }).collect())
+            .collect();
+        let scrambled = CsvSliceParser::from_rows(&case.headers, &scrambled, config());
+        prop_assert_eq!(scrambled.parse_slice::<Spy<W, MIN>>(index).unwrap(), values);
+    }
+
+    Ok(())
+}
+
+/// rows wide enough that a slice can cross the 64 columns of a bitmap word
+fn wide_rows() -> impl Strategy<Value = Vec<Vec<String>>> {
+    prop::collection::vec(prop::collection::vec(prop_oneof![Just(""), Just(" "), Just("\u{3000}"), Just("x")], 0..=140), 0..=6)
+        .prop_map(|rows| rows.into_iter().map(|row| row.into_iter().map(str::to_string).collect()).collect())
+}
+
+/// parsing every slice at once has to skip the same rows as parsing them one by one
+fn check_all_slices<const W: usize>(rows: &[Vec<String>], gap: usize, any_empty: bool) -> Result<(), TestCaseError> {
+    let headers: Vec<String> = (0..140).map(|col| format!("h{}", col)).collect();
+    let empty_row_policy = if any_empty { EmptyPolicy::AnyEmpty } else { EmptyPolicy::AllEmpty };
+    let config = ParseConfig { slice_gap: gap, empty_row_policy, ..ParseConfig::default() };
+    let parser = CsvSliceParser::from_rows(&headers, rows, config);
+
+    let all = parser.parse_all_slices::<Cells<W>>().unwrap();
+    prop_assert_eq!(all.len(), parser.slice_count::<Cells<W>>());
+    for (index, slice) in all.iter().enumerate() {
+        let one = parser.parse_slice::<Cells<W>>(index).unwrap();
+        prop_assert_eq!(slice.iter().map(|cells| &cells.0).collect::<Vec<_>>(), one.iter().map(|cells| &cells.0).collect::<Vec<_>>());
+    }
+
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn slices_partition_the_columns(case in case()) {
+        match case.width {
+            1 => check_partition::<1>(&case)?,
+            2 => check_partition::<2>(&case)?,
+            3 => check_partition::<3>(&case)?,
+            _ => check_partition::<4>(&case)?,
+        }
+    }
+
+    #[test]
+    fn writing_a_slice_back_changes_nothing(case in case()) {
+        match case.width {
+            1 => check_round_trip::<1>(&case)?,
+            2 => check_round_trip::<2>(&case)?,
+            3 => check_round_trip::<3>(&case)?,
+            _ => check_round_trip::<4>(&case)?,
+        }
+    }
+
+    #[test]
+    fn a_narrower_last_slice_takes_the_leftover_columns(case in case()) {
+        match case.width {
+            1 => check_trailing::<1>(&case)?,
+            2 => check_trailing::<2>(&case)?,
+            3 => check_trailing::<3>(&case)?,
+            _ => check_trailing::<4>(&case)?,
+        }
+    }
+
+    #[test]
+    fn slices_stay_inside_their_columns((case, ragged_rows, empty_row_policy) in bounds_case()) {
+        match case.width {
+            1 => check_bounds::<1, 1>(&case, ragged_rows, empty_row_policy)?,
+            2 => check_bounds::<2, 2>(&case, ragged_rows, empty_row_policy)?,
+            3 => check_bounds::<3, 1>(&case, ragged_rows, empty_row_policy)?,
+            _ => check_bounds::<4, 3>(&case, ragged_rows, empty_row_policy)?,
+        }
+    }
+
+    #[test]
+    fn all_slices_skip_the_rows_single_slices_do(rows in wide_rows(), gap in 0..=3usize, width in 0..3usize, any_empty in any::<bool>()) {
+        match width {
+            0 => check_all_slices::<1>(&rows, gap, any_empty)?,
+            1 => check_all_slices::<7>(&rows, gap, any_empty)?,
+            _ => check_all_slices::<65>(&rows, gap, any_empty)?,
+        }
+    }
+}
+
+#[test]
+fn a_gap_too_big_to_count_with_is_refused() {
+    let config = ParseConfig { slice_gap: usize::MAX, ..ParseConfig::default() };
+    let parsed = CsvSliceParser::from_reader("Food,,\nりんご,apple,林檎\n".as_bytes(), config);
+    assert!(matches!(parsed, Err(ParseError::Layout(_))));
+
+    // parsers built without reading count the one slice that fits, without overflowing
+    let config = ParseConfig { slice_gap: usize::MAX, ..ParseConfig::default() };
+    let parser = CsvSliceParser::from_rows(["Food", "", ""], &[vec!["りんご", "apple", "林檎"]], config);
+    assert_eq!(parser.slice_count::<Cells<3>>(), 1);
+}
+
+#[test]
+fn a_narrow_last_slice_refuses_values_it_has_no_room_for() {
+    let mut parser = CsvSliceParser::from_rows(["Food", "", "", "Verbs"], &[vec!["りんご", "apple", "林檎", "いく"]], ParseConfig::default());
+    let verbs = parser.parse_slice::<Trailing<3>>(1).unwrap();
+
+    // its blank columns round trip, a value for them would be dropped
+    parser.write_slice(1, &verbs).unwrap();
+    let error = parser.write_slice(1, &[Trailing::<3>(vec!["たべる".to_string(), "to eat".to_string(), String::new()])]).unwrap_err();
+    assert!(matches!(error, ParseError::Layout(_)), "{}", error);
+    assert_eq!(parser.parse_slice::<Trailing<3>>(1).unwrap(), verbs);
+}
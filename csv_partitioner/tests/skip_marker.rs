@@ -0,0 +1,59 @@
+//! Rows held back with `ParseConfig::skip_marker`, per slice.
+
+use csv::StringRecord;
+use csv_partitioner::{CsvSliceParser, CsvSliceStreamer, FromColumnSlice, ParseConfig, ParseError};
+
+/// a reading and its meaning, the third column is for notes (and the marker)
+struct Word(String);
+
+impl FromColumnSlice for Word {
+    const COLUMN_COUNT: usize = 3;
+
+    fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+        Ok(Word(record.get(start_col).unwrap_or("").to_string()))
+    }
+}
+
+/// みず and いく are marked, パン only has the marker as part of a note
+const SHEET: &str = "\
+Food,,,Verbs,,
+りんご,apple,,いく,to go,!skip
+みず,water, !skip ,たべる,to eat,
+パン,bread,!skipped,のむ,to drink,
+";
+
+fn config() -> ParseConfig {
+    ParseConfig::builder().skip_marker(Some("!skip".to_string())).build()
+}
+
+fn readings(slices: Vec<Vec<Word>>) -> Vec<Vec<String>> {
+    slices.into_iter().map(|slice| slice.into_iter().map(|Word(reading)| reading).collect()).collect()
+}
+
+#[test]
+fn marked_rows_are_left_out_of_their_own_slice_only() {
+    let parser = CsvSliceParser::from_str(SHEET, config()).unwrap();
+
+    assert_eq!(readings(parser.parse_all_slices().unwrap()), vec![
+        vec!["りんご", "パン"],
+        vec!["たべる", "のむ"],
+    ]);
+}
+
+#[test]
+fn rows_without_the_marker_are_all_kept() {
+    let parser = CsvSliceParser::from_str(SHEET, ParseConfig::default()).unwrap();
+
+    assert_eq!(readings(parser.parse_all_slices().unwrap()), vec![
+        vec!["りんご", "みず", "パン"],
+        vec!["いく", "たべる", "のむ"],
+    ]);
+}
+
+#[test]
+fn streaming_skips_the_same_rows() {
+    let streamer = CsvSliceStreamer::from_reader(SHEET.as_bytes(), config()).unwrap();
+    let verbs: Vec<Word> = streamer.slice::<Word>(1).unwrap().collect::<Result<_, _>>().unwrap();
+
+    assert_eq!(readings(vec![verbs]), vec![vec!["たべる", "のむ"]]);
+}
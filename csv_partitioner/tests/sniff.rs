@@ -0,0 +1,53 @@
+//! Sniffing the delimiter and header row of real-world exports.
+
+use csv_partitioner::{sniff_format, SNIFF_BYTES};
+
+#[test]
+fn delimiters_inside_fields_dont_count() {
+    let quoted = "Word,Meaning\n水,\"water; fluid\"\n猫,\"cat; kitty\"\n";
+    assert_eq!(sniff_format(quoted.as_bytes()).delimiter, b',');
+
+    let semicolons = "Wort;Bedeutung\nHund;dog, hound\nKatze;cat\n";
+    assert_eq!(sniff_format(semicolons.as_bytes()).delimiter, b';');
+
+    // a single column falls back to commas
+    assert_eq!(sniff_format("水\n猫\n".as_bytes()).delimiter, b',');
+}
+
+#[test]
+fn topic_headers_with_blanks_are_headers() {
+    let sliced = "\u{feff}Food,,Verbs,\nりんご,apple,いく,to go\nみず,water,みる,to see\n";
+    let format = sniff_format(sliced.as_bytes());
+
+    assert_eq!(format.delimiter, b',');
+    assert!(format.has_headers);
+}
+
+#[test]
+fn rows_cut_off_at_the_end_of_the_sample_are_ignored() {
+    // cut mid-character, as reading the first few KB does
+    let big = jisho_export();
+    assert_eq!(sniff_format(&big.as_bytes()[..SNIFF_BYTES]).delimiter, b'\t');
+}
+
+#[test]
+#[cfg(feature = "fs")]
+fn files_are_read_with_what_was_sniffed() {
+    let path = std::env::temp_dir().join(format!("csv-partitioner-sniff-{}.txt", std::process::id()));
+    std::fs::write(&path, jisho_export()).unwrap();
+
+    let parser = csv_partitioner::CsvSliceParser::from_file_auto(&path).unwrap();
+    assert_eq!(parser.headers().iter().collect::<Vec<_>>(), vec!["Kanji", "Reading", "Meaning"]);
+    assert_eq!(&parser.records()[0][2], "water, fluid");
+
+    std::fs::remove_file(path).unwrap();
+}
+
+/// a tab separated export longer than the sniffed sample
+fn jisho_export() -> String {
+    let mut big = String::from("Kanji\tReading\tMeaning\n");
+    while big.len() < SNIFF_BYTES + 100 {
+        big.push_str("水\tみず\twater, fluid\n");
+    }
+    big
+}
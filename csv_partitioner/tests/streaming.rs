@@ -0,0 +1,35 @@
+//! Reading one slice of a streamed CSV.
+
+use csv::StringRecord;
+use csv_partitioner::{CsvSliceStreamer, FromColumnSlice, ParseConfig, ParseError};
+
+/// a word whose reading has to be filled in, blank readings fail to parse
+struct Word(String);
+
+impl FromColumnSlice for Word {
+    const COLUMN_COUNT: usize = 2;
+
+    fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+        match record.get(start_col).filter(|reading| !reading.is_empty()) {
+            Some(reading) => Ok(Word(reading.to_string())),
+            None => Err(ParseError::missing_field(start_col, "reading")),
+        }
+    }
+}
+
+#[test]
+fn a_single_slice_ignores_cells_the_other_slices_cannot_parse() {
+    let data = "Food,,Verbs,\nりんご,apple,,to go\nみず,water,たべる,to eat\n";
+    let food: Vec<String> = CsvSliceStreamer::from_reader(data.as_bytes(), ParseConfig::default()).unwrap()
+        .slice::<Word>(0).unwrap()
+        .map(|word| word.map(|Word(reading)| reading))
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(food, vec!["りんご", "みず"]);
+
+    let verbs: Vec<Result<Word, ParseError>> = CsvSliceStreamer::from_reader(data.as_bytes(), ParseConfig::default()).unwrap()
+        .slice::<Word>(1).unwrap()
+        .collect();
+    assert!(matches!(verbs[0], Err(ParseError::MissingField { row: Some(0), col: 2, .. })));
+    assert!(matches!(&verbs[1], Ok(Word(reading)) if reading == "たべる"));
+}
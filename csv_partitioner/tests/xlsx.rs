@@ -0,0 +1,65 @@
+//! Reading slices from a spreadsheet file instead of its CSV export.
+
+#![cfg(feature = "xlsx")]
+
+use csv::StringRecord;
+use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError};
+
+const WORKBOOK: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/vocab.xlsx");
+
+#[derive(Debug, PartialEq)]
+struct Entry(String, String, String);
+
+impl FromColumnSlice for Entry {
+    const COLUMN_COUNT: usize = 3;
+
+    fn from_record(record: &StringRecord, start_col: usize) -> Result<Self, ParseError> {
+        let cell = |offset: usize| record.get(start_col + offset).unwrap_or("").to_string();
+        Ok(Entry(cell(0), cell(1), cell(2)))
+    }
+}
+
+fn entry(a: &str, b: &str, c: &str) -> Entry {
+    Entry(a.to_string(), b.to_string(), c.to_string())
+}
+
+#[test]
+fn sheets_are_listed_in_workbook_order() {
+    assert_eq!(CsvSliceParser::xlsx_sheet_names(WORKBOOK).unwrap(), vec!["Topics", "Notes"]);
+}
+
+#[test]
+fn slices_read_like_the_csv_export() {
+    let parser = CsvSliceParser::from_xlsx(WORKBOOK, "Topics").unwrap();
+
+    assert_eq!(parser.slice_count::<Entry>(), 2);
+    assert_eq!(parser.parse_slice::<Entry>(0).unwrap(), vec![
+        entry("りんご", "apple", "1"),
+        entry("みず", "water", "2.5"),
+        entry("ねこ", "cat", ""),
+    ]);
+    assert_eq!(parser.parse_slice::<Entry>(1).unwrap(), vec![
+        entry("たべる", "to eat", "2024-03-01"),
+        entry("のむ", "to drink", ""),
+    ]);
+}
+
+#[test]
+fn leading_empty_rows_and_columns_are_kept() {
+    let config = ParseConfig { has_headers: false, ..ParseConfig::default() };
+    let parser = CsvSliceParser::from_xlsx_with_config(WORKBOOK, "Notes", config).unwrap();
+
+    assert_eq!(parser.record_count(), 3);
+    assert_eq!(parser.headers().len(), 3);
+    assert_eq!(parser.records()[2].iter().collect::<Vec<_>>(), vec!["", "りんご", "red"]);
+}
+
+#[test]
+fn missing_sheets_say_which_there_are() {
+    let Err(error) = CsvSliceParser::from_xlsx(WORKBOOK, "Verbs") else {
+        panic!("there is no Verbs sheet");
+    };
+
+    assert!(matches!(error, ParseError::Layout(_)));
+    assert_eq!(error.to_string(), "No sheet named 'Verbs', the workbook has: Topics, Notes");
+}
@@ -1,8 +1,9 @@
-use std::error::Error;
-use serde::{Deserialize, Serialize};
-use serde_json;
+use std::{collections::HashMap, error::Error, time::{Duration, Instant}};
+use serde::{ser::SerializeMap, Deserialize, Serialize, Serializer};
 use reqwest::{self};
 
+use crate::tags::escape_search;
+
 
 
 // ============================================================================================
@@ -47,6 +48,25 @@ struct AddNotesParams {
     notes: Vec<Note>
 }
 
+/// One answer of canAddNotesWithErrorDetail
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CanAddDetail {
+    can_add: bool,
+
+    #[serde(default)]
+    error: Option<String>,
+}
+
+impl CanAddDetail {
+    fn into_result(self) -> Result<(), String> {
+        match self.can_add {
+            true => Ok(()),
+            false => Err(self.error.unwrap_or_else(|| "Anki would not add the note".to_string())),
+        }
+    }
+}
+
 /// Anki note structure
 #[derive(Debug, Serialize, Clone)]
 pub struct Note {
@@ -72,14 +92,76 @@ pub struct Note {
 }
 
 
-/// Note fields for Japanese vocabularly
-#[derive(Debug, Serialize, Clone)]
+/// Note fields, kept in insertion order
+///
+/// AnkiConnect wants exactly the note type's field names, so fields are named
+/// rather than fixed struct members, and can be reordered/validated against
+/// `modelFieldNames` with `in_model_order`
+#[derive(Debug, Clone, Default)]
 pub struct NoteFields {
-    #[serde(rename = "Front")]
-    pub(crate) front: String,
+    fields: Vec<(String, String)>,
+}
+
+impl NoteFields {
+    pub fn new() -> Self {
+        NoteFields::default()
+    }
+
+    /// builder form of `set`
+    pub fn with(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    /// set a field, replacing any existing value with the same name
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        let (name, value) = (name.into(), value.into());
+
+        match self.fields.iter_mut().find(|(existing, _)| *existing == name) {
+            Some((_, existing)) => *existing = value,
+            None => self.fields.push((name, value)),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields.iter()
+            .find(|(existing, _)| existing == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.fields.iter().map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Reorder to match a note type's fields (as returned by `modelFieldNames`)
+    ///
+    /// missing fields are sent empty, unknown field names are an error
+    pub fn in_model_order(&self, model_name: &str, model_fields: &[String]) -> Result<NoteFields, Box<dyn Error>> {
+        if let Some((unknown, _)) = self.fields.iter().find(|(name, _)| !model_fields.contains(name)) {
+            return Err(format!(
+                "Field '{}' does not exist on note type '{}' (fields: {})",
+                unknown, model_name, model_fields.join(", ")
+            ).into());
+        }
 
-    #[serde(rename = "Back")]
-    pub(crate) back: String,
+        Ok(NoteFields {
+            fields: model_fields.iter()
+                .map(|name| (name.clone(), self.get(name).unwrap_or_default().to_string()))
+                .collect(),
+        })
+    }
+}
+
+impl Serialize for NoteFields {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.fields.len()))?;
+
+        for (name, value) in &self.fields {
+            map.serialize_entry(name, value)?;
+        }
+
+        map.end()
+    }
 }
 
 
@@ -105,6 +187,125 @@ struct CreateDeckParams {
 }
 
 
+/// Parameters for findCards / findNotes
+#[derive(Debug, Serialize)]
+struct QueryParams {
+    query: String
+}
+
+
+/// Parameters for deleteNotes
+#[derive(Debug, Serialize)]
+struct DeleteNotesParams {
+    notes: Vec<i64>,
+}
+
+/// Parameters for moving cards between decks
+#[derive(Debug, Serialize)]
+struct ChangeDeckParams {
+    cards: Vec<i64>,
+    deck: String,
+}
+
+
+/// Parameters for updateNoteFields
+#[derive(Debug, Serialize)]
+struct UpdateNoteFieldsParams<'a> {
+    note: NoteFieldsUpdate<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct NoteFieldsUpdate<'a> {
+    id: i64,
+    fields: &'a NoteFields,
+}
+
+/// Parameters for addTags / removeTags (tags are space separated)
+#[derive(Debug, Serialize)]
+struct TagsParams {
+    notes: Vec<i64>,
+    tags: String,
+}
+
+
+/// Parameters for deleting decks
+#[derive(Debug, Serialize)]
+struct DeleteDecksParams {
+    decks: Vec<String>,
+
+    #[serde(rename = "cardsToo")]
+    cards_too: bool,
+}
+
+
+/// Parameters for model (note type) queries
+#[derive(Debug, Serialize)]
+struct ModelNameParams {
+    #[serde(rename = "modelName")]
+    model_name: String
+}
+
+
+/// Parameters for listing media files (glob pattern, e.g. `*.mp3`)
+#[derive(Debug, Serialize)]
+struct MediaPatternParams {
+    pattern: String
+}
+
+
+/// Parameters for deleting a media file
+#[derive(Debug, Serialize)]
+struct MediaFileParams {
+    filename: String
+}
+
+
+/// Parameters for running several actions in one request
+#[derive(Debug, Serialize)]
+struct MultiParams<T> {
+    actions: Vec<AnkiRequest<T>>
+}
+
+
+/// Parameters for notesInfo
+#[derive(Debug, Serialize)]
+struct NotesParams {
+    notes: Vec<i64>
+}
+
+
+/// Parameters for card actions (cardsInfo, forgetCards, relearnCards, unsuspend)
+#[derive(Debug, Serialize)]
+struct CardsParams {
+    cards: Vec<i64>
+}
+
+
+/// Parameters for rescheduling cards, `days` uses anki's syntax (`0` = today, `1-7` = random in range)
+#[derive(Debug, Serialize)]
+struct SetDueDateParams {
+    cards: Vec<i64>,
+    days: String,
+}
+
+
+/// Parameters for exporting a deck as an .apkg
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportPackageParams {
+    deck: String,
+    path: String,
+    include_sched: bool,
+}
+
+
+/// Parameters for importing an .apkg
+#[derive(Debug, Serialize)]
+struct ImportPackageParams {
+    path: String
+}
+
+
 /// Parameters for checking permissions
 #[derive(Debug, Serialize)]
 struct RequestPermissionParams {}
@@ -115,27 +316,135 @@ struct RequestPermissionParams {}
 struct _GetDeckNamesParams {}
 
 
+/// One field of a note as returned by cardsInfo / notesInfo
+#[derive(Debug, Deserialize, Clone)]
+pub struct FieldValue {
+    pub value: String,
+    pub order: usize,
+}
+
+/// A card as returned by cardsInfo (only the parts we use)
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CardInfo {
+    pub note: i64,
+    pub deck_name: String,
+
+    #[serde(default)]
+    pub fields: HashMap<String, FieldValue>,
+}
+
+impl CardInfo {
+    /// field values sorted by their position in the note type
+    pub fn ordered_fields(&self) -> Vec<&str> {
+        ordered_values(&self.fields)
+    }
+}
+
+/// A note as returned by notesInfo (only the parts we use)
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteInfo {
+    pub note_id: i64,
+
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    #[serde(default)]
+    pub fields: HashMap<String, FieldValue>,
+
+    #[serde(default)]
+    pub cards: Vec<i64>,
+}
+
+impl NoteInfo {
+    /// field values sorted by their position in the note type
+    pub fn ordered_fields(&self) -> Vec<&str> {
+        ordered_values(&self.fields)
+    }
+}
+
+fn ordered_values(fields: &HashMap<String, FieldValue>) -> Vec<&str> {
+    let mut fields: Vec<&FieldValue> = fields.values().collect();
+    fields.sort_by_key(|field| field.order);
+    fields.into_iter().map(|field| field.value.as_str()).collect()
+}
+
+
+/// Where AnkiConnect looks for duplicates of a note's first field
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateScope {
+    /// only the target deck (or `duplicate_scope_options.deck_name`)
+    #[default]
+    Deck,
+
+    /// the whole collection
+    Collection,
+}
+
+impl std::str::FromStr for DuplicateScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "deck" => Ok(DuplicateScope::Deck),
+            "collection" => Ok(DuplicateScope::Collection),
+            other => Err(format!("Unknown duplicate scope '{}', expected one of: deck, collection", other)),
+        }
+    }
+}
+
+/// Per-note `options` for addNote(s)
 #[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct OptionFields {
-    #[serde(rename = "allowDuplicate")]
     pub(crate) allow_duplicate: bool,
 
-    #[serde(rename = "duplicateScope")]
-    pub(crate) duplicate_scope: String,
+    pub(crate) duplicate_scope: DuplicateScope,
 
-    #[serde(rename = "duplicateScopeOptions")]
     pub(crate) duplicate_scope_options: DuplicateScopeOptions
 }
 
-#[derive(Debug, Serialize, Clone)]
+impl OptionFields {
+    /// add the note even if an identical first field already exists
+    pub fn allow_duplicates() -> Self {
+        OptionFields {
+            allow_duplicate: true,
+            duplicate_scope: DuplicateScope::Deck,
+            duplicate_scope_options: DuplicateScopeOptions::default(),
+        }
+    }
+
+    /// reject notes whose first field already exists in `deck_name` (not its subdecks)
+    pub fn reject_duplicates_in_deck(deck_name: impl Into<String>) -> Self {
+        Self::reject_duplicates(
+            DuplicateScope::Deck,
+            DuplicateScopeOptions { deck_name: Some(deck_name.into()), ..DuplicateScopeOptions::default() },
+        )
+    }
+
+    /// reject the note if its first field already exists within `scope`
+    pub fn reject_duplicates(scope: DuplicateScope, options: DuplicateScopeOptions) -> Self {
+        OptionFields {
+            allow_duplicate: false,
+            duplicate_scope: scope,
+            duplicate_scope_options: options,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
 pub struct DuplicateScopeOptions {
-    #[serde(rename = "deckName")]
-    pub(crate) deck_name: String,
+    /// deck to check when scope is `Deck`, anki uses the note's own deck when omitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) deck_name: Option<String>,
 
-    #[serde(rename = "checkChildren")]
+    /// also check subdecks of the deck
     pub(crate) check_children: bool,
 
-    #[serde(rename = "checkAllModels")]
+    /// check notes of every note type, not just this one
     pub(crate) check_all_models: bool
 }
 
@@ -144,9 +453,58 @@ pub struct DuplicateScopeOptions {
 // ============================================================================================
 
 
+/// ids sent per notesInfo / cardsInfo request, big lists time out in one go
+pub const DEFAULT_CHUNK_SIZE: usize = 500;
+
+/// sent with every request unless overridden, so proxy/AnkiConnect logs show who's calling
+pub const DEFAULT_USER_AGENT: &str = concat!("csv-to-anki/", env!("CARGO_PKG_VERSION"));
+
+/// actions that make anki do real work, everything else counts as quick
+const HEAVY_ACTIONS: &[&str] = &[
+    "addNote", "addNotes", "canAddNotesWithErrorDetail", "updateNoteFields", "multi",
+    "deleteNotes", "deleteDecks", "changeDeck", "addTags", "removeTags",
+    "forgetCards", "relearnCards", "setDueDate", "unsuspend",
+    "storeMediaFile", "importPackage", "exportPackage", "sync",
+];
+
+/// How long AnkiConnect requests may take before giving up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    /// lookups and small edits (deckNames, findNotes, notesInfo, ...)
+    pub quick: Duration,
+
+    /// actions listed in `HEAVY_ACTIONS` (adding, checking, changing or deleting notes in bulk,
+    /// rescheduling cards, media, packages, sync)
+    pub heavy: Duration,
+
+    /// limit for everything one client sends, counted from when the timeouts are set
+    pub deadline: Option<Duration>,
+}
+
+impl Timeouts {
+    fn for_action(&self, action: &str) -> Duration {
+        if HEAVY_ACTIONS.contains(&action) { self.heavy } else { self.quick }
+    }
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Timeouts {
+            quick: Duration::from_secs(10),
+            heavy: Duration::from_secs(120),
+            deadline: None,
+        }
+    }
+}
+
 pub struct AnkiConnectClient {
     base_url: String,
     client: reqwest::blocking::Client,
+    chunk_size: usize,
+    user_agent: String,
+    headers: Vec<(String, String)>,
+    timeouts: Timeouts,
+    deadline: Option<Instant>,
 }
 
 impl AnkiConnectClient {
@@ -159,10 +517,44 @@ impl AnkiConnectClient {
     pub fn with_url(url: impl Into<String>) -> Self {
         AnkiConnectClient { 
             base_url: url.into(), 
-            client: reqwest::blocking::Client::new() 
+            client: reqwest::blocking::Client::new(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            headers: Vec::new(),
+            timeouts: Timeouts::default(),
+            deadline: None,
         }
     }
 
+    /// per-action timeouts, the deadline (if any) starts counting now
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.deadline = timeouts.deadline.map(|deadline| Instant::now() + deadline);
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// send a custom User-Agent instead of `csv-to-anki/<version>`
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// send an extra header with every request (e.g. an auth token for a reverse proxy)
+    ///
+    /// invalid header names/values are reported when the first request is sent
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// how many ids to send per bulk request (notesInfo, cardsInfo, changeDeck, addTags,
+    /// removeTags, deleteNotes, note updates, forgetCards, relearnCards, setDueDate, unsuspend),
+    /// at least 1
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
     /// check if ankiconnect is available and request permission
     pub fn check_connection(&self) -> Result<(), Box<dyn Error>> {
         let request = AnkiRequest::new("requestPermission", RequestPermissionParams {});
@@ -177,7 +569,7 @@ impl AnkiConnectClient {
 
 
     /// get all deck names
-    pub fn _get_deck_names(&self) -> Result<Vec<String>, Box<dyn Error>> {
+    pub fn deck_names(&self) -> Result<Vec<String>, Box<dyn Error>> {
         let request = AnkiRequest::new("deckNames", _GetDeckNamesParams {});
         let response: AnkiResponse<Vec<String>> = self.send_request(&request)?;
 
@@ -207,6 +599,375 @@ impl AnkiConnectClient {
         Ok(response.result.unwrap_or(0))
     }
 
+    /// create several decks in one `multi` request, returns their ids in the same order
+    pub fn create_decks(&self, deck_names: &[String]) -> Result<Vec<i64>, Box<dyn Error>> {
+        let actions = deck_names.iter()
+            .map(|deck| AnkiRequest::new("createDeck", CreateDeckParams { deck: deck.clone() }))
+            .collect();
+
+        self.multi::<_, i64>(actions)?
+            .into_iter()
+            .zip(deck_names)
+            .map(|(result, deck)| result.map_err(|e| format!("Failed to create deck '{}': {}", deck, e).into()))
+            .collect()
+    }
+
+    /// find card ids matching an anki search query (e.g. `"deck:Japanese::Food"`)
+    pub fn find_cards(&self, query: &str) -> Result<Vec<i64>, Box<dyn Error>> {
+        let request = AnkiRequest::new("findCards", QueryParams { query: query.to_string() });
+        let response: AnkiResponse<Vec<i64>> = self.send_request(&request)?;
+
+        if let Some(error) = response.error {
+            return Err(format!("Failed to find cards: {}", error).into());
+        }
+
+        Ok(response.result.unwrap_or_default())
+    }
+
+    /// find note ids matching an anki search query
+    pub fn find_notes(&self, query: &str) -> Result<Vec<i64>, Box<dyn Error>> {
+        let request = AnkiRequest::new("findNotes", QueryParams { query: query.to_string() });
+        let response: AnkiResponse<Vec<i64>> = self.send_request(&request)?;
+
+        if let Some(error) = response.error {
+            return Err(format!("Failed to find notes: {}", error).into());
+        }
+
+        Ok(response.result.unwrap_or_default())
+    }
+
+    /// overwrite the given fields of an existing note, fields left out keep their value
+    pub fn update_note_fields(&self, id: i64, fields: &NoteFields) -> Result<(), Box<dyn Error>> {
+        let request = AnkiRequest::new("updateNoteFields", UpdateNoteFieldsParams { note: NoteFieldsUpdate { id, fields } });
+        let response: AnkiResponse<serde_json::Value> = self.send_request(&request)?;
+
+        if let Some(error) = response.error {
+            return Err(format!("Failed to update note {}: {}", id, error).into());
+        }
+
+        Ok(())
+    }
+
+    /// `update_note_fields` for many notes at once
+    ///
+    /// anki has no bulk action for this, so the updates go in one `multi` request,
+    /// each with its own result. a lone update is sent on its own
+    pub fn update_notes_fields(&self, updates: &[(i64, &NoteFields)]) -> Result<Vec<Result<(), String>>, Box<dyn Error>> {
+        if let [(id, fields)] = updates {
+            return Ok(vec![self.update_note_fields(*id, fields).map_err(|e| e.to_string())]);
+        }
+
+        collect_chunks(chunked(updates, self.chunk_size, |chunk| {
+            let actions = chunk.iter()
+                .map(|&(id, fields)| AnkiRequest::new("updateNoteFields", UpdateNoteFieldsParams { note: NoteFieldsUpdate { id, fields } }))
+                .collect();
+
+            self.multi(actions)
+        }))
+    }
+
+    /// move cards into a deck (the deck is created if it doesn't exist)
+    pub fn change_deck(&self, cards: &[i64], deck_name: &str) -> Result<(), Box<dyn Error>> {
+        self.send_chunked(cards, "Failed to change deck", |cards| AnkiRequest::new(
+            "changeDeck",
+            ChangeDeckParams { cards: cards.to_vec(), deck: deck_name.to_string() },
+        ))
+    }
+
+    /// add space separated tags to notes
+    pub fn add_tags(&self, notes: &[i64], tags: &str) -> Result<(), Box<dyn Error>> {
+        self.send_chunked(notes, "Failed to add tags", |notes| AnkiRequest::new(
+            "addTags",
+            TagsParams { notes: notes.to_vec(), tags: tags.to_string() },
+        ))
+    }
+
+    /// remove space separated tags from notes
+    pub fn remove_tags(&self, notes: &[i64], tags: &str) -> Result<(), Box<dyn Error>> {
+        self.send_chunked(notes, "Failed to remove tags", |notes| AnkiRequest::new(
+            "removeTags",
+            TagsParams { notes: notes.to_vec(), tags: tags.to_string() },
+        ))
+    }
+
+    /// Move a tag and everything nested under it, returns how many notes were retagged
+    ///
+    /// `topic::food` -> `vocab::food` also turns `topic::food::fruit` into `vocab::food::fruit`,
+    /// tags are matched case-insensitively like anki does
+    pub fn rename_tag(&self, from: &str, into: &str) -> Result<usize, Box<dyn Error>> {
+        if tag_suffix(into, from).is_some() {
+            return Err(format!("Cannot move tag '{}' into itself ('{}')", from, into).into());
+        }
+
+        let note_ids = self.find_notes(&tag_search(from))?;
+
+        let mut renamed: HashMap<String, Vec<i64>> = HashMap::new();
+        let mut old_tags: Vec<String> = Vec::new();
+        let mut retagged: Vec<i64> = Vec::new();
+
+        for note in self.notes_info(&note_ids)? {
+            for tag in &note.tags {
+                let Some(suffix) = tag_suffix(tag, from) else {
+                    continue;
+                };
+
+                renamed.entry(format!("{}{}", into, suffix)).or_default().push(note.note_id);
+                if !old_tags.contains(tag) {
+                    old_tags.push(tag.clone());
+                }
+                if !retagged.contains(&note.note_id) {
+                    retagged.push(note.note_id);
+                }
+            }
+        }
+
+        // add first, removing can't lose anything then if a request fails halfway
+        for (tag, notes) in &renamed {
+            self.add_tags(notes, tag)?;
+        }
+
+        if !old_tags.is_empty() {
+            self.remove_tags(&retagged, &old_tags.join(" "))?;
+        }
+
+        Ok(retagged.len())
+    }
+
+    /// delete decks, anki 2.1.28+ refuses unless cards_too is true (even for empty decks)
+    pub fn delete_decks(&self, deck_names: &[String], cards_too: bool) -> Result<(), Box<dyn Error>> {
+        let request = AnkiRequest::new(
+            "deleteDecks",
+            DeleteDecksParams { decks: deck_names.to_vec(), cards_too },
+        );
+        let response: AnkiResponse<serde_json::Value> = self.send_request(&request)?;
+
+        if let Some(error) = response.error {
+            return Err(format!("Failed to delete decks: {}", error).into());
+        }
+
+        Ok(())
+    }
+
+    /// delete one deck, see `delete_decks`
+    pub fn delete_deck(&self, deck_name: &str, cards_too: bool) -> Result<(), Box<dyn Error>> {
+        self.delete_decks(&[deck_name.to_string()], cards_too)
+    }
+
+    /// delete decks under `parent` (and `parent` itself) that hold no cards
+    ///
+    /// returns the names of the decks removed. AnkiConnect has no action for this, so it's a
+    /// card search per deck. decks outside `parent` are never touched
+    pub fn remove_empty_decks(&self, parent: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut decks: Vec<String> = self.deck_names()?
+            .into_iter()
+            .filter(|deck| deck == parent || deck.starts_with(&format!("{}::", parent)))
+            .collect();
+        decks.sort();
+
+        // a deck search includes subdecks, so an empty deck has no cards below it either
+        let mut empty = Vec::new();
+        for deck in decks {
+            if self.find_cards(&deck_search(&deck))?.is_empty() {
+                empty.push(deck);
+            }
+        }
+
+        // deleting a deck deletes its subdecks, so only the topmost empty ones are sent.
+        // anki refuses deleteDecks without cardsToo, there are no cards to lose here
+        let topmost: Vec<String> = empty.iter()
+            .filter(|deck| !empty.iter().any(|other| deck.starts_with(&format!("{}::", other))))
+            .cloned()
+            .collect();
+
+        if !topmost.is_empty() {
+            self.delete_decks(&topmost, true)?;
+        }
+
+        Ok(empty)
+    }
+
+    /// delete notes and all their cards
+    pub fn delete_notes(&self, notes: &[i64]) -> Result<(), Box<dyn Error>> {
+        self.send_chunked(notes, "Failed to delete notes", |notes| {
+            AnkiRequest::new("deleteNotes", DeleteNotesParams { notes: notes.to_vec() })
+        })
+    }
+
+    /// get a note type's field names, in the order anki displays them
+    pub fn model_field_names(&self, model_name: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        let request = AnkiRequest::new(
+            "modelFieldNames",
+            ModelNameParams { model_name: model_name.to_string() },
+        );
+        let response: AnkiResponse<Vec<String>> = self.send_request(&request)?;
+
+        if let Some(error) = response.error {
+            return Err(format!("Failed to get fields for note type '{}': {}", model_name, error).into());
+        }
+
+        Ok(response.result.unwrap_or_default())
+    }
+
+    /// list media files in the collection matching a glob pattern (`*` for everything)
+    pub fn get_media_files_names(&self, pattern: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        let request = AnkiRequest::new(
+            "getMediaFilesNames",
+            MediaPatternParams { pattern: pattern.to_string() },
+        );
+        let response: AnkiResponse<Vec<String>> = self.send_request(&request)?;
+
+        if let Some(error) = response.error {
+            return Err(format!("Failed to list media files: {}", error).into());
+        }
+
+        Ok(response.result.unwrap_or_default())
+    }
+
+    /// delete a media file from the collection
+    pub fn delete_media_file(&self, filename: &str) -> Result<(), Box<dyn Error>> {
+        let request = AnkiRequest::new(
+            "deleteMediaFile",
+            MediaFileParams { filename: filename.to_string() },
+        );
+        let response: AnkiResponse<serde_json::Value> = self.send_request(&request)?;
+
+        if let Some(error) = response.error {
+            return Err(format!("Failed to delete media file: {}", error).into());
+        }
+
+        Ok(())
+    }
+
+    /// media files matching `pattern` that no note mentions, e.g. left behind by an undone import
+    ///
+    /// AnkiConnect has no action for this, so it's a note search per file. files starting with
+    /// `_` belong to note type templates (anki never counts them as unused) and are skipped
+    pub fn unused_media_files(&self, pattern: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut unused = Vec::new();
+
+        for filename in self.get_media_files_names(pattern)? {
+            if filename.starts_with('_') {
+                continue;
+            }
+
+            if self.find_notes(&format!("\"{}\"", escape_search(&filename)))?.is_empty() {
+                unused.push(filename);
+            }
+        }
+
+        Ok(unused)
+    }
+
+    /// get fields, tags and cards for notes, fetched in chunks and collected
+    pub fn notes_info(&self, notes: &[i64]) -> Result<Vec<NoteInfo>, Box<dyn Error>> {
+        collect_chunks(self.notes_info_chunked(notes))
+    }
+
+    /// get note info one chunk at a time, so huge collections can be processed as they arrive
+    pub fn notes_info_chunked<'a>(&'a self, notes: &'a [i64])
+        -> impl Iterator<Item = Result<Vec<NoteInfo>, Box<dyn Error>>> + 'a
+    {
+        chunked(notes, self.chunk_size, |chunk| self.notes_info_chunk(chunk))
+    }
+
+    fn notes_info_chunk(&self, notes: &[i64]) -> Result<Vec<NoteInfo>, Box<dyn Error>> {
+        let request = AnkiRequest::new("notesInfo", NotesParams { notes: notes.to_vec() });
+        let response: AnkiResponse<Vec<NoteInfo>> = self.send_request(&request)?;
+
+        if let Some(error) = response.error {
+            return Err(format!("Failed to get note info: {}", error).into());
+        }
+
+        Ok(response.result.unwrap_or_default())
+    }
+
+    /// get deck, fields and review info for cards, fetched in chunks and collected
+    pub fn cards_info(&self, cards: &[i64]) -> Result<Vec<CardInfo>, Box<dyn Error>> {
+        collect_chunks(self.cards_info_chunked(cards))
+    }
+
+    /// get card info one chunk at a time
+    pub fn cards_info_chunked<'a>(&'a self, cards: &'a [i64])
+        -> impl Iterator<Item = Result<Vec<CardInfo>, Box<dyn Error>>> + 'a
+    {
+        chunked(cards, self.chunk_size, |chunk| self.cards_info_chunk(chunk))
+    }
+
+    fn cards_info_chunk(&self, cards: &[i64]) -> Result<Vec<CardInfo>, Box<dyn Error>> {
+        let request = AnkiRequest::new("cardsInfo", CardsParams { cards: cards.to_vec() });
+        let response: AnkiResponse<Vec<CardInfo>> = self.send_request(&request)?;
+
+        if let Some(error) = response.error {
+            return Err(format!("Failed to get card info: {}", error).into());
+        }
+
+        Ok(response.result.unwrap_or_default())
+    }
+
+    /// reset cards back to new, forgetting their review history
+    pub fn forget_cards(&self, cards: &[i64]) -> Result<(), Box<dyn Error>> {
+        self.send_chunked(cards, "Failed to reset cards", |cards| {
+            AnkiRequest::new("forgetCards", CardsParams { cards: cards.to_vec() })
+        })
+    }
+
+    /// put cards back into (re)learning, keeping their review history
+    pub fn relearn_cards(&self, cards: &[i64]) -> Result<(), Box<dyn Error>> {
+        self.send_chunked(cards, "Failed to relearn cards", |cards| {
+            AnkiRequest::new("relearnCards", CardsParams { cards: cards.to_vec() })
+        })
+    }
+
+    /// set the due date of cards (`days` in anki's syntax, e.g. `0`, `3`, `1-7`)
+    pub fn set_due_date(&self, cards: &[i64], days: &str) -> Result<(), Box<dyn Error>> {
+        self.send_chunked(cards, "Failed to reschedule cards", |cards| AnkiRequest::new(
+            "setDueDate",
+            SetDueDateParams { cards: cards.to_vec(), days: days.to_string() },
+        ))
+    }
+
+    /// unsuspend cards (anki suspends leeches by default)
+    pub fn unsuspend(&self, cards: &[i64]) -> Result<(), Box<dyn Error>> {
+        self.send_chunked(cards, "Failed to unsuspend cards", |cards| {
+            AnkiRequest::new("unsuspend", CardsParams { cards: cards.to_vec() })
+        })
+    }
+
+    /// export a deck to an .apkg at `path` (absolute, on the machine anki runs on)
+    pub fn export_package(&self, deck_name: &str, path: &str, include_sched: bool) -> Result<(), Box<dyn Error>> {
+        let request = AnkiRequest::new(
+            "exportPackage",
+            ExportPackageParams { deck: deck_name.to_string(), path: path.to_string(), include_sched },
+        );
+        let response: AnkiResponse<bool> = self.send_request(&request)?;
+
+        if let Some(error) = response.error {
+            return Err(format!("Failed to export '{}': {}", deck_name, error).into());
+        }
+
+        if response.result != Some(true) {
+            return Err(format!("Anki did not export '{}' to {}", deck_name, path).into());
+        }
+
+        Ok(())
+    }
+
+    /// import an .apkg at `path` (absolute, on the machine anki runs on)
+    pub fn import_package(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let request = AnkiRequest::new("importPackage", ImportPackageParams { path: path.to_string() });
+        let response: AnkiResponse<bool> = self.send_request(&request)?;
+
+        if let Some(error) = response.error {
+            return Err(format!("Failed to import {}: {}", path, error).into());
+        }
+
+        if response.result != Some(true) {
+            return Err(format!("Anki did not import {}", path).into());
+        }
+
+        Ok(())
+    }
+
     /// Add a single note to anki
     pub fn _add_note(&self, note: Note) -> Result<i64, Box<dyn Error>> {
         let request = AnkiRequest::new(
@@ -229,45 +990,93 @@ impl AnkiConnectClient {
     }
 
 
-    /// Add multiple notes in batch 
-    /// /// Parameters for bulk adding notes
-    // #[derive(Debug, Serialize)]
-    // struct AddNotesParams {
-    //     notes: Vec<Note>
-    // }
-    pub fn add_notes(&self, notes: Vec<Note>) 
+    /// Add multiple notes in one addNotes request, with a result per note in the same order
+    ///
+    /// AnkiConnect rejects the whole batch when any note fails (older versions return `null`
+    /// for that note instead), so a rejected batch is sent again as a `multi` of single
+    /// addNote actions, one round-trip still, to find out which notes failed and why
+    pub fn add_notes(&self, notes: Vec<Note>)
         -> Result<Vec<Result<i64, String>>, Box<dyn Error>>
     {
-        let request: AnkiRequest<AddNotesParams> = AnkiRequest::new(
-            "addNotes", 
-            AddNotesParams { notes },
-        );
-
+        let expected = notes.len();
+        let request = AnkiRequest::new("addNotes", AddNotesParams { notes });
         let response: AnkiResponse<Vec<Option<i64>>> = self.send_request(&request)?;
 
-        // println!("{:?}", &response);
+        let ids = match (response.result, response.error) {
+            (Some(ids), None) => ids,
+            (_, Some(_)) => return self.add_notes_one_by_one(request.params.notes),
+            (None, None) => return Err("AnkiConnect returned no result for addNotes".into()),
+        };
 
-        let results: Vec<Result<i64, String>> = response.result.ok_or("my program isn't working")?
-            .into_iter()
-            .enumerate()
-            .map(|(idx, opt)| match opt {
-                Some(id) => Ok(id),
-                None => Err(format!("Note at index {} could not be created", idx)),
-            })
+        if ids.len() != expected {
+            return Err(format!("Expected {} addNotes results, got {}", expected, ids.len()).into());
+        }
+
+        Ok(ids.into_iter()
+            .map(|id| id.ok_or_else(|| "Note could not be created".to_string()))
+            .collect())
+    }
+
+    /// addNote for every note, batched in one `multi` request
+    fn add_notes_one_by_one(&self, notes: Vec<Note>) -> Result<Vec<Result<i64, String>>, Box<dyn Error>> {
+        let actions = notes.into_iter()
+            .map(|note| AnkiRequest::new("addNote", _AddNoteParams { note }))
             .collect();
 
-        Ok(results)
+        self.multi(actions)
+    }
+
+    /// check which notes anki would accept, with anki's reason for each note it would refuse
+    /// (e.g. `cannot create note because it is a duplicate`)
+    ///
+    /// uses each note's `options`, so duplicate scope settings apply just like in addNotes
+    pub fn can_add_notes_with_error_detail(&self, notes: &[Note]) -> Result<Vec<Result<(), String>>, Box<dyn Error>> {
+        let request = AnkiRequest::new("canAddNotesWithErrorDetail", AddNotesParams { notes: notes.to_vec() });
+        let response: AnkiResponse<Vec<CanAddDetail>> = self.send_request(&request)?;
+
+        if let Some(error) = response.error {
+            return Err(format!("Failed to check notes: {}", error).into());
+        }
+
+        let results = response.result.unwrap_or_default();
+        if results.len() != notes.len() {
+            return Err(format!("Expected {} canAddNotesWithErrorDetail results, got {}", notes.len(), results.len()).into());
+        }
+
+        Ok(results.into_iter().map(CanAddDetail::into_result).collect())
     }
 
     /// send a request to ankiconnect
     fn send_request<T: Serialize, R: for<'de> Deserialize<'de>>(
         &self,
-        request: &T
+        request: &AnkiRequest<T>
     ) -> Result<R, Box<dyn Error>> {
-        let response: reqwest::blocking::Response = self.client
+        let mut timeout = self.timeouts.for_action(&request.action);
+
+        if let Some(deadline) = self.deadline {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(format!("Run deadline passed before '{}' could be sent", request.action).into());
+            }
+            timeout = timeout.min(remaining);
+        }
+
+        let builder = self.client
             .post(&self.base_url)
-            .json(request)
-            .send()?;
+            .header(reqwest::header::USER_AGENT, &self.user_agent)
+            .timeout(timeout)
+            .json(request);
+
+        let response: reqwest::blocking::Response = self.headers.iter()
+            .fold(builder, |builder, (name, value)| builder.header(name.as_str(), value.as_str()))
+            .send()
+            .map_err(|e| -> Box<dyn Error> {
+                if e.is_timeout() {
+                    format!("AnkiConnect didn't answer '{}' within {:.0?}", request.action, timeout).into()
+                } else {
+                    e.into()
+                }
+            })?;
 
         if !response.status().is_success() {
             return Err(format!("HTTP error: {}", response.status()).into());
@@ -276,10 +1085,317 @@ impl AnkiConnectClient {
         let result: R = response.json::<R>()?;
         Ok(result)
     }
+
+    /// run several actions in one request, each action gets its own result or error
+    fn multi<T: Serialize, R: for<'de> Deserialize<'de>>(
+        &self,
+        actions: Vec<AnkiRequest<T>>,
+    ) -> Result<Vec<Result<R, String>>, Box<dyn Error>> {
+        let expected = actions.len();
+        let request = AnkiRequest::new("multi", MultiParams { actions });
+        let response: AnkiResponse<Vec<AnkiResponse<R>>> = self.send_request(&request)?;
+
+        if let Some(error) = response.error {
+            return Err(format!("Failed to run batched actions: {}", error).into());
+        }
+
+        let results = response.result.unwrap_or_default();
+        if results.len() != expected {
+            return Err(format!("Expected {} batched results, got {}", expected, results.len()).into());
+        }
+
+        Ok(results.into_iter()
+            .map(|response| match (response.result, response.error) {
+                (_, Some(error)) => Err(error),
+                (Some(result), None) => Ok(result),
+                // actions like updateNoteFields answer null, fine when that's what R expects
+                (None, None) => serde_json::from_value(serde_json::Value::Null).map_err(|_| "no result".to_string()),
+            })
+            .collect())
+    }
+
+    /// send an action without a result for `ids` one chunk at a time, `failed` starts the error
+    fn send_chunked<T: Serialize>(
+        &self,
+        ids: &[i64],
+        failed: &str,
+        request: impl Fn(&[i64]) -> AnkiRequest<T>,
+    ) -> Result<(), Box<dyn Error>> {
+        collect_chunks(chunked(ids, self.chunk_size, |chunk| {
+            let response: AnkiResponse<serde_json::Value> = self.send_request(&request(chunk))?;
+
+            match response.error {
+                Some(error) => Err(format!("{}: {}", failed, error).into()),
+                None => Ok(Vec::<()>::new()),
+            }
+        }))?;
+
+        Ok(())
+    }
+}
+
+/// an anki search for the cards/notes in `deck` and its subdecks, with `_`, `*` and `"` in the
+/// name taken literally
+pub fn deck_search(deck: &str) -> String {
+    format!("\"deck:{}\"", escape_search(deck))
+}
+
+/// an anki search for the notes tagged `tag` or a tag under it, taken literally like `deck_search`
+pub fn tag_search(tag: &str) -> String {
+    format!("\"tag:{}\"", escape_search(tag))
+}
+
+/// run a bulk query over `ids` one chunk at a time
+fn chunked<'a, T, R>(
+    ids: &'a [T],
+    chunk_size: usize,
+    fetch: impl Fn(&[T]) -> Result<Vec<R>, Box<dyn Error>> + 'a,
+) -> impl Iterator<Item = Result<Vec<R>, Box<dyn Error>>> + 'a {
+    ids.chunks(chunk_size.max(1)).map(fetch)
+}
+
+/// concatenate chunk results, stopping at the first failed chunk
+fn collect_chunks<R>(chunks: impl Iterator<Item = Result<Vec<R>, Box<dyn Error>>>) -> Result<Vec<R>, Box<dyn Error>> {
+    let mut all = Vec::new();
+    for chunk in chunks {
+        all.extend(chunk?);
+    }
+
+    Ok(all)
+}
+
+/// what follows `hierarchy` in `tag` (`""` or `::child...`), if `tag` is it or nested under it
+fn tag_suffix<'a>(tag: &'a str, hierarchy: &str) -> Option<&'a str> {
+    let head = tag.get(..hierarchy.len())?;
+    let suffix = &tag[hierarchy.len()..];
+
+    (head.to_lowercase() == hierarchy.to_lowercase() && (suffix.is_empty() || suffix.starts_with("::")))
+        .then_some(suffix)
 }
 
 impl Default for AnkiConnectClient {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn note(options: OptionFields) -> Note {
+        Note {
+            deck_name: "Default".to_string(),
+            model_name: "Basic".to_string(),
+            fields: NoteFields::new().with("Front", "front content").with("Back", "back content"),
+            tags: vec!["yomichan".to_string()],
+            options: Some(options),
+            audio: None,
+            picture: None,
+        }
+    }
+
+    /// payload from the AnkiConnect `addNote` documentation
+    #[test]
+    fn add_note_matches_recorded_payload() {
+        let options = OptionFields::reject_duplicates(
+            DuplicateScope::Deck,
+            DuplicateScopeOptions {
+                deck_name: Some("Default".to_string()),
+                check_children: false,
+                check_all_models: false,
+            },
+        );
+        let request = AnkiRequest::new("addNote", _AddNoteParams { note: note(options) });
+
+        assert_eq!(serde_json::to_value(&request).unwrap(), json!({
+            "action": "addNote",
+            "version": 6,
+            "params": {
+                "note": {
+                    "deckName": "Default",
+                    "modelName": "Basic",
+                    "fields": {
+                        "Front": "front content",
+                        "Back": "back content"
+                    },
+                    "options": {
+                        "allowDuplicate": false,
+                        "duplicateScope": "deck",
+                        "duplicateScopeOptions": {
+                            "deckName": "Default",
+                            "checkChildren": false,
+                            "checkAllModels": false
+                        }
+                    },
+                    "tags": ["yomichan"]
+                }
+            }
+        }));
+    }
+
+    /// response from the AnkiConnect `canAddNotesWithErrorDetail` documentation
+    #[test]
+    fn can_add_details_keep_the_reason() {
+        let response: AnkiResponse<Vec<CanAddDetail>> = serde_json::from_value(json!({
+            "result": [
+                {"canAdd": false, "error": "cannot create note because it is a duplicate"},
+                {"canAdd": true}
+            ],
+            "error": null
+        })).unwrap();
+
+        let results: Vec<Result<(), String>> = response.result.unwrap().into_iter().map(CanAddDetail::into_result).collect();
+        assert_eq!(results, vec![Err("cannot create note because it is a duplicate".to_string()), Ok(())]);
+    }
+
+    /// payload from the AnkiConnect `updateNoteFields` documentation
+    #[test]
+    fn update_note_fields_matches_recorded_payload() {
+        let fields = NoteFields::new().with("Front", "new front content").with("Back", "new back content");
+        let request = AnkiRequest::new("updateNoteFields", UpdateNoteFieldsParams { note: NoteFieldsUpdate { id: 1514547547030, fields: &fields } });
+
+        assert_eq!(serde_json::to_value(&request).unwrap(), json!({
+            "action": "updateNoteFields",
+            "version": 6,
+            "params": {
+                "note": {
+                    "id": 1514547547030i64,
+                    "fields": {
+                        "Front": "new front content",
+                        "Back": "new back content"
+                    }
+                }
+            }
+        }));
+    }
+
+    /// payloads from the AnkiConnect `getMediaFilesNames` and `deleteMediaFile` documentation
+    #[test]
+    fn media_actions_match_recorded_payloads() {
+        let list = AnkiRequest::new("getMediaFilesNames", MediaPatternParams { pattern: "_hell*.txt".to_string() });
+        let delete = AnkiRequest::new("deleteMediaFile", MediaFileParams { filename: "_hello.txt".to_string() });
+
+        assert_eq!(serde_json::to_value(&list).unwrap(), json!({
+            "action": "getMediaFilesNames",
+            "version": 6,
+            "params": {
+                "pattern": "_hell*.txt"
+            }
+        }));
+        assert_eq!(serde_json::to_value(&delete).unwrap(), json!({
+            "action": "deleteMediaFile",
+            "version": 6,
+            "params": {
+                "filename": "_hello.txt"
+            }
+        }));
+    }
+
+    #[test]
+    fn bulk_changes_get_the_heavy_timeout() {
+        let timeouts = Timeouts::default();
+
+        for action in [
+            "addNotes", "canAddNotesWithErrorDetail", "updateNoteFields", "deleteNotes", "deleteDecks", "changeDeck",
+            "addTags", "removeTags", "forgetCards", "relearnCards", "setDueDate", "unsuspend",
+        ] {
+            assert_eq!(timeouts.for_action(action), timeouts.heavy, "{}", action);
+        }
+        assert_eq!(timeouts.for_action("findNotes"), timeouts.quick);
+    }
+
+    #[test]
+    fn bulk_queries_are_split_into_chunks() {
+        let ids: Vec<i64> = (1..=7).collect();
+        let sizes: Vec<usize> = chunked(&ids, 3, |chunk| Ok(chunk.to_vec()))
+            .map(|chunk| chunk.unwrap().len())
+            .collect();
+
+        assert_eq!(sizes, vec![3, 3, 1]);
+        assert_eq!(collect_chunks(chunked(&ids, 3, |chunk| Ok(chunk.to_vec()))).unwrap(), ids);
+    }
+
+    /// payload from the AnkiConnect `multi` documentation
+    #[test]
+    fn multi_wraps_versioned_actions() {
+        let request = AnkiRequest::new("multi", MultiParams {
+            actions: vec![AnkiRequest::new("createDeck", CreateDeckParams { deck: "Japanese::Food".to_string() })],
+        });
+
+        assert_eq!(serde_json::to_value(&request).unwrap(), json!({
+            "action": "multi",
+            "version": 6,
+            "params": {
+                "actions": [
+                    { "action": "createDeck", "version": 6, "params": { "deck": "Japanese::Food" } }
+                ]
+            }
+        }));
+    }
+
+    #[test]
+    fn allow_duplicates_omits_deck_name() {
+        let value = serde_json::to_value(OptionFields::allow_duplicates()).unwrap();
+
+        assert_eq!(value, json!({
+            "allowDuplicate": true,
+            "duplicateScope": "deck",
+            "duplicateScopeOptions": {
+                "checkChildren": false,
+                "checkAllModels": false
+            }
+        }));
+    }
+
+    /// what the importer sends by default, a re-import must not add the same notes again
+    #[test]
+    fn reject_duplicates_in_deck_checks_only_that_deck() {
+        let value = serde_json::to_value(OptionFields::reject_duplicates_in_deck("Japanese::Food")).unwrap();
+
+        assert_eq!(value, json!({
+            "allowDuplicate": false,
+            "duplicateScope": "deck",
+            "duplicateScopeOptions": {
+                "deckName": "Japanese::Food",
+                "checkChildren": false,
+                "checkAllModels": false
+            }
+        }));
+    }
+
+    #[test]
+    fn collection_scope_serialises_lowercase() {
+        let options = OptionFields::reject_duplicates(DuplicateScope::Collection, DuplicateScopeOptions::default());
+
+        assert_eq!(serde_json::to_value(options).unwrap()["duplicateScope"], "collection");
+    }
+
+    #[test]
+    fn fields_serialise_in_model_order() {
+        let fields = NoteFields::new().with("Back", "b").with("Front", "f");
+        let model_fields = vec!["Front".to_string(), "Back".to_string(), "Extra".to_string()];
+
+        let ordered = fields.in_model_order("Basic", &model_fields).unwrap();
+
+        assert_eq!(serde_json::to_string(&ordered).unwrap(), r#"{"Front":"f","Back":"b","Extra":""}"#);
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        let fields = NoteFields::new().with("Front", "f").with("Category", "food");
+        let model_fields = vec!["Front".to_string(), "Back".to_string()];
+
+        assert!(fields.in_model_order("Basic", &model_fields).is_err());
+    }
+
+    #[test]
+    fn tag_suffix_matches_whole_hierarchy_levels() {
+        assert_eq!(tag_suffix("topic::food", "topic::food"), Some(""));
+        assert_eq!(tag_suffix("Topic::Food::fruit", "topic::food"), Some("::fruit"));
+        assert_eq!(tag_suffix("topic::seafood", "topic::food"), None);
+        assert_eq!(tag_suffix("topic::foods", "topic::food"), None);
+        assert_eq!(tag_suffix("topic", "topic::food"), None);
+    }
+}
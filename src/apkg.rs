@@ -0,0 +1,456 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    env,
+    error::Error,
+    fs::{self, File},
+    io::Write,
+    path::Path,
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rusqlite::{params, Connection};
+use serde_json::{json, Value};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::anki::Note;
+
+// ============================================================================================
+//                                      .apkg Export
+// ============================================================================================
+
+// An .apkg is a zip holding `collection.anki2`, an anki sqlite collection (schema 11, which
+// every anki version still imports), and `media`, a json map of the media files in the zip.
+// The collection gets one note type built from the notes' fields, a deck per subdeck the
+// notes go in (and their parents), and a new card per note.
+//
+// Note guids come from the main deck, subdeck and front, so importing a newer file for the
+// same deck updates the notes anki already has instead of adding them twice.
+
+/// schema version anki writes for the legacy collection format
+const SCHEMA_VERSION: i64 = 11;
+
+/// anki separates note fields with the unit separator
+const FIELD_SEPARATOR: &str = "\x1f";
+
+/// characters of anki's base91 guids
+const GUID_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789!#$%&()*+,-./:;<=>?@[]^_`{|}~";
+
+const SCHEMA: &str = "
+CREATE TABLE col (
+    id integer primary key, crt integer not null, mod integer not null, scm integer not null,
+    ver integer not null, dty integer not null, usn integer not null, ls integer not null,
+    conf text not null, models text not null, decks text not null, dconf text not null, tags text not null
+);
+CREATE TABLE notes (
+    id integer primary key, guid text not null, mid integer not null, mod integer not null,
+    usn integer not null, tags text not null, flds text not null, sfld integer not null,
+    csum integer not null, flags integer not null, data text not null
+);
+CREATE TABLE cards (
+    id integer primary key, nid integer not null, did integer not null, ord integer not null,
+    mod integer not null, usn integer not null, type integer not null, queue integer not null,
+    due integer not null, ivl integer not null, factor integer not null, reps integer not null,
+    lapses integer not null, left integer not null, odue integer not null, odid integer not null,
+    flags integer not null, data text not null
+);
+CREATE TABLE revlog (
+    id integer primary key, cid integer not null, usn integer not null, ease integer not null,
+    ivl integer not null, lastIvl integer not null, factor integer not null, time integer not null,
+    type integer not null
+);
+CREATE TABLE graves (usn integer not null, oid integer not null, type integer not null);
+CREATE INDEX ix_notes_usn on notes (usn);
+CREATE INDEX ix_cards_usn on cards (usn);
+CREATE INDEX ix_revlog_usn on revlog (usn);
+CREATE INDEX ix_cards_nid on cards (nid);
+CREATE INDEX ix_cards_sched on cards (did, queue, due);
+CREATE INDEX ix_revlog_cid on revlog (cid);
+CREATE INDEX ix_notes_csum on notes (csum);
+";
+
+/// Write `notes` (as built by `word_to_note`) into an .apkg at `path`
+///
+/// `main_deck` is the deck the notes belong to, their subdecks are created under it.
+/// Returns the number of notes written.
+pub fn write_apkg(path: &Path, main_deck: &str, model_name: &str, notes: &[Note]) -> Result<usize, Box<dyn Error>> {
+    if notes.is_empty() {
+        return Err("Nothing to export, no notes were built from the input".into());
+    }
+
+    let collection = env::temp_dir().join(format!("csv-to-anki-{}-{}.anki2", process::id(), unix_millis()));
+    let written = write_collection(&collection, main_deck, model_name, notes)
+        .and_then(|written| zip_package(path, &collection).map(|()| written));
+
+    // the collection was only needed to fill the zip
+    let _ = fs::remove_file(&collection);
+
+    written
+}
+
+fn write_collection(path: &Path, main_deck: &str, model_name: &str, notes: &[Note]) -> Result<usize, Box<dyn Error>> {
+    let now = unix_millis();
+    let field_names = field_names(notes);
+    let model_id = model_id(model_name, &field_names);
+    let decks = deck_ids(notes, now);
+
+    let conn = Connection::open(path)?;
+    conn.execute_batch(SCHEMA)?;
+
+    conn.execute(
+        "INSERT INTO col VALUES (1, ?1, ?2, ?2, ?3, 0, 0, 0, ?4, ?5, ?6, ?7, '{}')",
+        params![
+            now / 1000,
+            now,
+            SCHEMA_VERSION,
+            collection_conf(model_id).to_string(),
+            json!({ model_id.to_string(): model_json(model_id, model_name, &field_names, now) }).to_string(),
+            decks_json(&decks, now).to_string(),
+            json!({ "1": deck_conf() }).to_string(),
+        ],
+    )?;
+
+    let mut guids = HashSet::new();
+    for (position, note) in notes.iter().enumerate() {
+        let id = now + position as i64;
+        let fields = note.fields.in_model_order(model_name, &field_names)?;
+        let values: Vec<&str> = fields.iter().map(|(_, value)| value).collect();
+
+        let sort_field = strip_html(values[0]);
+        let guid = unique_guid(&mut guids, main_deck, &note.deck_name, &sort_field);
+
+        conn.execute(
+            "INSERT INTO notes VALUES (?1, ?2, ?3, ?4, -1, ?5, ?6, ?7, ?8, 0, '')",
+            params![id, guid, model_id, now / 1000, tag_string(&note.tags), values.join(FIELD_SEPARATOR), sort_field, checksum(&sort_field)],
+        )?;
+
+        // a new card, due in file order
+        conn.execute(
+            "INSERT INTO cards VALUES (?1, ?1, ?2, 0, ?3, -1, 0, 0, ?4, 0, 0, 0, 0, 0, 0, 0, 0, '')",
+            params![id, decks[&note.deck_name], now / 1000, position as i64 + 1],
+        )?;
+    }
+
+    conn.close().map_err(|(_, e)| e)?;
+
+    Ok(notes.len())
+}
+
+fn zip_package(path: &Path, collection: &Path) -> Result<(), Box<dyn Error>> {
+    let file = File::create(path)
+        .map_err(|e| format!("Could not create {}: {}", path.display(), e))?;
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut zip = ZipWriter::new(file);
+    zip.start_file("collection.anki2", options)?;
+    zip.write_all(&fs::read(collection)?)?;
+    zip.start_file("media", options)?;
+    zip.write_all(b"{}")?;
+    zip.finish()?;
+
+    Ok(())
+}
+
+/// every field name the notes use, in the order they first appear (front and back first)
+fn field_names(notes: &[Note]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+
+    for (name, _) in notes.iter().flat_map(|note| note.fields.iter()) {
+        if !names.iter().any(|existing| existing == name) {
+            names.push(name.to_string());
+        }
+    }
+
+    names
+}
+
+/// deck ids by name, for the notes' decks and every deck above them
+///
+/// anki matches imported decks by name, so the ids only have to be unique in the file
+fn deck_ids(notes: &[Note], now: i64) -> BTreeMap<String, i64> {
+    let mut decks = BTreeMap::new();
+
+    for note in notes {
+        let parts: Vec<&str> = note.deck_name.split("::").collect();
+        for depth in 1..=parts.len() {
+            decks.entry(parts[..depth].join("::")).or_insert(0);
+        }
+    }
+
+    for (position, id) in decks.values_mut().enumerate() {
+        *id = now + position as i64;
+    }
+
+    decks
+}
+
+/// stable for a note type name and its fields, so anki reuses the note type on a later import
+fn model_id(model_name: &str, field_names: &[String]) -> i64 {
+    let digest = sha1_smol::Sha1::from(format!("{}{}{}", model_name, FIELD_SEPARATOR, field_names.join(FIELD_SEPARATOR))).digest().bytes();
+    let id = i64::from_be_bytes(digest[..8].try_into().expect("a sha1 digest has 20 bytes"));
+
+    // anki ids are millisecond timestamps, keep it in that range
+    1_000_000_000_000 + id.rem_euclid(1_000_000_000_000)
+}
+
+/// a guid for the note, with a counter added when the file has the same front twice
+fn unique_guid(seen: &mut HashSet<String>, main_deck: &str, deck: &str, front: &str) -> String {
+    let mut repeat = 0;
+
+    loop {
+        let key = format!("{}{sep}{}{sep}{}{sep}{}", main_deck, deck, front, repeat, sep = FIELD_SEPARATOR);
+        let guid = base91(u64::from_be_bytes(sha1_smol::Sha1::from(key).digest().bytes()[..8].try_into().expect("a sha1 digest has 20 bytes")));
+
+        if seen.insert(guid.clone()) {
+            return guid;
+        }
+        repeat += 1;
+    }
+}
+
+fn base91(mut value: u64) -> String {
+    let mut chars = Vec::new();
+
+    loop {
+        chars.push(GUID_CHARS[(value % GUID_CHARS.len() as u64) as usize]);
+        value /= GUID_CHARS.len() as u64;
+        if value == 0 {
+            break;
+        }
+    }
+
+    chars.reverse();
+    String::from_utf8(chars).expect("guid characters are ascii")
+}
+
+/// anki's duplicate check: the first 8 hex digits of the sha1 of the stripped sort field
+fn checksum(sort_field: &str) -> i64 {
+    let digest = sha1_smol::Sha1::from(sort_field).digest().bytes();
+    i64::from(u32::from_be_bytes(digest[..4].try_into().expect("a sha1 digest has 20 bytes")))
+}
+
+/// text of a field without its html tags, what anki sorts and checks duplicates by
+fn strip_html(field: &str) -> String {
+    let mut text = String::with_capacity(field.len());
+    let mut in_tag = false;
+
+    for c in field.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {},
+        }
+    }
+
+    text
+}
+
+/// anki keeps tags space separated with a space at either end
+fn tag_string(tags: &[String]) -> String {
+    if tags.is_empty() {
+        return String::new();
+    }
+
+    format!(" {} ", tags.join(" "))
+}
+
+fn model_json(id: i64, name: &str, field_names: &[String], now: i64) -> Value {
+    let fields: Vec<Value> = field_names.iter().enumerate()
+        .map(|(ord, name)| json!({
+            "name": name, "ord": ord, "font": "Arial", "size": 20,
+            "media": [], "rtl": false, "sticky": false,
+        }))
+        .collect();
+
+    let back = field_names.get(1).map(String::as_str).unwrap_or(&field_names[0]);
+
+    json!({
+        "id": id,
+        "name": name,
+        "type": 0,
+        "mod": now / 1000,
+        "usn": -1,
+        "sortf": 0,
+        "did": 1,
+        "flds": fields,
+        "tmpls": [{
+            "name": "Card 1",
+            "ord": 0,
+            "qfmt": format!("{{{{{}}}}}", field_names[0]),
+            "afmt": format!("{{{{FrontSide}}}}\n\n<hr id=answer>\n\n{{{{{}}}}}", back),
+            "bqfmt": "",
+            "bafmt": "",
+            "did": null,
+        }],
+        "css": ".card {\n font-family: arial;\n font-size: 20px;\n text-align: center;\n color: black;\n background-color: white;\n}\n",
+        "latexPre": "\\documentclass[12pt]{article}\n\\special{papersize=3in,5in}\n\\usepackage[utf8]{inputenc}\n\\usepackage{amssymb,amsmath}\n\\pagestyle{empty}\n\\setlength{\\parindent}{0in}\n\\begin{document}\n",
+        "latexPost": "\\end{document}",
+        "latexsvg": false,
+        "req": [[0, "any", [0]]],
+        "tags": [],
+        "vers": [],
+    })
+}
+
+fn decks_json(decks: &BTreeMap<String, i64>, now: i64) -> Value {
+    let mut json = serde_json::Map::new();
+    json.insert("1".to_string(), deck_json(1, "Default", now));
+
+    for (name, id) in decks {
+        json.insert(id.to_string(), deck_json(*id, name, now));
+    }
+
+    Value::Object(json)
+}
+
+fn deck_json(id: i64, name: &str, now: i64) -> Value {
+    json!({
+        "id": id,
+        "name": name,
+        "mod": now / 1000,
+        "usn": -1,
+        "desc": "",
+        "dyn": 0,
+        "conf": 1,
+        "collapsed": false,
+        "extendNew": 10,
+        "extendRev": 50,
+        "newToday": [0, 0],
+        "revToday": [0, 0],
+        "lrnToday": [0, 0],
+        "timeToday": [0, 0],
+    })
+}
+
+/// anki's default deck options
+fn deck_conf() -> Value {
+    json!({
+        "id": 1,
+        "name": "Default",
+        "mod": 0,
+        "usn": 0,
+        "dyn": false,
+        "maxTaken": 60,
+        "timer": 0,
+        "autoplay": true,
+        "replayq": true,
+        "new": {
+            "delays": [1.0, 10.0], "ints": [1, 4, 7], "initialFactor": 2500,
+            "order": 1, "perDay": 20, "bury": true, "separate": true,
+        },
+        "rev": {
+            "perDay": 200, "ease4": 1.3, "fuzz": 0.05, "ivlFct": 1.0,
+            "maxIvl": 36500, "minSpace": 1, "bury": true,
+        },
+        "lapse": {
+            "delays": [10.0], "mult": 0.0, "minInt": 1, "leechFails": 8, "leechAction": 0,
+        },
+    })
+}
+
+fn collection_conf(model_id: i64) -> Value {
+    json!({
+        "activeDecks": [1],
+        "curDeck": 1,
+        "curModel": model_id.to_string(),
+        "newSpread": 0,
+        "collapseTime": 1200,
+        "timeLim": 0,
+        "estTimes": true,
+        "dueCounts": true,
+        "nextPos": 1,
+        "sortType": "noteFld",
+        "sortBackwards": false,
+        "addToCur": true,
+    })
+}
+
+fn unix_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+    use crate::anki::NoteFields;
+
+    fn note(deck: &str, front: &str, back: &str) -> Note {
+        Note {
+            deck_name: deck.to_string(),
+            model_name: "Basic".to_string(),
+            fields: NoteFields::new().with("Front", front).with("Back", back),
+            tags: vec!["Food".to_string(), "japanese".to_string()],
+            options: None,
+            audio: None,
+            picture: None,
+        }
+    }
+
+    #[test]
+    fn packages_notes_into_topic_subdecks() {
+        let dir = env::temp_dir().join(format!("csv-to-anki-apkg-test-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("deck.apkg");
+
+        let mut with_join = note("Japanese::Verbs", "食べる", "to eat");
+        with_join.fields.set("Example", "<b>パンを</b>食べる");
+        let notes = vec![
+            note("Japanese::Food", "<b>林檎</b>", "apple"),
+            with_join,
+            note("Japanese::Food", "<b>林檎</b>", "apple (again)"),
+        ];
+        assert_eq!(write_apkg(&path, "Japanese", "Basic", &notes).unwrap(), 3);
+
+        let mut zip = zip::ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let mut media = String::new();
+        zip.by_name("media").unwrap().read_to_string(&mut media).unwrap();
+        assert_eq!(media, "{}");
+
+        let collection = dir.join("collection.anki2");
+        std::io::copy(&mut zip.by_name("collection.anki2").unwrap(), &mut File::create(&collection).unwrap()).unwrap();
+        let conn = Connection::open(&collection).unwrap();
+
+        let decks: String = conn.query_row("SELECT decks FROM col", [], |row| row.get(0)).unwrap();
+        let mut deck_names: Vec<String> = serde_json::from_str::<Value>(&decks).unwrap()
+            .as_object().unwrap()
+            .values()
+            .map(|deck| deck["name"].as_str().unwrap().to_string())
+            .collect();
+        deck_names.sort();
+        assert_eq!(deck_names, vec!["Default", "Japanese", "Japanese::Food", "Japanese::Verbs"]);
+
+        let models: String = conn.query_row("SELECT models FROM col", [], |row| row.get(0)).unwrap();
+        let models: Value = serde_json::from_str(&models).unwrap();
+        let model = models.as_object().unwrap().values().next().unwrap();
+        let fields: Vec<&str> = model["flds"].as_array().unwrap().iter().map(|f| f["name"].as_str().unwrap()).collect();
+        assert_eq!(fields, vec!["Front", "Back", "Example"]);
+
+        let rows: Vec<(String, String, String, String)> = conn
+            .prepare("SELECT n.flds, n.sfld, n.tags, n.guid FROM notes n JOIN cards c ON c.nid = n.id ORDER BY c.due").unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))).unwrap()
+            .collect::<Result<_, _>>().unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(rows[0].0, "<b>林檎</b>\x1fapple\x1f");
+        assert_eq!(rows[1].0, "食べる\x1fto eat\x1f<b>パンを</b>食べる");
+        assert_eq!((rows[0].1.as_str(), rows[0].2.as_str()), ("林檎", " Food japanese "));
+        // the same front twice still gets two notes
+        assert_ne!(rows[0].3, rows[2].3);
+    }
+
+    #[test]
+    fn guids_are_stable_between_exports() {
+        let guid = |deck: &str| unique_guid(&mut HashSet::new(), "Japanese", deck, "林檎");
+
+        assert_eq!(guid("Japanese::Food"), guid("Japanese::Food"));
+        assert_ne!(guid("Japanese::Food"), guid("Japanese::Fruit"));
+        assert_eq!(checksum("林檎"), checksum("林檎"));
+    }
+}
@@ -0,0 +1,45 @@
+use std::{
+    env,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::anki::AnkiConnectClient;
+use crate::state::{file_stem, unix_now};
+
+// ============================================================================================
+//                                          Backups
+// ============================================================================================
+
+// Destructive commands (merge, leech reset/reschedule, ...) export the affected deck
+// as an .apkg first. AnkiConnect writes the file from inside Anki, so the path has to
+// be absolute and on the machine Anki runs on (the same one for a local install).
+
+/// `~/csv-to-anki-backups`, or the temp dir if there's no home directory
+pub fn backup_dir() -> PathBuf {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+        .unwrap_or_else(env::temp_dir)
+        .join("csv-to-anki-backups")
+}
+
+/// export `deck_name` (with scheduling) to a timestamped .apkg in the backup directory
+pub fn backup_deck(client: &AnkiConnectClient, deck_name: &str) -> Result<PathBuf, Box<dyn Error>> {
+    let dir = backup_dir();
+    fs::create_dir_all(&dir)?;
+
+    let path = dir.join(format!("{}-{}.apkg", file_stem(deck_name), unix_now()));
+    client.export_package(deck_name, &path.display().to_string(), true)?;
+
+    Ok(path)
+}
+
+/// import a backup made by `backup_deck` back into anki
+pub fn restore(client: &AnkiConnectClient, path: &Path) -> Result<(), Box<dyn Error>> {
+    let path = path.canonicalize()
+        .map_err(|e| format!("Cannot find backup '{}': {}", path.display(), e))?;
+
+    client.import_package(&path.display().to_string())
+}
@@ -0,0 +1,172 @@
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::parse::Word;
+use crate::sanitize::{unescape, HtmlPolicy};
+use crate::tags::TagTemplate;
+
+// ============================================================================================
+//                                      Card Composition
+// ============================================================================================
+
+// What a word looks like once it's a card: which deck it lands in, the text on each side
+// and its tags. The importer wraps this in an anki note (model, field names, duplicate
+// options), the browser preview shows it as is, so both always agree.
+
+/// What goes on the front of each card
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FrontStrategy {
+    /// front: kanji if present, else kana | back: kana + english (or just english)
+    #[default]
+    KanjiPreferred,
+
+    /// front: kana | back: kanji + english (or just english), for beginners
+    KanaAlways,
+
+    /// front: anki furigana syntax `漢字[かんじ]` | back: english
+    /// (needs a `{{furigana:Front}}` card template to render the reading above the kanji)
+    KanjiWithFurigana,
+
+    /// front: english | back: kanji + kana (or just kana), for production practice
+    EnglishFirst,
+}
+
+impl FrontStrategy {
+    pub const NAMES: &'static [&'static str] = &["kanji", "kana", "furigana", "english"];
+
+    /// (front, back) text for a word
+    pub fn sides(&self, word: &Word) -> (String, String) {
+        let (kana, english, kanji) = (word.japanese(), word.english(), word.kanji());
+        let has_kanji = !kanji.trim().is_empty();
+
+        match self {
+            FrontStrategy::KanjiPreferred if has_kanji => (kanji.clone(), format!("{} | {}", kana, english)),
+            FrontStrategy::KanjiPreferred => (kana.clone(), english.clone()),
+
+            FrontStrategy::KanaAlways if has_kanji => (kana.clone(), format!("{} | {}", kanji, english)),
+            FrontStrategy::KanaAlways => (kana.clone(), english.clone()),
+
+            FrontStrategy::KanjiWithFurigana if has_kanji => (format!("{}[{}]", kanji, kana), english.clone()),
+            FrontStrategy::KanjiWithFurigana => (kana.clone(), english.clone()),
+
+            FrontStrategy::EnglishFirst if has_kanji => (english.clone(), format!("{} | {}", kanji, kana)),
+            FrontStrategy::EnglishFirst => (english.clone(), kana.clone()),
+        }
+    }
+
+    /// reverse of `sides`, recover a word from the text of a card made with this strategy
+    ///
+    /// the sides are field html, escaped entities (`&lt;`, `&amp;`, ...) are turned back into text
+    pub fn word_from_sides(&self, front: &str, back: &str) -> Word {
+        let (front, back) = (unescape(front), unescape(back));
+        let (front, back) = (front.trim(), back.trim());
+        let split_back = back.split_once(" | ").map(|(a, b)| (a.trim(), b.trim()));
+
+        match (self, split_back) {
+            (FrontStrategy::KanjiPreferred, Some((kana, english))) => Word::new(kana, english, front),
+            (FrontStrategy::KanaAlways, Some((kanji, english))) => Word::new(front, english, kanji),
+            (FrontStrategy::EnglishFirst, Some((kanji, kana))) => Word::new(kana, front, kanji),
+            (FrontStrategy::EnglishFirst, None) => Word::new(back, front, ""),
+
+            (FrontStrategy::KanjiWithFurigana, _) => match front.strip_suffix(']').and_then(|f| f.split_once('[')) {
+                Some((kanji, kana)) => Word::new(kana, back, kanji),
+                None => Word::new(front, back, ""),
+            },
+
+            (_, None) => Word::new(front, back, ""),
+        }
+    }
+}
+
+impl FromStr for FrontStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "kanji" => Ok(FrontStrategy::KanjiPreferred),
+            "kana" => Ok(FrontStrategy::KanaAlways),
+            "furigana" => Ok(FrontStrategy::KanjiWithFurigana),
+            "english" => Ok(FrontStrategy::EnglishFirst),
+            other => Err(format!(
+                "Unknown front strategy '{}', expected one of: {}", other, FrontStrategy::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// A word as a card, everything about a note except anki's model and field names
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Card {
+    pub deck: String,
+    pub front: String,
+    pub back: String,
+    pub tags: Vec<String>,
+}
+
+/// How words of a deck become cards
+#[derive(Debug, Clone, Copy)]
+pub struct CardStyle<'a> {
+    pub deck_name: &'a str,
+    pub front_strategy: FrontStrategy,
+    pub flat_deck: bool,
+    pub tags: &'a TagTemplate,
+    pub html: &'a HtmlPolicy,
+}
+
+impl CardStyle<'_> {
+    /// deck a card of `topic` goes in, the topic subdeck unless the deck is flat
+    pub fn deck_for(&self, topic: &str) -> String {
+        if topic.is_empty() || self.flat_deck {
+            self.deck_name.to_string()
+        } else {
+            format!("{}::{}", self.deck_name, topic)
+        }
+    }
+
+    /// the card for a word of `topic`
+    ///
+    /// front and back are html, made from the word by the `html` policy
+    ///
+    /// errors if the front would be empty, anki can't show a blank question
+    pub fn card(&self, word: &Word, topic: &str) -> Result<Card, String> {
+        let (front, back) = self.front_strategy.sides(word);
+
+        if front.trim().is_empty() {
+            return Err(format!(
+                "Empty front in topic '{}' ({:?} front for '{}' / '{}')",
+                topic, self.front_strategy, word.japanese(), word.english()
+            ));
+        }
+
+        Ok(Card {
+            deck: self.deck_for(topic),
+            front: self.html.apply(&front),
+            back: self.html.apply(&back),
+            tags: self.tags.render(topic, self.deck_name),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escaped_cards_give_back_the_word() {
+        let tags = TagTemplate::default();
+        let html = HtmlPolicy::Escape;
+        let word = Word::new("あと", "after & <then>", "後\"");
+
+        for front_strategy in [
+            FrontStrategy::KanjiPreferred, FrontStrategy::KanaAlways,
+            FrontStrategy::KanjiWithFurigana, FrontStrategy::EnglishFirst,
+        ] {
+            let style = CardStyle { deck_name: "Japanese", front_strategy, flat_deck: false, tags: &tags, html: &html };
+            let card = style.card(&word, "Time").unwrap();
+
+            assert!(card.back.contains("&amp;") || card.front.contains("&amp;"), "{:?}", card);
+            assert_eq!(front_strategy.word_from_sides(&card.front, &card.back), word, "{:?}", front_strategy);
+        }
+    }
+}
@@ -0,0 +1,882 @@
+use std::{collections::HashMap, error::Error, ops::Range, path::Path, time::Duration};
+
+use csv_partitioner::{sniff_format, HeaderMatching, ParseConfig, SampleStrategy, SNIFF_BYTES};
+use encoding_rs::Encoding;
+
+use crate::anki::{AnkiConnectClient, DuplicateScope, Timeouts, DEFAULT_CHUNK_SIZE, DEFAULT_USER_AGENT};
+use crate::config::{negated_switch, ResolvedConfig};
+use crate::diff::DiffFormat;
+use crate::encoding;
+use crate::join::JoinOptions;
+use crate::output::OutputStyle;
+use crate::parse::TopicOrder;
+use crate::preflight::Limits;
+use crate::sheet::SheetFormat;
+use crate::tags::{TagTemplate, DEFAULT_TAG_TEMPLATE};
+use crate::card::FrontStrategy;
+use crate::roles::ColumnRole;
+use crate::sanitize::HtmlPolicy;
+use crate::vocab_importer::{LeechAction, SchedulingReset};
+
+// ============================================================================================
+//                                      Command Line
+// ============================================================================================
+
+pub const USAGE: &str = "USAGE:
+  [path to input] [desired deck name] [--output PATH.apkg] [input options] [note options]
+                         --output writes the deck to an .apkg to open in anki, without AnkiConnect
+  merge [deck name] [from topic] [into topic] [--tags TEMPLATE]
+  prune [path to input] [deck name] [input options] [note options]
+                         delete notes whose row is gone from the input, and the subdecks left empty
+  rename-tag [from tag] [into tag]
+  clean-media [PATTERN] [--yes]  list media no note uses (e.g. '*.mp3', default: all), --yes deletes them
+  render [path to input] [--topic NAME] [--rows 1-5] [--sample N] [--sample-strategy head|random|stride]
+         [--deck NAME] [input options] [note options]
+  scaffold [path to word list or flat .csv] [--output PATH] [--roles ROLE,ROLE,...]
+  schema [path to input] [input options]
+  diff-csv [old csv] [new csv] [--format text|json] [--output PATH] [input options]
+  export [path to input] [--format html] [--output PATH] [--title TEXT] [input options] [--conjugations]
+  pause [deck name] | resume [deck name] | status [deck name]
+  state doctor [--repair]  check the files imports keep between runs, --repair fixes them
+  leeches [deck name] [--output PATH] [--reset | --reschedule DAYS] [--front STRATEGY]
+  restore [path to .apkg backup]
+  gui
+
+config (any command):
+  --config PATH          read options from PATH instead of ./csv-to-anki.conf or ~/csv-to-anki.conf
+  --show-config          print every option, its value and where it came from, then exit
+                         precedence: defaults < config file < CSV_TO_ANKI_* env vars < command line
+  --no-SWITCH            turn off a switch the config file or environment turned on (e.g. --no-flat-deck)
+
+output options (any command):
+  --no-color             never colour output (also honours NO_COLOR)
+  --ascii                plain ascii status symbols instead of unicode glyphs
+
+connection options (any command):
+  --timeout SECS         give up on quick AnkiConnect actions (lookups, small edits) after SECS (default: 10)
+  --heavy-timeout SECS   same for adding, updating, moving, tagging or deleting notes, rescheduling cards,
+                         media, packages and sync (default: 120)
+  --deadline SECS        give up on the whole run after SECS, however far it got (default: none)
+  --chunk-size N         ids sent per bulk AnkiConnect request, lower it if big decks time out (default: 500)
+  --user-agent TEXT      User-Agent sent to AnkiConnect (default: csv-to-anki/<version>)
+  --header NAME:VALUE    extra header sent with every request, e.g. a token for a reverse proxy,
+                         repeat it for more than one
+
+import limits (imports only):
+  --max-notes N          refuse to add more than N notes without --yes (default: 5000, 'none' for no limit)
+  --max-media-mb N       same for the media files notes reference, in MB (default: 200)
+  --yes                  go ahead even when the import is over a limit
+
+input options:
+                         [path to input] can be a google sheets link shared as 'anyone with the link'
+  --keep-empty-rows      import blank rows (empty fronts are reported as errors)
+  --exact-headers        match topic names byte-for-byte
+  --max-topic-size N     split bigger topics into numbered subdecks
+  --topic-order ORDER    csv (default) | alphabetical | words (biggest first)
+  --number-topics        prefix subdecks with their position (01 Food) so anki keeps the order
+  --fix-columns          swap columns back in topics where they look mixed up (see the warnings)
+  --slice-gap N          number of empty separator columns between topics (default: 0)
+  --skip-marker TEXT     leave out rows where a cell of the topic holds exactly TEXT (e.g. !skip)
+  --encoding NAME        read the file as NAME instead of utf-8 (e.g. shift_jis for japanese excel)
+  --delimiter CHAR       field separator, e.g. ';' or tab (default: tab for .tsv files, sniffed otherwise)
+  --join PATH            add the columns of another csv to matching words, as note fields named
+                         after its headers (its first column holds the key, e.g. the kana)
+  --join-key KEY         kana (default) | kanji | english, what the first column is matched against
+  --join-type TYPE       left (default, words without a row are imported as is) | inner (left out)
+
+note options:
+  --model NAME           note type to create (default: Basic)
+  --front-field NAME     field that receives the front text (default: Front)
+  --back-field NAME      field that receives the back text (default: Back)
+  --front STRATEGY       kanji (default) | kana | furigana | english
+  --topic-field NAME     also write the topic name into this field (e.g. Category)
+  --tags TEMPLATE        tags for every note, {topic} and {deck} are filled in, :: nests them
+                         (default: '{topic} japanese vocabularly', e.g. 'topic::{topic} jlpt::n5')
+  --html POLICY          escape (default) | allow | allow:b,i,ruby,rt | raw
+                         escape shows '<' as typed, allow keeps those formatting tags
+                         (default list: bold/italic/furigana and friends), raw sends the text as is
+  --flat-deck            put every note in the main deck instead of topic subdecks
+  --allow-duplicates     add notes even if the same front is already in the deck
+                         (by default re-importing a file adds nothing new)
+  --duplicate-scope S    deck (default) | collection, where a front counts as a duplicate
+                         (collection also skips words already in other decks)
+  --update-existing      update notes whose front is already in the deck with the file's fields
+                         instead of skipping them (e.g. after fixing translations)
+  --reset-scheduling HOW none | forget | relearn, reschedule cards of notes --update-existing
+                         changed (default: none, they keep their review history)
+  --conjugations         add te-form/past/negative cards for verbs and i-adjectives to a Conjugation subdeck";
+
+/// switches that take no value
+const SWITCHES: &[&str] = &["keep-empty-rows", "exact-headers", "reset", "flat-deck", "no-color", "ascii", "conjugations", "number-topics", "fix-columns", "show-config", "allow-duplicates", "update-existing", "yes", "repair"];
+
+/// flags that can be given more than once, their values are kept one per line
+const REPEATABLE: &[&str] = &["header"];
+
+/// options that can be set from the config file / environment, with their defaults
+const CONFIGURABLE: &[(&str, &str)] = &[
+    ("keep-empty-rows", "false"),
+    ("exact-headers", "false"),
+    ("max-topic-size", "none"),
+    ("topic-order", "csv"),
+    ("number-topics", "false"),
+    ("fix-columns", "false"),
+    ("slice-gap", "0"),
+    ("skip-marker", "none"),
+    ("encoding", "utf-8"),
+    ("delimiter", "auto"),
+    ("join", "none"),
+    ("join-key", "kana"),
+    ("join-type", "left"),
+    ("model", "Basic"),
+    ("front-field", "Front"),
+    ("back-field", "Back"),
+    ("front", "kanji"),
+    ("topic-field", "none"),
+    ("tags", DEFAULT_TAG_TEMPLATE),
+    ("html", "escape"),
+    ("flat-deck", "false"),
+    ("allow-duplicates", "false"),
+    ("duplicate-scope", "deck"),
+    ("update-existing", "false"),
+    ("reset-scheduling", "none"),
+    ("conjugations", "false"),
+    ("no-color", "false"),
+    ("ascii", "false"),
+    ("timeout", "10"),
+    ("heavy-timeout", "120"),
+    ("deadline", "none"),
+    ("chunk-size", "500"),
+    ("user-agent", DEFAULT_USER_AGENT),
+    ("header", "none"),
+    ("max-notes", "5000"),
+    ("max-media-mb", "200"),
+];
+
+/// `--flag value` pairs keyed by flag name (without the leading dashes)
+pub(crate) type Flags = HashMap<String, String>;
+
+/// options controlling how the input file is read
+#[derive(Debug, Clone, Default)]
+pub struct InputOptions {
+    /// pass blank rows through to the importer instead of skipping them
+    pub keep_empty_rows: bool,
+
+    /// compare topic/header names byte-for-byte instead of case and width insensitively
+    pub exact_headers: bool,
+
+    /// split topics with more words than this into numbered subdecks
+    pub max_topic_size: Option<usize>,
+
+    /// order topics are created in
+    pub topic_order: TopicOrder,
+
+    /// prefix topic names with a zero-padded index
+    pub number_topics: bool,
+
+    /// swap columns back when a topic's reading/gloss/kanji look mixed up
+    pub fix_columns: bool,
+
+    /// separator columns between topic blocks
+    pub slice_gap: usize,
+
+    /// rows with a cell holding exactly this are left out of their topic
+    pub skip_marker: Option<String>,
+
+    /// encoding of the input file, utf-8 when not set
+    pub encoding: Option<&'static Encoding>,
+
+    /// field separator, picked from the file extension or sniffed from the contents when not set
+    pub delimiter: Option<u8>,
+
+    /// another csv whose columns are added to matching words
+    pub join: Option<JoinOptions>,
+}
+
+impl InputOptions {
+    fn from_flags(flags: &Flags) -> Result<Self, Box<dyn Error>> {
+        let max_topic_size = flags.get("max-topic-size")
+            .map(|size| match size.parse::<usize>() {
+                Ok(size) if size > 0 => Ok(size),
+                _ => Err(format!("Error: --max-topic-size must be a positive number, got '{}'", size)),
+            })
+            .transpose()?;
+
+        let slice_gap = flags.get("slice-gap")
+            .map(|gap| gap.parse::<usize>()
+                .map_err(|_| format!("Error: --slice-gap must be a number, got '{}'", gap)))
+            .transpose()?
+            .unwrap_or(0);
+
+        let join = flags.get("join")
+            .map(|path| -> Result<JoinOptions, Box<dyn Error>> {
+                Ok(JoinOptions {
+                    path: path.clone(),
+                    key: flags.get("join-key").map(|key| key.parse()).transpose()?.unwrap_or_default(),
+                    join_type: flags.get("join-type").map(|join_type| join_type.parse()).transpose()?.unwrap_or_default(),
+                })
+            })
+            .transpose()?;
+
+        Ok(InputOptions {
+            keep_empty_rows: flags.contains_key("keep-empty-rows"),
+            exact_headers: flags.contains_key("exact-headers"),
+            max_topic_size,
+            topic_order: flags.get("topic-order").map(|order| order.parse()).transpose()?.unwrap_or_default(),
+            number_topics: flags.contains_key("number-topics"),
+            fix_columns: flags.contains_key("fix-columns"),
+            slice_gap,
+            skip_marker: flags.get("skip-marker").cloned(),
+            encoding: flags.get("encoding").map(|label| encoding::parse_encoding(label)).transpose()?,
+            delimiter: flags.get("delimiter").map(|delimiter| parse_delimiter(delimiter)).transpose()?.flatten(),
+            join,
+        })
+    }
+
+    /// the delimiter to read `path` with, tab for `.tsv` files and sniffed from `contents`
+    /// for the rest, unless one was given
+    pub fn delimiter_for(&self, path: &str, contents: &str) -> u8 {
+        let is_tsv = Path::new(path).extension().is_some_and(|ext| ext.eq_ignore_ascii_case("tsv"));
+
+        self.delimiter.unwrap_or_else(|| {
+            if is_tsv {
+                b'\t'
+            } else {
+                let sample = &contents.as_bytes()[..contents.len().min(SNIFF_BYTES)];
+                sniff_format(sample).delimiter
+            }
+        })
+    }
+
+    /// the partitioner config these options read `path` (holding `contents`) with
+    pub fn parse_config(&self, path: &str, contents: &str) -> ParseConfig {
+        ParseConfig {
+            skip_empty_rows: !self.keep_empty_rows,
+            header_matching: self.header_matching(),
+            skip_marker: self.skip_marker.clone(),
+            slice_gap: self.slice_gap,
+            delimiter: self.delimiter_for(path, contents),
+            ..ParseConfig::default()
+        }
+    }
+
+    pub fn header_matching(&self) -> HeaderMatching {
+        if self.exact_headers {
+            HeaderMatching::Exact
+        } else {
+            HeaderMatching::Normalized
+        }
+    }
+}
+
+/// options for talking to AnkiConnect
+#[derive(Debug, Clone)]
+pub struct ConnectionOptions {
+    pub timeouts: Timeouts,
+
+    /// ids sent per bulk request (notesInfo, changeDeck, addTags, ...)
+    pub chunk_size: usize,
+
+    pub user_agent: String,
+
+    /// extra (name, value) headers sent with every request
+    pub headers: Vec<(String, String)>,
+}
+
+impl Default for ConnectionOptions {
+    fn default() -> Self {
+        ConnectionOptions {
+            timeouts: Timeouts::default(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            headers: Vec::new(),
+        }
+    }
+}
+
+impl ConnectionOptions {
+    fn from_flags(flags: &Flags) -> Result<Self, Box<dyn Error>> {
+        let chunk_size = flags.get("chunk-size")
+            .map(|size| match size.parse::<usize>() {
+                Ok(size) if size > 0 => Ok(size),
+                _ => Err(format!("Error: --chunk-size must be a positive number, got '{}'", size)),
+            })
+            .transpose()?;
+
+        let headers = flags.get("header")
+            .filter(|headers| !headers.trim().eq_ignore_ascii_case("none"))
+            .map_or(Ok(Vec::new()), |headers| headers.lines()
+                .map(|header| match header.split_once(':') {
+                    Some((name, value)) if !name.trim().is_empty() => Ok((name.trim().to_string(), value.trim().to_string())),
+                    _ => Err(format!("Error: --header must look like NAME:VALUE, got '{}'", header)),
+                })
+                .collect::<Result<Vec<_>, _>>())?;
+
+        Ok(ConnectionOptions {
+            timeouts: parse_timeouts(flags)?,
+            chunk_size: chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE),
+            user_agent: flags.get("user-agent").cloned().unwrap_or_else(|| DEFAULT_USER_AGENT.to_string()),
+            headers,
+        })
+    }
+
+    /// a client for the default AnkiConnect URL with these options, the deadline (if any)
+    /// starts counting now
+    pub fn client(&self) -> AnkiConnectClient {
+        let client = AnkiConnectClient::new()
+            .with_timeouts(self.timeouts)
+            .with_chunk_size(self.chunk_size)
+            .with_user_agent(&self.user_agent);
+
+        self.headers.iter().fold(client, |client, (name, value)| client.with_header(name, value))
+    }
+}
+
+/// options controlling how notes are built
+#[derive(Debug, Clone)]
+pub struct NoteOptions {
+    pub model: String,
+    pub front_field: String,
+    pub back_field: String,
+    pub front_strategy: FrontStrategy,
+
+    /// note field that receives the topic name, if any
+    pub topic_field: Option<String>,
+
+    /// tags put on every note
+    pub tags: TagTemplate,
+
+    /// how cell text becomes field html
+    pub html: HtmlPolicy,
+
+    /// one deck for everything, topics only live in tags / the topic field
+    pub flat_deck: bool,
+
+    /// add notes even when their front already exists in the deck
+    pub allow_duplicates: bool,
+
+    /// where anki looks for notes with the same front
+    pub duplicate_scope: DuplicateScope,
+
+    /// update notes already in the deck instead of counting them as duplicates
+    pub update_existing: bool,
+
+    /// how cards of notes changed by `update_existing` get rescheduled, if at all
+    pub reset_scheduling: Option<SchedulingReset>,
+
+    /// generate conjugation drill cards for verbs and i-adjectives
+    pub conjugations: bool,
+}
+
+impl Default for NoteOptions {
+    fn default() -> Self {
+        NoteOptions {
+            model: "Basic".to_string(),
+            front_field: "Front".to_string(),
+            back_field: "Back".to_string(),
+            front_strategy: FrontStrategy::default(),
+            topic_field: None,
+            tags: TagTemplate::default(),
+            html: HtmlPolicy::default(),
+            flat_deck: false,
+            allow_duplicates: false,
+            duplicate_scope: DuplicateScope::default(),
+            update_existing: false,
+            reset_scheduling: None,
+            conjugations: false,
+        }
+    }
+}
+
+impl NoteOptions {
+    fn from_flags(flags: &Flags) -> Result<Self, Box<dyn Error>> {
+        let defaults = NoteOptions::default();
+
+        if flags.contains_key("update-existing") && flags.contains_key("allow-duplicates") {
+            return Err("Error: --update-existing and --allow-duplicates can't be combined, duplicates are what gets updated".into());
+        }
+
+        // `none` (the default) is spelled out so a config file can turn a reset back off
+        let reset_scheduling = flags.get("reset-scheduling")
+            .filter(|reset| !reset.trim().eq_ignore_ascii_case("none"))
+            .map(|reset| reset.parse::<SchedulingReset>())
+            .transpose()?;
+        if reset_scheduling.is_some() && !flags.contains_key("update-existing") {
+            return Err("Error: --reset-scheduling only applies to notes changed by --update-existing".into());
+        }
+
+        Ok(NoteOptions {
+            model: flags.get("model").cloned().unwrap_or(defaults.model),
+            front_field: flags.get("front-field").cloned().unwrap_or(defaults.front_field),
+            back_field: flags.get("back-field").cloned().unwrap_or(defaults.back_field),
+            front_strategy: match flags.get("front") {
+                Some(strategy) => strategy.parse()?,
+                None => defaults.front_strategy,
+            },
+            topic_field: flags.get("topic-field").cloned(),
+            tags: match flags.get("tags") {
+                Some(template) => template.parse()?,
+                None => defaults.tags,
+            },
+            html: flags.get("html").map(|policy| policy.parse()).transpose()?.unwrap_or(defaults.html),
+            flat_deck: flags.contains_key("flat-deck"),
+            allow_duplicates: flags.contains_key("allow-duplicates"),
+            duplicate_scope: flags.get("duplicate-scope").map(|scope| scope.parse()).transpose()?.unwrap_or(defaults.duplicate_scope),
+            update_existing: flags.contains_key("update-existing"),
+            reset_scheduling,
+            conjugations: flags.contains_key("conjugations"),
+        })
+    }
+}
+
+pub enum Command {
+    /// import a csv into a deck (default command), or write it to an .apkg with `output`
+    Import { path: String, deck_name: String, output: Option<String>, input: InputOptions, notes: NoteOptions, limits: Limits },
+
+    /// move every card from one topic subdeck into another
+    Merge { deck_name: String, from_topic: String, into_topic: String, notes: NoteOptions },
+
+    /// delete notes of a deck whose row is no longer in the input
+    Prune { path: String, deck_name: String, input: InputOptions, notes: NoteOptions },
+
+    /// rename a tag and every tag nested under it, across the whole collection
+    RenameTag { from: String, into: String },
+
+    /// list (and with `delete` remove) media files matching a pattern that no note uses
+    CleanMedia { pattern: String, delete: bool },
+
+    /// print notes as they would be sent to anki, without importing
+    Render {
+        path: String,
+        deck_name: String,
+        topic: Option<String>,
+        rows: Option<Range<usize>>,
+        /// only parse this many rows per topic, picked by the strategy
+        sample: Option<(usize, SampleStrategy)>,
+        input: InputOptions,
+        notes: NoteOptions,
+    },
+
+    /// write parsed topics as a printable study sheet, `output` and `title` default to the
+    /// input's name (a google sheet's title, known once it's downloaded)
+    Export {
+        path: String,
+        output: Option<String>,
+        title: Option<String>,
+        format: SheetFormat,
+        input: InputOptions,
+        notes: NoteOptions,
+    },
+
+    /// report words added, removed and changed per topic between two versions of a sheet
+    DiffCsv { old: String, new: String, format: DiffFormat, output: Option<String>, input: InputOptions },
+
+    /// report what each column of every topic looks like, to check a sheet before importing
+    Schema { path: String, input: InputOptions },
+
+    /// turn a flat word list into a repeating-column csv skeleton
+    Scaffold { path: String, output: Option<String>, roles: Option<Vec<ColumnRole>> },
+
+    /// ask a running import to pause before its next batch
+    Pause { deck_name: String },
+
+    /// let a paused import continue
+    Resume { deck_name: String },
+
+    /// show progress of a running import
+    Status { deck_name: String },
+
+    /// export leeches to a "problem words" csv, optionally resetting or rescheduling them
+    Leeches { deck_name: String, output: String, action: Option<LeechAction>, notes: NoteOptions },
+
+    /// import a deck backup made before a destructive command
+    Restore { path: String },
+
+    /// check (and with `repair` fix) the state files imports leave between runs
+    StateDoctor { repair: bool },
+
+    /// print the effective configuration and where each value came from
+    ShowConfig(ResolvedConfig),
+
+    /// open the desktop front-end (requires the `gui` feature)
+    Gui,
+}
+
+/// a parsed command line: what to run and how to print it
+pub struct Cli {
+    pub command: Command,
+    pub style: OutputStyle,
+    pub connection: ConnectionOptions,
+}
+
+pub fn parse_args(args: impl Iterator<Item = String>) -> Result<Cli, Box<dyn Error>> {
+    let (positional, flags) = split_flags(args.skip(1), SWITCHES)?; // skip first argument (program name)
+    let config = ResolvedConfig::resolve(flags, CONFIGURABLE, SWITCHES)?;
+    let flags = &config.flags;
+    let style = OutputStyle::detect(flags.contains_key("no-color"), flags.contains_key("ascii"));
+    let connection = ConnectionOptions::from_flags(flags)?;
+
+    let command = if flags.contains_key("show-config") {
+        Command::ShowConfig(config.clone())
+    } else {
+        parse_command(positional, flags)?
+    };
+
+    Ok(Cli { command, style, connection })
+}
+
+fn parse_timeouts(flags: &Flags) -> Result<Timeouts, Box<dyn Error>> {
+    let seconds = |name: &str| flags.get(name)
+        .map(|secs| match secs.parse::<u64>() {
+            Ok(secs) if secs > 0 => Ok(Duration::from_secs(secs)),
+            _ => Err(format!("Error: --{} must be a positive number of seconds, got '{}'", name, secs)),
+        })
+        .transpose();
+
+    let defaults = Timeouts::default();
+
+    Ok(Timeouts {
+        quick: seconds("timeout")?.unwrap_or(defaults.quick),
+        heavy: seconds("heavy-timeout")?.unwrap_or(defaults.heavy),
+        deadline: seconds("deadline")?,
+    })
+}
+
+fn parse_limits(flags: &Flags) -> Result<Limits, Box<dyn Error>> {
+    let limit = |name: &str| flags.get(name)
+        .map(|limit| match limit.parse::<u64>() {
+            _ if limit.eq_ignore_ascii_case("none") => Ok(None),
+            Ok(limit) => Ok(Some(limit)),
+            Err(_) => Err(format!("Error: --{} must be a number or 'none', got '{}'", name, limit)),
+        })
+        .transpose();
+
+    let defaults = Limits::default();
+
+    Ok(Limits {
+        max_notes: limit("max-notes")?.map_or(defaults.max_notes, |max| max.map(|max| max as usize)),
+        max_media_bytes: match limit("max-media-mb")? {
+            Some(Some(mb)) => Some(mb.checked_mul(1024 * 1024)
+                .ok_or_else(|| format!("Error: --max-media-mb {} is too big, pass 'none' for no limit", mb))?),
+            Some(None) => None,
+            None => defaults.max_media_bytes,
+        },
+        confirmed: flags.contains_key("yes"),
+    })
+}
+
+/// print the resolved configuration for `--show-config`
+pub fn show_config(config: &ResolvedConfig) {
+    config.print(CONFIGURABLE, SWITCHES);
+}
+
+fn parse_command(positional: Vec<String>, flags: &Flags) -> Result<Command, Box<dyn Error>> {
+    let mut positional = positional.into_iter();
+
+    let first = positional.next()
+        .ok_or(format!("Error: Missing file path argument.\n{}", USAGE))?;
+
+    match first.as_str() {
+        "merge" => {
+            let deck_name = next_arg(&mut positional, "deck name")?;
+            let from_topic = next_arg(&mut positional, "from topic")?;
+            let into_topic = next_arg(&mut positional, "into topic")?;
+
+            Ok(Command::Merge { deck_name, from_topic, into_topic, notes: NoteOptions::from_flags(flags)? })
+        },
+
+        "prune" => {
+            let path = next_arg(&mut positional, "file path")?;
+            let deck_name = next_arg(&mut positional, "deck name")?;
+
+            Ok(Command::Prune { path, deck_name, input: InputOptions::from_flags(flags)?, notes: NoteOptions::from_flags(flags)? })
+        },
+
+        "rename-tag" => {
+            let from = next_arg(&mut positional, "from tag")?;
+            let into = next_arg(&mut positional, "into tag")?;
+
+            Ok(Command::RenameTag { from, into })
+        },
+
+        "clean-media" => {
+            let pattern = positional.next().unwrap_or_else(|| "*".to_string());
+
+            Ok(Command::CleanMedia { pattern, delete: flags.contains_key("yes") })
+        },
+
+        "gui" => Ok(Command::Gui),
+
+        "state" => match next_arg(&mut positional, "state subcommand (doctor)")?.as_str() {
+            "doctor" => Ok(Command::StateDoctor { repair: flags.contains_key("repair") }),
+            other => Err(format!("Error: Unknown state subcommand '{}', expected: doctor", other).into()),
+        },
+
+        "restore" => Ok(Command::Restore { path: next_arg(&mut positional, "backup path")? }),
+
+        "pause" => Ok(Command::Pause { deck_name: next_arg(&mut positional, "deck name")? }),
+        "resume" => Ok(Command::Resume { deck_name: next_arg(&mut positional, "deck name")? }),
+        "status" => Ok(Command::Status { deck_name: next_arg(&mut positional, "deck name")? }),
+
+        "leeches" => {
+            let deck_name = next_arg(&mut positional, "deck name")?;
+            let output = flags.get("output").cloned().unwrap_or_else(|| "problem-words.csv".to_string());
+
+            let action = match (flags.contains_key("reset"), flags.get("reschedule")) {
+                (true, Some(_)) => return Err("Error: Use either --reset or --reschedule, not both".into()),
+                (true, None) => Some(LeechAction::Reset),
+                (false, Some(days)) => Some(LeechAction::Reschedule(days.clone())),
+                (false, None) => None,
+            };
+
+            Ok(Command::Leeches { deck_name, output, action, notes: NoteOptions::from_flags(flags)? })
+        },
+
+        "scaffold" => {
+            let path = next_arg(&mut positional, "word list path")?;
+            let output = flags.get("output").cloned();
+
+            // one role per column of a flat sheet, instead of the guessed ones
+            let roles = flags.get("roles")
+                .map(|roles| roles.split(',').map(str::parse).collect::<Result<Vec<ColumnRole>, _>>())
+                .transpose()?;
+
+            Ok(Command::Scaffold { path, output, roles })
+        },
+
+        "diff-csv" => {
+            let old = next_arg(&mut positional, "old csv path")?;
+            let new = next_arg(&mut positional, "new csv path")?;
+            let format: DiffFormat = flags.get("format").map(|format| format.parse()).transpose()?.unwrap_or_default();
+
+            Ok(Command::DiffCsv { old, new, format, output: flags.get("output").cloned(), input: InputOptions::from_flags(flags)? })
+        },
+
+        "schema" => {
+            let path = next_arg(&mut positional, "file path")?;
+
+            Ok(Command::Schema { path, input: InputOptions::from_flags(flags)? })
+        },
+
+        "export" => {
+            let path = next_arg(&mut positional, "file path")?;
+            let format: SheetFormat = flags.get("format").map(|format| format.parse()).transpose()?.unwrap_or_default();
+            let output = flags.get("output").cloned();
+            let title = flags.get("title").cloned();
+
+            Ok(Command::Export {
+                path,
+                output,
+                title,
+                format,
+                input: InputOptions::from_flags(flags)?,
+                notes: NoteOptions::from_flags(flags)?,
+            })
+        },
+
+        "render" => {
+            let path = next_arg(&mut positional, "file path")?;
+            let deck_name = flags.get("deck").cloned().unwrap_or_else(|| "Deck".to_string());
+            let topic = flags.get("topic").cloned();
+            let rows = flags.get("rows").map(|rows| parse_row_range(rows)).transpose()?;
+            let strategy = flags.get("sample-strategy")
+                .map(|strategy| parse_sample_strategy(strategy))
+                .transpose()?
+                .unwrap_or(SampleStrategy::Stride);
+            let sample = flags.get("sample")
+                .map(|n| match n.parse::<usize>() {
+                    Ok(n) if n > 0 => Ok(n),
+                    _ => Err(format!("Error: --sample must be a positive number, got '{}'", n)),
+                })
+                .transpose()?
+                .map(|n| (n, strategy));
+
+            Ok(Command::Render {
+                path,
+                deck_name,
+                topic,
+                rows,
+                sample,
+                input: InputOptions::from_flags(flags)?,
+                notes: NoteOptions::from_flags(flags)?,
+            })
+        },
+
+        _ => {
+            let deck_name = next_arg(&mut positional, "deck name")?;
+            let output = flags.get("output")
+                .map(|output| match output.to_lowercase().ends_with(".apkg") {
+                    true => Ok(output.clone()),
+                    false => Err(format!("Error: --output for an import has to be an .apkg file, got '{}'", output)),
+                })
+                .transpose()?;
+
+            Ok(Command::Import {
+                path: first,
+                deck_name,
+                output,
+                input: InputOptions::from_flags(flags)?,
+                notes: NoteOptions::from_flags(flags)?,
+                limits: parse_limits(flags)?,
+            })
+        },
+    }
+}
+
+fn next_arg(args: &mut impl Iterator<Item = String>, name: &str) -> Result<String, Box<dyn Error>> {
+    args.next()
+        .ok_or_else(|| format!("Error: Missing {} argument.\n{}", name, USAGE).into())
+}
+
+/// split raw arguments into positionals and `--flag value` pairs
+/// flags listed in `switches` take no value and are stored as "true", values of `REPEATABLE`
+/// flags given more than once are joined by newlines
+fn split_flags(args: impl Iterator<Item = String>, switches: &[&str])
+    -> Result<(Vec<String>, Flags), Box<dyn Error>>
+{
+    let mut args = args.peekable();
+    let mut positional = Vec::new();
+    let mut flags = Flags::new();
+
+    while let Some(arg) = args.next() {
+        let Some(name) = arg.strip_prefix("--") else {
+            positional.push(arg);
+            continue;
+        };
+
+        if switches.contains(&name) || negated_switch(name, switches).is_some() {
+            flags.insert(name.to_string(), "true".to_string());
+            continue;
+        }
+
+        let value = args.next()
+            .ok_or(format!("Error: Missing value for --{}.\n{}", name, USAGE))?;
+
+        match flags.get_mut(name) {
+            Some(earlier) if REPEATABLE.contains(&name) => {
+                earlier.push('\n');
+                earlier.push_str(&value);
+            },
+            _ => {
+                flags.insert(name.to_string(), value);
+            },
+        }
+    }
+
+    Ok((positional, flags))
+}
+
+/// `auto` (from the file extension) is `None`
+fn parse_delimiter(delimiter: &str) -> Result<Option<u8>, String> {
+    match delimiter {
+        "auto" => Ok(None),
+        "tab" | "\\t" | "\t" => Ok(Some(b'\t')),
+        single if single.len() == 1 && single.is_ascii() => Ok(Some(single.as_bytes()[0])),
+        other => Err(format!("Error: --delimiter must be a single character or 'tab', got '{}'", other)),
+    }
+}
+
+fn parse_sample_strategy(strategy: &str) -> Result<SampleStrategy, String> {
+    match strategy.trim().to_lowercase().as_str() {
+        "head" => Ok(SampleStrategy::Head),
+        "random" => Ok(SampleStrategy::Random),
+        "stride" => Ok(SampleStrategy::Stride),
+        other => Err(format!("Unknown sample strategy '{}', expected one of: head, random, stride", other)),
+    }
+}
+
+/// parse a 1-based inclusive row range (`1-5` or `3`) into a 0-based range
+fn parse_row_range(rows: &str) -> Result<Range<usize>, Box<dyn Error>> {
+    let invalid = || format!("Error: Invalid row range '{}', expected e.g. 1-5 or 3", rows);
+
+    let (start, end) = rows.split_once('-').unwrap_or((rows, rows));
+    let start: usize = start.trim().parse().map_err(|_| invalid())?;
+    let end: usize = end.trim().parse().map_err(|_| invalid())?;
+
+    if start == 0 || end < start {
+        return Err(invalid().into());
+    }
+
+    Ok(start - 1..end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags(pairs: &[(&str, &str)]) -> Flags {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn reset_scheduling_none_turns_it_off() {
+        let notes = NoteOptions::from_flags(&flags(&[("reset-scheduling", "none")])).unwrap();
+        assert_eq!(notes.reset_scheduling, None);
+
+        let notes = NoteOptions::from_flags(&flags(&[("update-existing", ""), ("reset-scheduling", "relearn")])).unwrap();
+        assert_eq!(notes.reset_scheduling, Some(SchedulingReset::Relearn));
+
+        assert!(NoteOptions::from_flags(&flags(&[("reset-scheduling", "forget")])).is_err());
+    }
+
+    #[test]
+    fn duplicate_scope_defaults_to_the_deck() {
+        assert_eq!(NoteOptions::from_flags(&flags(&[])).unwrap().duplicate_scope, DuplicateScope::Deck);
+
+        let notes = NoteOptions::from_flags(&flags(&[("duplicate-scope", "Collection")])).unwrap();
+        assert_eq!(notes.duplicate_scope, DuplicateScope::Collection);
+
+        assert!(NoteOptions::from_flags(&flags(&[("duplicate-scope", "model")])).is_err());
+    }
+
+    #[test]
+    fn chunk_size_must_be_positive() {
+        assert_eq!(ConnectionOptions::from_flags(&flags(&[])).unwrap().chunk_size, DEFAULT_CHUNK_SIZE);
+        assert_eq!(ConnectionOptions::from_flags(&flags(&[("chunk-size", "50")])).unwrap().chunk_size, 50);
+        assert!(ConnectionOptions::from_flags(&flags(&[("chunk-size", "0")])).is_err());
+    }
+
+    #[test]
+    fn header_needs_a_name_and_a_value() {
+        let connection = ConnectionOptions::from_flags(&flags(&[("header", "X-Token: abc:123")])).unwrap();
+        assert_eq!(connection.headers, vec![("X-Token".to_string(), "abc:123".to_string())]);
+
+        assert_eq!(ConnectionOptions::from_flags(&flags(&[("header", "none")])).unwrap().headers, vec![]);
+        assert!(ConnectionOptions::from_flags(&flags(&[("header", "X-Token")])).is_err());
+        assert!(ConnectionOptions::from_flags(&flags(&[("header", ":abc")])).is_err());
+    }
+
+    #[test]
+    fn every_header_flag_is_kept() {
+        let args = ["--header", "X-Token: abc", "--model", "Basic", "--header", "X-User: me"].map(String::from);
+        let (_, flags) = split_flags(args.into_iter(), SWITCHES).unwrap();
+
+        assert_eq!(ConnectionOptions::from_flags(&flags).unwrap().headers, vec![
+            ("X-Token".to_string(), "abc".to_string()),
+            ("X-User".to_string(), "me".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn media_limits_that_overflow_are_refused() {
+        let limits = parse_limits(&flags(&[("max-media-mb", "5")])).unwrap();
+        assert_eq!(limits.max_media_bytes, Some(5 * 1024 * 1024));
+
+        assert_eq!(parse_limits(&flags(&[("max-media-mb", "none")])).unwrap().max_media_bytes, None);
+
+        let error = parse_limits(&flags(&[("max-media-mb", &u64::MAX.to_string())])).err().unwrap();
+        assert!(error.to_string().contains("too big"), "{}", error);
+    }
+
+    #[test]
+    fn delimiter_comes_from_the_extension_the_contents_or_the_flag() {
+        let input = InputOptions::default();
+        let semicolons = "kana;english;kanji\nあと;after;後\nいま;now;今\n";
+
+        assert_eq!(input.delimiter_for("words.TSV", semicolons), b'\t');
+        assert_eq!(input.delimiter_for("words.csv", semicolons), b';');
+        assert_eq!(input.delimiter_for("words.csv", "kana,english\nあと,after\n"), b',');
+
+        let input = InputOptions { delimiter: Some(b'|'), ..InputOptions::default() };
+        assert_eq!(input.delimiter_for("words.tsv", semicolons), b'|');
+    }
+}
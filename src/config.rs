@@ -0,0 +1,312 @@
+use std::{collections::HashMap, env, error::Error, fmt, fs, path::{Path, PathBuf}};
+
+use crate::cli::Flags;
+
+// ============================================================================================
+//                                  Configuration Sources
+// ============================================================================================
+
+// Every option can come from four places, later ones win:
+//
+//   defaults < config file < environment < command line
+//
+// The config file and environment use the same names as the command line flags:
+//
+//   # csv-to-anki.conf
+//   model = Japanese
+//   keep-empty-rows = true
+//
+//   CSV_TO_ANKI_MODEL=Japanese CSV_TO_ANKI_KEEP_EMPTY_ROWS=true
+
+/// config file looked for in the working directory, then the home directory
+pub const CONFIG_FILE_NAME: &str = "csv-to-anki.conf";
+
+/// prefix for option environment variables
+pub const ENV_PREFIX: &str = "CSV_TO_ANKI_";
+
+/// points at a config file, like `--config`
+const CONFIG_ENV: &str = "CSV_TO_ANKI_CONFIG";
+
+/// Where an option's effective value came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    File(PathBuf),
+    Env(String),
+    Cli,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::File(path) => write!(f, "config file {}", path.display()),
+            Source::Env(name) => write!(f, "env {}", name),
+            Source::Cli => write!(f, "command line"),
+        }
+    }
+}
+
+/// Options merged from every source, plus where each one came from
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedConfig {
+    pub flags: Flags,
+    sources: HashMap<String, Source>,
+    file: Option<PathBuf>,
+}
+
+impl ResolvedConfig {
+    /// layer the config file and environment under the command line flags
+    ///
+    /// switches (flags without a value) accept `true`/`false` in the file and environment,
+    /// a `false` from a later source turns off a switch set by an earlier one, on the command
+    /// line that's `--no-<switch>`. options the file or environment set must be in `known`, so a
+    /// typo doesn't go unnoticed and switches left out of it (`--yes`, `--reset`, ...) can only
+    /// be given on the command line
+    pub fn resolve(cli: Flags, known: &[(&str, &str)], switches: &[&str]) -> Result<Self, Box<dyn Error>> {
+        let file = match cli.get("config") {
+            Some(path) => Some(PathBuf::from(path)),
+            None => find_config_file(),
+        };
+
+        let file = match file {
+            Some(path) => {
+                let text = fs::read_to_string(&path)
+                    .map_err(|e| format!("Error: Cannot read config file {}: {}", path.display(), e))?;
+                Some((path, text))
+            },
+            None => None,
+        };
+
+        let env_vars = env::vars()
+            .filter(|(name, _)| name.starts_with(ENV_PREFIX) && name != CONFIG_ENV)
+            .collect();
+
+        Self::resolve_from(cli, file, env_vars, known, switches)
+    }
+
+    /// `resolve` with the config file (path, contents) and environment already read
+    fn resolve_from(
+        cli: Flags,
+        file: Option<(PathBuf, String)>,
+        mut env_vars: Vec<(String, String)>,
+        known: &[(&str, &str)],
+        switches: &[&str],
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut config = ResolvedConfig::default();
+
+        if let Some((path, text)) = file {
+            for (name, value) in parse_config_file(&text, &path)? {
+                config.set(name, value, Source::File(path.clone()), known, switches)?;
+            }
+            config.file = Some(path);
+        }
+
+        env_vars.sort();
+
+        for (var, value) in env_vars {
+            let name = var[ENV_PREFIX.len()..].to_lowercase().replace('_', "-");
+            config.set(name, value, Source::Env(var), known, switches)?;
+        }
+
+        for (name, value) in cli {
+            match negated_switch(&name, switches) {
+                Some(switch) => {
+                    config.flags.remove(switch);
+                    config.sources.insert(switch.to_string(), Source::Cli);
+                },
+                None => {
+                    config.sources.insert(name.clone(), Source::Cli);
+                    config.flags.insert(name, value);
+                },
+            }
+        }
+
+        Ok(config)
+    }
+
+    fn set(&mut self, name: String, value: String, source: Source, known: &[(&str, &str)], switches: &[&str]) -> Result<(), Box<dyn Error>> {
+        if !known.iter().any(|(known, _)| *known == name) {
+            return Err(match switches.contains(&name.as_str()) {
+                true => format!("Error: '{}' from {} can only be given on the command line, as --{}", name, source, name),
+                false => format!("Error: Unknown option '{}' from {}", name, source),
+            }.into());
+        }
+
+        if !switches.contains(&name.as_str()) {
+            self.sources.insert(name.clone(), source);
+            self.flags.insert(name, value);
+            return Ok(());
+        }
+
+        match value.trim().to_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => {
+                self.flags.insert(name.clone(), "true".to_string());
+            },
+            "false" | "0" | "no" | "off" => {
+                self.flags.remove(&name);
+            },
+            _ => return Err(format!("Error: '{}' from {} must be true or false, got '{}'", name, source, value).into()),
+        }
+
+        self.sources.insert(name, source);
+        Ok(())
+    }
+
+    /// where an option's value came from
+    pub fn source(&self, name: &str) -> Source {
+        self.sources.get(name).cloned().unwrap_or(Source::Default)
+    }
+
+    /// print every known option with its effective value and source
+    ///
+    /// `known` lists (option, default) pairs, options set but not in `known` are printed after
+    pub fn print(&self, known: &[(&str, &str)], switches: &[&str]) {
+        match &self.file {
+            Some(path) => println!("Config file: {}", path.display()),
+            None => println!("Config file: none (looked for ./{} and ~/{})", CONFIG_FILE_NAME, CONFIG_FILE_NAME),
+        }
+        println!();
+
+        let mut names: Vec<&str> = known.iter().map(|(name, _)| *name).collect();
+        let mut extra: Vec<&str> = self.sources.keys()
+            .map(String::as_str)
+            .filter(|name| !names.contains(name) && *name != "show-config")
+            .collect();
+        extra.sort();
+        names.extend(extra);
+
+        let width = names.iter().map(|name| name.len()).max().unwrap_or(0);
+
+        for name in names {
+            let value = match self.flags.get(name) {
+                Some(value) => value.as_str(),
+                None if switches.contains(&name) && self.sources.contains_key(name) => "false",
+                None => known.iter().find(|(known, _)| *known == name).map_or("", |(_, default)| *default),
+            };
+
+            println!("  {:width$}  = {:12}  ({})", name, value, self.source(name), width = width);
+        }
+    }
+}
+
+/// the switch `--no-<switch>` turns off, `no-color` is a switch of its own
+pub fn negated_switch<'a>(name: &'a str, switches: &[&str]) -> Option<&'a str> {
+    if switches.contains(&name) {
+        return None;
+    }
+
+    name.strip_prefix("no-").filter(|switch| switches.contains(switch))
+}
+
+fn find_config_file() -> Option<PathBuf> {
+    if let Some(path) = env::var_os(CONFIG_ENV) {
+        return Some(PathBuf::from(path));
+    }
+
+    let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")).map(PathBuf::from);
+
+    [Some(PathBuf::from(CONFIG_FILE_NAME)), home.map(|home| home.join(CONFIG_FILE_NAME))]
+        .into_iter()
+        .flatten()
+        .find(|path| path.is_file())
+}
+
+/// `name = value` lines, `#` starts a comment, values may be quoted
+fn parse_config_file(text: &str, path: &Path) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let mut options = Vec::new();
+
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (name, value) = line.split_once('=')
+            .ok_or_else(|| format!("Error: {}:{}: expected `name = value`, got '{}'", path.display(), idx + 1, line))?;
+
+        let value = value.trim();
+        let value = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value);
+
+        options.push((name.trim().trim_start_matches("--").to_string(), value.to_string()));
+    }
+
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KNOWN: &[(&str, &str)] = &[("model", "Basic"), ("front", "kanji"), ("tags", "{topic}"), ("flat-deck", "false")];
+    const SWITCHES: &[&str] = &["flat-deck", "no-color", "yes"];
+
+    fn flags(pairs: &[(&str, &str)]) -> Flags {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    fn env(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(name, value)| (format!("{}{}", ENV_PREFIX, name), value.to_string())).collect()
+    }
+
+    fn file(text: &str) -> Option<(PathBuf, String)> {
+        Some((PathBuf::from(CONFIG_FILE_NAME), text.to_string()))
+    }
+
+    #[test]
+    fn the_command_line_beats_the_environment_beats_the_file() {
+        let config = ResolvedConfig::resolve_from(
+            flags(&[("model", "Cli")]),
+            file("model = File\nfront = kana\ntags = \"from file\""),
+            env(&[("MODEL", "Env"), ("FRONT", "english")]),
+            KNOWN,
+            SWITCHES,
+        ).unwrap();
+
+        assert_eq!(config.flags["model"], "Cli");
+        assert_eq!(config.flags["front"], "english");
+        assert_eq!(config.flags["tags"], "from file");
+        assert_eq!(config.source("model"), Source::Cli);
+        assert_eq!(config.source("front"), Source::Env("CSV_TO_ANKI_FRONT".to_string()));
+        assert_eq!(config.source("tags"), Source::File(PathBuf::from(CONFIG_FILE_NAME)));
+    }
+
+    #[test]
+    fn switches_can_be_turned_off_again() {
+        let config = ResolvedConfig::resolve_from(
+            flags(&[("no-flat-deck", "true"), ("no-color", "true")]),
+            file("flat-deck = true"),
+            Vec::new(),
+            KNOWN,
+            SWITCHES,
+        ).unwrap();
+
+        assert!(!config.flags.contains_key("flat-deck"));
+        assert_eq!(config.source("flat-deck"), Source::Cli);
+        assert!(config.flags.contains_key("no-color"));
+
+        let config = ResolvedConfig::resolve_from(flags(&[]), file("flat-deck = true"), env(&[("FLAT_DECK", "false")]), KNOWN, SWITCHES).unwrap();
+        assert!(!config.flags.contains_key("flat-deck"));
+    }
+
+    #[test]
+    fn unknown_options_are_refused() {
+        let error = ResolvedConfig::resolve_from(flags(&[]), file("modle = Japanese"), Vec::new(), KNOWN, SWITCHES).unwrap_err();
+        assert!(error.to_string().contains("Unknown option 'modle'"), "{}", error);
+
+        let error = ResolvedConfig::resolve_from(flags(&[]), None, env(&[("FRONTS", "kana")]), KNOWN, SWITCHES).unwrap_err();
+        assert!(error.to_string().contains("env CSV_TO_ANKI_FRONTS"), "{}", error);
+    }
+
+    #[test]
+    fn command_line_only_switches_are_refused_from_file_and_env() {
+        let error = ResolvedConfig::resolve_from(flags(&[]), file("yes = true"), Vec::new(), KNOWN, SWITCHES).unwrap_err();
+        assert!(error.to_string().contains("only be given on the command line"), "{}", error);
+
+        let error = ResolvedConfig::resolve_from(flags(&[]), None, env(&[("YES", "1")]), KNOWN, SWITCHES).unwrap_err();
+        assert!(error.to_string().contains("env CSV_TO_ANKI_YES"), "{}", error);
+
+        let config = ResolvedConfig::resolve_from(flags(&[("yes", "true")]), None, Vec::new(), KNOWN, SWITCHES).unwrap();
+        assert!(config.flags.contains_key("yes"));
+    }
+}
@@ -0,0 +1,276 @@
+use crate::parse::{Topic, Word};
+
+// ============================================================================================
+//                                  Conjugation Drills
+// ============================================================================================
+
+/// subdeck (under the main deck) that generated conjugation cards go into
+pub const CONJUGATION_TOPIC: &str = "Conjugation";
+
+/// What kind of word a vocab entry is, as far as conjugation goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordClass {
+    /// 食べる, 見る: drop る
+    Ichidan,
+
+    /// 書く, 飲む, 話す: change the final kana
+    Godan,
+
+    /// 行く: godan, but the te/past forms are 行って / 行った
+    Iku,
+
+    /// する and noun + する (勉強する)
+    Suru,
+
+    /// 来る
+    Kuru,
+
+    /// 高い, 美味しい (いい/良い is handled as a special case)
+    IAdjective,
+}
+
+/// The conjugations drilled for each verb/adjective
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Form {
+    Te,
+    Past,
+    Negative,
+}
+
+impl Form {
+    pub const ALL: [Form; 3] = [Form::Te, Form::Past, Form::Negative];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Form::Te => "te-form",
+            Form::Past => "past",
+            Form::Negative => "negative",
+        }
+    }
+}
+
+/// godan る-verbs that look ichidan (i/e sound before る)
+const GODAN_RU_KANJI: &[&str] = &[
+    "帰る", "入る", "走る", "知る", "切る", "要る", "減る", "参る", "喋る", "滑る", "握る", "蹴る", "限る", "焦る", "照る", "散る",
+];
+const GODAN_RU_KANA: &[&str] = &["はいる", "はしる", "しる", "しゃべる", "すべる", "にぎる", "かぎる", "あせる"];
+
+/// kana that end in an i or e sound, so the verb before る is (usually) ichidan
+const I_E_KANA: &str = "いきしちにひみりぎじびぴえけせてねへめれげぜでべぺ";
+
+/// guess a word's class from the sheet
+///
+/// verbs are entries whose translation starts with "to " (the sheet's convention),
+/// i-adjectives need the kanji column to end in い (高い) or the kana to end in しい,
+/// so plain nouns ending in い (とけい) aren't conjugated
+pub fn classify(word: &Word) -> Option<WordClass> {
+    let kana = word.japanese().trim();
+    let kanji = word.kanji().trim();
+    let english = word.english().trim().to_lowercase();
+
+    if english.starts_with("to ") {
+        return classify_verb(kana, kanji);
+    }
+
+    let adjective = (kanji.ends_with('い') && kanji != kana) || kana.ends_with("しい") || kana.ends_with("いい");
+    (adjective && kana.ends_with('い')).then_some(WordClass::IAdjective)
+}
+
+fn classify_verb(kana: &str, kanji: &str) -> Option<WordClass> {
+    if kana.ends_with("する") {
+        return Some(WordClass::Suru);
+    }
+
+    if kana.ends_with("くる") && (kanji.ends_with("来る") || kana == "くる") {
+        return Some(WordClass::Kuru);
+    }
+
+    if kana.ends_with("いく") && (kanji.ends_with("行く") || kana == "いく") {
+        return Some(WordClass::Iku);
+    }
+
+    let before_ru = kana.strip_suffix('る').and_then(|stem| stem.chars().last());
+
+    match before_ru {
+        Some(c) if I_E_KANA.contains(c) => {
+            let godan = GODAN_RU_KANJI.iter().any(|verb| kanji.ends_with(verb))
+                || GODAN_RU_KANA.contains(&kana);
+
+            Some(if godan { WordClass::Godan } else { WordClass::Ichidan })
+        },
+        _ if kana.ends_with(|c| "うくぐすつぬぶむる".contains(c)) => Some(WordClass::Godan),
+        _ => None,
+    }
+}
+
+/// conjugate a dictionary form (kana or kanji spelling) into `form`
+///
+/// returns None if the word doesn't end the way its class expects
+pub fn conjugate(word: &str, class: WordClass, form: Form) -> Option<String> {
+    let word = word.trim();
+
+    match class {
+        WordClass::Ichidan => {
+            let stem = word.strip_suffix('る')?;
+            Some(format!("{}{}", stem, match form { Form::Te => "て", Form::Past => "た", Form::Negative => "ない" }))
+        },
+
+        WordClass::Suru => {
+            let stem = word.strip_suffix("する")?;
+            Some(format!("{}{}", stem, match form { Form::Te => "して", Form::Past => "した", Form::Negative => "しない" }))
+        },
+
+        WordClass::Kuru => {
+            // 来る keeps its kanji, only the reading changes
+            if let Some(stem) = word.strip_suffix("来る") {
+                return Some(format!("{}来{}", stem, match form { Form::Te => "て", Form::Past => "た", Form::Negative => "ない" }));
+            }
+            let stem = word.strip_suffix("くる")?;
+            Some(format!("{}{}", stem, match form { Form::Te => "きて", Form::Past => "きた", Form::Negative => "こない" }))
+        },
+
+        WordClass::Iku => match form {
+            Form::Te => Some(format!("{}って", word.strip_suffix('く')?)),
+            Form::Past => Some(format!("{}った", word.strip_suffix('く')?)),
+            Form::Negative => conjugate(word, WordClass::Godan, form),
+        },
+
+        WordClass::Godan => {
+            let last = word.chars().last()?;
+            let stem = &word[..word.len() - last.len_utf8()];
+
+            // ある has no あらない
+            if form == Form::Negative && word == "ある" {
+                return Some("ない".to_string());
+            }
+
+            let ending = match (form, last) {
+                (Form::Te, 'う' | 'つ' | 'る') => "って",
+                (Form::Te, 'む' | 'ぶ' | 'ぬ') => "んで",
+                (Form::Te, 'く') => "いて",
+                (Form::Te, 'ぐ') => "いで",
+                (Form::Te, 'す') => "して",
+                (Form::Past, 'う' | 'つ' | 'る') => "った",
+                (Form::Past, 'む' | 'ぶ' | 'ぬ') => "んだ",
+                (Form::Past, 'く') => "いた",
+                (Form::Past, 'ぐ') => "いだ",
+                (Form::Past, 'す') => "した",
+                (Form::Negative, 'う') => "わない",
+                (Form::Negative, 'つ') => "たない",
+                (Form::Negative, 'る') => "らない",
+                (Form::Negative, 'む') => "まない",
+                (Form::Negative, 'ぶ') => "ばない",
+                (Form::Negative, 'ぬ') => "なない",
+                (Form::Negative, 'く') => "かない",
+                (Form::Negative, 'ぐ') => "がない",
+                (Form::Negative, 'す') => "さない",
+                _ => return None,
+            };
+
+            Some(format!("{}{}", stem, ending))
+        },
+
+        WordClass::IAdjective => {
+            // いい conjugates from よい
+            let stem = match word.strip_suffix("いい") {
+                Some(stem) => format!("{}よ", stem),
+                None => word.strip_suffix('い')?.to_string(),
+            };
+            Some(format!("{}{}", stem, match form { Form::Te => "くて", Form::Past => "かった", Form::Negative => "くない" }))
+        },
+    }
+}
+
+/// the drill words for one vocab entry (empty if it isn't a verb/adjective we can conjugate)
+pub fn conjugation_words(word: &Word) -> Vec<Word> {
+    let Some(class) = classify(word) else {
+        return Vec::new();
+    };
+
+    Form::ALL.iter()
+        .filter_map(|form| {
+            let kana = conjugate(word.japanese(), class, *form)?;
+            let kanji = match word.kanji().trim() {
+                "" => String::new(),
+                kanji => conjugate(kanji, class, *form)?,
+            };
+            let english = format!("{} ({})", word.english().trim(), form.label());
+
+            Some(Word::new(kana, english, kanji))
+        })
+        .collect()
+}
+
+/// a `Conjugation` topic with drills for every verb/adjective in `topics`, if there are any
+pub fn conjugation_topic(topics: &[Topic]) -> Option<Topic> {
+    let words: Vec<Word> = topics.iter()
+        .flat_map(|topic| topic.words())
+        .flat_map(conjugation_words)
+        .collect();
+
+    (!words.is_empty()).then(|| Topic { name: CONJUGATION_TOPIC.to_string(), words })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (te-form, past, negative) of a dictionary form
+    fn forms(word: &str, class: WordClass) -> [Option<String>; 3] {
+        Form::ALL.map(|form| conjugate(word, class, form))
+    }
+
+    fn expect(te: &str, past: &str, negative: &str) -> [Option<String>; 3] {
+        [Some(te.to_string()), Some(past.to_string()), Some(negative.to_string())]
+    }
+
+    #[test]
+    fn regular_verbs_and_adjectives() {
+        assert_eq!(forms("たべる", WordClass::Ichidan), expect("たべて", "たべた", "たべない"));
+        assert_eq!(forms("書く", WordClass::Godan), expect("書いて", "書いた", "書かない"));
+        assert_eq!(forms("のむ", WordClass::Godan), expect("のんで", "のんだ", "のまない"));
+        assert_eq!(forms("話す", WordClass::Godan), expect("話して", "話した", "話さない"));
+        assert_eq!(forms("およぐ", WordClass::Godan), expect("およいで", "およいだ", "およがない"));
+        assert_eq!(forms("かう", WordClass::Godan), expect("かって", "かった", "かわない"));
+        assert_eq!(forms("高い", WordClass::IAdjective), expect("高くて", "高かった", "高くない"));
+    }
+
+    #[test]
+    fn irregular_verbs_and_adjectives() {
+        assert_eq!(forms("する", WordClass::Suru), expect("して", "した", "しない"));
+        assert_eq!(forms("勉強する", WordClass::Suru), expect("勉強して", "勉強した", "勉強しない"));
+        assert_eq!(forms("くる", WordClass::Kuru), expect("きて", "きた", "こない"));
+        assert_eq!(forms("来る", WordClass::Kuru), expect("来て", "来た", "来ない"));
+        assert_eq!(forms("いく", WordClass::Iku), expect("いって", "いった", "いかない"));
+        assert_eq!(forms("行く", WordClass::Iku), expect("行って", "行った", "行かない"));
+        assert_eq!(forms("ある", WordClass::Godan), expect("あって", "あった", "ない"));
+        assert_eq!(forms("いい", WordClass::IAdjective), expect("よくて", "よかった", "よくない"));
+        assert_eq!(forms("かっこいい", WordClass::IAdjective), expect("かっこよくて", "かっこよかった", "かっこよくない"));
+    }
+
+    #[test]
+    fn classes_come_from_the_sheet() {
+        let class = |kana: &str, english: &str, kanji: &str| classify(&Word::new(kana, english, kanji));
+
+        assert_eq!(class("たべる", "to eat", "食べる"), Some(WordClass::Ichidan));
+        assert_eq!(class("かえる", "to return", "帰る"), Some(WordClass::Godan));
+        assert_eq!(class("はいる", "to enter", ""), Some(WordClass::Godan));
+        assert_eq!(class("いく", "to go", "行く"), Some(WordClass::Iku));
+        assert_eq!(class("くる", "to come", "来る"), Some(WordClass::Kuru));
+        assert_eq!(class("べんきょうする", "to study", "勉強する"), Some(WordClass::Suru));
+        assert_eq!(class("いい", "good", "良い"), Some(WordClass::IAdjective));
+        assert_eq!(class("とけい", "clock", "時計"), None);
+    }
+
+    #[test]
+    fn drills_conjugate_kana_and_kanji_together() {
+        let drills = conjugation_words(&Word::new("いく", "to go", "行く"));
+
+        assert_eq!(drills, vec![
+            Word::new("いって", "to go (te-form)", "行って"),
+            Word::new("いった", "to go (past)", "行った"),
+            Word::new("いかない", "to go (negative)", "行かない"),
+        ]);
+        assert!(conjugation_words(&Word::new("とけい", "clock", "時計")).is_empty());
+    }
+}
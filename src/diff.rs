@@ -0,0 +1,233 @@
+use std::{error::Error, io::Write};
+
+use csv_partitioner::{diff_by_key, HeaderMatching};
+use serde::Serialize;
+
+use crate::output::{Color, OutputStyle};
+use crate::parse::{Topic, Word};
+
+// ============================================================================================
+//                                      Spreadsheet Diff
+// ============================================================================================
+
+// What changed between two versions of a spreadsheet, topic by topic, so a teacher can hand
+// out a change log with the updated sheet. Topics are matched by name (they can move between
+// columns) and words by their reading, so a fixed translation or kanji is a change rather
+// than one word removed and another added.
+
+/// Formats `diff-csv` can write
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiffFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl std::str::FromStr for DiffFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "text" => Ok(DiffFormat::Text),
+            "json" => Ok(DiffFormat::Json),
+            other => Err(format!("Unknown diff format '{}', expected one of: text, json", other)),
+        }
+    }
+}
+
+/// Whether a topic is in both versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TopicStatus {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A word whose reading stayed but whose translation or kanji didn't
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WordChange {
+    pub before: Word,
+    pub after: Word,
+}
+
+/// Changes to one topic, topics without any aren't reported
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TopicDiff {
+    pub topic: String,
+    pub status: TopicStatus,
+    pub added: Vec<Word>,
+    pub removed: Vec<Word>,
+    pub changed: Vec<WordChange>,
+}
+
+/// The whole report, what `--format json` prints
+#[derive(Debug, Clone, Serialize)]
+pub struct SheetDiff {
+    pub old: String,
+    pub new: String,
+    pub topics: Vec<TopicDiff>,
+}
+
+/// compare two parsed versions of a sheet, in the new version's topic order with removed topics last
+pub fn diff_topics(old: Vec<Topic>, new: Vec<Topic>, matching: HeaderMatching) -> Vec<TopicDiff> {
+    let mut old: Vec<Option<Topic>> = old.into_iter().map(Some).collect();
+    let mut diffs = Vec::new();
+
+    for topic in new {
+        let previous = old.iter_mut()
+            .find(|previous| previous.as_ref().is_some_and(|previous| matching.matches(&previous.name, &topic.name)))
+            .and_then(Option::take);
+
+        let (status, previous_words) = match previous {
+            Some(previous) => (TopicStatus::Changed, previous.words),
+            None => (TopicStatus::Added, Vec::new()),
+        };
+
+        let diff = diff_by_key(previous_words, topic.words, |word: &Word| word.japanese().trim().to_string());
+        if diff.is_empty() {
+            continue;
+        }
+
+        diffs.push(TopicDiff {
+            topic: topic.name,
+            status,
+            added: diff.added,
+            removed: diff.removed,
+            changed: diff.changed.into_iter().map(|(before, after)| WordChange { before, after }).collect(),
+        });
+    }
+
+    diffs.extend(old.into_iter().flatten().map(|topic| TopicDiff {
+        topic: topic.name,
+        status: TopicStatus::Removed,
+        added: Vec::new(),
+        removed: topic.words,
+        changed: Vec::new(),
+    }));
+
+    diffs
+}
+
+impl SheetDiff {
+    pub fn write<W: Write>(&self, format: DiffFormat, style: OutputStyle, mut writer: W) -> Result<(), Box<dyn Error>> {
+        match format {
+            DiffFormat::Json => writeln!(writer, "{}", serde_json::to_string_pretty(self)?)?,
+            DiffFormat::Text => self.write_text(style, &mut writer)?,
+        }
+
+        Ok(())
+    }
+
+    /// one block per topic, `+` added, `-` removed and `~` changed words
+    fn write_text<W: Write>(&self, style: OutputStyle, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        if self.topics.is_empty() {
+            writeln!(writer, "No changes between {} and {}", self.old, self.new)?;
+            return Ok(());
+        }
+
+        writeln!(writer, "Changes from {} to {}", self.old, self.new)?;
+
+        for topic in &self.topics {
+            let label = match topic.status {
+                TopicStatus::Added => " (new topic)",
+                TopicStatus::Removed => " (topic removed)",
+                TopicStatus::Changed => "",
+            };
+
+            writeln!(writer)?;
+            writeln!(
+                writer, "{}{}: {}",
+                style.paint(&topic.topic, Color::Bold), label,
+                counts(topic.added.len(), topic.removed.len(), topic.changed.len())
+            )?;
+
+            for word in &topic.added {
+                writeln!(writer, "  {}", style.paint(format!("+ {}", describe(word)), Color::Green))?;
+            }
+            for word in &topic.removed {
+                writeln!(writer, "  {}", style.paint(format!("- {}", describe(word)), Color::Red))?;
+            }
+            for change in &topic.changed {
+                writeln!(writer, "  {}", style.paint(format!("~ {}", describe_change(change)), Color::Yellow))?;
+            }
+        }
+
+        let total = |count: fn(&TopicDiff) -> usize| self.topics.iter().map(count).sum::<usize>();
+
+        writeln!(writer)?;
+        writeln!(
+            writer, "{} topics: {}",
+            self.topics.len(),
+            counts(total(|t| t.added.len()), total(|t| t.removed.len()), total(|t| t.changed.len()))
+        )?;
+
+        Ok(())
+    }
+}
+
+/// `3 added, 1 removed, 0 changed`
+fn counts(added: usize, removed: usize, changed: usize) -> String {
+    format!("{} added, {} removed, {} changed", added, removed, changed)
+}
+
+/// `りんご [林檎] = apple`
+fn describe(word: &Word) -> String {
+    if word.kanji().is_empty() {
+        format!("{} = {}", word.japanese(), word.english())
+    } else {
+        format!("{} [{}] = {}", word.japanese(), word.kanji(), word.english())
+    }
+}
+
+/// `みず: english "water" -> "cold water"`
+fn describe_change(change: &WordChange) -> String {
+    let (before, after) = (&change.before, &change.after);
+
+    let fields: Vec<String> = [("english", before.english(), after.english()), ("kanji", before.kanji(), after.kanji())]
+        .into_iter()
+        .filter(|(_, before, after)| before != after)
+        .map(|(name, before, after)| format!("{} \"{}\" -> \"{}\"", name, before, after))
+        .collect();
+
+    format!("{}: {}", after.japanese(), fields.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topic(name: &str, words: &[(&str, &str, &str)]) -> Topic {
+        Topic {
+            name: name.to_string(),
+            words: words.iter().map(|&(japanese, english, kanji)| Word::new(japanese, english, kanji)).collect(),
+        }
+    }
+
+    #[test]
+    fn topics_match_by_name_and_words_by_reading() {
+        let old = vec![
+            topic("Food", &[("りんご", "apple", "林檎"), ("みず", "water", ""), ("ぱん", "bread", "")]),
+            topic("Verbs", &[("たべる", "to eat", "食べる")]),
+            topic("Colours", &[("あか", "red", "赤")]),
+        ];
+        // topics moved around, Colours is gone and Weather is new
+        let new = vec![
+            topic("Weather", &[("あめ", "rain", "雨")]),
+            topic("Verbs", &[("たべる", "to eat", "食べる")]),
+            topic("food", &[("みず", "water", "水"), ("りんご", "apple", "林檎"), ("ぎゅうにゅう", "milk", "牛乳")]),
+        ];
+
+        let diffs = diff_topics(old, new, HeaderMatching::Normalized);
+        let summary: Vec<(&str, TopicStatus, usize, usize, usize)> = diffs.iter()
+            .map(|d| (d.topic.as_str(), d.status, d.added.len(), d.removed.len(), d.changed.len()))
+            .collect();
+
+        assert_eq!(summary, vec![
+            ("Weather", TopicStatus::Added, 1, 0, 0),
+            ("food", TopicStatus::Changed, 1, 1, 1),
+            ("Colours", TopicStatus::Removed, 0, 1, 0),
+        ]);
+        assert_eq!(describe_change(&diffs[1].changed[0]), "みず: kanji \"\" -> \"水\"");
+    }
+}
@@ -0,0 +1,256 @@
+use std::{error::Error, fs, path::Path};
+
+use encoding_rs::{Encoding, EUC_JP, SHIFT_JIS, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+
+// ============================================================================================
+//                                      Input Encoding
+// ============================================================================================
+
+// Spreadsheets saved from Excel on a Japanese Windows install come out as Shift_JIS, and
+// csv files that went through the wrong program once come back as mojibake (ã‚Š, 縺ゅ).
+// Both import "fine" and only show up as garbage cards days later, so the input is decoded
+// strictly and scanned for the usual kinds of garbling before anything gets parsed.
+
+/// encodings worth guessing for a file that isn't what it was read as
+const CANDIDATES: &[&Encoding] = &[UTF_8, SHIFT_JIS, EUC_JP, WINDOWS_1252];
+
+/// encodings that turn utf-8 into the classic mojibake when utf-8 is opened as them and saved again
+const MISREAD_AS: &[&Encoding] = &[WINDOWS_1252, SHIFT_JIS];
+
+/// what the lead bytes of utf-8 kana (E3 81, E3 82, E3 83) become when read as shift_jis,
+/// the odd byte left over is usually lost so these lines can't be undone, only recognised
+const SHIFT_JIS_KANA_MOJIBAKE: &[char] = &['縺', '繧', '繝'];
+
+/// look up an `--encoding` label (`shift_jis`, `sjis`, `euc-jp`, `utf-16le`, `windows-1252`, ...)
+pub fn parse_encoding(label: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(label.trim().as_bytes())
+        .ok_or_else(|| format!("Unknown encoding '{}', expected e.g. utf-8, shift_jis, euc-jp, utf-16le", label))
+}
+
+/// Text read by [`read_input`] and the name it goes by
+pub struct Input {
+    pub text: String,
+    /// the file's path, `<sheet title>.csv` (in the working directory) for a google sheet
+    pub name: String,
+}
+
+impl Input {
+    /// `lists/words.csv` -> `words`
+    pub fn stem(&self) -> String {
+        Path::new(&self.name)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.name.clone())
+    }
+
+    /// where output made from the input goes unless told otherwise, `words.txt` -> `words.csv`
+    pub fn default_output(&self, extension: &str) -> String {
+        Path::new(&self.name).with_extension(extension).display().to_string()
+    }
+}
+
+/// Read a text file as `encoding` (utf-8 when `None`), a BOM overrides it
+///
+/// refuses bytes that aren't valid in the encoding (with a guess at the real one),
+/// and text that decodes but looks like mojibake. a google sheet link is downloaded instead,
+/// and named after the sheet
+pub fn read_input(path: &str, encoding: Option<&'static Encoding>) -> Result<Input, Box<dyn Error>> {
+    // a shared google sheet is downloaded as utf-8 csv, there's no encoding to get wrong
+    if is_google_sheet_link(path) {
+        let sheet = csv_partitioner::download_google_sheet(path)
+            .map_err(|e| format!("Couldn't download {}: {}", path, e))?;
+        let title = sheet.title.as_deref().unwrap_or("google-sheet");
+
+        return Ok(Input { text: sheet.csv, name: format!("{}.csv", file_name_safe(title)) });
+    }
+
+    let bytes = fs::read(path)?;
+    let (text, used, had_errors) = encoding.unwrap_or(UTF_8).decode(&bytes);
+
+    if had_errors {
+        let line = first_bad_line(&text);
+        let hint = match guess_encoding(&bytes, used) {
+            Some((guess, sample)) => format!(
+                "It looks like {} (\"{}\"), try --encoding {}",
+                guess.name(), sample, guess.name().to_lowercase()
+            ),
+            None => "Re-save it as \"CSV UTF-8\" or pass its encoding with --encoding".to_string(),
+        };
+
+        return Err(format!("{} isn't valid {} (first unreadable text on line {}). {}", path, used.name(), line, hint).into());
+    }
+
+    if let Some(garbled) = find_mojibake(&text) {
+        return Err(format!("{} looks garbled: {}", path, garbled.describe(used)).into());
+    }
+
+    Ok(Input { text: text.into_owned(), name: path.to_string() })
+}
+
+/// `true` for a docs.google.com spreadsheet link, which is read instead of a file
+pub fn is_google_sheet_link(path: &str) -> bool {
+    path.starts_with("https://docs.google.com/spreadsheets/") || path.starts_with("docs.google.com/spreadsheets/")
+}
+
+/// a sheet title usable as a file name, characters windows or unix refuse become `-`
+fn file_name_safe(title: &str) -> String {
+    title.trim()
+        .chars()
+        .map(|c| if c.is_control() || "/\\:*?\"<>|".contains(c) { '-' } else { c })
+        .collect()
+}
+
+/// A line that decoded fine but is probably mangled text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mojibake {
+    /// the file already holds U+FFFD, whatever was there is gone
+    ///
+    /// `misread_as` is set when the rest of the line looks like utf-8 that was opened as it
+    ReplacementCharacters { line: usize, misread_as: Option<&'static Encoding> },
+
+    /// utf-8 text that was opened as `misread_as` and saved again, `original` is what it said
+    Misread { line: usize, misread_as: &'static Encoding, garbled: String, original: String },
+}
+
+impl Mojibake {
+    /// explanation and fix, `used` is the encoding the file was read as
+    pub fn describe(&self, used: &'static Encoding) -> String {
+        match self {
+            Mojibake::ReplacementCharacters { line, misread_as: None } => format!(
+                "line {} contains \u{FFFD} (characters that were already lost when the file was saved). \
+                Export it again from the original spreadsheet as \"CSV UTF-8\"", line
+            ),
+            Mojibake::ReplacementCharacters { line, misread_as: Some(misread_as) } => format!(
+                "line {} contains \u{FFFD} and looks like UTF-8 that got opened as {} and saved again. \
+                Export it again from the original spreadsheet as \"CSV UTF-8\"", line, misread_as.name()
+            ),
+            Mojibake::Misread { line, misread_as, garbled, original } if *misread_as == used => format!(
+                "line {} reads \"{}\" but would be \"{}\" as UTF-8. \
+                The file isn't {}, drop --encoding (or pass --encoding utf-8)", line, garbled, original, used.name()
+            ),
+            Mojibake::Misread { line, misread_as, garbled, original } => format!(
+                "line {} reads \"{}\" but probably said \"{}\". The file was UTF-8, got opened as {} and saved again. \
+                Export it again from the original spreadsheet as \"CSV UTF-8\"", line, garbled, original, misread_as.name()
+            ),
+        }
+    }
+}
+
+/// first line showing signs of mojibake, lines are numbered from 1
+pub fn find_mojibake(text: &str) -> Option<Mojibake> {
+    for (idx, line) in text.lines().enumerate() {
+        if line.contains('\u{FFFD}') {
+            let misread_as = line.contains(SHIFT_JIS_KANA_MOJIBAKE).then_some(SHIFT_JIS);
+            return Some(Mojibake::ReplacementCharacters { line: idx + 1, misread_as });
+        }
+
+        if line.is_ascii() {
+            continue;
+        }
+
+        for &misread_as in MISREAD_AS {
+            if let Some(original) = undo_misread(line, misread_as) {
+                return Some(Mojibake::Misread { line: idx + 1, misread_as, garbled: line.to_string(), original });
+            }
+        }
+    }
+
+    None
+}
+
+/// the utf-8 text `line` used to be before it was decoded as `misread_as`, if it looks like it was
+fn undo_misread(line: &str, misread_as: &'static Encoding) -> Option<String> {
+    let (bytes, _, unmappable) = misread_as.encode(line);
+    if unmappable {
+        return None;
+    }
+
+    // genuine shift_jis/latin text is almost never valid utf-8 once encoded back,
+    // requiring japanese in the result rules out the rest
+    let original = std::str::from_utf8(&bytes).ok()?;
+    (original != line && japanese_chars(original) > 0).then(|| original.to_string())
+}
+
+/// guess the encoding of bytes that didn't decode as `tried`, with the first line that shows it
+fn guess_encoding(bytes: &[u8], tried: &'static Encoding) -> Option<(&'static Encoding, String)> {
+    let candidates: Vec<&'static Encoding> = if bytes.contains(&0) {
+        // commas and newlines are nul-padded in utf-16 (other encodings never contain nul),
+        // which side of the byte the nul is on gives the byte order
+        let odd_nuls = bytes.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+        let even_nuls = bytes.iter().step_by(2).filter(|&&b| b == 0).count();
+        vec![if odd_nuls >= even_nuls { UTF_16LE } else { UTF_16BE }]
+    } else {
+        CANDIDATES.to_vec()
+    };
+
+    candidates.into_iter()
+        .filter(|&candidate| candidate != tried)
+        .filter_map(|candidate| {
+            let text = candidate.decode_without_bom_handling_and_without_replacement(bytes)?;
+            Some((candidate, japanese_chars(&text), text))
+        })
+        // most japanese wins, ties go to the later candidate (windows-1252 decodes almost anything)
+        .max_by_key(|(_, score, _)| *score)
+        .map(|(candidate, _, text)| {
+            let sample = text.lines()
+                .find(|line| !line.is_ascii())
+                .unwrap_or_default()
+                .chars()
+                .take(40)
+                .collect();
+            (candidate, sample)
+        })
+}
+
+/// line number (from 1) of the first U+FFFD in decoded text
+fn first_bad_line(text: &str) -> usize {
+    text.lines().position(|line| line.contains('\u{FFFD}')).map_or(1, |idx| idx + 1)
+}
+
+/// kana and kanji (not halfwidth katakana, which is what misdecoded bytes tend to become)
+fn japanese_chars(text: &str) -> usize {
+    text.chars()
+        .filter(|c| matches!(c, '\u{3040}'..='\u{30FF}' | '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}'))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf8_read_as_windows_1252_is_undone() {
+        let (bytes, _, _) = UTF_8.encode("りんご,apple,林檎");
+        let (garbled, _, _) = WINDOWS_1252.decode(&bytes);
+
+        match find_mojibake(&format!("Food,,\n{}\n", garbled)) {
+            Some(Mojibake::Misread { line, misread_as, original, .. }) => {
+                assert_eq!((line, misread_as, original.as_str()), (2, WINDOWS_1252, "りんご,apple,林檎"));
+            },
+            other => panic!("expected a windows-1252 misread, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn clean_text_is_left_alone() {
+        assert_eq!(find_mojibake("Food,,\nりんご,apple,林檎\ncafé,coffee,\nÃ©clair,éclair,\n"), None);
+    }
+
+    #[test]
+    fn shift_jis_bytes_are_recognised() {
+        let (bytes, _, _) = SHIFT_JIS.encode("Food,,\nりんご,apple,林檎\n");
+
+        let (guess, sample) = guess_encoding(&bytes, UTF_8).unwrap();
+        assert_eq!((guess, sample.as_str()), (SHIFT_JIS, "りんご,apple,林檎"));
+    }
+
+    #[test]
+    fn outputs_are_named_after_the_input() {
+        let file = Input { text: String::new(), name: "lists/words.txt".to_string() };
+        assert_eq!((file.stem().as_str(), file.default_output("csv").as_str()), ("words", "lists/words.csv"));
+
+        // a sheet's output lands in the working directory, named after its title
+        let sheet = Input { text: String::new(), name: format!("{}.csv", file_name_safe("N5 / N4 - Sheet1")) };
+        assert_eq!((sheet.stem().as_str(), sheet.default_output("html").as_str()), ("N5 - N4 - Sheet1", "N5 - N4 - Sheet1.html"));
+    }
+}
@@ -0,0 +1,188 @@
+use std::error::Error;
+use std::sync::mpsc::Receiver;
+use std::thread::{self, JoinHandle};
+
+use eframe::egui;
+
+use crate::cli::{ConnectionOptions, InputOptions, NoteOptions};
+use crate::import_with_progress;
+use crate::preflight::Limits;
+use crate::progress::{self, ProgressEvent};
+use crate::card::FrontStrategy;
+use crate::vocab_importer::ImportResult;
+
+// ============================================================================================
+//                                      Desktop GUI
+// ============================================================================================
+
+/// open the importer window (blocks until it is closed)
+pub fn run(connection: ConnectionOptions) -> Result<(), Box<dyn Error>> {
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default().with_inner_size([560.0, 640.0]),
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "csv-to-anki",
+        options,
+        Box::new(move |_cc| Ok(Box::new(ImporterApp { connection, ..ImporterApp::default() }))),
+    )
+    .map_err(|e| format!("GUI error: {}", e).into())
+}
+
+/// an import running on a worker thread, reporting through the progress channel
+struct RunningImport {
+    receiver: Receiver<ProgressEvent>,
+    handle: JoinHandle<Result<Vec<ImportResult>, String>>,
+}
+
+#[derive(Default)]
+struct ImporterApp {
+    path: String,
+    deck_name: String,
+    input: InputOptions,
+    notes: NoteOptions,
+    connection: ConnectionOptions,
+    log: Vec<String>,
+    running: Option<RunningImport>,
+    outcome: Option<Result<Vec<ImportResult>, String>>,
+}
+
+impl ImporterApp {
+    fn start_import(&mut self) {
+        let (progress, receiver) = progress::channel();
+        let (path, deck_name) = (self.path.clone(), self.deck_name.clone());
+        let (input, notes, connection) = (self.input.clone(), self.notes.clone(), self.connection.clone());
+
+        let handle = thread::spawn(move || {
+            // the window has nowhere to ask for confirmation, its import button is the confirmation
+            import_with_progress(&path, deck_name, &input, &notes, Limits::unlimited(), &connection, progress)
+                .map_err(|e| e.to_string())
+        });
+
+        self.log.clear();
+        self.outcome = None;
+        self.running = Some(RunningImport { receiver, handle });
+    }
+
+    /// drain pending progress events and collect the result once the worker finishes
+    fn poll_import(&mut self) {
+        let Some(running) = &self.running else {
+            return;
+        };
+
+        self.log.extend(running.receiver.try_iter().map(|event| event.to_string()));
+
+        if running.handle.is_finished() {
+            let running = self.running.take().expect("checked above");
+            self.log.extend(running.receiver.try_iter().map(|event| event.to_string()));
+
+            self.outcome = Some(
+                running.handle.join().unwrap_or_else(|_| Err("Import thread panicked".to_string()))
+            );
+        }
+    }
+
+    fn accept_dropped_file(&mut self, ctx: &egui::Context) {
+        let dropped = ctx.input(|i| i.raw.dropped_files.first().and_then(|file| file.path.clone()));
+
+        if let Some(path) = dropped {
+            self.path = path.display().to_string();
+        }
+    }
+
+    fn show_outcome(&self, ui: &mut egui::Ui) {
+        match &self.outcome {
+            Some(Ok(results)) => {
+                let added: usize = results.iter().map(|r| r.added).sum();
+                let duplicates: usize = results.iter().map(|r| r.duplicates).sum();
+                let errors: usize = results.iter().map(|r| r.errors).sum();
+
+                ui.heading("Import complete");
+                ui.label(format!("Successfully added: {}", added));
+                ui.label(format!("Duplicates skipped: {}", duplicates));
+                ui.label(format!("Errors: {}", errors));
+            },
+
+            Some(Err(e)) => {
+                ui.colored_label(egui::Color32::RED, format!("Import failed: {}", e));
+            },
+
+            None => {},
+        }
+    }
+}
+
+impl eframe::App for ImporterApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.accept_dropped_file(ctx);
+        self.poll_import();
+
+        let running = self.running.is_some();
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("CSV to Anki");
+            ui.label("Drop a vocabulary CSV onto this window, or type its path.");
+            ui.add_space(8.0);
+
+            egui::Grid::new("inputs").num_columns(2).show(ui, |ui| {
+                ui.label("CSV file");
+                ui.text_edit_singleline(&mut self.path);
+                ui.end_row();
+
+                ui.label("Deck name");
+                ui.text_edit_singleline(&mut self.deck_name);
+                ui.end_row();
+
+                ui.label("Note type");
+                ui.text_edit_singleline(&mut self.notes.model);
+                ui.end_row();
+
+                ui.label("Card front");
+                egui::ComboBox::from_id_salt("front_strategy")
+                    .selected_text(format!("{:?}", self.notes.front_strategy))
+                    .show_ui(ui, |ui| {
+                        for strategy in [
+                            FrontStrategy::KanjiPreferred,
+                            FrontStrategy::KanaAlways,
+                            FrontStrategy::KanjiWithFurigana,
+                            FrontStrategy::EnglishFirst,
+                        ] {
+                            ui.selectable_value(&mut self.notes.front_strategy, strategy, format!("{:?}", strategy));
+                        }
+                    });
+                ui.end_row();
+            });
+
+            ui.checkbox(&mut self.input.keep_empty_rows, "Keep empty rows");
+            ui.checkbox(&mut self.input.exact_headers, "Exact topic name matching");
+            ui.checkbox(&mut self.notes.flat_deck, "One flat deck (topics kept in tags)");
+            ui.add_space(8.0);
+
+            let ready = !running && !self.path.trim().is_empty() && !self.deck_name.trim().is_empty();
+            if ui.add_enabled(ready, egui::Button::new("Import")).clicked() {
+                self.start_import();
+            }
+
+            if running {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Importing... (Anki must stay open)");
+                });
+            }
+
+            self.show_outcome(ui);
+            ui.separator();
+
+            egui::ScrollArea::vertical().stick_to_bottom(true).show(ui, |ui| {
+                for line in &self.log {
+                    ui.monospace(line);
+                }
+            });
+        });
+
+        if running {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use std::{collections::HashMap, error::Error, str::FromStr};
+
+use crate::parse::{Topic, Word};
+
+// ============================================================================================
+//                                      Joining Extra Data
+// ============================================================================================
+
+// Readings, audio files or example sentences are often kept in a separate sheet, one row
+// per word. Joining it onto the vocabulary by a key column puts every other column of that
+// sheet on the matching word's note, named after the column header (so the note type needs
+// fields with those names).
+
+/// Which part of a word is looked up in the join file's first column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JoinKey {
+    #[default]
+    Kana,
+    Kanji,
+    English,
+}
+
+impl JoinKey {
+    fn of<'w>(&self, word: &'w Word) -> &'w str {
+        match self {
+            JoinKey::Kana => word.japanese(),
+            JoinKey::Kanji => word.kanji(),
+            JoinKey::English => word.english(),
+        }
+        .trim()
+    }
+}
+
+impl FromStr for JoinKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "kana" | "japanese" => Ok(JoinKey::Kana),
+            "kanji" => Ok(JoinKey::Kanji),
+            "english" => Ok(JoinKey::English),
+            other => Err(format!("Unknown join key '{}', expected one of: kana, kanji, english", other)),
+        }
+    }
+}
+
+/// What happens to words without a row in the join file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JoinType {
+    /// they're imported without the extra fields
+    #[default]
+    Left,
+
+    /// they're left out
+    Inner,
+}
+
+impl FromStr for JoinType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "left" => Ok(JoinType::Left),
+            "inner" => Ok(JoinType::Inner),
+            other => Err(format!("Unknown join type '{}', expected one of: left, inner", other)),
+        }
+    }
+}
+
+/// `--join`: which file to join and how
+#[derive(Debug, Clone)]
+pub struct JoinOptions {
+    pub path: String,
+    pub key: JoinKey,
+    pub join_type: JoinType,
+}
+
+/// A join file: extra columns by key
+#[derive(Debug, Clone, Default)]
+pub struct JoinTable {
+    /// headers of the extra columns (every column but the key)
+    columns: Vec<String>,
+    rows: HashMap<String, Vec<String>>,
+}
+
+impl JoinTable {
+    /// read a csv whose first column is the key and whose header row names the note fields
+    pub fn parse(contents: &str, delimiter: u8) -> Result<Self, Box<dyn Error>> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(contents.as_bytes());
+
+        let columns: Vec<String> = reader.headers()?.iter().skip(1).map(str::to_string).collect();
+        if columns.is_empty() {
+            return Err("The join file needs a key column and at least one more column".into());
+        }
+        if let Some(idx) = columns.iter().position(|name| name.is_empty()) {
+            return Err(format!("Column {} of the join file has no header, headers name the note fields", idx + 2).into());
+        }
+
+        let mut rows: HashMap<String, Vec<String>> = HashMap::new();
+        let mut first_row: HashMap<String, usize> = HashMap::new();
+
+        for (row, record) in reader.records().enumerate() {
+            let record = record?;
+            let key = record.get(0).unwrap_or("").to_string();
+            if key.is_empty() {
+                continue;
+            }
+
+            // rows are numbered like a spreadsheet, the header is row 1
+            if let Some(first) = first_row.insert(key.clone(), row + 2) {
+                return Err(format!("'{}' is in the join file twice (rows {} and {})", key, first, row + 2).into());
+            }
+
+            rows.insert(key, (1..=columns.len()).map(|i| record.get(i).unwrap_or("").to_string()).collect());
+        }
+
+        Ok(JoinTable { columns, rows })
+    }
+
+    /// names of the fields the join adds
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+}
+
+/// How many words found a row
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JoinReport {
+    pub matched: usize,
+    pub unmatched: usize,
+}
+
+/// add the table's columns to every word whose key has a row, topics left empty by an inner join are dropped
+pub fn join_topics(topics: Vec<Topic>, table: &JoinTable, key: JoinKey, join_type: JoinType) -> (Vec<Topic>, JoinReport) {
+    let mut report = JoinReport::default();
+
+    let topics = topics.into_iter()
+        .filter_map(|topic| {
+            let words: Vec<Word> = topic.words.into_iter()
+                .filter_map(|word| match table.rows.get(key.of(&word)) {
+                    Some(values) => {
+                        report.matched += 1;
+                        Some(table.columns.iter().zip(values).fold(word, |word, (name, value)| word.with_field(name, value)))
+                    },
+                    None => {
+                        report.unmatched += 1;
+                        (join_type == JoinType::Left).then_some(word)
+                    },
+                })
+                .collect();
+
+            (!words.is_empty() || join_type == JoinType::Left).then_some(Topic { name: topic.name, words })
+        })
+        .collect();
+
+    (topics, report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extra_columns_land_on_matching_words() {
+        let table = JoinTable::parse("word,Audio,Example\nりんご,[sound:ringo.mp3],りんごを食べる\nねこ,[sound:neko.mp3],\n", b',').unwrap();
+        let topics = vec![Topic {
+            name: "Food".to_string(),
+            words: vec![Word::new("りんご", "apple", "林檎"), Word::new("みず", "water", "水")],
+        }];
+
+        let (joined, report) = join_topics(topics.clone(), &table, JoinKey::Kana, JoinType::Left);
+        assert_eq!(report, JoinReport { matched: 1, unmatched: 1 });
+        assert_eq!(joined[0].words[0].extra_fields(), &[
+            ("Audio".to_string(), "[sound:ringo.mp3]".to_string()),
+            ("Example".to_string(), "りんごを食べる".to_string()),
+        ]);
+        assert!(joined[0].words[1].extra_fields().is_empty());
+
+        let (joined, _) = join_topics(topics, &table, JoinKey::Kana, JoinType::Inner);
+        assert_eq!(joined[0].words.len(), 1);
+    }
+
+    #[test]
+    fn repeated_keys_are_refused() {
+        let error = JoinTable::parse("word,Audio\nねこ,a.mp3\nねこ,b.mp3\n", b',').unwrap_err();
+        assert_eq!(error.to_string(), "'ねこ' is in the join file twice (rows 2 and 3)");
+    }
+}
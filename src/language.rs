@@ -0,0 +1,152 @@
+use crate::parse::{Topic, Word};
+
+// ============================================================================================
+//                                  Column Language Detection
+// ============================================================================================
+
+// Each slice should be (kana reading, english gloss, kanji). The most common mistakes
+// with these sheets are swapping the reading and gloss columns, or the reading and
+// kanji columns, so every topic is checked by which script its columns are written in.
+
+/// Writing system of a piece of text, decided by the majority of its letters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Script {
+    Kana,
+    Kanji,
+    Latin,
+    Other,
+}
+
+/// A column mix-up detected in a topic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnIssue {
+    /// english in the reading column and japanese in the gloss column
+    GlossSwapped,
+
+    /// kanji in the reading column and kana in the kanji column
+    KanjiSwapped,
+}
+
+impl ColumnIssue {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            ColumnIssue::GlossSwapped => "the japanese and english columns look swapped",
+            ColumnIssue::KanjiSwapped => "the reading and kanji columns look swapped",
+        }
+    }
+}
+
+/// classify text by counting letters per script (punctuation, digits and spaces are ignored)
+pub fn script_of(text: &str) -> Script {
+    let (mut kana, mut kanji, mut latin, mut other) = (0, 0, 0, 0);
+
+    for c in text.chars().filter(|c| c.is_alphabetic()) {
+        match c {
+            '\u{3040}'..='\u{30FF}' | '\u{FF66}'..='\u{FF9F}' => kana += 1,
+            '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' | '々' => kanji += 1,
+            c if c.is_ascii_alphabetic() || ('\u{00C0}'..='\u{024F}').contains(&c) => latin += 1,
+            _ => other += 1,
+        }
+    }
+
+    // a single kanji makes a word "kanji", readings are all kana
+    if kanji > 0 && kanji + kana >= latin {
+        Script::Kanji
+    } else if kana > 0 && kana >= latin {
+        Script::Kana
+    } else if latin > 0 && latin >= other {
+        Script::Latin
+    } else {
+        Script::Other
+    }
+}
+
+/// whether most non-empty cells are written in a matching script (false for an empty column)
+fn mostly(texts: impl Iterator<Item = String>, matches: impl Fn(Script) -> bool) -> bool {
+    let scripts: Vec<Script> = texts
+        .filter(|text| !text.trim().is_empty())
+        .map(|text| script_of(&text))
+        .collect();
+
+    scripts.iter().filter(|script| matches(**script)).count() * 2 > scripts.len()
+}
+
+/// look for a column mix-up in a topic, only reported when most of the topic agrees
+pub fn detect_issue(topic: &Topic) -> Option<ColumnIssue> {
+    let column = |get: fn(&Word) -> &String| topic.words().iter().map(move |word| get(word).clone());
+    let japanese = |script: Script| matches!(script, Script::Kana | Script::Kanji);
+
+    if mostly(column(Word::japanese), |s| s == Script::Latin) && mostly(column(Word::english), japanese) {
+        return Some(ColumnIssue::GlossSwapped);
+    }
+
+    if mostly(column(Word::japanese), |s| s == Script::Kanji) && mostly(column(Word::kanji), |s| s == Script::Kana) {
+        return Some(ColumnIssue::KanjiSwapped);
+    }
+
+    None
+}
+
+/// swap the columns back for every word in the topic
+pub fn fix_topic(topic: Topic, issue: ColumnIssue) -> Topic {
+    let words = topic.words.into_iter()
+        .map(|word| match issue {
+            ColumnIssue::GlossSwapped => Word::new(word.english().as_str(), word.japanese().as_str(), word.kanji().as_str()),
+            ColumnIssue::KanjiSwapped => Word::new(word.kanji().as_str(), word.english().as_str(), word.japanese().as_str()),
+        })
+        .collect();
+
+    Topic { name: topic.name, words }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topic(words: &[(&str, &str, &str)]) -> Topic {
+        Topic {
+            name: "Food".to_string(),
+            words: words.iter().map(|(kana, english, kanji)| Word::new(*kana, *english, *kanji)).collect(),
+        }
+    }
+
+    #[test]
+    fn each_script_is_recognised() {
+        assert_eq!(script_of("りんご"), Script::Kana);
+        assert_eq!(script_of("コーヒー"), Script::Kana);
+        assert_eq!(script_of("ｺｰﾋｰ"), Script::Kana);
+        assert_eq!(script_of("食べる"), Script::Kanji);
+        assert_eq!(script_of("人々"), Script::Kanji);
+        assert_eq!(script_of("to eat (formal)"), Script::Latin);
+        assert_eq!(script_of("café"), Script::Latin);
+        assert_eq!(script_of("사과"), Script::Other);
+    }
+
+    #[test]
+    fn text_without_letters_counts_as_other() {
+        assert_eq!(script_of(""), Script::Other);
+        assert_eq!(script_of("123 - !"), Script::Other);
+        assert_eq!(script_of("Tシャツ"), Script::Kana);
+    }
+
+    #[test]
+    fn well_formed_topics_have_no_issue() {
+        let words = topic(&[("りんご", "apple", "林檎"), ("みず", "water", "水"), ("パン", "bread", "")]);
+        assert_eq!(detect_issue(&words), None);
+        assert_eq!(detect_issue(&topic(&[])), None);
+    }
+
+    #[test]
+    fn swapped_columns_are_found_and_fixed() {
+        let swapped = topic(&[("apple", "りんご", "林檎"), ("water", "みず", "水"), ("bread", "パン", "")]);
+        assert_eq!(detect_issue(&swapped), Some(ColumnIssue::GlossSwapped));
+        assert_eq!(detect_issue(&fix_topic(swapped, ColumnIssue::GlossSwapped)), None);
+
+        let swapped = topic(&[("林檎", "apple", "りんご"), ("水", "water", "みず")]);
+        assert_eq!(detect_issue(&swapped), Some(ColumnIssue::KanjiSwapped));
+
+        let fixed = fix_topic(swapped, ColumnIssue::KanjiSwapped);
+        assert_eq!(fixed.words()[0], Word::new("りんご", "apple", "林檎"));
+        assert_eq!(detect_issue(&fixed), None);
+    }
+}
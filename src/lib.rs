@@ -0,0 +1,22 @@
+//! The parts of csv-to-anki that don't talk to anki: reading spreadsheets into topics and
+//! turning words into cards. The command line tool is built on top of it, and with the
+//! `wasm` feature it compiles to a browser module that previews a deck (see `web/`).
+//!
+//! Features:
+//! - `cli` (default): the `csv-to-anki` binary and its AnkiConnect client. Nothing in the
+//!   library needs it, depend with `default-features = false` to skip reqwest and friends
+//! - `gui`: the desktop window, implies `cli`
+//! - `apkg`: `--output deck.apkg` writes a deck file instead of using AnkiConnect, implies `cli`
+//! - `wasm`: the browser preview bindings
+
+pub mod card;
+pub mod conjugation;
+pub mod language;
+pub mod parse;
+pub mod preview;
+pub mod roles;
+pub mod sanitize;
+pub mod tags;
+
+#[cfg(feature = "wasm")]
+mod wasm;
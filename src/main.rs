@@ -1,15 +1,42 @@
-#[allow(dead_code)]
+use std::{error::Error, env, fs::File, ops::Range, path::Path, thread};
 
-use std::{error::Error, env};
-
-mod parse;
+mod output;
 mod anki;
+#[cfg(feature = "apkg")]
+mod apkg;
+mod backup;
+mod cli;
+mod config;
+mod diff;
+mod encoding;
+#[cfg(feature = "gui")]
+mod gui;
+mod join;
+mod preflight;
+mod progress;
+mod queue;
+mod report;
+mod scaffold;
+mod sheet;
+mod state;
 mod vocab_importer;
 
-use csv_partitioner::{CsvSliceParser, FromColumnSlice};
-
-use crate::parse::{Topic, Word};
-use crate::vocab_importer::{ImportResult, JapaneseVocabImporter};
+use csv_partitioner::{ColumnType, SampleStrategy};
+use csv_to_anki::{card, conjugation, language, parse, roles, sanitize, tags};
+
+use crate::anki::Note;
+use crate::cli::{Cli, Command, ConnectionOptions, InputOptions, NoteOptions};
+use crate::join::{JoinOptions, JoinTable, JoinType};
+use crate::diff::{DiffFormat, SheetDiff};
+use crate::encoding::Input;
+use crate::output::{Color, Glyph, OutputStyle};
+use crate::parse::Topic;
+use crate::preflight::{Estimate, Limits};
+use crate::progress::{ProgressEvent, ProgressSender};
+use crate::report::{Align, NumberFormat, Table};
+use crate::roles::ColumnRole;
+use crate::sheet::SheetFormat;
+use crate::vocab_importer::{ImportResult, JapaneseVocabImporter, LeechAction};
 
 // ============================================================================================
 //                                          csv-to-anki
@@ -22,30 +49,487 @@ fn main() -> Result<(), Box<dyn Error>> {
 }
 
 fn run() -> Result<(), Box<dyn Error>> {
-    let (path, deck_name) = get_inputs()?;
+    let Cli { command, style, connection } = cli::parse_args(env::args())?;
+
+    match command {
+        Command::Import { path, deck_name, output: Some(output), input, notes, .. } => {
+            run_apkg_export(&path, deck_name, &output, &input, &notes)
+        },
+        Command::Import { path, deck_name, output: None, input, notes, limits } => {
+            run_import(path, deck_name, &input, &notes, limits, &connection, style)
+        },
+        Command::Merge { deck_name, from_topic, into_topic, notes } => {
+            run_merge(deck_name, &from_topic, &into_topic, &notes, &connection)
+        },
+        Command::Prune { path, deck_name, input, notes } => run_prune(&path, deck_name, &input, &notes, &connection),
+        Command::RenameTag { from, into } => run_rename_tag(&from, &into, &connection),
+        Command::CleanMedia { pattern, delete } => run_clean_media(&pattern, delete, &connection),
+        Command::Render { path, deck_name, topic, rows, sample, input, notes } => {
+            run_render(&path, deck_name, topic.as_deref(), rows, sample, &input, &notes)
+        },
+        Command::Export { path, output, title, format, input, notes } => {
+            run_export(&path, output.as_deref(), title.as_deref(), format, &input, &notes)
+        },
+        Command::DiffCsv { old, new, format, output, input } => {
+            run_diff(old, new, format, output.as_deref(), &input, style)
+        },
+        Command::Schema { path, input } => run_schema(&path, &input, style),
+        Command::Scaffold { path, output, roles } => run_scaffold(&path, output.as_deref(), roles.as_deref()),
+        Command::Pause { deck_name } => {
+            queue::pause(&deck_name)?;
+            println!("Import into '{}' will pause before its next batch", deck_name);
+            Ok(())
+        },
+        Command::Resume { deck_name } => {
+            queue::resume(&deck_name)?;
+            println!("Import into '{}' resumed", deck_name);
+            Ok(())
+        },
+        Command::Status { deck_name } => {
+            match queue::status(&deck_name)? {
+                Some(status) => status.print_summary(),
+                None => println!("No import running into '{}'", deck_name),
+            }
+            Ok(())
+        },
+        Command::Leeches { deck_name, output, action, notes } => {
+            run_leeches(deck_name, &output, action, &notes, &connection)
+        },
+        Command::Restore { path } => run_restore(&path, &connection),
+        Command::StateDoctor { repair } => {
+            state::print_findings(&state::doctor(repair)?, repair, style);
+            Ok(())
+        },
+        Command::ShowConfig(config) => {
+            cli::show_config(&config);
+            Ok(())
+        },
+        Command::Gui => run_gui(connection),
+    }
+}
 
-    println!("Step 1: Parsing CSV file...");
-    let topics: Vec<Topic> = handle_parsing(&path)?;
+fn run_import(
+    path: String,
+    deck_name: String,
+    input: &InputOptions,
+    notes: &NoteOptions,
+    limits: Limits,
+    connection: &ConnectionOptions,
+    style: OutputStyle,
+) -> Result<(), Box<dyn Error>> {
+    let (progress, receiver) = progress::channel();
+    let printer = thread::spawn(move || progress::print_events(receiver, style));
 
-    println!("\nStep 2: Creating Anki importer...");
-    let importer = JapaneseVocabImporter::new(deck_name);
+    let results = import_with_progress(&path, deck_name, input, notes, limits, connection, progress);
+
+    // every sender has been dropped by now, so the printer drains the channel and exits
+    printer.join().map_err(|_| "Progress printer thread panicked")?;
+
+    display_import_results(results?, style);
+
+    Ok(())
+}
 
-    println!("\nStep 3: Initializing connection to Anki...");
+fn import_with_progress(
+    path: &str,
+    deck_name: String,
+    input: &InputOptions,
+    notes: &NoteOptions,
+    limits: Limits,
+    connection: &ConnectionOptions,
+    progress: ProgressSender,
+) -> Result<Vec<ImportResult>, Box<dyn Error>> {
+    progress.step(1, "Parsing CSV file...");
+    let topics: Vec<Topic> = with_generated_topics(handle_parsing(path, input, None, &progress)?, notes);
+    check_limits(&topics, path, input, limits, &progress)?;
+
+    progress.step(2, "Creating Anki importer...");
+    let importer = build_importer(deck_name, notes, connection).with_progress(progress.clone());
+
+    progress.step(3, "Initializing connection to Anki...");
     connect_to_anki(&importer)?;
 
-    println!("\nStep 4: Building sub-decks in Anki...");
+    progress.step(4, "Building sub-decks in Anki...");
     build_sub_decks(&importer, &topics)?;
 
-    println!("\nStep 5: Populating decks with vocabulary in Anki...");
-    let results: Vec<ImportResult> = importer.import_all_topics(&topics)?;
+    progress.step(5, "Populating decks with vocabulary in Anki...");
+    importer.import_all_topics(&topics)
+}
+
+fn run_merge(deck_name: String, from_topic: &str, into_topic: &str, notes: &NoteOptions, connection: &ConnectionOptions)
+    -> Result<(), Box<dyn Error>>
+{
+    let importer = build_importer(deck_name, notes, connection);
+
+    println!("Step 1: Initializing connection to Anki...");
+    connect_to_anki(&importer)?;
+
+    println!("\nStep 2: Backing up '{}'...", importer.deck_name());
+    backup_before_changes(&importer)?;
 
-    display_import_results(results);
+    println!("\nStep 3: Merging '{}' into '{}'...", from_topic, into_topic);
+    let result = importer.merge_topics(from_topic, into_topic)?;
+
+    result.print_summary();
 
     Ok(())
 }
 
+fn run_prune(path: &str, deck_name: String, input: &InputOptions, notes: &NoteOptions, connection: &ConnectionOptions)
+    -> Result<(), Box<dyn Error>>
+{
+    println!("Step 1: Parsing CSV file...");
+    let topics: Vec<Topic> = with_generated_topics(parse_showing_warnings(path, input, None)?, notes);
+    let importer = build_importer(deck_name, notes, connection);
+
+    println!("\nStep 2: Initializing connection to Anki...");
+    connect_to_anki(&importer)?;
+
+    println!("\nStep 3: Backing up '{}'...", importer.deck_name());
+    backup_before_changes(&importer)?;
+
+    println!("\nStep 4: Deleting notes that are no longer in {}...", path);
+    let result = importer.prune(&topics)?;
+
+    result.print_summary();
+
+    Ok(())
+}
+
+fn run_rename_tag(from: &str, into: &str, connection: &ConnectionOptions) -> Result<(), Box<dyn Error>> {
+    let client = connection.client();
+
+    client.check_connection()
+        .map_err(|e| format!("Cannot connect to to Anki. Is Anki running with AnkiConnect installed? Error: {}", e))?;
+
+    let retagged = client.rename_tag(from, into)?;
+    println!("Moved '{}' (and the tags under it) to '{}' on {} notes", from, into, retagged);
+
+    Ok(())
+}
+
+fn run_clean_media(pattern: &str, delete: bool, connection: &ConnectionOptions) -> Result<(), Box<dyn Error>> {
+    let client = connection.client();
+
+    client.check_connection()
+        .map_err(|e| format!("Cannot connect to to Anki. Is Anki running with AnkiConnect installed? Error: {}", e))?;
+
+    let unused = client.unused_media_files(pattern)?;
+    if unused.is_empty() {
+        println!("No unused media files matching '{}'", pattern);
+        return Ok(());
+    }
+
+    for filename in &unused {
+        if delete {
+            client.delete_media_file(filename)?;
+        }
+        println!("  {}", filename);
+    }
+
+    match delete {
+        true => println!("Deleted {} media files no note uses", unused.len()),
+        false => println!("{} media files no note uses, run again with --yes to delete them", unused.len()),
+    }
+
+    Ok(())
+}
+
+fn run_leeches(
+    deck_name: String,
+    output: &str,
+    action: Option<LeechAction>,
+    notes: &NoteOptions,
+    connection: &ConnectionOptions,
+) -> Result<(), Box<dyn Error>> {
+    let importer = build_importer(deck_name, notes, connection);
+
+    println!("Step 1: Initializing connection to Anki...");
+    connect_to_anki(&importer)?;
+
+    println!("\nStep 2: Finding leeches...");
+    let report = importer.find_leeches()?;
+    report.print_summary();
+
+    if report.topics.is_empty() {
+        println!("\nNo leeches, nothing to export.");
+        return Ok(());
+    }
+
+    scaffold::write_topics_csv(&report.topics, File::create(output)?)?;
+    println!("\nWrote problem words to {}", output);
+
+    if let Some(action) = action {
+        println!("\nStep 3: Backing up '{}'...", importer.deck_name());
+        backup_before_changes(&importer)?;
+
+        println!("\nStep 4: Applying {:?} to {} cards...", action, report.card_ids.len());
+        importer.treat_leeches(&report, &action)?;
+    }
+
+    Ok(())
+}
+
+fn run_restore(path: &str, connection: &ConnectionOptions) -> Result<(), Box<dyn Error>> {
+    let client = connection.client();
+
+    println!("Restoring {}...", path);
+    backup::restore(&client, Path::new(path))?;
+    println!("Done, check the deck in Anki");
+
+    Ok(())
+}
+
+fn backup_before_changes(importer: &JapaneseVocabImporter) -> Result<(), Box<dyn Error>> {
+    let path = backup::backup_deck(&importer.client, importer.deck_name())?;
+    println!("Saved backup to {}", path.display());
+    println!("(undo with `csv-to-anki restore \"{}\"`)", path.display());
+
+    Ok(())
+}
+
+/// build the notes an import would add and write them to an .apkg instead, anki doesn't have to run
+#[cfg(feature = "apkg")]
+fn run_apkg_export(
+    path: &str,
+    deck_name: String,
+    output: &str,
+    input: &InputOptions,
+    notes: &NoteOptions,
+) -> Result<(), Box<dyn Error>> {
+    let topics: Vec<Topic> = with_generated_topics(parse_showing_warnings(path, input, None)?, notes);
+    let importer = build_importer(deck_name, notes, &ConnectionOptions::default());
+
+    let mut built = Vec::new();
+    for topic in &topics {
+        for (idx, word) in topic.words().iter().enumerate() {
+            match importer.word_to_note(word, topic.name()) {
+                Ok(note) => built.push(note),
+                Err(e) => println!("[{} #{}] Skipped: {}", topic.name(), idx + 1, e),
+            }
+        }
+    }
+
+    let written = apkg::write_apkg(Path::new(output), importer.deck_name(), &notes.model, &built)?;
+    println!("Wrote {} notes in {} topics to {}, open it in anki to import", written, topics.len(), output);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "apkg"))]
+fn run_apkg_export(_path: &str, _deck_name: String, _output: &str, _input: &InputOptions, _notes: &NoteOptions) -> Result<(), Box<dyn Error>> {
+    Err("csv-to-anki was built without .apkg export, rebuild with `cargo build --features apkg`".into())
+}
+
+fn run_render(
+    path: &str,
+    deck_name: String,
+    topic: Option<&str>,
+    rows: Option<Range<usize>>,
+    sample: Option<(usize, SampleStrategy)>,
+    input: &InputOptions,
+    notes: &NoteOptions,
+) -> Result<(), Box<dyn Error>> {
+    // render only prints notes, but parsing warnings are worth seeing before an import
+    let topics: Vec<Topic> = with_generated_topics(parse_showing_warnings(path, input, sample)?, notes);
+    let importer = build_importer(deck_name, notes, &ConnectionOptions::default());
+
+    let selected: Vec<&Topic> = topics.iter()
+        .filter(|t| topic.is_none_or(|name| input.header_matching().matches(t.name(), name)))
+        .collect();
+
+    if let (Some(name), true) = (topic, selected.is_empty()) {
+        return Err(format!("Topic '{}' not found in {}", name, path).into());
+    }
+
+    for topic in selected {
+        let rows = rows.clone().unwrap_or(0..topic.words().len());
+
+        for (idx, word) in topic.words().iter().enumerate().skip(rows.start).take(rows.len()) {
+            match importer.word_to_note(word, topic.name()) {
+                Ok(note) => display_rendered_note(topic.name(), idx + 1, &note),
+                Err(e) => println!("[{} #{}] Error: {}\n", topic.name(), idx + 1, e),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run_export(
+    path: &str,
+    output: Option<&str>,
+    title: Option<&str>,
+    format: SheetFormat,
+    input: &InputOptions,
+    notes: &NoteOptions,
+) -> Result<(), Box<dyn Error>> {
+    let file = encoding::read_input(path, input.encoding)?;
+    let output = output.map_or_else(|| file.default_output("html"), str::to_string);
+    let title = title.map_or_else(|| file.stem(), str::to_string);
+
+    if path == output {
+        return Err(format!("Refusing to overwrite the input '{}', pass --output", path).into());
+    }
+
+    let (progress, receiver) = progress::channel();
+    let parsed = parse_input(&file, input, None, &progress);
+    drop(progress);
+
+    for event in receiver.try_iter() {
+        match event {
+            ProgressEvent::Warning(_) => eprintln!("{}\n", event),
+            _ => println!("{}", event),
+        }
+    }
+
+    let topics: Vec<Topic> = with_generated_topics(parsed?, notes);
+
+    match format {
+        SheetFormat::Html => sheet::write_html(&topics, &title, File::create(&output)?)?,
+    }
+
+    println!("\nWrote {} topics to {}", topics.len(), output);
+
+    Ok(())
+}
+
+fn run_diff(
+    old: String,
+    new: String,
+    format: DiffFormat,
+    output: Option<&str>,
+    input: &InputOptions,
+    style: OutputStyle,
+) -> Result<(), Box<dyn Error>> {
+    let old_topics = parse_showing_warnings(&old, input, None)?;
+    let new_topics = parse_showing_warnings(&new, input, None)?;
+
+    let report = SheetDiff {
+        topics: diff::diff_topics(old_topics, new_topics, input.header_matching()),
+        old,
+        new,
+    };
+
+    match output {
+        Some(output) => {
+            report.write(format, OutputStyle::PLAIN, File::create(output)?)?;
+            println!("Wrote changes in {} topics to {}", report.topics.len(), output);
+        },
+        None => report.write(format, style, std::io::stdout().lock())?,
+    }
+
+    Ok(())
+}
+
+/// what the word columns hold, in slice order
+const WORD_COLUMNS: [&str; 3] = ["kana", "english", "kanji"];
+
+fn run_schema(path: &str, input: &InputOptions, style: OutputStyle) -> Result<(), Box<dyn Error>> {
+    let file = encoding::read_input(path, input.encoding)?;
+    let schema = parse::infer_csv_schema(&file.text, input.parse_config(&file.name, &file.text))?;
+    let mut issues = 0;
+
+    for slice in &schema {
+        let name = if slice.name().trim().is_empty() { "(no topic name)" } else { slice.name() };
+        println!("{} ({} rows, columns {}-{})", style.paint(name, Color::Bold), slice.rows, slice.columns.start + 1, slice.columns.end);
+
+        for (role, column) in WORD_COLUMNS.iter().zip(&slice.column_schemas) {
+            // words are text, numbers or dates usually mean a shifted column or excel reformatting cells
+            let mut problems = Vec::new();
+            if matches!(column.column_type, ColumnType::Integer | ColumnType::Float | ColumnType::Date) {
+                problems.push(format!("{} values", column.column_type));
+            }
+            if *role != "kanji" && column.nulls > 0 {
+                problems.push(format!("{} blank", column.nulls));
+            }
+            if *role == "kana" && !column.unique {
+                problems.push("repeated words".to_string());
+            }
+
+            let status = if problems.is_empty() { style.status(Glyph::Added) } else { style.status(Glyph::Skipped) };
+            println!(
+                "  {} {:<8} {:<8} {} blank, {} distinct{}",
+                status, role, column.column_type, column.nulls, column.distinct,
+                if problems.is_empty() { String::new() } else { style.paint(format!("  ({})", problems.join(", ")), Color::Yellow) }
+            );
+
+            issues += usize::from(!problems.is_empty());
+        }
+    }
+
+    match issues {
+        0 => println!("\nEvery column looks like it should"),
+        1 => println!("\n1 column looks off, worth a look before importing"),
+        n => println!("\n{} columns look off, worth a look before importing", n),
+    }
+
+    Ok(())
+}
+
+fn run_scaffold(path: &str, output: Option<&str>, roles: Option<&[ColumnRole]>) -> Result<(), Box<dyn Error>> {
+    let file = encoding::read_input(path, None)?;
+    let output = output.map_or_else(|| file.default_output("csv"), str::to_string);
+
+    if path == output {
+        return Err(format!("Refusing to overwrite the word list '{}', pass --output", path).into());
+    }
+
+    let scaffold = scaffold::scaffold_file(&file, &output, roles)?;
+
+    if !scaffold.columns.is_empty() {
+        println!("Read the columns as:");
+        for (name, role) in &scaffold.columns {
+            println!("  {} -> {}", name, role);
+        }
+        println!("(pass --roles {} to change that)\n", scaffold.columns.iter().map(|(_, role)| role.to_string()).collect::<Vec<_>>().join(","));
+    }
+
+    println!("Wrote {} topics to {}:", scaffold.topics.len(), output);
+    for topic in &scaffold.topics {
+        println!("  - {}: {} words", topic.name(), topic.words().len());
+    }
+    if scaffold.columns.is_empty() {
+        println!("\nFill in the translation and kanji columns, then import it.");
+    } else {
+        println!("\nCheck the words landed in the right columns, then import it.");
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "gui")]
+fn run_gui(connection: ConnectionOptions) -> Result<(), Box<dyn Error>> {
+    gui::run(connection)
+}
+
+#[cfg(not(feature = "gui"))]
+fn run_gui(_connection: ConnectionOptions) -> Result<(), Box<dyn Error>> {
+    Err("csv-to-anki was built without the GUI, rebuild with `cargo build --features gui`".into())
+}
+
+fn build_importer(deck_name: String, notes: &NoteOptions, connection: &ConnectionOptions) -> JapaneseVocabImporter {
+    let importer = JapaneseVocabImporter::new(deck_name)
+        .with_client(connection.client())
+        .with_model(&notes.model)
+        .with_fields(&notes.front_field, &notes.back_field)
+        .with_front_strategy(notes.front_strategy)
+        .with_tags(notes.tags.clone())
+        .with_html_policy(notes.html.clone())
+        .with_flat_deck(notes.flat_deck)
+        .with_allow_duplicates(notes.allow_duplicates)
+        .with_duplicate_scope(notes.duplicate_scope)
+        .with_update_existing(notes.update_existing)
+        .with_reset_scheduling(notes.reset_scheduling);
+
+    match &notes.topic_field {
+        Some(topic_field) => importer.with_topic_field(topic_field),
+        None => importer,
+    }
+}
+
 fn build_sub_decks(importer: &JapaneseVocabImporter, topics: &[Topic]) -> Result<(), Box<dyn Error>> {
-    importer.initialise_with_topics(&topics)?;
+    importer.initialise_with_topics(topics)?;
 
     Ok(())
 }
@@ -60,75 +544,208 @@ fn connect_to_anki(importer: &JapaneseVocabImporter) -> Result<(), Box<dyn Error
     Ok(())
 }
 
-fn get_inputs() -> Result<(String, String), Box<dyn Error>> {
-    let mut args = env::args();
-    args.next(); // skip first argument (program name)
+/// parse the input into topics, `sample` keeps only that many rows per topic (csv input only)
+fn handle_parsing(
+    file_path: &str,
+    input: &InputOptions,
+    sample: Option<(usize, SampleStrategy)>,
+    progress: &ProgressSender,
+) -> Result<Vec<Topic>, Box<dyn Error>> {
+    parse_input(&encoding::read_input(file_path, input.encoding)?, input, sample, progress)
+}
 
-    let file_path = args.next()
-        .ok_or(format!("Error: Missing file path argument.\nUSAGE: [path to input] [desired deck name]"))?;
+/// `handle_parsing` for an input that was already read
+fn parse_input(
+    file: &Input,
+    input: &InputOptions,
+    sample: Option<(usize, SampleStrategy)>,
+    progress: &ProgressSender,
+) -> Result<Vec<Topic>, Box<dyn Error>> {
+    let topics: Vec<Topic> = if parse::is_anki_export(&file.text) {
+        progress.message("Detected Anki plain text export, converting notes back into topics...");
+        parse::parse_topics_from_anki_export(&file.text)?
+    } else {
+        parse::parse_topics_from_csv(&file.text, input.parse_config(&file.name, &file.text), sample)?
+    };
+
+    let topics = check_columns(topics, input.fix_columns, progress);
+
+    let topics = match &input.join {
+        Some(join) => join_file(topics, join, input, progress)?,
+        None => topics,
+    };
+
+    // order and number before splitting, so `01 Food::1` and `01 Food::2` stay under one parent deck
+    let topics = parse::order_topics(topics, input.topic_order);
+    let topics = if input.number_topics { parse::number_topics(topics) } else { topics };
+
+    let topics = match input.max_topic_size {
+        Some(max_words) => parse::split_oversized_topics(topics, max_words),
+        None => topics,
+    };
+
+    progress.message(format!("\nParsed {} topics:", topics.len()));
+    for topic in &topics {
+        progress.emit(ProgressEvent::TopicParsed { name: topic.name.clone(), words: topic.words.len() });
+    }
+
+    Ok(topics)
+}
 
-    let deck_name = args.next()
-        .ok_or(format!("Error: Missing deck name argument.\nUSAGE: [path to input] [desired deck name]"))?;
+/// parse without printing progress, only warnings (to stderr)
+fn parse_showing_warnings(
+    file_path: &str,
+    input: &InputOptions,
+    sample: Option<(usize, SampleStrategy)>,
+) -> Result<Vec<Topic>, Box<dyn Error>> {
+    let (progress, receiver) = progress::channel();
+    let parsed = handle_parsing(file_path, input, sample, &progress);
+    drop(progress);
+
+    for event in receiver.try_iter().filter(|event| matches!(event, ProgressEvent::Warning(_))) {
+        eprintln!("{}\n", event);
+    }
 
-    Ok((file_path, deck_name))
+    parsed
 }
 
-fn handle_parsing(file_path: &str) -> Result<Vec<Topic>, Box<dyn Error>> {
-    let topics: Vec<Topic> = parse_topics_from_csv(file_path)?;
+/// warn about topics whose columns look mixed up, and swap them back if asked to
+fn check_columns(topics: Vec<Topic>, fix: bool, progress: &ProgressSender) -> Vec<Topic> {
+    topics.into_iter()
+        .map(|topic| match language::detect_issue(&topic) {
+            Some(issue) if fix => {
+                progress.message(format!("Fixed '{}': {}, swapped them back", topic.name(), issue.describe()));
+                language::fix_topic(topic, issue)
+            },
+            Some(issue) => {
+                progress.warn(format!(
+                    "in '{}' {}, rerun with --fix-columns to swap them", topic.name(), issue.describe()
+                ));
+                topic
+            },
+            None => topic,
+        })
+        .collect()
+}
 
-    println!("\nParsed {} topics:", topics.len());
-    for topic in &topics {
-        println!("  - {}: {} words", topic.name, topic.words.len());
+/// add the columns of the `--join` file to the words it has rows for
+fn join_file(
+    topics: Vec<Topic>,
+    join: &JoinOptions,
+    input: &InputOptions,
+    progress: &ProgressSender,
+) -> Result<Vec<Topic>, Box<dyn Error>> {
+    let file = encoding::read_input(&join.path, input.encoding)?;
+    let table = JoinTable::parse(&file.text, input.delimiter_for(&file.name, &file.text))?;
+    let (topics, report) = join::join_topics(topics, &table, join.key, join.join_type);
+
+    progress.message(format!(
+        "Joined {} ({}) onto {} words",
+        join.path, table.columns().join(", "), report.matched
+    ));
+
+    if report.matched == 0 {
+        progress.warn(format!("no word matched a row of {}, is --join-key right? ({:?} was used)", join.path, join.key));
+    } else if report.unmatched > 0 {
+        let what = match join.join_type {
+            JoinType::Left => "imported without them",
+            JoinType::Inner => "left out",
+        };
+        progress.message(format!("{} words have no row in {}, {}", report.unmatched, join.path, what));
     }
 
     Ok(topics)
 }
 
-fn parse_topics_from_csv(file_path: &str) -> Result<Vec<Topic>, Box<dyn Error>> {
-    let parser = CsvSliceParser::from_file(file_path)?;
-
-    Ok((0..parser.slice_count::<Word>())
-        .filter_map(|slice_idx| {
-            let topic_name: String = parser.headers()
-                .get(slice_idx * Word::COLUMN_COUNT)?
-                .to_string();
-
-            // skip empty topic names
-            if topic_name.trim().is_empty() {
-                return None;
-            }
+/// estimate what the import adds and refuse to go on over a limit unless `--yes` was given
+fn check_limits(
+    topics: &[Topic],
+    path: &str,
+    input: &InputOptions,
+    limits: Limits,
+    progress: &ProgressSender,
+) -> Result<(), Box<dyn Error>> {
+    let media_dirs = preflight::media_dirs(path, input.join.as_ref().map(|join| join.path.as_str()));
+    let estimate = Estimate::of(topics, &media_dirs);
+
+    progress.message(format!("\nAbout to add up to {}", estimate.describe()));
+
+    let exceeded = estimate.exceeded(&limits);
+    if !exceeded.is_empty() {
+        return Err(format!(
+            "Error: this import is bigger than the configured limits ({}), \
+             rerun with --yes if that's intended or raise --max-notes / --max-media-mb",
+            exceeded.join(", ")
+        ).into());
+    }
 
-            let words: Vec<Word> = parser.parse_slice::<Word>(slice_idx).ok()?;
+    Ok(())
+}
 
-            // skip empty word vecs
-            if words.is_empty() {
-                return None;
-            }
+/// append topics generated from the vocab (conjugation drills) when enabled
+fn with_generated_topics(mut topics: Vec<Topic>, notes: &NoteOptions) -> Vec<Topic> {
+    if notes.conjugations
+        && let Some(conjugations) = conjugation::conjugation_topic(&topics)
+    {
+        topics.push(conjugations);
+    }
 
-            Some(Topic {
-                name: topic_name,
-                words,
-            })
-        })
-        .collect::<Vec<_>>())
+    topics
 }
 
+fn display_rendered_note(topic_name: &str, row: usize, note: &Note) {
+    println!("[{} #{}] deck: {}", topic_name, row, note.deck_name);
+    for (name, value) in note.fields.iter() {
+        println!("  {}: {}", name, value);
+    }
+    println!("  Tags: {}", note.tags.join(" "));
+    println!();
+}
 
-fn display_import_results(results: Vec<ImportResult>) {
+fn display_import_results(results: Vec<ImportResult>, style: OutputStyle) {
     println!("\n========================================");
     println!("IMPORT COMPLETE");
     println!("========================================");
-    
-    // for result in &results {
-    //     result.print_summary();
-    // }
+
+    let numbers = NumberFormat::detect();
 
     let total_added: usize = results.iter().map(|r| r.added).sum();
+    let total_updated: usize = results.iter().map(|r| r.updated).sum();
     let total_duplicates: usize = results.iter().map(|r| r.duplicates).sum();
     let total_errors: usize = results.iter().map(|r| r.errors).sum();
-    
+
+    let table = results.iter()
+        .fold(
+            Table::new(&[
+                ("Topic", Align::Left),
+                ("Added", Align::Right),
+                ("Updated", Align::Right),
+                ("Duplicates", Align::Right),
+                ("Errors", Align::Right),
+            ]),
+            |table, result| table.row(vec![
+                result.topic_name.clone(),
+                numbers.format(result.added),
+                numbers.format(result.updated),
+                numbers.format(result.duplicates),
+                numbers.format(result.errors),
+            ]),
+        )
+        .footer(vec![
+            format!("{} topics", numbers.format(results.len())),
+            numbers.format(total_added),
+            numbers.format(total_updated),
+            numbers.format(total_duplicates),
+            numbers.format(total_errors),
+        ]);
+
+    println!("\n{}", table.render(2, style));
+
     println!("\nOverall Summary:");
-    println!("  ✓ Successfully added: {}", total_added);
-    println!("  ⊘ Duplicates skipped: {}", total_duplicates);
-    println!("  ✗ Errors: {}", total_errors);
+    println!("  {} Successfully added: {}", style.status(Glyph::Added), numbers.format(total_added));
+    if total_updated > 0 {
+        println!("  {} Updated: {}", style.status(Glyph::Added), numbers.format(total_updated));
+    }
+    println!("  {} Duplicates skipped: {}", style.status(Glyph::Skipped), numbers.format(total_duplicates));
+    println!("  {} Errors: {}", style.status(Glyph::Failed), numbers.format(total_errors));
 }
\ No newline at end of file
@@ -0,0 +1,94 @@
+use std::{env, io::{self, IsTerminal}};
+
+// ============================================================================================
+//                                  Terminal Output
+// ============================================================================================
+
+/// What the terminal can show: ANSI colours and/or unicode glyphs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputStyle {
+    pub color: bool,
+    pub ascii: bool,
+}
+
+/// Status glyphs used in summaries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Glyph {
+    Added,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Green,
+    Yellow,
+    Red,
+    Bold,
+}
+
+impl OutputStyle {
+    /// plain text, safe for logs and any terminal
+    pub const PLAIN: OutputStyle = OutputStyle { color: false, ascii: true };
+
+    /// work out what stdout supports, the flags can only turn features off
+    ///
+    /// - colour needs a terminal, and is off with `NO_COLOR` set or `TERM=dumb`
+    /// - glyphs are off on legacy windows consoles (outside windows terminal) which mangle them
+    pub fn detect(no_color: bool, ascii: bool) -> Self {
+        let terminal = io::stdout().is_terminal();
+        let no_color_env = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        let dumb = env::var("TERM").is_ok_and(|term| term == "dumb");
+        let legacy_console = cfg!(windows) && env::var_os("WT_SESSION").is_none();
+
+        OutputStyle {
+            color: terminal && !no_color && !no_color_env && !dumb,
+            ascii: ascii || legacy_console,
+        }
+    }
+
+    pub fn glyph(&self, glyph: Glyph) -> &'static str {
+        match (glyph, self.ascii) {
+            (Glyph::Added, false) => "✓",
+            (Glyph::Skipped, false) => "⊘",
+            (Glyph::Failed, false) => "✗",
+            (Glyph::Added, true) => "+",
+            (Glyph::Skipped, true) => "-",
+            (Glyph::Failed, true) => "x",
+        }
+    }
+
+    /// wrap text in an ANSI colour, or return it unchanged when colour is off
+    pub fn paint(&self, text: impl AsRef<str>, color: Color) -> String {
+        let text = text.as_ref();
+        if !self.color {
+            return text.to_string();
+        }
+
+        let code = match color {
+            Color::Green => "32",
+            Color::Yellow => "33",
+            Color::Red => "31",
+            Color::Bold => "1",
+        };
+
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    }
+
+    /// glyph coloured to match what it means
+    pub fn status(&self, glyph: Glyph) -> String {
+        let color = match glyph {
+            Glyph::Added => Color::Green,
+            Glyph::Skipped => Color::Yellow,
+            Glyph::Failed => Color::Red,
+        };
+
+        self.paint(self.glyph(glyph), color)
+    }
+}
+
+impl Default for OutputStyle {
+    fn default() -> Self {
+        OutputStyle::detect(false, false)
+    }
+}
@@ -1,21 +1,46 @@
-#[allow(dead_code)]
+use csv_partitioner::{CsvSliceParser, FromColumnSlice, ParseConfig, ParseError, SampleStrategy, SliceSchema, ToColumnSlice};
+use serde::Serialize;
+use std::{error::Error, str::FromStr, sync::Arc};
 
-
-use csv_partitioner::{CsvSliceParser, FromColumnSlice};
-use std::{error::Error, sync::Arc};
+use crate::card::FrontStrategy;
 
 // ============================================================================================
 //                                      Input Parsing
 // ============================================================================================
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Word {
     japanese: String,
     english: String,
     kanji: String,
+
+    /// more note fields for this word (name, value), e.g. joined in from another csv
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    extra_fields: Vec<(String, String)>,
 }
 
 impl Word {
+    pub fn new(japanese: impl Into<String>, english: impl Into<String>, kanji: impl Into<String>) -> Self {
+        Word {
+            japanese: japanese.into(),
+            english: english.into(),
+            kanji: kanji.into(),
+            extra_fields: Vec::new(),
+        }
+    }
+
+    /// add a note field, replacing one with the same name
+    pub fn with_field(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let (name, value) = (name.into(), value.into());
+
+        match self.extra_fields.iter_mut().find(|(existing, _)| *existing == name) {
+            Some((_, existing)) => *existing = value,
+            None => self.extra_fields.push((name, value)),
+        }
+
+        self
+    }
+
     pub fn japanese(&self) -> &String {
         &self.japanese
     }
@@ -27,32 +52,50 @@ impl Word {
     pub fn kanji(&self) -> &String {
         &self.kanji
     }
+
+    pub fn extra_fields(&self) -> &[(String, String)] {
+        &self.extra_fields
+    }
+
+    /// true when every field is blank
+    pub fn is_empty(&self) -> bool {
+        [&self.japanese, &self.english, &self.kanji]
+            .iter()
+            .all(|field| field.trim().is_empty())
+    }
 }
 
 impl FromColumnSlice for Word {
     const COLUMN_COUNT: usize = 3;
 
-    fn from_record(record: &csv::StringRecord, start_col: usize) -> Result<Self, Box<dyn std::error::Error>> {
+    fn from_record(record: &csv::StringRecord, start_col: usize) -> Result<Self, ParseError> {
         let japanese = record.get(start_col)    
-            .ok_or("Missing japanese field")?
+            .ok_or_else(|| ParseError::missing_field(start_col, "japanese field"))?
             .to_string();
 
         let english = record.get(start_col + 1)    
-            .ok_or("Missing english field")?
+            .ok_or_else(|| ParseError::missing_field(start_col + 1, "english field"))?
             .to_string();
 
         let kanji = record.get(start_col + 2)    
             .unwrap_or("") // <--- kanji is optional
             .to_string();
 
-        Ok(Word { japanese, english, kanji })
+        Ok(Word::new(japanese, english, kanji))
+    }
+}
+
+/// the same three columns, so edited words can be written back into the sheet
+impl ToColumnSlice for Word {
+    fn to_fields(&self) -> Vec<String> {
+        vec![self.japanese.clone(), self.english.clone(), self.kanji.clone()]
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Topic {
-    pub(crate) name: String,
-    pub(crate) words: Vec<Word>,
+    pub name: String,
+    pub words: Vec<Word>,
 }
 
 impl Topic {
@@ -63,41 +106,462 @@ impl Topic {
     pub fn words(&self) -> &Vec<Word> {
         &self.words
     }
+
+    /// Split into numbered subtopics (`Food::1`, `Food::2`, ...) of at most `max_words` words
+    ///
+    /// numbers are zero-padded when there are 10+ parts so anki's alphabetical deck list keeps them in order
+    pub fn split_by_size(self, max_words: usize) -> Vec<Topic> {
+        if max_words == 0 || self.words.len() <= max_words {
+            return vec![self];
+        }
+
+        let parts = self.words.len().div_ceil(max_words);
+        let width = parts.to_string().len();
+
+        self.words
+            .chunks(max_words)
+            .enumerate()
+            .map(|(idx, words)| Topic {
+                name: format!("{}::{:0width$}", self.name, idx + 1, width = width),
+                words: words.to_vec(),
+            })
+            .collect()
+    }
+}
+
+/// Order topics are created (and numbered) in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TopicOrder {
+    /// left to right, as they appear in the csv
+    #[default]
+    Csv,
+
+    /// by name, ignoring case
+    Alphabetical,
+
+    /// biggest topic first
+    WordCount,
 }
 
+impl FromStr for TopicOrder {
+    type Err = String;
 
-pub struct _TopicWithWordIter {
-    name: String,
-    parser: Arc<CsvSliceParser>,
-    slice_index: usize,
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "csv" => Ok(TopicOrder::Csv),
+            "alphabetical" | "alpha" => Ok(TopicOrder::Alphabetical),
+            "words" | "word-count" => Ok(TopicOrder::WordCount),
+            other => Err(format!("Unknown topic order '{}', expected one of: csv, alphabetical, words", other)),
+        }
+    }
 }
 
-impl _TopicWithWordIter {
-    pub fn words(&self) -> Result<impl Iterator<Item = Result<Word, Box<dyn Error>>> + '_, Box<dyn Error>> {
-        self.parser.parse_slice_iter::<Word>(self.slice_index)
+/// reorder topics, ties keep their csv order
+pub fn order_topics(mut topics: Vec<Topic>, order: TopicOrder) -> Vec<Topic> {
+    match order {
+        TopicOrder::Csv => {},
+        TopicOrder::Alphabetical => topics.sort_by_key(|topic| topic.name.to_lowercase()),
+        TopicOrder::WordCount => topics.sort_by_key(|topic| std::cmp::Reverse(topic.words.len())),
     }
 
-    pub fn name(&self) -> &String {
+    topics
+}
+
+/// prefix topic names with their position (`01 Food`, `02 Verbs`, ...)
+///
+/// anki lists decks alphabetically, the zero-padded index keeps them in this order instead
+pub fn number_topics(topics: Vec<Topic>) -> Vec<Topic> {
+    let width = topics.len().to_string().len().max(2);
+
+    topics.into_iter()
+        .enumerate()
+        .map(|(idx, topic)| Topic {
+            name: format!("{:0width$} {}", idx + 1, topic.name, width = width),
+            words: topic.words,
+        })
+        .collect()
+}
+
+/// split every topic larger than `max_words` into numbered subtopics
+pub fn split_oversized_topics(topics: Vec<Topic>, max_words: usize) -> Vec<Topic> {
+    topics.into_iter()
+        .flat_map(|topic| topic.split_by_size(max_words))
+        .collect()
+}
+
+
+/// A topic as seen while streaming words: its name and slice position, cheap to clone
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopicRef {
+    name: Arc<str>,
+    index: usize,
+}
+
+impl TopicRef {
+    pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// position of the topic's slice in the csv (0 = leftmost)
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+/// one streamed entry: the word and the topic it belongs to
+pub type TopicWord = (TopicRef, Word);
+
+/// Stream every (topic, word) pair in the file, topic by topic, without building `Topic`s
+///
+/// blank rows are skipped unless `skip_empty_rows` is off, as in `parse_topics_from_csv`,
+/// words in a slice with no topic name are errors
+///
+/// ```
+/// # use csv_partitioner::ParseConfig;
+/// # use csv_to_anki::parse;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let path = std::env::temp_dir().join(format!("iter_words_doc_{}.csv", std::process::id()));
+/// # std::fs::write(&path, "Food,,,Verbs,,\nりんご,apple,林檎,いく,to go,行く\n,,,たべる,to eat,食べる\n")?;
+/// # let file_path = path.to_str().unwrap();
+/// let mut words = Vec::new();
+/// for entry in parse::iter_words(file_path, ParseConfig::default())? {
+///     let (topic, word) = entry?;
+///     words.push(format!("{}: {}", topic.name(), word.japanese()));
+/// }
+///
+/// assert_eq!(words, ["Food: りんご", "Verbs: いく", "Verbs: たべる"]);
+/// # std::fs::remove_file(path)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn iter_words(file_path: &str, config: ParseConfig)
+    -> Result<impl Iterator<Item = Result<TopicWord, Box<dyn Error>>>, Box<dyn Error>>
+{
+    let keep_empty = !config.skip_empty_rows;
+    let parser = CsvSliceParser::from_file_with_config(file_path, config)?;
+    let slices = parser.slices::<Word>();
+
+    Ok(slices.into_iter().flat_map(move |slice| {
+        let entries: Vec<Result<TopicWord, Box<dyn Error>>> = if slice.name().trim().is_empty() {
+            match slice.is_empty() {
+                true => Vec::new(),
+                false => vec![Err(format!(
+                    "Column {} has vocabulary but no topic name in the header row", slice.columns.start + 1
+                ).into())],
+            }
+        } else {
+            let topic = TopicRef {
+                name: slice.name().trim().into(),
+                index: slice.index,
+            };
+
+            match parser.parse_slice::<Word>(slice.index) {
+                Ok(words) => words.into_iter()
+                    .filter(|word| keep_empty || !word.is_empty())
+                    .map(|word| Ok((topic.clone(), word)))
+                    .collect(),
+                Err(e) => vec![Err(e.into())],
+            }
+        };
+
+        entries
+    }))
+}
+
+
+// ============================================================================================
+//                                  Anki Plain Text Import
+// ============================================================================================
+
+/// Header metadata from an Anki "Notes in Plain Text" export
+/// e.g. `#separator:tab`, `#html:true`, `#deck column:3`
+#[derive(Debug, Default)]
+struct AnkiExportHeader {
+    separator: u8,
+    html: bool,
+    deck_column: Option<usize>,
+    tags_column: Option<usize>,
+    notetype_column: Option<usize>,
+    guid_column: Option<usize>,
 }
 
-pub fn parse_topics_nested_iter(file_path: &str)
-    -> Result<impl Iterator<Item = Result<_TopicWithWordIter, Box<dyn Error>>>, Box<dyn Error>> 
-    {
-        let parser = Arc::new(CsvSliceParser::from_file(file_path)?);
-        let slice_count = parser.slice_count::<Word>();
+impl AnkiExportHeader {
+    fn parse(lines: &[&str]) -> Result<Self, Box<dyn Error>> {
+        let mut header = AnkiExportHeader { separator: b'\t', ..Default::default() };
 
-        Ok(
-            (0..slice_count).map(move |i: usize| {
-                let topic_name = parser
-                    .headers()
-                    .get(i * Word::COLUMN_COUNT)
-                    .unwrap_or("")
-                    .to_string();
+        for line in lines {
+            let Some((key, value)) = line.trim_start_matches('#').split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
 
-                Ok(_TopicWithWordIter { 
-                    name: topic_name, parser: Arc::clone(&parser), slice_index: i 
-                })
-            }))
-    }
\ No newline at end of file
+            match key.trim() {
+                "separator" => header.separator = Self::parse_separator(value)?,
+                "html" => header.html = value == "true",
+                // anki numbers columns from 1
+                "deck column" => header.deck_column = Some(Self::parse_column(value)?),
+                "tags column" => header.tags_column = Some(Self::parse_column(value)?),
+                "notetype column" => header.notetype_column = Some(Self::parse_column(value)?),
+                "guid column" => header.guid_column = Some(Self::parse_column(value)?),
+                _ => {}
+            }
+        }
+
+        Ok(header)
+    }
+
+    fn parse_separator(value: &str) -> Result<u8, Box<dyn Error>> {
+        match value.to_lowercase().as_str() {
+            "tab" => Ok(b'\t'),
+            "comma" => Ok(b','),
+            "semicolon" => Ok(b';'),
+            "pipe" => Ok(b'|'),
+            "colon" => Ok(b':'),
+            "space" => Ok(b' '),
+            other if other.len() == 1 => Ok(other.as_bytes()[0]),
+            other => Err(format!("Unknown separator in Anki export: '{}'", other).into()),
+        }
+    }
+
+    fn parse_column(value: &str) -> Result<usize, Box<dyn Error>> {
+        let column: usize = value.parse()
+            .map_err(|_| format!("Invalid column number in Anki export: '{}'", value))?;
+
+        column.checked_sub(1)
+            .ok_or_else(|| "Column numbers in Anki exports start at 1".into())
+    }
+
+    fn is_metadata_column(&self, idx: usize) -> bool {
+        [self.deck_column, self.tags_column, self.notetype_column, self.guid_column]
+            .contains(&Some(idx))
+    }
+}
+
+/// parse spreadsheet contents into one topic per column slice, named by the header row,
+/// `sample` keeps only that many words per topic
+///
+/// Empty row policy:
+/// - skip_empty_rows = true: blank rows are dropped by the parser, topics left with no words are skipped
+/// - skip_empty_rows = false: every row becomes a Word and every named topic is kept,
+///   blank words are rejected later by `word_to_note` with an explicit error
+///
+/// Either way a slice with words but no topic name in the header is an error, not silently dropped
+pub fn parse_topics_from_csv(
+    contents: &str,
+    config: ParseConfig,
+    sample: Option<(usize, SampleStrategy)>,
+) -> Result<Vec<Topic>, Box<dyn Error>> {
+    let keep_empty = !config.skip_empty_rows;
+    let parser = CsvSliceParser::from_str(contents, config)?;
+    let mut topics: Vec<Topic> = Vec::new();
+
+    for slice in parser.slices::<Word>() {
+        if slice.name().trim().is_empty() {
+            if !slice.is_empty() {
+                return Err(format!(
+                    "Column {} has vocabulary but no topic name in the header row \
+                    (if topics are separated by empty columns, pass --slice-gap)", slice.columns.start + 1
+                ).into());
+            }
+
+            continue;
+        }
+
+        let words: Vec<Word> = match sample {
+            Some((rows, strategy)) => parser.sample_slice::<Word>(slice.index, rows, strategy)?,
+            None => parser.parse_slice::<Word>(slice.index)?,
+        };
+
+        if words.is_empty() && !keep_empty {
+            continue;
+        }
+
+        topics.push(Topic {
+            name: slice.name().to_string(),
+            words,
+        });
+    }
+
+    Ok(topics)
+}
+
+/// the inferred column types of every topic, unnamed slices without words are left out
+pub fn infer_csv_schema(contents: &str, config: ParseConfig) -> Result<Vec<SliceSchema>, Box<dyn Error>> {
+    let parser = CsvSliceParser::from_str(contents, config)?;
+
+    Ok(parser.infer_schema::<Word>()
+        .into_iter()
+        .filter(|slice| !slice.name().trim().is_empty() || slice.rows > 0)
+        .collect())
+}
+
+/// check whether file contents look like an Anki "Notes in Plain Text" export
+/// (starts with `#key:value` header lines such as `#separator:tab`)
+pub fn is_anki_export(contents: &str) -> bool {
+    contents.lines().next().is_some_and(|line| line.starts_with("#separator:"))
+}
+
+/// Parse an Anki "Notes in Plain Text" export back into topics
+///
+/// topic: the deck column without the main deck (`Japanese::Food::1` -> `Food::1`), else first tag
+/// (notes straight in the main deck), else "Imported"
+/// word: reverses `word_to_note` - a back of `japanese | english` means the front was kanji
+pub fn parse_topics_from_anki_export(contents: &str) -> Result<Vec<Topic>, Box<dyn Error>> {
+    let header_lines: Vec<&str> = contents.lines()
+        .take_while(|line| line.starts_with('#'))
+        .collect();
+    let header = AnkiExportHeader::parse(&header_lines)?;
+
+    let body: String = contents.lines()
+        .skip(header_lines.len())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(header.separator)
+        .from_reader(body.as_bytes());
+
+    let mut topics: Vec<Topic> = Vec::new();
+
+    for result in reader.records() {
+        let record = result?;
+
+        let fields: Vec<String> = record.iter()
+            .enumerate()
+            .filter(|(idx, _)| !header.is_metadata_column(*idx))
+            .map(|(_, field)| if header.html { strip_html(field) } else { field.trim().to_string() })
+            .collect();
+
+        let (Some(front), Some(back)) = (fields.first(), fields.get(1)) else {
+            continue;
+        };
+
+        // exports we can reverse were written with the default front strategy
+        let word = FrontStrategy::default().word_from_sides(front, back);
+
+        let topic_name = anki_export_topic_name(&header, &record);
+
+        match topics.iter_mut().find(|topic| topic.name == topic_name) {
+            Some(topic) => topic.words.push(word),
+            None => topics.push(Topic { name: topic_name, words: vec![word] }),
+        }
+    }
+
+    Ok(topics)
+}
+
+fn anki_export_topic_name(header: &AnkiExportHeader, record: &csv::StringRecord) -> String {
+    let from_deck = header.deck_column
+        .and_then(|idx| record.get(idx))
+        .and_then(|deck| deck.split_once("::"))
+        .map(|(_, topic)| topic)
+        .map(str::trim)
+        .filter(|name| !name.is_empty());
+
+    let from_tags = header.tags_column
+        .and_then(|idx| record.get(idx))
+        .and_then(|tags| tags.split_whitespace().find(|tag| !matches!(*tag, "japanese" | "vocabularly")));
+
+    from_deck.or(from_tags)
+        .unwrap_or("Imported")
+        .to_string()
+}
+
+/// remove html tags, a `<br>` becomes a space so the lines around it don't run together.
+/// entities are left for `word_from_sides` to decode (once)
+fn strip_html(field: &str) -> String {
+    let mut out = String::with_capacity(field.len());
+    let mut tag: Option<String> = None;
+
+    for c in field.chars() {
+        match (&mut tag, c) {
+            (None, '<') => tag = Some(String::new()),
+            (None, c) => out.push(c),
+            (Some(name), '>') => {
+                let name = name.split(|c: char| c.is_whitespace() || c == '/').find(|part| !part.is_empty());
+                if name.is_some_and(|name| name.eq_ignore_ascii_case("br")) {
+                    out.push(' ');
+                }
+                tag = None;
+            },
+            (Some(name), c) => name.push(c),
+        }
+    }
+
+    out.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streamed_words_skip_the_gap_columns() {
+        let path = std::env::temp_dir().join(format!("iter_words_gap_{}.csv", std::process::id()));
+        std::fs::write(&path, "Food,,,,Verbs,,\nりんご,apple,林檎,x,いく,to go,行く\n,,,,たべる,to eat,食べる\n").unwrap();
+
+        let config = ParseConfig { slice_gap: 1, ..ParseConfig::default() };
+        let words: Vec<(String, usize, String)> = iter_words(path.to_str().unwrap(), config).unwrap()
+            .map(|entry| entry.map(|(topic, word)| (topic.name().to_string(), topic.index(), word.kanji().clone())))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(words, vec![
+            ("Food".to_string(), 0, "林檎".to_string()),
+            ("Verbs".to_string(), 1, "行く".to_string()),
+            ("Verbs".to_string(), 1, "食べる".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn streamed_words_keep_blank_rows_when_asked() {
+        let path = std::env::temp_dir().join(format!("iter_words_blank_{}.csv", std::process::id()));
+        std::fs::write(&path, "Food,,\nりんご,apple,林檎\n,,\nみず,water,水\n").unwrap();
+
+        let words = |config: ParseConfig| -> Vec<String> {
+            iter_words(path.to_str().unwrap(), config).unwrap()
+                .map(|entry| entry.map(|(_, word)| word.kanji().clone()))
+                .collect::<Result<_, _>>()
+                .unwrap()
+        };
+
+        assert_eq!(words(ParseConfig::default()), vec!["林檎", "水"]);
+        assert_eq!(words(ParseConfig { skip_empty_rows: false, ..ParseConfig::default() }), vec!["林檎", "", "水"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn anki_exports_keep_subdecks_below_the_main_deck() {
+        let export = "#separator:tab\n#html:true\n#deck column:3\n#tags column:4\n\
+            林檎\tりんご | <b>apple</b>\tJapanese::Food::1\tjapanese food\n\
+            食べる\tたべる | to eat\tJapanese::Verbs::1\tjapanese\n\
+            飲む\tのむ | to drink\tJapanese::Verbs::1\tjapanese\n\
+            行く\tいく | to go\tJapanese::Verbs::2\tjapanese\n\
+            いぬ\tdog\tJapanese\tjapanese animals\n";
+
+        assert!(is_anki_export(export));
+
+        let topics = parse_topics_from_anki_export(export).unwrap();
+        let summary: Vec<(&str, Vec<&str>)> = topics.iter()
+            .map(|topic| (topic.name().as_str(), topic.words().iter().map(|word| word.english().as_str()).collect()))
+            .collect();
+
+        assert_eq!(summary, vec![
+            ("Food::1", vec!["apple"]),
+            ("Verbs::1", vec!["to eat", "to drink"]),
+            ("Verbs::2", vec!["to go"]),
+            ("animals", vec!["dog"]),
+        ]);
+        assert_eq!(topics[1].words()[0].kanji(), "食べる");
+    }
+
+    #[test]
+    fn line_breaks_in_exported_fields_become_spaces() {
+        assert_eq!(strip_html("<b>食べる</b><br>to eat"), "食べる to eat");
+        assert_eq!(strip_html("one<BR/>two<br class=\"x\" />three"), "one two three");
+        assert_eq!(strip_html("<div>&lt;tag&gt;</div>"), "&lt;tag&gt;");
+    }
+}
@@ -0,0 +1,187 @@
+use std::{collections::BTreeSet, fs, path::{Path, PathBuf}};
+
+use crate::parse::Topic;
+
+// ============================================================================================
+//                                      Import Preflight
+// ============================================================================================
+
+// A collection syncs to every device, so a run that adds ten thousand notes or a few
+// gigabytes of audio is hard to take back. Before anything is sent, the notes and the media
+// they reference are counted and compared with limits, going over needs an explicit --yes.
+
+/// How big an import may be before it needs `--yes`, `None` is no limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    pub max_notes: Option<usize>,
+    pub max_media_bytes: Option<u64>,
+
+    /// the user already agreed to go over
+    pub confirmed: bool,
+}
+
+impl Limits {
+    pub const DEFAULT_MAX_NOTES: usize = 5000;
+    pub const DEFAULT_MAX_MEDIA_MB: u64 = 200;
+
+    /// no limits, for front-ends that can't ask
+    #[cfg(feature = "gui")]
+    pub fn unlimited() -> Self {
+        Limits { max_notes: None, max_media_bytes: None, confirmed: true }
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_notes: Some(Self::DEFAULT_MAX_NOTES),
+            max_media_bytes: Some(Self::DEFAULT_MAX_MEDIA_MB * 1024 * 1024),
+            confirmed: false,
+        }
+    }
+}
+
+/// What an import is about to add
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Estimate {
+    pub notes: usize,
+
+    /// distinct media files referenced by note fields
+    pub media_files: usize,
+
+    /// size of the referenced files that were found on disk
+    pub media_bytes: u64,
+
+    /// referenced files that weren't found, their size is unknown
+    pub missing_media: Vec<String>,
+}
+
+impl Estimate {
+    /// count notes and the media (`[sound:...]`, `<img src="...">`) their extra fields reference,
+    /// media names are looked up in `media_dirs` in order
+    pub fn of(topics: &[Topic], media_dirs: &[PathBuf]) -> Self {
+        let names: BTreeSet<String> = topics.iter()
+            .flat_map(|topic| &topic.words)
+            .flat_map(|word| word.extra_fields())
+            .flat_map(|(_, value)| media_references(value))
+            .collect();
+
+        let mut estimate = Estimate {
+            notes: topics.iter().map(|topic| topic.words.len()).sum(),
+            media_files: names.len(),
+            ..Estimate::default()
+        };
+
+        for name in names {
+            let size = media_dirs.iter().find_map(|dir| fs::metadata(dir.join(&name)).ok()).map(|meta| meta.len());
+
+            match size {
+                Some(bytes) => estimate.media_bytes += bytes,
+                None => estimate.missing_media.push(name),
+            }
+        }
+
+        estimate
+    }
+
+    /// `1200 notes, 40 media files (12.5 MB)`
+    pub fn describe(&self) -> String {
+        let mut text = format!(
+            "{} notes, {} media files ({:.1} MB)",
+            self.notes, self.media_files, self.media_bytes as f64 / (1024.0 * 1024.0)
+        );
+
+        if !self.missing_media.is_empty() {
+            text.push_str(&format!(", {} of them not found so not counted", self.missing_media.len()));
+        }
+
+        text
+    }
+
+    /// the limits this goes over, empty when it's fine (or confirmed)
+    pub fn exceeded(&self, limits: &Limits) -> Vec<String> {
+        if limits.confirmed {
+            return Vec::new();
+        }
+
+        let mut exceeded = Vec::new();
+
+        if let Some(max) = limits.max_notes.filter(|&max| self.notes > max) {
+            exceeded.push(format!("{} notes is over --max-notes {}", self.notes, max));
+        }
+        if let Some(max) = limits.max_media_bytes.filter(|&max| self.media_bytes > max) {
+            exceeded.push(format!(
+                "{:.1} MB of media is over --max-media-mb {}",
+                self.media_bytes as f64 / (1024.0 * 1024.0), max / (1024 * 1024)
+            ));
+        }
+
+        exceeded
+    }
+}
+
+/// the directories media names are resolved against: next to the input, then next to the join file
+pub fn media_dirs(input_path: &str, join_path: Option<&str>) -> Vec<PathBuf> {
+    [Some(input_path), join_path].into_iter()
+        .flatten()
+        .filter(|path| !crate::encoding::is_google_sheet_link(path))
+        .map(|path| Path::new(path).parent().unwrap_or(Path::new("")).to_path_buf())
+        .collect()
+}
+
+/// file names in anki's `[sound:name]` and `<img src="name">` syntax
+fn media_references(field: &str) -> Vec<String> {
+    let sounds = field.split("[sound:")
+        .skip(1)
+        .filter_map(|rest| rest.split_once(']'))
+        .map(|(name, _)| name);
+
+    let images = field.split("src=")
+        .skip(1)
+        .filter_map(|rest| {
+            let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+            rest[1..].split_once(quote).map(|(name, _)| name)
+        });
+
+    sounds.chain(images)
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::Word;
+
+    #[test]
+    fn counts_notes_and_referenced_media() {
+        let dir = std::env::temp_dir().join(format!("csv-to-anki-preflight-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("ringo.mp3"), [0u8; 1000]).unwrap();
+
+        let topics = vec![Topic {
+            name: "Food".to_string(),
+            words: vec![
+                Word::new("りんご", "apple", "").with_field("Audio", "[sound:ringo.mp3]"),
+                Word::new("みず", "water", "").with_field("Picture", "<img src=\"mizu.jpg\">"),
+                Word::new("ねこ", "cat", "").with_field("Audio", "[sound:ringo.mp3]"),
+            ],
+        }];
+
+        let estimate = Estimate::of(&topics, std::slice::from_ref(&dir));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(estimate, Estimate {
+            notes: 3,
+            media_files: 2,
+            media_bytes: 1000,
+            missing_media: vec!["mizu.jpg".to_string()],
+        });
+
+        let limits = Limits { max_notes: Some(2), ..Limits::default() };
+        assert_eq!(estimate.exceeded(&limits), vec!["3 notes is over --max-notes 2"]);
+        assert!(estimate.exceeded(&Limits { confirmed: true, ..limits }).is_empty());
+    }
+}
@@ -0,0 +1,93 @@
+use std::error::Error;
+
+use csv_partitioner::ParseConfig;
+use serde::Serialize;
+
+use crate::card::{Card, CardStyle, FrontStrategy};
+use crate::parse::{self, Topic};
+use crate::sanitize::HtmlPolicy;
+use crate::tags::TagTemplate;
+
+// ============================================================================================
+//                                      Deck Preview
+// ============================================================================================
+
+// Every card a spreadsheet turns into, built exactly like an import builds them but without
+// anki: no connection, no files, just text in and cards out. It's what the browser preview
+// runs, so people can check their spreadsheet before installing anything.
+
+/// The options that change what cards say, the rest only matter when talking to anki
+#[derive(Debug, Clone, Default)]
+pub struct PreviewOptions {
+    pub deck_name: String,
+    pub front_strategy: FrontStrategy,
+    pub tags: TagTemplate,
+    pub flat_deck: bool,
+    pub html: HtmlPolicy,
+}
+
+/// Cards of one topic, words that can't become a card are listed in `errors`
+#[derive(Debug, Clone, Serialize)]
+pub struct TopicPreview {
+    pub name: String,
+    pub cards: Vec<Card>,
+    pub errors: Vec<String>,
+}
+
+/// parse `contents` (a csv, or an anki plain text export) and build every card
+pub fn preview(contents: &str, options: &PreviewOptions) -> Result<Vec<TopicPreview>, Box<dyn Error>> {
+    let topics = if parse::is_anki_export(contents) {
+        parse::parse_topics_from_anki_export(contents)?
+    } else {
+        parse::parse_topics_from_csv(contents, ParseConfig::default(), None)?
+    };
+
+    let style = CardStyle {
+        deck_name: &options.deck_name,
+        front_strategy: options.front_strategy,
+        flat_deck: options.flat_deck,
+        tags: &options.tags,
+        html: &options.html,
+    };
+
+    Ok(topics.iter().map(|topic| preview_topic(topic, &style)).collect())
+}
+
+fn preview_topic(topic: &Topic, style: &CardStyle) -> TopicPreview {
+    let mut preview = TopicPreview { name: topic.name.clone(), cards: Vec::new(), errors: Vec::new() };
+
+    for word in &topic.words {
+        match style.card(word, &topic.name) {
+            Ok(card) => preview.cards.push(card),
+            Err(e) => preview.errors.push(e),
+        }
+    }
+
+    preview
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cards_match_what_an_import_sends() {
+        let options = PreviewOptions {
+            deck_name: "Japanese".to_string(),
+            tags: "topic::{topic}".parse().unwrap(),
+            ..PreviewOptions::default()
+        };
+
+        let topics = preview("Food,,\nりんご,apple,林檎\n,banana,\n", &options).unwrap();
+        assert_eq!(topics.len(), 1);
+
+        let food = &topics[0];
+        assert_eq!(food.cards, vec![Card {
+            deck: "Japanese::Food".to_string(),
+            front: "林檎".to_string(),
+            back: "りんご | apple".to_string(),
+            tags: vec!["topic::Food".to_string()],
+        }]);
+        assert_eq!(food.errors.len(), 1, "the word without a front can't become a card");
+    }
+}
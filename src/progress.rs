@@ -0,0 +1,105 @@
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use crate::output::{Color, OutputStyle};
+use crate::vocab_importer::ImportResult;
+
+// ============================================================================================
+//                                  Progress Reporting
+// ============================================================================================
+
+/// Events emitted while parsing and importing
+///
+/// Frontends (CLI, GUI, TUI) subscribe to these instead of the library printing directly
+#[derive(Debug, Clone)]
+pub enum ProgressEvent {
+    /// a top level step of the run has started
+    Step { number: usize, description: String },
+
+    /// a topic was read from the input file
+    TopicParsed { name: String, words: usize },
+
+    /// a deck or subdeck exists in anki
+    DeckReady { deck_name: String, deck_id: i64 },
+
+    /// a topic's notes are about to be sent to anki
+    TopicStarted { name: String, words: usize },
+
+    /// a topic finished importing
+    TopicFinished(ImportResult),
+
+    /// free-form status message
+    Message(String),
+
+    /// something looks wrong with the input but the run carries on
+    Warning(String),
+}
+
+/// Cloneable, thread-safe handle for emitting progress events
+///
+/// The default sender has no subscriber and drops every event
+#[derive(Debug, Clone, Default)]
+pub struct ProgressSender {
+    sender: Option<Sender<ProgressEvent>>,
+}
+
+impl ProgressSender {
+    pub fn emit(&self, event: ProgressEvent) {
+        if let Some(sender) = &self.sender {
+            // a dropped receiver just means nobody is listening anymore
+            let _ = sender.send(event);
+        }
+    }
+
+    pub fn step(&self, number: usize, description: impl Into<String>) {
+        self.emit(ProgressEvent::Step { number, description: description.into() });
+    }
+
+    pub fn message(&self, message: impl Into<String>) {
+        self.emit(ProgressEvent::Message(message.into()));
+    }
+
+    pub fn warn(&self, warning: impl Into<String>) {
+        self.emit(ProgressEvent::Warning(warning.into()));
+    }
+}
+
+/// create a connected sender/receiver pair
+pub fn channel() -> (ProgressSender, Receiver<ProgressEvent>) {
+    let (sender, receiver) = mpsc::channel();
+
+    (ProgressSender { sender: Some(sender) }, receiver)
+}
+
+impl fmt::Display for ProgressEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProgressEvent::Step { number, description } => write!(f, "Step {}: {}", number, description),
+            ProgressEvent::TopicParsed { name, words } => write!(f, "  - {}: {} words", name, words),
+            ProgressEvent::DeckReady { deck_name, deck_id } => {
+                write!(f, "  Success: Created - '{}', id = {}", deck_name, deck_id)
+            },
+            ProgressEvent::TopicStarted { name, words } => write!(f, "\nImporting topic: {} ({} words)", name, words),
+            ProgressEvent::TopicFinished(result) => write!(f, "{}", result.summary()),
+            ProgressEvent::Message(message) => write!(f, "{}", message),
+            ProgressEvent::Warning(warning) => write!(f, "Warning: {}", warning),
+        }
+    }
+}
+
+/// print events to stdout until every sender has been dropped
+pub fn print_events(receiver: Receiver<ProgressEvent>, style: OutputStyle) {
+    for event in receiver {
+        match event {
+            ProgressEvent::Step { number, .. } => {
+                if number > 1 {
+                    println!();
+                }
+                println!("{}", style.paint(event.to_string(), Color::Bold));
+            },
+            ProgressEvent::DeckReady { .. } => println!("{}", style.paint(event.to_string(), Color::Green)),
+            ProgressEvent::Warning(_) => println!("{}", style.paint(event.to_string(), Color::Yellow)),
+            _ => println!("{}", event),
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use std::{
+    cell::RefCell,
+    error::Error,
+    fs::{self, File, TryLockError},
+    io::{Read, Seek, Write},
+    path::PathBuf,
+    process,
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::progress::ProgressSender;
+use crate::state::{self, file_stem, state_dir, unix_now};
+
+// ============================================================================================
+//                                      Import Queue
+// ============================================================================================
+
+// An import is a queue of note batches. Between batches the importer records its
+// progress to a status file and waits while a pause marker exists.
+//
+// `pause`/`resume` only create/remove the marker and the importer only writes the
+// status file, so the two processes never write the same file.
+//
+// The importer also holds an OS lock on a per-deck lock file for as long as the queue
+// lives, so a second import into the same deck fails fast instead of interleaving
+// batches. The OS drops the lock if the process dies, so a crash never leaves it stuck.
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QueueState {
+    Running,
+    Paused,
+}
+
+/// snapshot of a running import, written to the status file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueStatus {
+    pub deck_name: String,
+    pub state: QueueState,
+    pub pid: u32,
+    pub notes_total: usize,
+    pub notes_done: usize,
+    pub current_topic: Option<String>,
+    pub updated_at: u64,
+}
+
+impl QueueStatus {
+    pub fn print_summary(&self) {
+        let state = match self.state {
+            QueueState::Running => "running",
+            QueueState::Paused => "paused",
+        };
+        let age = unix_now().saturating_sub(self.updated_at);
+
+        println!("Deck '{}': {} (pid {})", self.deck_name, state, self.pid);
+        println!("  Progress: {}/{} notes", self.notes_done, self.notes_total);
+        if let Some(topic) = &self.current_topic {
+            println!("  Current topic: {}", topic);
+        }
+        println!("  Last update: {}s ago", age);
+    }
+}
+
+pub struct ImportQueue {
+    deck_name: String,
+    status: RefCell<QueueStatus>,
+
+    /// held until the queue is dropped
+    _lock: File,
+}
+
+impl ImportQueue {
+    /// start tracking an import of `notes_total` notes into `deck_name`
+    pub fn start(deck_name: &str, notes_total: usize) -> Result<Self, Box<dyn Error>> {
+        fs::create_dir_all(state_dir())?;
+        let lock = acquire_lock(deck_name)?;
+
+        let queue = ImportQueue {
+            deck_name: deck_name.to_string(),
+            status: RefCell::new(QueueStatus {
+                deck_name: deck_name.to_string(),
+                state: QueueState::Running,
+                pid: process::id(),
+                notes_total,
+                notes_done: 0,
+                current_topic: None,
+                updated_at: unix_now(),
+            }),
+            _lock: lock,
+        };
+
+        queue.write_status()?;
+
+        Ok(queue)
+    }
+
+    /// called before each batch: records progress, then blocks while the import is paused
+    pub fn checkpoint(&self, topic: &str, progress: &ProgressSender) -> Result<(), Box<dyn Error>> {
+        self.status.borrow_mut().current_topic = Some(topic.to_string());
+
+        if pause_path(&self.deck_name).exists() {
+            self.set_state(QueueState::Paused)?;
+            progress.message(format!(
+                "  Paused, run `csv-to-anki resume \"{}\"` to continue...", self.deck_name
+            ));
+
+            while pause_path(&self.deck_name).exists() {
+                thread::sleep(POLL_INTERVAL);
+            }
+
+            progress.message("  Resumed");
+        }
+
+        self.set_state(QueueState::Running)
+    }
+
+    /// record that a batch of notes was sent
+    pub fn complete(&self, notes: usize) -> Result<(), Box<dyn Error>> {
+        self.status.borrow_mut().notes_done += notes;
+        self.write_status()
+    }
+
+    /// remove the status file once the import is over
+    pub fn finish(self) -> Result<(), Box<dyn Error>> {
+        let path = status_path(&self.deck_name);
+
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+
+        Ok(())
+    }
+
+    fn set_state(&self, state: QueueState) -> Result<(), Box<dyn Error>> {
+        self.status.borrow_mut().state = state;
+        self.write_status()
+    }
+
+    fn write_status(&self) -> Result<(), Box<dyn Error>> {
+        let mut status = self.status.borrow_mut();
+        status.updated_at = unix_now();
+
+        state::write(&status_path(&self.deck_name), &*status)
+    }
+}
+
+/// ask a running import to pause before its next batch
+pub fn pause(deck_name: &str) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(state_dir())?;
+    fs::write(pause_path(deck_name), "")?;
+
+    Ok(())
+}
+
+/// let a paused import continue (also clears a pause requested before the import started)
+pub fn resume(deck_name: &str) -> Result<(), Box<dyn Error>> {
+    let path = pause_path(deck_name);
+
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+/// status of the import into `deck_name`, if one is running
+pub fn status(deck_name: &str) -> Result<Option<QueueStatus>, Box<dyn Error>> {
+    let path = status_path(deck_name);
+
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    Ok(Some(state::read(&path)?))
+}
+
+/// lock the deck for this process, the lock file records our pid for the error message
+fn acquire_lock(deck_name: &str) -> Result<File, Box<dyn Error>> {
+    let path = lock_path(deck_name);
+    let mut file = File::options().read(true).write(true).create(true).truncate(false).open(&path)?;
+
+    match file.try_lock() {
+        Ok(()) => {},
+        Err(TryLockError::WouldBlock) => {
+            let mut holder = String::new();
+            file.read_to_string(&mut holder)?;
+
+            return Err(format!(
+                "Another import into '{}' is already running (pid {}), wait for it or check `csv-to-anki status \"{}\"`",
+                deck_name, holder.trim(), deck_name
+            ).into());
+        },
+        Err(TryLockError::Error(e)) => return Err(format!("Failed to lock {}: {}", path.display(), e).into()),
+    }
+
+    file.set_len(0)?;
+    file.rewind()?;
+    write!(file, "{}", process::id())?;
+    file.flush()?;
+
+    Ok(file)
+}
+
+fn status_path(deck_name: &str) -> PathBuf {
+    state_dir().join(format!("{}.status.json", file_stem(deck_name)))
+}
+
+fn lock_path(deck_name: &str) -> PathBuf {
+    state_dir().join(format!("{}.lock", file_stem(deck_name)))
+}
+
+fn pause_path(deck_name: &str) -> PathBuf {
+    state_dir().join(format!("{}.pause", file_stem(deck_name)))
+}
@@ -0,0 +1,192 @@
+use std::env;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::output::{Color, OutputStyle};
+
+// ============================================================================================
+//                                      Summary Tables
+// ============================================================================================
+
+// A sheet with forty topics prints forty lines of counts, and `{}` padding counts chars, not
+// terminal columns, so every japanese topic name pushes its row two columns further per
+// character. Tables here pad by display width, cut very long names and group digits the
+// way the user's locale does.
+
+/// names wider than this (in terminal columns) are cut short with '…'
+const MAX_CELL_WIDTH: usize = 32;
+
+/// How big numbers are written: `12,345`, `12.345` or `12 345`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    separator: char,
+}
+
+impl NumberFormat {
+    /// from `LC_ALL`, `LC_NUMERIC` or `LANG`, whichever is set first
+    pub fn detect() -> Self {
+        ["LC_ALL", "LC_NUMERIC", "LANG"].iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|locale| !locale.is_empty())
+            .map_or(NumberFormat::default(), |locale| NumberFormat::for_locale(&locale))
+    }
+
+    /// `de_DE.UTF-8` groups with '.', `fr_FR` with a space, english, japanese and unknown locales with ','
+    pub fn for_locale(locale: &str) -> Self {
+        let language = locale.split(['_', '-', '.', '@']).next().unwrap_or("").to_lowercase();
+
+        let separator = match language.as_str() {
+            "de" | "es" | "it" | "nl" | "pt" | "id" | "da" | "tr" | "el" => '.',
+            "fr" | "ru" | "pl" | "sv" | "fi" | "nb" | "no" | "cs" | "sk" | "uk" | "hu" => ' ',
+            _ => ',',
+        };
+
+        NumberFormat { separator }
+    }
+
+    pub fn format(&self, n: usize) -> String {
+        let digits = n.to_string();
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+
+        for (idx, digit) in digits.chars().enumerate() {
+            if idx > 0 && (digits.len() - idx).is_multiple_of(3) {
+                grouped.push(self.separator);
+            }
+            grouped.push(digit);
+        }
+
+        grouped
+    }
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat { separator: ',' }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// A plain text table, columns sized to their widest cell
+pub struct Table {
+    columns: Vec<(String, Align)>,
+    rows: Vec<Vec<String>>,
+
+    /// a last row set apart by a rule, for totals
+    footer: Option<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(columns: &[(&str, Align)]) -> Self {
+        Table {
+            columns: columns.iter().map(|(name, align)| (name.to_string(), *align)).collect(),
+            rows: Vec::new(),
+            footer: None,
+        }
+    }
+
+    pub fn row(mut self, cells: Vec<String>) -> Self {
+        self.rows.push(cells);
+        self
+    }
+
+    pub fn footer(mut self, cells: Vec<String>) -> Self {
+        self.footer = Some(cells);
+        self
+    }
+
+    /// the table as lines, each indented by `indent` spaces
+    pub fn render(&self, indent: usize, style: OutputStyle) -> String {
+        let cells = |row: &[String]| -> Vec<String> { row.iter().map(|cell| truncate(cell, MAX_CELL_WIDTH)).collect() };
+        let rows: Vec<Vec<String>> = self.rows.iter().map(|row| cells(row)).collect();
+        let footer = self.footer.as_deref().map(cells);
+        let header: Vec<String> = self.columns.iter().map(|(name, _)| name.clone()).collect();
+
+        let widths: Vec<usize> = (0..self.columns.len())
+            .map(|col| {
+                [&header].into_iter().chain(&rows).chain(&footer)
+                    .filter_map(|row| row.get(col))
+                    .map(|cell| cell.width())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let line = |row: &[String]| -> String {
+            let padded: Vec<String> = self.columns.iter().zip(&widths).enumerate()
+                .map(|(col, ((_, align), width))| pad(row.get(col).map_or("", String::as_str), *width, *align))
+                .collect();
+            format!("{}{}", " ".repeat(indent), padded.join("  ").trim_end())
+        };
+
+        let rule = format!("{}{}", " ".repeat(indent), "-".repeat(widths.iter().sum::<usize>() + 2 * widths.len().saturating_sub(1)));
+
+        let mut lines = vec![style.paint(line(&header), Color::Bold), rule.clone()];
+        lines.extend(rows.iter().map(|row| line(row)));
+        if let Some(footer) = &footer {
+            lines.push(rule);
+            lines.push(line(footer));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// `text` padded with spaces to `width` terminal columns
+fn pad(text: &str, width: usize, align: Align) -> String {
+    let fill = " ".repeat(width.saturating_sub(text.width()));
+
+    match align {
+        Align::Left => format!("{}{}", text, fill),
+        Align::Right => format!("{}{}", fill, text),
+    }
+}
+
+/// `text` cut to at most `width` terminal columns, ending in '…' when cut
+fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+
+    let mut cut = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let c_width = c.width().unwrap_or(0);
+        if used + c_width + 1 > width {
+            break;
+        }
+        cut.push(c);
+        used += c_width;
+    }
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_are_grouped_per_locale() {
+        assert_eq!(NumberFormat::default().format(1234567), "1,234,567");
+        assert_eq!(NumberFormat::for_locale("de_DE.UTF-8").format(12345), "12.345");
+        assert_eq!(NumberFormat::for_locale("ja_JP.UTF-8").format(999), "999");
+    }
+
+    #[test]
+    fn wide_names_line_up() {
+        let table = Table::new(&[("Topic", Align::Left), ("Added", Align::Right)])
+            .row(vec!["食べ物".to_string(), "12".to_string()])
+            .row(vec!["Verbs".to_string(), "1,024".to_string()])
+            .render(0, OutputStyle::PLAIN);
+
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[2], "食べ物     12");
+        assert_eq!(lines[3], "Verbs   1,024");
+        assert_eq!(lines[2].width(), lines[3].width());
+    }
+}
@@ -0,0 +1,273 @@
+use std::{collections::HashSet, fmt, str::FromStr};
+
+use crate::language::{script_of, Script};
+
+// ============================================================================================
+//                                  Column Role Inference
+// ============================================================================================
+
+// Sheets that didn't come from the japanese course rarely have the (reading, gloss, kanji)
+// layout, they're whatever columns someone found useful: a word, maybe its pronunciation,
+// a translation, an example sentence, a lesson number. Headers are the best hint when
+// they're recognisable, otherwise every column is judged by what's in it: which script it's
+// written in, how long its cells are, how often values repeat.
+
+/// What a column of a flat vocabulary sheet holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColumnRole {
+    /// the word being learnt (kanji spelling for japanese)
+    Term,
+
+    /// how it's pronounced: kana, pinyin, romanisation
+    Reading,
+
+    /// the translation
+    Gloss,
+
+    /// a sentence using the word
+    Example,
+
+    /// a label shared by many rows: lesson, category, topic
+    Tags,
+
+    /// not used for cards
+    Unused,
+}
+
+impl ColumnRole {
+    /// names recognised in headers, compared per word and lowercased
+    fn header_names(&self) -> &'static [&'static str] {
+        match self {
+            ColumnRole::Term => &["word", "term", "vocab", "vocabulary", "kanji", "expression", "front", "単語", "漢字"],
+            ColumnRole::Reading => &["reading", "kana", "furigana", "hiragana", "pronunciation", "pinyin", "romaji", "読み", "ふりがな"],
+            ColumnRole::Gloss => &["meaning", "translation", "english", "definition", "gloss", "back", "意味", "英語"],
+            ColumnRole::Example => &["example", "examples", "sentence", "sentences", "例文"],
+            ColumnRole::Tags => &["tags", "tag", "category", "topic", "lesson", "chapter", "unit"],
+            ColumnRole::Unused => &[],
+        }
+    }
+
+    /// the role a header names, if any
+    fn from_header(header: &str) -> Option<Self> {
+        let header = header.trim().to_lowercase();
+        let words: Vec<&str> = header.split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()).collect();
+
+        ROLES.into_iter().find(|role| {
+            role.header_names().iter().any(|name| words.contains(name) || header == *name)
+        })
+    }
+}
+
+/// every role a column can be given, in the order headers are checked
+const ROLES: [ColumnRole; 5] = [ColumnRole::Example, ColumnRole::Tags, ColumnRole::Reading, ColumnRole::Gloss, ColumnRole::Term];
+
+impl fmt::Display for ColumnRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ColumnRole::Term => "term",
+            ColumnRole::Reading => "reading",
+            ColumnRole::Gloss => "gloss",
+            ColumnRole::Example => "example",
+            ColumnRole::Tags => "tags",
+            ColumnRole::Unused => "unused",
+        };
+        f.pad(name)
+    }
+}
+
+impl FromStr for ColumnRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "term" | "word" => Ok(ColumnRole::Term),
+            "reading" => Ok(ColumnRole::Reading),
+            "gloss" | "meaning" => Ok(ColumnRole::Gloss),
+            "example" => Ok(ColumnRole::Example),
+            "tags" | "tag" => Ok(ColumnRole::Tags),
+            "unused" | "skip" | "" => Ok(ColumnRole::Unused),
+            other => Err(format!(
+                "Unknown column role '{}', expected one of: term, reading, gloss, example, tags, unused", other
+            )),
+        }
+    }
+}
+
+/// What the cells of one column look like
+#[derive(Debug, Clone, Copy)]
+struct ColumnProfile {
+    filled: usize,
+    script: Script,
+
+    /// share of cells that read like a sentence
+    sentences: f64,
+
+    /// distinct values per filled cell, low for labels like lesson numbers
+    distinct: f64,
+
+    /// share of cells with accented latin letters (pinyin tones, romanisation)
+    accented: f64,
+}
+
+impl ColumnProfile {
+    fn of<'a>(cells: impl Iterator<Item = &'a str>) -> Self {
+        let cells: Vec<&str> = cells.map(str::trim).filter(|cell| !cell.is_empty()).collect();
+        let share = |count: usize| if cells.is_empty() { 0.0 } else { count as f64 / cells.len() as f64 };
+
+        let mut scripts = [(Script::Kana, 0), (Script::Kanji, 0), (Script::Latin, 0), (Script::Other, 0)];
+        for cell in &cells {
+            let script = script_of(cell);
+            if let Some((_, count)) = scripts.iter_mut().find(|(s, _)| *s == script) {
+                *count += 1;
+            }
+        }
+        // first script with the most cells, ties go to the japanese scripts
+        let script = scripts.iter().fold((Script::Other, 0), |best, &(script, count)| {
+            if count > best.1 { (script, count) } else { best }
+        }).0;
+
+        ColumnProfile {
+            filled: cells.len(),
+            script,
+            sentences: share(cells.iter().filter(|cell| is_sentence(cell)).count()),
+            distinct: share(cells.iter().collect::<HashSet<_>>().len()),
+            accented: share(cells.iter().filter(|cell| cell.chars().any(|c| ('\u{00C0}'..='\u{024F}').contains(&c))).count()),
+        }
+    }
+
+    fn is_foreign(&self) -> bool {
+        !matches!(self.script, Script::Latin)
+    }
+}
+
+/// several words ending like a sentence, or just long
+fn is_sentence(cell: &str) -> bool {
+    let words = cell.split_whitespace().count();
+    let chars = cell.chars().count();
+    let ends = cell.ends_with(['.', '!', '?', '。', '！', '？']);
+    let cjk = matches!(script_of(cell), Script::Kana | Script::Kanji);
+
+    (ends && (words >= 3 || (cjk && chars >= 6))) || words >= 6 || (cjk && chars >= 12)
+}
+
+/// Propose a role for every column of a flat sheet
+///
+/// `headers` may be empty (no header row). Recognised headers decide first, the remaining
+/// columns are guessed from their contents. Every role but `Unused` is given at most once,
+/// and there's always a `Term` when some column has text.
+pub fn infer_roles(headers: &[String], rows: &[Vec<String>]) -> Vec<ColumnRole> {
+    let width = rows.iter().map(Vec::len).chain([headers.len()]).max().unwrap_or(0);
+    let profiles: Vec<ColumnProfile> = (0..width)
+        .map(|col| ColumnProfile::of(rows.iter().filter_map(|row| row.get(col)).map(String::as_str)))
+        .collect();
+
+    let mut roles = vec![ColumnRole::Unused; width];
+    let taken = |roles: &[ColumnRole], role: ColumnRole| roles.contains(&role);
+
+    for (col, header) in headers.iter().enumerate() {
+        if let Some(role) = ColumnRole::from_header(header).filter(|role| !taken(&roles, *role)) {
+            roles[col] = role;
+        }
+    }
+
+    let free = |roles: &[ColumnRole]| -> Vec<usize> {
+        (0..width).filter(|&col| roles[col] == ColumnRole::Unused && profiles[col].filled > 0).collect()
+    };
+
+    // sentences and labels stand out whatever the language
+    if !taken(&roles, ColumnRole::Example)
+        && let Some(col) = free(&roles).into_iter().find(|&col| profiles[col].sentences > 0.5)
+    {
+        roles[col] = ColumnRole::Example;
+    }
+    if !taken(&roles, ColumnRole::Tags)
+        && let Some(col) = free(&roles).into_iter().find(|&col| profiles[col].filled >= 4 && profiles[col].distinct <= 0.5)
+    {
+        roles[col] = ColumnRole::Tags;
+    }
+
+    // the term is the one in the foreign script (kanji before kana), or the first column for latin pairs
+    if !taken(&roles, ColumnRole::Term) {
+        let free = free(&roles);
+        let term = free.iter().find(|&&col| profiles[col].script == Script::Kanji)
+            .or_else(|| free.iter().find(|&&col| profiles[col].is_foreign()))
+            .or(free.first());
+
+        if let Some(&col) = term {
+            roles[col] = ColumnRole::Term;
+        }
+    }
+
+    let term = roles.iter().position(|role| *role == ColumnRole::Term).map(|col| profiles[col]);
+
+    // kana next to kanji, or accented latin next to a foreign script, is how the term is read
+    if !taken(&roles, ColumnRole::Reading)
+        && let Some(term) = term
+    {
+        let reading = free(&roles).into_iter().find(|&col| match (term.script, profiles[col].script) {
+            (Script::Kanji, Script::Kana) => true,
+            (Script::Latin, _) => false,
+            (_, Script::Latin) => profiles[col].accented > 0.3,
+            _ => false,
+        });
+
+        if let Some(col) = reading {
+            roles[col] = ColumnRole::Reading;
+        }
+    }
+
+    // the translation is in another script than the term if there's such a column
+    if !taken(&roles, ColumnRole::Gloss) {
+        let free = free(&roles);
+        let gloss = free.iter().find(|&&col| term.is_none_or(|term| profiles[col].script != term.script))
+            .or(free.first());
+
+        if let Some(&col) = gloss {
+            roles[col] = ColumnRole::Gloss;
+        }
+    }
+
+    roles
+}
+
+/// `true` when a header row names at least one role, used to tell a header row from data
+pub fn looks_like_headers(row: &[String]) -> bool {
+    row.iter().any(|cell| ColumnRole::from_header(cell).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(rows: &[&[&str]]) -> Vec<Vec<String>> {
+        rows.iter().map(|row| row.iter().map(|cell| cell.to_string()).collect()).collect()
+    }
+
+    #[test]
+    fn japanese_sheet_without_headers() {
+        let rows = rows(&[
+            &["apple", "りんご", "林檎", "りんごを食べます。", "L1"],
+            &["water", "みず", "水", "水を飲みます。", "L1"],
+            &["cat", "ねこ", "猫", "猫が好きです。", "L1"],
+            &["to eat", "たべる", "食べる", "ご飯を食べる。", "L2"],
+        ]);
+
+        assert_eq!(infer_roles(&[], &rows), vec![
+            ColumnRole::Gloss, ColumnRole::Reading, ColumnRole::Term, ColumnRole::Example, ColumnRole::Tags,
+        ]);
+    }
+
+    #[test]
+    fn other_language_pairs() {
+        let chinese = rows(&[&["你好", "nǐ hǎo", "hello"], &["谢谢", "xiè xie", "thanks"], &["猫", "māo", "cat"]]);
+        assert_eq!(infer_roles(&[], &chinese), vec![ColumnRole::Term, ColumnRole::Reading, ColumnRole::Gloss]);
+
+        let spanish = rows(&[&["hello", "hola"], &["cat", "gato"]]);
+        let headers = vec!["English".to_string(), "Spanish".to_string()];
+        assert!(looks_like_headers(&headers));
+        assert_eq!(infer_roles(&headers, &spanish), vec![ColumnRole::Gloss, ColumnRole::Term]);
+
+        let russian = rows(&[&["привет", "hello"], &["кошка", "cat"]]);
+        assert_eq!(infer_roles(&[], &russian), vec![ColumnRole::Term, ColumnRole::Gloss]);
+    }
+}
@@ -0,0 +1,223 @@
+use std::str::FromStr;
+
+// ============================================================================================
+//                                      Field Sanitising
+// ============================================================================================
+
+// Anki fields are html. A `<` in an example sentence opens a tag that never closes and the
+// rest of the card disappears, so text from the spreadsheet is escaped on its way into a
+// note. People who style their cards with <b> or <ruby> can keep a list of tags instead,
+// anything else is still escaped and script/style never make it through.
+
+/// tags kept by `--html allow` when no list is given: inline formatting and furigana
+pub const DEFAULT_ALLOWED_TAGS: &[&str] = &[
+    "b", "i", "u", "em", "strong", "s", "sub", "sup", "small", "br", "ruby", "rb", "rt", "rp",
+];
+
+/// elements dropped with everything inside them, whatever the policy
+const DROPPED_ELEMENTS: &[&str] = &["script", "style"];
+
+/// tags without a closing tag
+const VOID_TAGS: &[&str] = &["br", "hr", "wbr"];
+
+/// How spreadsheet text is turned into html for note fields
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum HtmlPolicy {
+    /// everything is text, `<b>` shows up as `<b>`
+    #[default]
+    Escape,
+
+    /// these tags (lowercase, attributes removed) are kept, other tags are escaped,
+    /// script and style are removed along with their contents
+    Allow(Vec<String>),
+
+    /// the field is sent as it is, for sheets that already hold the exact html wanted
+    Raw,
+}
+
+impl HtmlPolicy {
+    /// `--html allow` with the default tag list
+    pub fn allow_default() -> Self {
+        HtmlPolicy::Allow(DEFAULT_ALLOWED_TAGS.iter().map(|tag| tag.to_string()).collect())
+    }
+
+    /// field html for spreadsheet text
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            HtmlPolicy::Escape => escape(text, false),
+            HtmlPolicy::Allow(tags) => sanitize(text, tags),
+            HtmlPolicy::Raw => text.to_string(),
+        }
+    }
+}
+
+impl FromStr for HtmlPolicy {
+    type Err = String;
+
+    /// `escape`, `raw`, `allow` or `allow:b,i,ruby,rt`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        let (name, tags) = s.split_once(':').map_or((s.as_str(), None), |(name, tags)| (name, Some(tags)));
+
+        match (name, tags) {
+            ("escape", None) => Ok(HtmlPolicy::Escape),
+            ("raw", None) => Ok(HtmlPolicy::Raw),
+            ("allow", None) => Ok(HtmlPolicy::allow_default()),
+            ("allow", Some(tags)) => {
+                let tags: Vec<String> = tags.split(',')
+                    .map(|tag| tag.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+                    .filter(|tag| !tag.is_empty())
+                    .collect();
+
+                match tags.iter().find(|tag| DROPPED_ELEMENTS.contains(&tag.as_str())) {
+                    Some(tag) => Err(format!("<{}> can't be allowed in note fields", tag)),
+                    None => Ok(HtmlPolicy::Allow(tags)),
+                }
+            },
+            _ => Err(format!("Unknown html policy '{}', expected one of: escape, allow, allow:TAG,TAG, raw", s)),
+        }
+    }
+}
+
+/// escape text for html, `keep_entities` leaves `&amp;`-style entities alone
+fn escape(text: &str, keep_entities: bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for (idx, c) in text.char_indices() {
+        match c {
+            '&' if keep_entities && is_entity(&text[idx..]) => escaped.push('&'),
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// reverse of `HtmlPolicy::Escape`, field html back to the text that was typed
+///
+/// only the entities escaping produces (and anki's `&#39;` and `&nbsp;`) are decoded,
+/// `&amp;` last so `&amp;lt;` stays `&lt;`
+pub fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// `&nbsp;`, `&#12354;` or `&#x3042;` at the start of `text`
+fn is_entity(text: &str) -> bool {
+    let Some(end) = text.find(';').filter(|&end| end > 1 && end <= 10) else {
+        return false;
+    };
+
+    let body = &text[1..end];
+    match body.strip_prefix('#') {
+        Some(number) => number.strip_prefix(['x', 'X'])
+            .map_or(!number.is_empty() && number.chars().all(|c| c.is_ascii_digit()), |hex| {
+                !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit())
+            }),
+        None => body.chars().all(|c| c.is_ascii_alphanumeric()),
+    }
+}
+
+/// a tag at the start of `text`: (name lowercased, is closing, length in bytes)
+fn parse_tag(text: &str) -> Option<(String, bool, usize)> {
+    let inner = text.strip_prefix('<')?;
+    let (closing, inner) = match inner.strip_prefix('/') {
+        Some(inner) => (true, inner),
+        None => (false, inner),
+    };
+
+    let name_len = inner.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(inner.len());
+    if name_len == 0 || !inner.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    // the tag ends at the first '>', a '<' before it means this wasn't a tag
+    let end = inner.find(['>', '<']).filter(|&end| inner[end..].starts_with('>'))?;
+
+    let len = text.len() - inner.len() + end + 1;
+    Some((inner[..name_len].to_lowercase(), closing, len))
+}
+
+/// keep `allowed` tags (without attributes), escape every other bit of markup
+fn sanitize(text: &str, allowed: &[String]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('<') {
+        out.push_str(&escape(&rest[..start], true));
+        rest = &rest[start..];
+
+        let Some((name, closing, len)) = parse_tag(rest) else {
+            out.push_str("&lt;");
+            rest = &rest[1..];
+            continue;
+        };
+
+        if DROPPED_ELEMENTS.contains(&name.as_str()) {
+            rest = if closing { &rest[len..] } else { skip_element(rest, &name) };
+            continue;
+        }
+
+        if allowed.iter().any(|tag| tag.eq_ignore_ascii_case(&name)) {
+            match (closing, VOID_TAGS.contains(&name.as_str())) {
+                (true, true) => {},
+                (true, false) => out.push_str(&format!("</{}>", name)),
+                (false, _) => out.push_str(&format!("<{}>", name)),
+            }
+        } else {
+            out.push_str(&escape(&rest[..len], true));
+        }
+
+        rest = &rest[len..];
+    }
+
+    out.push_str(&escape(rest, true));
+    out
+}
+
+/// the text after `<name ...>...</name>`, or nothing if it's never closed
+fn skip_element<'a>(text: &'a str, name: &str) -> &'a str {
+    let lower = text.to_ascii_lowercase();
+    let close = format!("</{}", name);
+
+    lower.find(&close)
+        .and_then(|start| text[start..].find('>').map(|end| &text[start + end + 1..]))
+        .unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_keeps_sentences_intact() {
+        assert_eq!(HtmlPolicy::Escape.apply("a < b & <b>c</b>"), "a &lt; b &amp; &lt;b&gt;c&lt;/b&gt;");
+    }
+
+    #[test]
+    fn unescape_reverses_escape() {
+        let text = r#"a < b & "c" > d &lt;"#;
+        assert_eq!(unescape(&HtmlPolicy::Escape.apply(text)), text);
+        assert_eq!(unescape("it&#39;s&nbsp;ok"), "it's ok");
+    }
+
+    #[test]
+    fn allow_keeps_listed_tags_only() {
+        let policy: HtmlPolicy = "allow".parse().unwrap();
+
+        assert_eq!(
+            policy.apply(r#"<ruby>漢字<rt>かんじ</rt></ruby> <B onclick="x()">bold</B> <span>hi</span> 1 < 2"#),
+            "<ruby>漢字<rt>かんじ</rt></ruby> <b>bold</b> &lt;span&gt;hi&lt;/span&gt; 1 &lt; 2"
+        );
+        assert_eq!(policy.apply("a<script>alert(1)</script>b<STYLE>p{}</style >c"), "abc");
+        assert_eq!(policy.apply("tom&nbsp;&amp; jerry & co<br/>"), "tom&nbsp;&amp; jerry &amp; co<br>");
+    }
+}
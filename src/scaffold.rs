@@ -0,0 +1,168 @@
+use std::{error::Error, fs, io::Write, path::Path};
+
+use csv_partitioner::FromColumnSlice;
+
+use crate::encoding::Input;
+use crate::parse::{Topic, Word};
+use crate::roles::{self, ColumnRole};
+
+// ============================================================================================
+//                                  CSV Scaffolding
+// ============================================================================================
+
+/// Build topics from a flat word list, one word per line
+///
+/// blank lines separate topics, a block starting with `# name` uses that as the topic name,
+/// otherwise topics are numbered ("Topic 1", "Topic 2", ...)
+pub fn topics_from_word_list(text: &str) -> Vec<Topic> {
+    let mut topics: Vec<Topic> = Vec::new();
+    let mut current: Option<Topic> = None;
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            topics.extend(current.take());
+            continue;
+        }
+
+        let topic = current.get_or_insert_with(|| Topic {
+            name: String::new(),
+            words: Vec::new(),
+        });
+
+        match line.strip_prefix('#') {
+            Some(name) if topic.words.is_empty() && topic.name.is_empty() => {
+                topic.name = name.trim().to_string();
+            },
+            _ => topic.words.push(Word::new(line, "", "")),
+        }
+    }
+
+    topics.extend(current);
+
+    for (idx, topic) in topics.iter_mut().enumerate() {
+        if topic.name.is_empty() {
+            topic.name = format!("Topic {}", idx + 1);
+        }
+    }
+
+    topics
+}
+
+/// Write topics in the repeating-column layout the importer expects
+///
+/// topic1, , ,  topic2, , , ...
+/// word, translation, kanji,  word, translation, kanji, ...
+pub fn write_topics_csv<W: Write>(topics: &[Topic], writer: W) -> Result<(), Box<dyn Error>> {
+    let mut writer = csv::Writer::from_writer(writer);
+
+    let headers: Vec<&str> = topics.iter()
+        .flat_map(|topic| {
+            std::iter::once(topic.name().as_str())
+                .chain(std::iter::repeat_n("", Word::COLUMN_COUNT - 1))
+        })
+        .collect();
+    writer.write_record(&headers)?;
+
+    let row_count = topics.iter().map(|topic| topic.words().len()).max().unwrap_or(0);
+
+    for row in 0..row_count {
+        let record: Vec<&str> = topics.iter()
+            .flat_map(|topic| match topic.words().get(row) {
+                Some(word) => [word.japanese().as_str(), word.english().as_str(), word.kanji().as_str()],
+                None => ["", "", ""],
+            })
+            .collect();
+        writer.write_record(&record)?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Build topics from a flat sheet, one word per row and columns used as `roles` says
+///
+/// the tags column (if any) groups words into topics named after its values, in the order
+/// they first appear. without one everything goes into "Topic 1"
+pub fn topics_from_table(rows: &[Vec<String>], roles: &[ColumnRole]) -> Vec<Topic> {
+    let column = |role: ColumnRole| roles.iter().position(|r| *r == role);
+    let (term, reading, gloss, tags) =
+        (column(ColumnRole::Term), column(ColumnRole::Reading), column(ColumnRole::Gloss), column(ColumnRole::Tags));
+
+    let cell = |row: &Vec<String>, col: Option<usize>| -> String {
+        col.and_then(|col| row.get(col)).map_or("", |cell| cell.trim()).to_string()
+    };
+
+    let mut topics: Vec<Topic> = Vec::new();
+
+    for row in rows {
+        // with a reading the term is the kanji spelling, without one it's what gets read
+        let word = match reading {
+            Some(_) => Word::new(cell(row, reading), cell(row, gloss), cell(row, term)),
+            None => Word::new(cell(row, term), cell(row, gloss), ""),
+        };
+        if word.is_empty() {
+            continue;
+        }
+
+        let name = Some(cell(row, tags)).filter(|tag| !tag.is_empty()).unwrap_or_else(|| "Topic 1".to_string());
+        match topics.iter_mut().find(|topic| topic.name == name) {
+            Some(topic) => topic.words.push(word),
+            None => topics.push(Topic { name, words: vec![word] }),
+        }
+    }
+
+    topics
+}
+
+/// A scaffolded file: its topics, and for a sheet the role each column was given
+pub struct Scaffold {
+    pub topics: Vec<Topic>,
+    pub columns: Vec<(String, ColumnRole)>,
+}
+
+/// read a word list (or a flat `.csv`/`.tsv` sheet) and write the CSV skeleton next to it (or to `output`)
+///
+/// sheet columns are mapped by `roles` when given, otherwise guessed
+pub fn scaffold_file(input: &Input, output: &str, roles: Option<&[ColumnRole]>) -> Result<Scaffold, Box<dyn Error>> {
+    let extension = Path::new(&input.name).extension().map(|ext| ext.to_ascii_lowercase());
+
+    let scaffold = match extension.as_ref().and_then(|ext| ext.to_str()) {
+        Some("csv") => sheet_scaffold(&input.text, b',', roles)?,
+        Some("tsv") => sheet_scaffold(&input.text, b'\t', roles)?,
+        _ => Scaffold { topics: topics_from_word_list(&input.text), columns: Vec::new() },
+    };
+
+    if scaffold.topics.is_empty() {
+        return Err(format!("No words found in {}", input.name).into());
+    }
+
+    write_topics_csv(&scaffold.topics, fs::File::create(output)?)?;
+
+    Ok(scaffold)
+}
+
+fn sheet_scaffold(text: &str, delimiter: u8, roles: Option<&[ColumnRole]>) -> Result<Scaffold, Box<dyn Error>> {
+    let mut rows: Vec<Vec<String>> = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes())
+        .records()
+        .map(|record| record.map(|record| record.iter().map(str::to_string).collect()))
+        .collect::<Result<_, _>>()?;
+
+    // the first row is only a header when it names something, plain word lists start with a word
+    let headers = if rows.first().is_some_and(|row| roles::looks_like_headers(row)) { rows.remove(0) } else { Vec::new() };
+
+    let roles = match roles {
+        Some(roles) => roles.to_vec(),
+        None => roles::infer_roles(&headers, &rows),
+    };
+
+    let columns = roles.iter().enumerate()
+        .map(|(col, role)| (headers.get(col).cloned().unwrap_or_else(|| format!("column {}", col + 1)), *role))
+        .collect();
+
+    Ok(Scaffold { topics: topics_from_table(&rows, &roles), columns })
+}
@@ -0,0 +1,126 @@
+use std::{error::Error, io::Write};
+
+use crate::parse::{Topic, Word};
+
+// ============================================================================================
+//                                      Study Sheets
+// ============================================================================================
+
+// Printable handouts built from the same parsed topics an import would send to anki.
+// The page is self-contained (inline css, no scripts) so it can be emailed or printed
+// straight from a browser, "Save as PDF" in the print dialog gives a PDF.
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; }
+h1 { margin-bottom: 0.2em; }
+section { margin-top: 2em; }
+table { border-collapse: collapse; width: 100%; }
+th, td { border: 1px solid #999; padding: 0.4em 0.6em; text-align: left; }
+th { background: #eee; }
+td.japanese { font-size: 1.4em; }
+td.index { width: 2em; color: #666; }
+rt { font-size: 0.55em; }
+@media print {
+  body { margin: 0; }
+  section { break-inside: avoid-page; }
+  section + section { break-before: page; }
+}
+";
+
+/// Output formats `export` can write
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SheetFormat {
+    #[default]
+    Html,
+}
+
+impl std::str::FromStr for SheetFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "html" => Ok(SheetFormat::Html),
+            "pdf" => Err("PDF export isn't built in, export html and use 'Save as PDF' when printing it".to_string()),
+            other => Err(format!("Unknown export format '{}', expected: html", other)),
+        }
+    }
+}
+
+/// Write topics as an html page with one vocabulary table per topic
+///
+/// words with kanji show their reading as furigana above it
+pub fn write_html<W: Write>(topics: &[Topic], title: &str, mut writer: W) -> Result<(), Box<dyn Error>> {
+    writeln!(writer, "<!DOCTYPE html>")?;
+    writeln!(writer, "<html lang=\"ja\">")?;
+    writeln!(writer, "<head>")?;
+    writeln!(writer, "<meta charset=\"utf-8\">")?;
+    writeln!(writer, "<title>{}</title>", escape(title))?;
+    writeln!(writer, "<style>{}</style>", STYLE)?;
+    writeln!(writer, "</head>")?;
+    writeln!(writer, "<body>")?;
+    writeln!(writer, "<h1>{}</h1>", escape(title))?;
+
+    for topic in topics {
+        writeln!(writer, "<section>")?;
+        writeln!(writer, "<h2>{}</h2>", escape(topic.name()))?;
+        writeln!(writer, "<table>")?;
+        writeln!(writer, "<tr><th></th><th>Japanese</th><th>English</th></tr>")?;
+
+        for (idx, word) in topic.words().iter().filter(|word| !word.is_empty()).enumerate() {
+            writeln!(
+                writer,
+                "<tr><td class=\"index\">{}</td><td class=\"japanese\">{}</td><td>{}</td></tr>",
+                idx + 1, furigana(word), escape(word.english())
+            )?;
+        }
+
+        writeln!(writer, "</table>")?;
+        writeln!(writer, "</section>")?;
+    }
+
+    writeln!(writer, "</body>")?;
+    writeln!(writer, "</html>")?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// `<ruby>漢字<rt>かんじ</rt></ruby>`, or just the kana when there's no kanji
+fn furigana(word: &Word) -> String {
+    let kanji = word.kanji().trim();
+
+    if kanji.is_empty() {
+        escape(word.japanese())
+    } else {
+        format!("<ruby>{}<rt>{}</rt></ruby>", escape(kanji), escape(word.japanese()))
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_are_escaped_and_kanji_get_furigana() {
+        let topics = vec![Topic {
+            name: "Q&A".to_string(),
+            words: vec![Word::new("あと", "after <then>", "後"), Word::new("はい", "\"yes\"", "")],
+        }];
+
+        let mut page = Vec::new();
+        write_html(&topics, "<Japanese>", &mut page).unwrap();
+        let page = String::from_utf8(page).unwrap();
+
+        assert!(page.contains("<title>&lt;Japanese&gt;</title>"));
+        assert!(page.contains("<h2>Q&amp;A</h2>"));
+        assert!(page.contains("<td class=\"japanese\"><ruby>後<rt>あと</rt></ruby></td><td>after &lt;then&gt;</td>"));
+        assert!(page.contains("<td class=\"japanese\">はい</td><td>&quot;yes&quot;</td>"));
+    }
+}
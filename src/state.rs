@@ -0,0 +1,341 @@
+use std::{
+    error::Error,
+    fs::{self, File, TryLockError},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+
+use crate::output::{Glyph, OutputStyle};
+
+// ============================================================================================
+//                                      State Files
+// ============================================================================================
+
+// Imports leave files behind between runs (status, locks, pause markers), and a newer
+// version may need more in them. Every json state file carries the format version it was
+// written with. Reading applies the migrations from that version up to the current one,
+// so files from an older csv-to-anki keep working, files from a newer one are refused
+// with a clear message instead of being misread. `state doctor` checks the whole
+// directory and with --repair rewrites old files and removes broken or stale ones.
+
+/// version written into every state file
+pub const STATE_VERSION: u64 = 2;
+
+/// upgrades a file's fields by one version
+type Migration = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// `MIGRATIONS[n]` turns a version n+1 file into version n+2
+///
+/// - 1 -> 2: version 1 had no `version` field at all, nothing else changed
+const MIGRATIONS: &[Migration] = &[
+    |_| Ok(()),
+];
+
+const _: () = assert!(MIGRATIONS.len() as u64 == STATE_VERSION - 1, "every version needs a migration to the next");
+
+/// where state files live
+pub fn state_dir() -> PathBuf {
+    std::env::temp_dir().join("csv-to-anki")
+}
+
+/// a deck name as a file name: `::`, spaces, dots etc. become `_`, so state files and backups
+/// of a deck all start with the same stem
+pub fn file_stem(deck_name: &str) -> String {
+    deck_name.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+/// seconds since the epoch, 0 if the clock is before it
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// read a state file, migrating it in memory if it's from an older version
+pub fn read<T: DeserializeOwned>(path: &Path) -> Result<T, Box<dyn Error>> {
+    let (value, _) = read_migrated(path)?;
+    Ok(serde_json::from_value(Value::Object(value))?)
+}
+
+/// write a state file with the current version, readers never see it half written
+pub fn write<T: Serialize>(path: &Path, state: &T) -> Result<(), Box<dyn Error>> {
+    let Value::Object(mut fields) = serde_json::to_value(state)? else {
+        return Err(format!("State for {} isn't a json object", path.display()).into());
+    };
+    fields.insert("version".to_string(), STATE_VERSION.into());
+
+    let temp = path.with_extension("tmp");
+    fs::write(&temp, serde_json::to_string_pretty(&fields)?)?;
+    fs::rename(temp, path)?;
+
+    Ok(())
+}
+
+/// the file's fields at the current version, and the version it was written with
+fn read_migrated(path: &Path) -> Result<(Map<String, Value>, u64), Box<dyn Error>> {
+    let Value::Object(mut fields) = serde_json::from_str(&fs::read_to_string(path)?)? else {
+        return Err(format!("{} isn't a json object", path.display()).into());
+    };
+
+    let version = match fields.get("version") {
+        None => 1,
+        Some(version) => version.as_u64().filter(|v| *v >= 1)
+            .ok_or_else(|| format!("{} has an invalid version {}", path.display(), version))?,
+    };
+
+    if version > STATE_VERSION {
+        return Err(format!(
+            "{} was written by a newer csv-to-anki (state version {}, this one reads up to {}), upgrade to use it",
+            path.display(), version, STATE_VERSION
+        ).into());
+    }
+
+    for migrate in &MIGRATIONS[(version - 1) as usize..] {
+        migrate(&mut fields).map_err(|e| format!("Failed to migrate {}: {}", path.display(), e))?;
+    }
+    fields.insert("version".to_string(), STATE_VERSION.into());
+
+    Ok((fields, version))
+}
+
+/// What `state doctor` found wrong with a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// written by an older version, readable but worth rewriting
+    Outdated(u64),
+
+    /// written by a newer version, left alone
+    TooNew(String),
+
+    /// not valid json, or not a state file
+    Unreadable(String),
+
+    /// status of an import that isn't running anymore (it crashed or was killed)
+    Stale,
+
+    /// a half-written file from an interrupted write
+    Leftover,
+}
+
+impl Problem {
+    fn describe(&self) -> String {
+        match self {
+            Problem::Outdated(version) => format!("written by an older version (state version {})", version),
+            Problem::TooNew(message) | Problem::Unreadable(message) => message.clone(),
+            Problem::Stale => "status of an import that isn't running anymore".to_string(),
+            Problem::Leftover => "left over from an interrupted write".to_string(),
+        }
+    }
+
+    /// `--repair` can do something about it
+    fn repairable(&self) -> bool {
+        !matches!(self, Problem::TooNew(_))
+    }
+}
+
+/// One checked file
+pub struct Finding {
+    pub path: PathBuf,
+    pub problem: Option<Problem>,
+    pub repaired: bool,
+}
+
+/// check every state file, fixing what can be fixed when `repair` is set
+pub fn doctor(repair: bool) -> Result<Vec<Finding>, Box<dyn Error>> {
+    doctor_in(&state_dir(), repair)
+}
+
+fn doctor_in(dir: &Path, repair: bool) -> Result<Vec<Finding>, Box<dyn Error>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+    paths.sort();
+
+    let mut findings = Vec::new();
+
+    for path in paths {
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("").to_string();
+
+        // locks and pause markers have no format to check
+        if !name.ends_with(".tmp") && !name.ends_with(".status.json") {
+            continue;
+        }
+
+        // held until the file is checked and fixed, so an import can't start in between.
+        // `file_stem` leaves no dots in a deck's stem, its lock is `<stem>.lock`
+        let stem = name.split('.').next().unwrap_or("");
+        let lock = DeckLock::acquire(&dir.join(format!("{}.lock", stem)))?;
+
+        let problem = if name.ends_with(".tmp") {
+            // a running import writes one on every batch, only ones older than its lock are leftovers
+            let in_flight = match (&lock, modified(&path)) {
+                (DeckLock::Ours { .. }, _) => false,
+                (DeckLock::Running(Some(since)), Some(written)) => written >= *since,
+                (DeckLock::Running(_), _) => true,
+            };
+            if in_flight {
+                continue;
+            }
+
+            Some(Problem::Leftover)
+        } else {
+            check_status(&path, &lock)
+        };
+
+        let repaired = match &problem {
+            Some(problem) if repair && problem.repairable() => {
+                fix(&path, problem)?;
+                true
+            },
+            _ => false,
+        };
+
+        findings.push(Finding { path, problem, repaired });
+    }
+
+    Ok(findings)
+}
+
+/// the version comes first, a status from a newer csv-to-anki is left alone even when its
+/// import is gone
+fn check_status(path: &Path, lock: &DeckLock) -> Option<Problem> {
+    let version = match read_migrated(path) {
+        Ok((_, version)) => version,
+        Err(e) if e.to_string().contains("newer csv-to-anki") => return Some(Problem::TooNew(e.to_string())),
+        Err(e) => return Some(Problem::Unreadable(e.to_string())),
+    };
+
+    // the import holds the lock for as long as it runs, if we got it the import is gone
+    if matches!(lock, DeckLock::Ours { .. }) {
+        return Some(Problem::Stale);
+    }
+
+    (version < STATE_VERSION).then_some(Problem::Outdated(version))
+}
+
+fn fix(path: &Path, problem: &Problem) -> Result<(), Box<dyn Error>> {
+    match problem {
+        Problem::Outdated(_) => {
+            let (fields, _) = read_migrated(path)?;
+            write(path, &fields)
+        },
+        Problem::Unreadable(_) | Problem::Stale | Problem::Leftover => Ok(fs::remove_file(path)?),
+        Problem::TooNew(_) => Ok(()),
+    }
+}
+
+/// A deck's import lock, as `state doctor` found it
+enum DeckLock {
+    /// doctor holds it now, no import is running
+    Ours { _lock: File },
+
+    /// a running import holds it, since the time the lock file was last written
+    Running(Option<SystemTime>),
+}
+
+impl DeckLock {
+    /// take the lock if no import holds it (the file is created like an import would)
+    fn acquire(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let file = File::options().read(true).write(true).create(true).truncate(false).open(path)?;
+
+        match file.try_lock() {
+            Ok(()) => Ok(DeckLock::Ours { _lock: file }),
+            Err(TryLockError::WouldBlock) => Ok(DeckLock::Running(modified(path))),
+            Err(TryLockError::Error(e)) => Err(format!("Failed to lock {}: {}", path.display(), e).into()),
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+pub fn print_findings(findings: &[Finding], repair: bool, style: OutputStyle) {
+    if findings.is_empty() {
+        println!("No state files in {}", state_dir().display());
+        return;
+    }
+
+    for finding in findings {
+        let name = finding.path.file_name().map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+
+        match &finding.problem {
+            None => println!("  {} {}", style.status(Glyph::Added), name),
+            Some(problem) if finding.repaired => {
+                println!("  {} {}: {}, repaired", style.status(Glyph::Skipped), name, problem.describe())
+            },
+            Some(problem) => println!("  {} {}: {}", style.status(Glyph::Failed), name, problem.describe()),
+        }
+    }
+
+    let unrepaired = findings.iter().filter(|f| f.problem.as_ref().is_some_and(Problem::repairable) && !f.repaired).count();
+    if !repair && unrepaired > 0 {
+        println!("\nRun `csv-to-anki state doctor --repair` to fix {} of them", unrepaired);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unversioned_files_are_migrated_and_newer_ones_refused() {
+        let dir = std::env::temp_dir().join(format!("csv-to-anki-state-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let old = dir.join("old.status.json");
+        fs::write(&old, r#"{"deck_name": "Japanese", "notes_done": 3}"#).unwrap();
+        let (fields, version) = read_migrated(&old).unwrap();
+        assert_eq!((fields["version"].as_u64(), version), (Some(STATE_VERSION), 1));
+
+        let new = dir.join("new.status.json");
+        fs::write(&new, format!(r#"{{"version": {}}}"#, STATE_VERSION + 1)).unwrap();
+        let error = read_migrated(&new).unwrap_err().to_string();
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(error.contains("newer csv-to-anki"), "{}", error);
+    }
+
+    #[test]
+    fn doctor_checks_versions_first_and_leaves_running_imports_alone() {
+        let dir = std::env::temp_dir().join(format!("csv-to-anki-doctor-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        // nothing runs these: one from a newer version, one finished
+        fs::write(dir.join("newer.status.json"), format!(r#"{{"version": {}}}"#, STATE_VERSION + 1)).unwrap();
+        fs::write(dir.join("done.status.json"), format!(r#"{{"version": {}}}"#, STATE_VERSION)).unwrap();
+
+        // a leftover from before the running import took its lock, and the write in flight
+        let leftover = File::create(dir.join("busy.old.tmp")).unwrap();
+        leftover.set_modified(SystemTime::now() - std::time::Duration::from_secs(60)).unwrap();
+        let lock = File::create(dir.join("busy.lock")).unwrap();
+        lock.try_lock().unwrap();
+        fs::write(dir.join("busy.status.tmp"), "{").unwrap();
+
+        let findings = doctor_in(&dir, true).unwrap();
+        let problems: Vec<(String, Option<Problem>)> = findings.into_iter()
+            .map(|finding| (finding.path.file_name().unwrap().to_string_lossy().into_owned(), finding.problem))
+            .collect();
+        let remaining = ["newer.status.json", "done.status.json", "busy.old.tmp", "busy.status.tmp"].map(|name| dir.join(name).exists());
+
+        drop(lock);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(&problems[..], [
+            (old, Some(Problem::Leftover)),
+            (done, Some(Problem::Stale)),
+            (newer, Some(Problem::TooNew(_))),
+        ] if old == "busy.old.tmp" && done == "done.status.json" && newer == "newer.status.json"), "{:?}", problems);
+        assert_eq!(remaining, [true, false, false, true]);
+    }
+}
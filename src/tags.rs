@@ -0,0 +1,195 @@
+use std::str::FromStr;
+
+// ============================================================================================
+//                                      Tag Templates
+// ============================================================================================
+
+// Tags put on every imported note come from a space separated template, e.g.
+//
+//   topic::{topic} jlpt::n5 {deck}
+//
+// `::` nests tags in anki's tag browser, so `topic::{topic}` files every topic under
+// one `topic` tag and split topics (`Food::1`) nest one level deeper.
+
+/// the tags notes got before templates existed
+pub const DEFAULT_TAG_TEMPLATE: &str = "{topic} japanese vocabularly";
+
+const PLACEHOLDERS: &[&str] = &["{topic}", "{deck}"];
+
+/// Tags to put on each note, with `{topic}` and `{deck}` filled in per note
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagTemplate {
+    tags: Vec<String>,
+}
+
+impl TagTemplate {
+    /// tags for a note in `topic` of `deck`
+    ///
+    /// spaces in the topic/deck name become `_` (anki tags can't contain spaces),
+    /// tags left with an empty part (`topic::` for a note without a topic) are dropped
+    pub fn render(&self, topic: &str, deck: &str) -> Vec<String> {
+        let topic = tag_safe(topic);
+        let deck = tag_safe(deck);
+
+        self.tags.iter()
+            .map(|tag| tag.replace("{topic}", &topic).replace("{deck}", &deck))
+            .filter(|tag| !tag.split("::").any(str::is_empty))
+            .collect()
+    }
+
+    /// anki search terms for notes this template tagged in `deck`, whatever their topic
+    ///
+    /// `{topic}` becomes a wildcard, tags that are nothing but `{topic}` say nothing and are
+    /// left out (empty when that leaves no tag), e.g. `"tag:japanese" "tag:vocabularly"`
+    pub fn search(&self, deck: &str) -> String {
+        let deck = tag_safe(deck);
+
+        self.tags.iter()
+            .filter(|tag| tag.as_str() != "{topic}")
+            .map(|tag| {
+                let parts: Vec<String> = tag.replace("{deck}", &deck)
+                    .split("{topic}")
+                    .map(escape_search)
+                    .collect();
+                format!("\"tag:{}\"", parts.join("*"))
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// whether a note's `tags` hold every tag this template gives notes of `deck`, whatever
+    /// their topic, i.e. whether an import with this template could have made the note
+    ///
+    /// anki compares tags case insensitively, so this does too
+    pub fn matches(&self, tags: &[String], deck: &str) -> bool {
+        let deck = tag_safe(deck).to_lowercase();
+        let tags: Vec<String> = tags.iter().map(|tag| tag.to_lowercase()).collect();
+
+        self.tags.iter().all(|template| {
+            let template = template.to_lowercase().replace("{deck}", &deck);
+            let parts: Vec<&str> = template.split("{topic}").collect();
+            tags.iter().any(|tag| matches_parts(&parts, tag))
+        })
+    }
+}
+
+impl Default for TagTemplate {
+    fn default() -> Self {
+        TagTemplate { tags: DEFAULT_TAG_TEMPLATE.split_whitespace().map(str::to_string).collect() }
+    }
+}
+
+impl FromStr for TagTemplate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tags: Vec<String> = s.split_whitespace().map(str::to_string).collect();
+
+        for tag in &tags {
+            let mut rest = tag.as_str();
+
+            while let Some(start) = rest.find('{') {
+                let placeholder = rest[start..].find('}')
+                    .map(|end| &rest[start..=start + end])
+                    .ok_or_else(|| format!("Unclosed '{{' in tag '{}'", tag))?;
+
+                if !PLACEHOLDERS.contains(&placeholder) {
+                    return Err(format!(
+                        "Unknown placeholder {} in tag '{}', expected one of: {}",
+                        placeholder, tag, PLACEHOLDERS.join(", ")
+                    ));
+                }
+
+                rest = &rest[start + placeholder.len()..];
+            }
+        }
+
+        Ok(TagTemplate { tags })
+    }
+}
+
+fn tag_safe(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+/// `text` escaped to go between the quotes of an anki search (`"deck:..."`, `"tag:..."`),
+/// where `*` and `_` are wildcards and `"` ends the search term
+pub fn escape_search(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if matches!(c, '\\' | '"' | '*' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// `text` is the parts with a non-empty topic between each pair
+fn matches_parts(parts: &[&str], text: &str) -> bool {
+    match parts {
+        [] => text.is_empty(),
+        [last] => text == *last,
+        [first, rest @ ..] => text.strip_prefix(first).is_some_and(|text| {
+            (1..=text.len())
+                .filter(|&idx| text.is_char_boundary(idx))
+                .any(|idx| matches_parts(rest, &text[idx..]))
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|tag| tag.to_string()).collect()
+    }
+
+    #[test]
+    fn notes_match_the_template_they_were_tagged_with() {
+        let template: TagTemplate = "topic::{topic} jlpt::n5 {deck}".parse().unwrap();
+        let rendered = template.render("Food Words", "Japanese N5");
+
+        assert!(template.matches(&rendered, "Japanese N5"));
+        assert!(template.matches(&tags(&["TOPIC::Verbs", "jlpt::N5", "japanese_n5", "mine"]), "Japanese N5"));
+        assert!(!template.matches(&tags(&["topic::Verbs", "japanese_n5"]), "Japanese N5"));
+        assert!(!template.matches(&tags(&["topic::", "jlpt::n5", "japanese_n5"]), "Japanese N5"));
+        assert!(!template.matches(&rendered, "Korean"));
+    }
+
+    #[test]
+    fn placeholders_are_filled_in_and_empty_tags_dropped() {
+        let template: TagTemplate = "topic::{topic} {deck}::{topic} jlpt".parse().unwrap();
+
+        assert_eq!(template.render("Food Words", "Japanese"), tags(&["topic::Food_Words", "Japanese::Food_Words", "jlpt"]));
+        assert_eq!(template.render("", "Japanese"), tags(&["jlpt"]));
+        assert_eq!(TagTemplate::default().render("Food", "Japanese"), tags(&["Food", "japanese", "vocabularly"]));
+    }
+
+    #[test]
+    fn unknown_and_unclosed_placeholders_are_refused() {
+        let unknown = "{topic} {level}".parse::<TagTemplate>().unwrap_err();
+        assert!(unknown.contains("Unknown placeholder {level}"), "{}", unknown);
+
+        let unclosed = "topic::{topic".parse::<TagTemplate>().unwrap_err();
+        assert!(unclosed.contains("Unclosed"), "{}", unclosed);
+    }
+
+    #[test]
+    fn searches_keep_the_fixed_part_of_each_tag() {
+        assert_eq!(TagTemplate::default().search("Japanese"), r#""tag:japanese" "tag:vocabularly""#);
+
+        let template: TagTemplate = "topic::{topic} {deck} n5_words".parse().unwrap();
+        assert_eq!(template.search("My Deck"), r#""tag:topic::*" "tag:My\_Deck" "tag:n5\_words""#);
+
+        assert_eq!("{topic}".parse::<TagTemplate>().unwrap().search("Japanese"), "");
+    }
+
+    #[test]
+    fn search_wildcards_are_escaped() {
+        assert_eq!(escape_search(r#"JLPT_N5 *"best"\"#), r#"JLPT\_N5 \*\"best\"\\"#);
+    }
+}
@@ -1,8 +1,10 @@
-#[allow(dead_code, unused_variables)]
-
-
-use crate::{anki::{AnkiConnectClient, DuplicateScopeOptions, Note, NoteFields, OptionFields}, parse::{Topic, Word}};
-use std::{error::Error, vec};
+use crate::{anki::{deck_search, AnkiConnectClient, DuplicateScope, DuplicateScopeOptions, Note, NoteFields, NoteInfo, OptionFields}, parse::{Topic, Word}};
+use crate::sanitize::HtmlPolicy;
+use crate::card::{CardStyle, FrontStrategy};
+use crate::progress::{ProgressEvent, ProgressSender};
+use crate::queue::ImportQueue;
+use crate::tags::{escape_search, TagTemplate};
+use std::{cell::OnceCell, collections::{HashMap, HashSet}, error::Error, vec};
 
 // ============================================================================================
 //                          High-Level API for Japanese Vocabularly
@@ -11,10 +13,60 @@ use std::{error::Error, vec};
 // TODO: 
 // Bulk import - import_topicS, add_noteS (DONE)
 
+/// notes sent per addNotes request, also how often a paused import is noticed
+const IMPORT_BATCH_SIZE: usize = 100;
+
+/// What to do with leeches once they've been exported
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LeechAction {
+    /// forget the cards so they start again as new
+    Reset,
+
+    /// set a new due date (anki's syntax, e.g. `0`, `3`, `1-7`)
+    Reschedule(String),
+}
+
+/// How cards of a note whose content changed on re-import get rescheduled
+///
+/// a fixed reading or a new meaning shouldn't keep the interval earned on the old one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchedulingReset {
+    /// back to new, review history dropped
+    Forget,
+
+    /// back into (re)learning, review history kept
+    Relearn,
+}
+
+impl std::str::FromStr for SchedulingReset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "forget" | "new" => Ok(SchedulingReset::Forget),
+            "relearn" => Ok(SchedulingReset::Relearn),
+            other => Err(format!("Unknown scheduling reset '{}', expected one of: forget, relearn", other)),
+        }
+    }
+}
+
 pub struct JapaneseVocabImporter {
     pub client: AnkiConnectClient,
     deck_name: String,
     model_name: String,
+    front_field: String,
+    back_field: String,
+    front_strategy: FrontStrategy,
+    topic_field: Option<String>,
+    flat_deck: bool,
+    allow_duplicates: bool,
+    duplicate_scope: DuplicateScope,
+    update_existing: bool,
+    reset_scheduling: Option<SchedulingReset>,
+    html: HtmlPolicy,
+    tags: TagTemplate,
+    model_fields: OnceCell<Vec<String>>,
+    progress: ProgressSender,
 }
 
 impl JapaneseVocabImporter {
@@ -24,13 +76,125 @@ impl JapaneseVocabImporter {
         JapaneseVocabImporter {
             client: AnkiConnectClient::new(),
             deck_name: deck_name.into(),
-            model_name: "Basic".to_string()  // <--- will add support for other models later
+            model_name: "Basic".to_string(),
+            front_field: "Front".to_string(),
+            back_field: "Back".to_string(),
+            front_strategy: FrontStrategy::default(),
+            topic_field: None,
+            flat_deck: false,
+            allow_duplicates: false,
+            duplicate_scope: DuplicateScope::default(),
+            update_existing: false,
+            reset_scheduling: None,
+            html: HtmlPolicy::default(),
+            tags: TagTemplate::default(),
+            model_fields: OnceCell::new(),
+            progress: ProgressSender::default(),
         }
     }
 
+    /// Report progress events to a subscriber instead of staying silent
+    pub fn with_progress(mut self, progress: ProgressSender) -> Self {
+        self.progress = progress;
+        self
+    }
+
     /// Set a custom note type/model
-    pub fn _with_model(mut self, model_name: impl Into<String>) -> Self {
+    pub fn with_model(mut self, model_name: impl Into<String>) -> Self {
         self.model_name = model_name.into();
+        self.model_fields = OnceCell::new();
+        self
+    }
+
+    /// Set which note type fields receive the front and back text
+    pub fn with_fields(mut self, front_field: impl Into<String>, back_field: impl Into<String>) -> Self {
+        self.front_field = front_field.into();
+        self.back_field = back_field.into();
+        self
+    }
+
+    /// Also write the topic name into this note field (e.g. `Category`)
+    pub fn with_topic_field(mut self, topic_field: impl Into<String>) -> Self {
+        self.topic_field = Some(topic_field.into());
+        self
+    }
+
+    /// Put every note straight into the main deck instead of one subdeck per topic,
+    /// topics are still kept in the tags (and the topic field, if set)
+    pub fn with_flat_deck(mut self, flat_deck: bool) -> Self {
+        self.flat_deck = flat_deck;
+        self
+    }
+
+    /// Add notes even when the same front already exists in their deck
+    ///
+    /// off by default, so importing the same file twice adds nothing the second time
+    pub fn with_allow_duplicates(mut self, allow_duplicates: bool) -> Self {
+        self.allow_duplicates = allow_duplicates;
+        self
+    }
+
+    /// Look for duplicates in the whole collection instead of just the note's deck
+    ///
+    /// with `Collection` a word already in another deck isn't added again (nor updated,
+    /// `with_update_existing` only finds notes in the same deck)
+    pub fn with_duplicate_scope(mut self, duplicate_scope: DuplicateScope) -> Self {
+        self.duplicate_scope = duplicate_scope;
+        self
+    }
+
+    /// Update notes whose front is already in their deck with the new fields, instead of
+    /// counting them as duplicates (e.g. after fixing translations)
+    pub fn with_update_existing(mut self, update_existing: bool) -> Self {
+        self.update_existing = update_existing;
+        self
+    }
+
+    /// Reschedule the cards of notes whose content gets changed by a re-import
+    ///
+    /// off by default, changed cards keep their review history
+    pub fn with_reset_scheduling(mut self, reset_scheduling: Option<SchedulingReset>) -> Self {
+        self.reset_scheduling = reset_scheduling;
+        self
+    }
+
+    /// Choose how spreadsheet text becomes field html (escaped by default)
+    pub fn with_html_policy(mut self, html: HtmlPolicy) -> Self {
+        self.html = html;
+        self
+    }
+
+    /// Set the tags put on every note (`{topic}`/`{deck}` are filled in per note)
+    pub fn with_tags(mut self, tags: TagTemplate) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Choose what goes on the front of each card
+    pub fn with_front_strategy(mut self, front_strategy: FrontStrategy) -> Self {
+        self.front_strategy = front_strategy;
+        self
+    }
+
+    /// field names of the target note type, fetched from anki once
+    fn model_field_names(&self) -> Result<&[String], Box<dyn Error>> {
+        if let Some(fields) = self.model_fields.get() {
+            return Ok(fields);
+        }
+
+        let fields = self.client.model_field_names(&self.model_name)?;
+        Ok(self.model_fields.get_or_init(|| fields))
+    }
+
+    /// put a note's fields in model order, rejecting fields the note type doesn't have
+    fn order_fields(&self, mut note: Note) -> Result<Note, Box<dyn Error>> {
+        note.fields = note.fields.in_model_order(&self.model_name, self.model_field_names()?)?;
+        Ok(note)
+    }
+
+    /// Use a preconfigured client (custom URL, User-Agent, headers, chunk size)
+    pub fn with_client(mut self, client: AnkiConnectClient) -> Self {
+        self.client = client;
         self
     }
 
@@ -48,85 +212,260 @@ impl JapaneseVocabImporter {
                 format!("Cannot connect to to Anki. Is Anki running with AnkiConnect installed? Error: {}", e)
             )?;
 
-        println!("Success: Connected to Anki");
+        self.progress.message("Success: Connected to Anki");
 
         // create deck (won't fail if it exists)
         self.client.create_deck(&self.deck_name)?;
 
-        println!("Success: Deck '{}' ready", self.deck_name);
+        self.progress.message(format!("Success: Deck '{}' ready", self.deck_name));
 
         Ok(())
     }
 
 
-    pub fn initialise_with_topics(&self, topics: &[Topic]) -> Result<(), Box<dyn Error>> {
-        self.client.create_deck(&self.deck_name)?;
+    pub fn initialise_with_topics(&self, topics: &[Topic]) -> Result<HashMap<String, i64>, Box<dyn Error>> {
+        // main deck first, then every subdeck, all in a single `multi` request
+        let subdecks = topics.iter()
+            .filter(|_| !self.flat_deck)
+            .map(|topic| self.subdeck_name(topic.name()));
+        let deck_names: Vec<String> = std::iter::once(self.deck_name.clone())
+            .chain(subdecks)
+            .collect();
 
-        println!("Success: Main Deck '{}' ready", self.deck_name);
+        let deck_ids = self.client.create_decks(&deck_names)?;
 
-        println!("\nCreating subdecks for topics: ");
-        for topic in topics {
-            let subdeck_name = format!("{}::{}", self.deck_name, topic.name());
-            let deck_id = self.client.create_deck(&subdeck_name)?;
-            println!("  Success: Created - '{}', id = {}", subdeck_name, &deck_id);
+        self.progress.message(format!("Success: Main Deck '{}' ready", self.deck_name));
+
+        if !self.flat_deck {
+            self.progress.message("\nCreating subdecks for topics: ");
         }
+        let mut topic_decks = HashMap::with_capacity(topics.len());
+        let subdecks = deck_names.into_iter().zip(deck_ids).skip(1);
 
-        Ok(())
+        for (topic, (deck_name, deck_id)) in topics.iter().zip(subdecks) {
+            self.progress.emit(ProgressEvent::DeckReady { deck_name, deck_id });
+            topic_decks.insert(topic.name().clone(), deck_id);
+        }
+
+        Ok(topic_decks)
     }
 
-    /// Convert a Word to an Anki Note
-    /// Creates a subdeck for each topic using :: notation
-    /// 
-    /// 
-    /// front: kanji, if present, else japanese
-    /// back: if front = kanji, japanese + english, else just english
-    pub fn word_to_note(&self, word: &Word, topic: &str) -> Note {
-        let full_deck_name = if topic.is_empty() {
-            self.deck_name.clone()
-        } else {
-            format!("{}::{}", self.deck_name, topic)
-        };
+    /// name of the main deck
+    pub fn deck_name(&self) -> &str {
+        &self.deck_name
+    }
+
+    /// full deck name for a topic subdeck, e.g. `Japanese::Food`
+    pub fn subdeck_name(&self, topic: &str) -> String {
+        format!("{}::{}", self.deck_name, topic)
+    }
+
+    /// merge one topic subdeck into another
+    ///
+    /// 1. move every card into the destination subdeck
+    /// 2. swap the source topic's tags for the destination's (as the tag template renders them)
+    ///    on the notes that have them
+    /// 3. delete the source subdeck, once a fresh search shows the move left it empty
+    pub fn merge_topics(&self, from_topic: &str, into_topic: &str) -> Result<MergeResult, Box<dyn Error>> {
+        let from_deck = self.subdeck_name(from_topic);
+        let into_deck = self.subdeck_name(into_topic);
+
+        if from_deck == into_deck {
+            return Err(format!("Cannot merge '{}' into itself", from_deck).into());
+        }
+
+        let query = deck_search(&from_deck);
+        let card_ids = self.client.find_cards(&query)?;
+        let note_ids = self.client.find_notes(&query)?;
+
+        self.client.create_deck(&into_deck)?;
+
+        if !card_ids.is_empty() {
+            self.client.change_deck(&card_ids, &into_deck)?;
+        }
 
+        let from_tags = self.tags.render(from_topic, &self.deck_name);
+        let into_tags = self.tags.render(into_topic, &self.deck_name);
+        let stale: Vec<&str> = from_tags.iter()
+            .filter(|tag| !into_tags.contains(tag))
+            .map(String::as_str)
+            .collect();
 
-        let front = if word.kanji().trim().is_empty() {
-            word.japanese().clone()
+        // only notes carrying a stale tag get retagged, e.g. not ones added to the deck by hand
+        let retagged: Vec<i64> = if stale.is_empty() {
+            Vec::new()
         } else {
-            word.kanji().clone()
+            self.client.notes_info(&note_ids)?
+                .into_iter()
+                .filter(|info| info.tags.iter().any(|tag| stale.iter().any(|stale| tag.to_lowercase() == stale.to_lowercase())))
+                .map(|info| info.note_id)
+                .collect()
         };
 
-        let back = if word.kanji().trim().is_empty() {
-            word.english().clone()
-        } else {
-            word.japanese().clone() + " | " + &word.english().clone()
+        if !retagged.is_empty() {
+            self.client.remove_tags(&retagged, &stale.join(" "))?;
+            self.client.add_tags(&retagged, &into_tags.join(" "))?;
+        }
+
+        // a card added to the source deck since it was searched would go with it
+        let left = self.client.find_cards(&query)?;
+        if !left.is_empty() {
+            return Err(format!(
+                "'{}' still holds {} cards after the move, not deleting it (run the merge again)",
+                from_deck, left.len()
+            ).into());
+        }
+
+        self.client.delete_deck(&from_deck, true)?;
+
+        Ok(MergeResult {
+            from_deck,
+            into_deck,
+            cards_moved: card_ids.len(),
+            notes_retagged: retagged.len(),
+        })
+    }
+
+    /// find leech cards in the deck and turn them back into words, grouped by topic subdeck
+    ///
+    /// only notes with the tags our tag template gives (its fixed part, see
+    /// `TagTemplate::search`) are looked at, so leeches of notes added by hand aren't reset.
+    /// a template that is nothing but `{topic}` can't tell them apart, the whole deck is searched
+    pub fn find_leeches(&self) -> Result<LeechReport, Box<dyn Error>> {
+        let query = format!("{} tag:leech {}", deck_search(&self.deck_name), self.tags.search(&self.deck_name));
+        let query = query.trim_end();
+        let card_ids = self.client.find_cards(query)?;
+
+        let mut report = LeechReport { topics: Vec::new(), card_ids, note_ids: Vec::new() };
+        if report.card_ids.is_empty() {
+            return Ok(report);
+        }
+
+        let prefix = format!("{}::", self.deck_name);
+
+        for card in self.client.cards_info(&report.card_ids)? {
+            // every card of a note carries the same fields, only export the note once
+            if report.note_ids.contains(&card.note) {
+                continue;
+            }
+            report.note_ids.push(card.note);
+
+            let fields = card.ordered_fields();
+            let (Some(front), Some(back)) = (fields.first(), fields.get(1)) else {
+                continue;
+            };
+
+            let word = self.front_strategy.word_from_sides(front, back);
+            let from_field = self.topic_field.as_ref()
+                .and_then(|field| card.fields.get(field))
+                .map(|field| field.value.trim())
+                .filter(|name| !name.is_empty());
+
+            let topic_name = from_field
+                .or_else(|| card.deck_name.strip_prefix(&prefix))
+                .unwrap_or("Misc")
+                .to_string();
+
+            match report.topics.iter_mut().find(|topic| topic.name == topic_name) {
+                Some(topic) => topic.words.push(word),
+                None => report.topics.push(Topic { name: topic_name, words: vec![word] }),
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// reset or reschedule the leeches found by `find_leeches`
+    ///
+    /// the cards are also unsuspended and untagged, so they're back in rotation
+    pub fn treat_leeches(&self, report: &LeechReport, action: &LeechAction) -> Result<(), Box<dyn Error>> {
+        if report.card_ids.is_empty() {
+            return Ok(());
+        }
+
+        match action {
+            LeechAction::Reset => self.client.forget_cards(&report.card_ids)?,
+            LeechAction::Reschedule(days) => self.client.set_due_date(&report.card_ids, days)?,
+        }
+
+        self.client.unsuspend(&report.card_ids)?;
+        self.client.remove_tags(&report.note_ids, "leech")?;
+
+        Ok(())
+    }
+
+    /// apply `with_reset_scheduling` to every card of the given notes, returns how many cards it touched
+    ///
+    /// does nothing (and asks anki nothing) when the reset is off
+    pub fn reset_scheduling(&self, note_ids: &[i64]) -> Result<usize, Box<dyn Error>> {
+        let Some(reset) = self.reset_scheduling else {
+            return Ok(0);
         };
+        if note_ids.is_empty() {
+            return Ok(0);
+        }
 
+        let ids: Vec<String> = note_ids.iter().map(i64::to_string).collect();
+        let card_ids = self.client.find_cards(&format!("nid:{}", ids.join(",")))?;
 
-        Note {
-            deck_name: full_deck_name.clone(),
-            model_name: self.model_name.clone(),
-            fields: NoteFields {
-                front: front,
-                back: back,
-            },
-            options: Some(OptionFields {
-                allow_duplicate: true,
-                duplicate_scope: "deck".to_string(),
-                duplicate_scope_options: DuplicateScopeOptions {
-                    deck_name: full_deck_name.clone(),
-                    check_children: false,
-                    check_all_models: false,
-                }
+        match reset {
+            SchedulingReset::Forget => self.client.forget_cards(&card_ids)?,
+            SchedulingReset::Relearn => self.client.relearn_cards(&card_ids)?,
+        }
+
+        Ok(card_ids.len())
+    }
+
+    /// Convert a Word to an Anki Note
+    /// Creates a subdeck for each topic using :: notation (unless the deck is flat)
+    ///
+    /// front/back text comes from the importer's `FrontStrategy`
+    ///
+    /// errors if the front would be empty, anki can't show a blank question
+    pub fn word_to_note(&self, word: &Word, topic: &str) -> Result<Note, Box<dyn Error>> {
+        let card = self.card_style().card(word, topic)?;
+
+        let mut fields = NoteFields::new()
+            .with(&self.front_field, card.front)
+            .with(&self.back_field, card.back);
+
+        if let Some(topic_field) = &self.topic_field {
+            fields.set(topic_field, self.html.apply(topic));
+        }
+
+        for (name, value) in word.extra_fields() {
+            fields.set(name, self.html.apply(value));
+        }
+
+        Ok(Note {
+            options: Some(match (self.allow_duplicates, self.duplicate_scope) {
+                (true, _) => OptionFields::allow_duplicates(),
+                (false, DuplicateScope::Deck) => OptionFields::reject_duplicates_in_deck(&card.deck),
+                (false, DuplicateScope::Collection) => OptionFields::reject_duplicates(DuplicateScope::Collection, DuplicateScopeOptions::default()),
             }),
-            tags: vec![topic.to_string(), "japanese".to_string(), "vocabularly".to_string()]
-            .into_iter().filter(|t| !t.is_empty()).collect(),
+            deck_name: card.deck,
+            model_name: self.model_name.clone(),
+            fields,
+            tags: card.tags,
             audio: None,
             picture: None,
+        })
+    }
+
+    /// how this importer turns words into cards
+    pub fn card_style(&self) -> CardStyle<'_> {
+        CardStyle {
+            deck_name: &self.deck_name,
+            front_strategy: self.front_strategy,
+            flat_deck: self.flat_deck,
+            tags: &self.tags,
+            html: &self.html,
         }
     }
 
     /// Import a single word
     pub fn _import_word(&self, word: &Word, topic_name: &str) -> Result<i64, Box<dyn Error>> {
-        let note = self.word_to_note(word, topic_name);
+        let note = self.order_fields(self.word_to_note(word, topic_name)?)?;
         self.client._add_note(note)
     }
 
@@ -143,51 +482,276 @@ impl JapaneseVocabImporter {
     /// 
     /// 1. create deck
     /// 2. populate deck
-    pub fn import_topic(&self, topic: &Topic) -> Result<ImportResult, Box<dyn Error>> {
-        let mut result: ImportResult = ImportResult::new(&topic.name());
+    fn import_topic_queued(&self, topic: &Topic, queue: Option<&ImportQueue>) -> Result<ImportResult, Box<dyn Error>> {
+        let mut result: ImportResult = ImportResult::new(topic.name());
         
         
-        let notes: Vec<Note> = topic.words()
-            .iter()
-            .map(|word| self.word_to_note(word, topic.name()))
-            .collect();
+        let mut notes: Vec<Note> = Vec::with_capacity(topic.words().len());
 
-        let add_results: Vec<Result<i64, String>> = self.client.add_notes(notes)?;
+        for word in topic.words() {
+            match self.word_to_note(word, topic.name()).and_then(|note| self.order_fields(note)) {
+                Ok(note) => notes.push(note),
+                Err(e) => {
+                    result.errors += 1;
+                    self.progress.message(format!("  Error: {}", e));
+                }
+            }
+        }
+
+        for batch in notes.chunks(IMPORT_BATCH_SIZE) {
+            self.import_batch(topic.name(), batch, queue, &mut result)?;
+        }
+
+        Ok(result)
+    }
 
-        // println!("{:?}", &add_results);
+    /// send one batch of notes, waiting first if the import has been paused
+    fn import_batch(
+        &self,
+        topic_name: &str,
+        batch: &[Note],
+        queue: Option<&ImportQueue>,
+        result: &mut ImportResult,
+    ) -> Result<(), Box<dyn Error>> {
+        if let Some(queue) = queue {
+            queue.checkpoint(topic_name, &self.progress)?;
+        }
+
+        let (notes, existing) = self.check_batch(batch, result)?;
+        self.update_existing_notes(&existing, result)?;
+
+        let add_results: Vec<Result<i64, String>> = if notes.is_empty() {
+            Vec::new()
+        } else {
+            self.client.add_notes(notes.clone())?
+        };
 
-        for (_idx, add_result) in add_results.iter().enumerate() {
+        for (note, add_result) in notes.iter().zip(&add_results) {
             match add_result {
-                Ok(_note_id) => {
-                    result.added += 1;
-                    // println!("  Success: Added card - {}, id = {}", idx, note_id);
-                },
+                Ok(_note_id) => result.added += 1,
 
-                Err(e) if e.contains("Duplicate") => {
-                    result.duplicates += 1;
-                    // println!("  Error: Duplicate card - {}, dupe count = {} | {}", idx, result.duplicates, e);
-                },
+                Err(e) if e.to_lowercase().contains("duplicate") => result.duplicates += 1,
 
                 Err(e) => {
                     result.errors += 1;
-                    // println!("  Error: Failed adding card - {}, error count = {} | {}", idx, result.errors, e);
+                    self.progress.message(format!("  Error: '{}': {}", front_of(note), e));
                 }
             }
         }
 
-        Ok(result)
+        if let Some(queue) = queue {
+            queue.complete(batch.len())?;
+        }
+
+        Ok(())
+    }
+
+
+    /// ask anki which notes it would add (canAddNotesWithErrorDetail), before sending any
+    ///
+    /// notes anki already has, or that repeat earlier in the batch, count as duplicates so a
+    /// re-import reports duplicates instead of errors. notes refused for another reason
+    /// count as errors. returns the notes left to add, and the ones anki already has when
+    /// they're to be updated instead
+    fn check_batch(&self, batch: &[Note], result: &mut ImportResult) -> Result<(Vec<Note>, Vec<Note>), Box<dyn Error>> {
+        let can_add = self.client.can_add_notes_with_error_detail(batch)?;
+        let mut seen: Vec<(&str, &str)> = Vec::with_capacity(batch.len());
+        let mut notes = Vec::with_capacity(batch.len());
+        let mut existing = Vec::new();
+
+        for (note, can_add) in batch.iter().zip(can_add) {
+            // anki compares the first field of the note type, the notes are already in model order
+            let deck = match self.duplicate_scope {
+                DuplicateScope::Deck => note.deck_name.as_str(),
+                DuplicateScope::Collection => "",
+            };
+            let key = (deck, front_of(note));
+
+            let repeated = seen.contains(&key);
+
+            match can_add {
+                Err(e) if e.to_lowercase().contains("duplicate") && self.update_existing && !repeated => {
+                    seen.push(key);
+                    existing.push(note.clone());
+                },
+                Err(e) if e.to_lowercase().contains("duplicate") => result.duplicates += 1,
+                Err(e) => {
+                    result.errors += 1;
+                    self.progress.message(format!("  Error: '{}': {}", key.1, e));
+                },
+                Ok(()) if !self.allow_duplicates && repeated => result.duplicates += 1,
+                Ok(()) => {
+                    seen.push(key);
+                    notes.push(note.clone());
+                },
+            }
+        }
+
+        Ok((notes, existing))
+    }
+
+    /// write the fields of notes anki already has into the matching notes (same deck and
+    /// front), then reschedule the changed ones as `with_reset_scheduling` says
+    ///
+    /// notes that already hold the same fields count as duplicates, as do ones whose match
+    /// can't be found (e.g. it has a different note type)
+    fn update_existing_notes(&self, notes: &[Note], result: &mut ImportResult) -> Result<(), Box<dyn Error>> {
+        let mut decks: HashMap<&str, HashMap<String, NoteInfo>> = HashMap::new();
+        let mut updates = Vec::new();
+
+        for note in notes {
+            if !decks.contains_key(note.deck_name.as_str()) {
+                let by_front = self.notes_in_deck(&note.deck_name)?
+                    .into_iter()
+                    .filter_map(|info| Some((info.ordered_fields().first()?.to_string(), info)))
+                    .collect();
+                decks.insert(&note.deck_name, by_front);
+            }
+
+            let fields: Vec<&str> = note.fields.iter().map(|(_, value)| value).collect();
+            let Some(info) = decks[note.deck_name.as_str()].get(front_of(note)) else {
+                result.duplicates += 1;
+                continue;
+            };
+
+            if info.ordered_fields() == fields {
+                result.duplicates += 1;
+                continue;
+            }
+
+            updates.push((note, info.note_id));
+        }
+
+        let fields: Vec<(i64, &NoteFields)> = updates.iter().map(|(note, id)| (*id, &note.fields)).collect();
+        let mut changed = Vec::new();
+
+        for ((note, id), outcome) in updates.iter().zip(self.client.update_notes_fields(&fields)?) {
+            match outcome {
+                Ok(()) => {
+                    result.updated += 1;
+                    changed.push(*id);
+                },
+                Err(e) => {
+                    result.errors += 1;
+                    self.progress.message(format!("  Error: '{}': {}", front_of(note), e));
+                },
+            }
+        }
+
+        let reset = self.reset_scheduling(&changed)?;
+        if reset > 0 {
+            self.progress.message(format!("  Rescheduled {} cards of updated notes", reset));
+        }
+
+        Ok(())
+    }
+
+    /// notes of this importer's note type in `deck` and its subdecks, each with the deck its
+    /// first card is in
+    ///
+    /// names are escaped in the search and decks come from the cards, so a `JLPT_N5` deck
+    /// doesn't pick up notes of `JLPTxN5`
+    fn notes_with_decks(&self, deck: &str) -> Result<Vec<(String, NoteInfo)>, Box<dyn Error>> {
+        let query = format!("{} \"note:{}\"", deck_search(deck), escape_search(&self.model_name));
+        let notes = self.client.notes_info(&self.client.find_notes(&query)?)?;
+
+        let first_cards: Vec<i64> = notes.iter().filter_map(|info| info.cards.first().copied()).collect();
+        let decks: HashMap<i64, String> = self.client.cards_info(&first_cards)?
+            .into_iter()
+            .map(|card| (card.note, card.deck_name))
+            .collect();
+
+        Ok(notes.into_iter()
+            .filter_map(|info| Some((decks.get(&info.note_id)?.clone(), info)))
+            .filter(|(note_deck, _)| *note_deck == deck || note_deck.starts_with(&format!("{}::", deck)))
+            .collect())
     }
 
+    /// notes of this importer's note type in `deck` itself, not its subdecks
+    fn notes_in_deck(&self, deck: &str) -> Result<Vec<NoteInfo>, Box<dyn Error>> {
+        Ok(self.notes_with_decks(deck)?
+            .into_iter()
+            .filter(|(note_deck, _)| note_deck == deck)
+            .map(|(_, info)| info)
+            .collect())
+    }
+
+    /// delete notes whose row is gone from the file, then the subdecks that leaves empty
+    ///
+    /// a note is kept when a card built from `topics` has its deck and front. only notes this
+    /// tool could have made are looked at: this importer's note type, in the deck or its
+    /// subdecks, with the tags the tag template gives them, so notes added by hand stay.
+    /// refuses to delete every such note, that's far more likely a changed front strategy
+    /// or note type than a sheet that lost all its rows
+    pub fn prune(&self, topics: &[Topic]) -> Result<PruneResult, Box<dyn Error>> {
+        let mut kept: HashSet<(String, String)> = HashSet::new();
+        for topic in topics {
+            for word in topic.words() {
+                if let Ok(note) = self.word_to_note(word, topic.name()).and_then(|note| self.order_fields(note)) {
+                    kept.insert((note.deck_name.clone(), front_of(&note).to_string()));
+                }
+            }
+        }
+
+        let imported: Vec<(String, NoteInfo)> = self.notes_with_decks(&self.deck_name)?
+            .into_iter()
+            .filter(|(_, info)| self.tags.matches(&info.tags, &self.deck_name))
+            .collect();
+
+        let mut stale_ids = Vec::new();
+        let mut stale = Vec::new();
+        for (deck, info) in &imported {
+            let front = info.ordered_fields().first().map_or(String::new(), |front| front.to_string());
+
+            if !kept.contains(&(deck.clone(), front.clone())) {
+                stale_ids.push(info.note_id);
+                stale.push(format!("{}: {}", deck, front));
+            }
+        }
+
+        if !imported.is_empty() && stale_ids.len() == imported.len() {
+            return Err(format!(
+                "Refusing to delete all {} notes of '{}', none of them match the file. \
+                 Are the note options (--front, --model, --front-field, --tags) the ones it was imported with?",
+                imported.len(), self.deck_name
+            ).into());
+        }
+
+        if !stale_ids.is_empty() {
+            self.client.delete_notes(&stale_ids)?;
+        }
+
+        Ok(PruneResult {
+            notes_deleted: stale,
+            decks_removed: self.client.remove_empty_decks(&self.deck_name)?,
+        })
+    }
 
     /// import all topics
+    ///
+    /// progress is tracked in an import queue so `csv-to-anki pause/resume/status` can control it
     pub fn import_all_topics(&self, topics: &[Topic]) -> Result<Vec<ImportResult>, Box<dyn Error>> {
+        let notes_total = topics.iter().map(|topic| topic.words().len()).sum();
+        let queue = ImportQueue::start(&self.deck_name, notes_total)?;
+
+        let results = self.import_topics_queued(topics, &queue);
+
+        queue.finish()?;
+
+        results
+    }
+
+    fn import_topics_queued(&self, topics: &[Topic], queue: &ImportQueue) -> Result<Vec<ImportResult>, Box<dyn Error>> {
         let mut results: Vec<ImportResult> = Vec::new();
 
         for topic in topics {
-            println!("\nImporting topic: {}", topic.name());
-            let result = self.import_topic(topic)?;
+            self.progress.emit(ProgressEvent::TopicStarted {
+                name: topic.name().clone(),
+                words: topic.words().len(),
+            });
+            let result = self.import_topic_queued(topic, Some(queue))?;
 
-            result.print_summary();
+            self.progress.emit(ProgressEvent::TopicFinished(result.clone()));
 
 
             results.push(result);
@@ -197,9 +761,16 @@ impl JapaneseVocabImporter {
     }
 }
 
+/// first field of a note in model order, what anki checks duplicates by
+fn front_of(note: &Note) -> &str {
+    note.fields.iter().next().map_or("", |(_, value)| value)
+}
+
+#[derive(Debug, Clone)]
 pub struct ImportResult {
     pub topic_name: String,
     pub added: usize,
+    pub updated: usize,
     pub duplicates: usize,
     pub errors: usize,
 }
@@ -209,6 +780,7 @@ impl ImportResult {
         ImportResult { 
             topic_name: topic_name.to_string(), 
             added: 0, 
+            updated: 0,
             duplicates: 0, 
             errors: 0 
         }
@@ -220,14 +792,557 @@ impl ImportResult {
     // }  
 
     pub fn total(&self) -> usize {
-        self.added + self.duplicates + self.errors
+        self.added + self.updated + self.duplicates + self.errors
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "\n{} Summary: \n  Added: {}\n  Updated: {}\n  Duplicates: {}\n  Errors: {}\n  Total: {}",
+            self.topic_name, self.added, self.updated, self.duplicates, self.errors, self.total()
+        )
+    }
+}
+
+pub struct MergeResult {
+    pub from_deck: String,
+    pub into_deck: String,
+    pub cards_moved: usize,
+    pub notes_retagged: usize,
+}
+
+impl MergeResult {
+    pub fn print_summary(&self) {
+        println!("\nMerge Summary: ");
+        println!("  Moved: {} cards", self.cards_moved);
+        println!("  Retagged: {} notes", self.notes_retagged);
+        println!("  Removed deck: '{}'", self.from_deck);
+        println!("  Destination: '{}'", self.into_deck);
+    }
+}
+
+pub struct PruneResult {
+    /// `deck: front` of every deleted note
+    pub notes_deleted: Vec<String>,
+    pub decks_removed: Vec<String>,
+}
+
+impl PruneResult {
+    pub fn print_summary(&self) {
+        println!("\nPrune Summary: ");
+        println!("  Deleted: {} notes", self.notes_deleted.len());
+        for note in &self.notes_deleted {
+            println!("    {}", note);
+        }
+        println!("  Removed: {} empty decks", self.decks_removed.len());
+        for deck in &self.decks_removed {
+            println!("    {}", deck);
+        }
     }
+}
+
+pub struct LeechReport {
+    pub topics: Vec<Topic>,
+    pub card_ids: Vec<i64>,
+    pub note_ids: Vec<i64>,
+}
 
+impl LeechReport {
     pub fn print_summary(&self) {
-        println!("\n{} Summary: ", self.topic_name);
-        println!("  Added: {}", self.added);
-        println!("  Duplicates: {}", self.duplicates);
-        println!("  Errors: {}", self.errors);
-        println!("  Total: {}", self.total());
+        println!("\nLeech Summary: ");
+        println!("  Leech cards: {}", self.card_ids.len());
+        for topic in &self.topics {
+            println!("  - {}: {} words", topic.name(), topic.words().len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anki::tag_search;
+    use serde_json::{json, Value};
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    /// minimal in-process AnkiConnect: remembers every added note (its id is its position + 1,
+    /// deleted ones become `null`) and answers canAddNotesWithErrorDetail/addNotes the way anki
+    /// does for deck-scoped duplicates, addNotes refuses the whole batch if any note is refused
+    fn spawn_fake_anki() -> String {
+        spawn_inspectable_fake_anki().0
+    }
+
+    /// `spawn_fake_anki`, with its state shared to look at what was sent
+    fn spawn_inspectable_fake_anki() -> (String, Arc<Mutex<FakeAnki>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let anki: Arc<Mutex<FakeAnki>> = Arc::default();
+        let inspected = Arc::clone(&anki);
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut content_length = 0;
+                let mut headers = Vec::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                        headers.push((name.to_lowercase(), value.trim().to_string()));
+                    }
+                }
+
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let request: Value = serde_json::from_slice(&body).unwrap();
+
+                let mut anki = anki.lock().unwrap();
+                anki.headers = headers;
+                let response = match handle(&request, &mut anki) {
+                    Ok(result) => json!({ "result": result, "error": null }),
+                    Err(error) => json!({ "result": null, "error": error }),
+                }.to_string();
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    response.len(), response
+                ).unwrap();
+            }
+        });
+
+        (url, inspected)
     }
-}
\ No newline at end of file
+
+    #[derive(Default)]
+    struct FakeAnki {
+        notes: Vec<Value>,
+        decks: Vec<String>,
+
+        /// headers of the last request, names lowercased
+        headers: Vec<(String, String)>,
+
+        /// how many cards each forgetCards/relearnCards/setDueDate/unsuspend request held
+        card_batches: Vec<usize>,
+    }
+
+    fn handle(request: &Value, anki: &mut FakeAnki) -> Result<Value, String> {
+        let params = &request["params"];
+        let key = |note: &Value| {
+            let front = note["fields"]["Front"].as_str().unwrap_or_default();
+            (note["deckName"].as_str().unwrap_or_default().to_string(), front.to_string())
+        };
+        let allowed = |note: &Value, notes: &[Value]| {
+            let same = |added: &Value| match note["options"]["duplicateScope"].as_str() {
+                Some("collection") => key(added).1 == key(note).1,
+                _ => key(added) == key(note),
+            };
+            note["options"]["allowDuplicate"].as_bool().unwrap_or(false) || !notes.iter().any(same)
+        };
+        // the deck or tag of the `"deck:X" ...`/`"tag:X"` queries the importer sends, with anki's
+        // wildcards: `None` for an unescaped `_` (any one character), `Some('*')`s stand for any run
+        let queried = |prefix: &str| {
+            let term = params["query"].as_str().unwrap().strip_prefix(prefix)?;
+            let mut pattern: Vec<Option<char>> = Vec::new();
+            let mut any = Vec::new();
+            let mut chars = term.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => pattern.push(chars.next()),
+                    '"' => break,
+                    '_' => pattern.push(None),
+                    '*' => { any.push(pattern.len()); },
+                    c => pattern.push(Some(c)),
+                }
+            }
+            assert!(any.is_empty(), "the fake doesn't do `*`");
+            Some(pattern)
+        };
+        // a name matches itself and everything nested under it
+        let in_hierarchy = |name: &str, pattern: &[Option<char>]| {
+            let name: Vec<char> = name.chars().collect();
+            let matches = |name: &[char]| name.len() == pattern.len()
+                && name.iter().zip(pattern).all(|(c, p)| p.is_none_or(|p| p == *c));
+            matches(&name) || (name.len() > pattern.len() + 2 && name[pattern.len()..].starts_with(&[':', ':']) && matches(&name[..pattern.len()]))
+        };
+        let found = |note: &Value| {
+            if note.is_null() {
+                return false;
+            }
+            if let Some(pattern) = queried("\"deck:") {
+                return in_hierarchy(note["deckName"].as_str().unwrap_or_default(), &pattern);
+            }
+            let pattern = queried("\"tag:").expect("the fake only searches decks and tags");
+            note["tags"].as_array().unwrap().iter().any(|tag| in_hierarchy(tag.as_str().unwrap(), &pattern))
+        };
+        let notes = &mut anki.notes;
+
+        Ok(match request["action"].as_str().unwrap() {
+            "multi" => params["actions"].as_array().unwrap().iter()
+                .map(|action| match handle(action, anki) {
+                    Ok(result) => json!({ "result": result, "error": null }),
+                    Err(error) => json!({ "result": null, "error": error }),
+                })
+                .collect(),
+            "createDeck" => {
+                let deck = params["deck"].as_str().unwrap().to_string();
+                if !anki.decks.contains(&deck) {
+                    anki.decks.push(deck);
+                }
+                json!(1)
+            },
+            "deckNames" => json!(anki.decks),
+            "deleteDecks" => {
+                // anki 2.1.28+ only deletes decks with their cards
+                if params["cardsToo"] != true {
+                    return Err("Since Anki 2.1.28 it's not possible to delete decks without deleting cards as well".to_string());
+                }
+                let deleted: Vec<&str> = params["decks"].as_array().unwrap().iter().map(|deck| deck.as_str().unwrap()).collect();
+                anki.decks.retain(|deck| !deleted.iter().any(|gone| deck == gone || deck.starts_with(&format!("{}::", gone))));
+                Value::Null
+            },
+            "modelFieldNames" => json!(["Front", "Back"]),
+            "canAddNotesWithErrorDetail" => {
+                params["notes"].as_array().unwrap().iter()
+                    .map(|note| match allowed(note, notes) {
+                        true => json!({ "canAdd": true }),
+                        false => json!({ "canAdd": false, "error": DUPLICATE }),
+                    })
+                    .collect()
+            },
+            "addNote" => {
+                if !allowed(&params["note"], notes) {
+                    return Err(DUPLICATE.to_string());
+                }
+                notes.push(params["note"].clone());
+                json!(notes.len())
+            },
+            "addNotes" => {
+                let batch = params["notes"].as_array().unwrap();
+                if batch.iter().any(|note| !allowed(note, notes)) {
+                    return Err(format!("['{}']", DUPLICATE));
+                }
+                batch.iter()
+                    .map(|note| {
+                        notes.push(note.clone());
+                        json!(notes.len())
+                    })
+                    .collect()
+            },
+            // a note's one card has its id, decks and tags match with their children like in anki
+            "findNotes" | "findCards" => (1..=notes.len()).filter(|&id| found(&notes[id - 1])).collect(),
+            "notesInfo" => {
+                params["notes"].as_array().unwrap().iter()
+                    .map(|id| {
+                        let note = &notes[id.as_u64().unwrap() as usize - 1];
+                        json!({
+                            "noteId": id,
+                            "tags": note["tags"],
+                            "cards": [id],
+                            "fields": {
+                                "Front": { "value": note["fields"]["Front"], "order": 0 },
+                                "Back": { "value": note["fields"]["Back"], "order": 1 },
+                            },
+                        })
+                    })
+                    .collect()
+            },
+            "cardsInfo" => {
+                params["cards"].as_array().unwrap().iter()
+                    .map(|id| json!({ "cardId": id, "note": id, "deckName": notes[id.as_u64().unwrap() as usize - 1]["deckName"] }))
+                    .collect()
+            },
+            "updateNoteFields" => {
+                let id = params["note"]["id"].as_u64().unwrap() as usize;
+                notes[id - 1]["fields"] = params["note"]["fields"].clone();
+                Value::Null
+            },
+            "changeDeck" => {
+                for id in params["cards"].as_array().unwrap() {
+                    notes[id.as_u64().unwrap() as usize - 1]["deckName"] = params["deck"].clone();
+                }
+                Value::Null
+            },
+            "addTags" | "removeTags" => {
+                let changed: Vec<&str> = params["tags"].as_str().unwrap().split_whitespace().collect();
+                for id in params["notes"].as_array().unwrap() {
+                    let note = &mut notes[id.as_u64().unwrap() as usize - 1];
+                    let mut tags: Vec<String> = serde_json::from_value(note["tags"].clone()).unwrap_or_default();
+                    tags.retain(|tag| !changed.contains(&tag.as_str()));
+                    if request["action"] == "addTags" {
+                        tags.extend(changed.iter().map(|tag| tag.to_string()));
+                    }
+                    note["tags"] = json!(tags);
+                }
+                Value::Null
+            },
+            "forgetCards" | "relearnCards" | "setDueDate" | "unsuspend" => {
+                anki.card_batches.push(params["cards"].as_array().unwrap().len());
+                Value::Null
+            },
+            "deleteNotes" => {
+                for id in params["notes"].as_array().unwrap() {
+                    notes[id.as_u64().unwrap() as usize - 1] = Value::Null;
+                }
+                Value::Null
+            },
+            _ => Value::Null,
+        })
+    }
+
+    const DUPLICATE: &str = "cannot create note because it is a duplicate";
+
+    fn topics() -> Vec<Topic> {
+        vec![
+            Topic {
+                name: "Food".to_string(),
+                words: vec![
+                    Word::new("りんご", "apple", ""),
+                    Word::new("さかな", "fish", "魚"),
+                    Word::new("りんご", "apple (again)", ""),
+                ],
+            },
+            Topic {
+                name: "Verbs".to_string(),
+                words: vec![Word::new("いく", "to go", "行く"), Word::new("りんご", "not food", "")],
+            },
+        ]
+    }
+
+    fn import(importer: &JapaneseVocabImporter) -> (usize, usize, usize) {
+        importer.initialise_with_topics(&topics()).unwrap();
+        let results = importer.import_all_topics(&topics()).unwrap();
+
+        (
+            results.iter().map(|r| r.added).sum(),
+            results.iter().map(|r| r.duplicates).sum(),
+            results.iter().map(|r| r.errors).sum(),
+        )
+    }
+
+    fn importer(deck: &str, url: String) -> JapaneseVocabImporter {
+        let deck = format!("{}-{}", deck, std::process::id());
+        JapaneseVocabImporter::new(deck).with_client(AnkiConnectClient::with_url(url))
+    }
+
+    #[test]
+    fn importing_twice_adds_nothing_new() {
+        let importer = importer("IdempotentImport", spawn_fake_anki());
+
+        // the repeated りんご in Food is a duplicate, the one in Verbs is a different deck
+        assert_eq!(import(&importer), (4, 1, 0));
+        assert_eq!(import(&importer), (0, 5, 0));
+    }
+
+    #[test]
+    fn allow_duplicates_adds_everything_again() {
+        let importer = importer("DuplicateImport", spawn_fake_anki()).with_allow_duplicates(true);
+
+        assert_eq!(import(&importer), (5, 0, 0));
+        assert_eq!(import(&importer), (5, 0, 0));
+    }
+
+    #[test]
+    fn collection_scope_skips_words_in_other_decks() {
+        let importer = importer("CollectionScope", spawn_fake_anki()).with_duplicate_scope(DuplicateScope::Collection);
+
+        // the りんご in Verbs is now a duplicate of the one in Food too
+        assert_eq!(import(&importer), (3, 2, 0));
+        assert_eq!(import(&importer), (0, 5, 0));
+    }
+
+    #[test]
+    fn requests_carry_the_user_agent_and_extra_headers() {
+        let (url, anki) = spawn_inspectable_fake_anki();
+        let header = |name: &str| anki.lock().unwrap().headers.iter()
+            .find(|(sent, _)| sent == name)
+            .map(|(_, value)| value.clone());
+
+        AnkiConnectClient::with_url(url.clone()).deck_names().unwrap();
+        assert_eq!(header("user-agent").as_deref(), Some(crate::anki::DEFAULT_USER_AGENT));
+
+        AnkiConnectClient::with_url(url)
+            .with_user_agent("deck-sync/2.0")
+            .with_header("X-Proxy-Token", "secret")
+            .with_header("X-Proxy-User", "me")
+            .deck_names()
+            .unwrap();
+        assert_eq!(header("user-agent").as_deref(), Some("deck-sync/2.0"));
+        assert_eq!(header("x-proxy-token").as_deref(), Some("secret"));
+        assert_eq!(header("x-proxy-user").as_deref(), Some("me"));
+    }
+
+    #[test]
+    fn a_refused_batch_still_reports_each_note() {
+        let importer = importer("RefusedBatch", spawn_fake_anki());
+        let note = |front: &str| importer.word_to_note(&Word::new(front, "meaning", ""), "Food").unwrap();
+
+        importer.client.add_notes(vec![note("りんご")]).unwrap();
+        let results = importer.client.add_notes(vec![note("りんご"), note("さかな")]).unwrap();
+
+        assert_eq!(results, vec![Err(DUPLICATE.to_string()), Ok(2)]);
+    }
+
+    #[test]
+    fn update_existing_rewrites_changed_notes() {
+        let importer = importer("UpdateExisting", spawn_fake_anki()).with_update_existing(true);
+        assert_eq!(import(&importer), (4, 1, 0));
+
+        let mut fixed = topics();
+        fixed[0].words[1] = Word::new("さかな", "fish (fixed)", "魚");
+        let results = importer.import_all_topics(&fixed).unwrap();
+
+        // the repeated りんご in Food stays a duplicate of the first one
+        let updated: usize = results.iter().map(|r| r.updated).sum();
+        let duplicates: usize = results.iter().map(|r| r.duplicates).sum();
+        assert_eq!((updated, duplicates), (1, 4));
+
+        let note = importer.client.notes_info(&[2]).unwrap().remove(0);
+        assert_eq!(note.ordered_fields(), vec!["魚", "さかな | fish (fixed)"]);
+    }
+
+    #[test]
+    fn prune_deletes_rows_gone_from_the_file() {
+        let importer = importer("Prune", spawn_fake_anki());
+        assert_eq!(import(&importer), (4, 1, 0));
+
+        // さかな is gone from Food, and Verbs is gone altogether
+        let mut trimmed = topics();
+        trimmed[0].words.remove(1);
+        trimmed.truncate(1);
+
+        let result = importer.prune(&trimmed).unwrap();
+        let food = importer.subdeck_name("Food");
+        let verbs = importer.subdeck_name("Verbs");
+
+        assert_eq!(result.notes_deleted, vec![
+            format!("{}: 魚", food),
+            format!("{}: 行く", verbs),
+            format!("{}: りんご", verbs),
+        ]);
+        assert_eq!(result.decks_removed, vec![verbs]);
+        assert_eq!(importer.client.deck_names().unwrap(), vec![importer.deck_name().to_string(), food]);
+
+        // a file that matches nothing in the deck is a mistake, not a reason to empty it
+        assert!(importer.prune(&[]).is_err());
+    }
+
+    #[test]
+    fn merge_moves_and_retags_then_deletes_the_source() {
+        let importer = importer("Merge", spawn_fake_anki());
+        assert_eq!(import(&importer), (4, 1, 0));
+
+        let result = importer.merge_topics("Food", "Verbs").unwrap();
+        assert_eq!((result.cards_moved, result.notes_retagged), (2, 2));
+
+        let verbs = importer.client.find_notes(&deck_search(&importer.subdeck_name("Verbs"))).unwrap();
+        let tags: Vec<Vec<String>> = importer.client.notes_info(&verbs).unwrap().into_iter().map(|note| note.tags).collect();
+        assert_eq!(verbs.len(), 4);
+        assert!(tags.iter().all(|tags| tags.contains(&"Verbs".to_string()) && !tags.contains(&"Food".to_string())));
+        assert!(!importer.client.deck_names().unwrap().contains(&importer.subdeck_name("Food")));
+    }
+
+    #[test]
+    fn merge_counts_only_the_notes_it_retagged() {
+        let importer = importer("MergeByHand", spawn_fake_anki());
+        assert_eq!(import(&importer), (4, 1, 0));
+
+        let mut by_hand = importer.word_to_note(&Word::new("みず", "water", "水"), "Food").unwrap();
+        by_hand.tags = vec!["mine".to_string()];
+        let by_hand = importer.client.add_notes(vec![by_hand]).unwrap().remove(0).unwrap();
+
+        let result = importer.merge_topics("Food", "Verbs").unwrap();
+        assert_eq!((result.cards_moved, result.notes_retagged), (3, 2));
+        assert_eq!(importer.client.notes_info(&[by_hand]).unwrap()[0].tags, vec!["mine".to_string()]);
+    }
+
+    #[test]
+    fn bulk_changes_are_sent_in_chunks() {
+        let deck = format!("ChunkedMerge-{}", std::process::id());
+        let client = AnkiConnectClient::with_url(spawn_fake_anki()).with_chunk_size(1);
+        let importer = JapaneseVocabImporter::new(deck).with_client(client);
+        assert_eq!(import(&importer), (4, 1, 0));
+
+        let result = importer.merge_topics("Food", "Verbs").unwrap();
+        assert_eq!((result.cards_moved, result.notes_retagged), (2, 2));
+
+        let verbs = importer.client.find_notes(&deck_search(&importer.subdeck_name("Verbs"))).unwrap();
+        assert_eq!(verbs.len(), 4);
+    }
+
+    #[test]
+    fn card_rescheduling_is_sent_in_chunks() {
+        let (url, anki) = spawn_inspectable_fake_anki();
+        let client = AnkiConnectClient::with_url(url).with_chunk_size(2);
+        let cards = [1, 2, 3, 4, 5];
+
+        client.forget_cards(&cards).unwrap();
+        client.relearn_cards(&cards).unwrap();
+        client.set_due_date(&cards, "0").unwrap();
+        client.unsuspend(&cards).unwrap();
+
+        assert_eq!(anki.lock().unwrap().card_batches, [2, 2, 1].repeat(4));
+    }
+
+    #[test]
+    fn merge_takes_underscores_literally() {
+        let importer = importer("MergeWildcard", spawn_fake_anki());
+        let topics = vec![
+            Topic { name: "JLPT_N5".to_string(), words: vec![Word::new("みず", "water", "水")] },
+            Topic { name: "JLPTxN5".to_string(), words: vec![Word::new("ひ", "fire", "火")] },
+            Topic { name: "Nouns".to_string(), words: vec![Word::new("き", "tree", "木")] },
+        ];
+        importer.initialise_with_topics(&topics).unwrap();
+        importer.import_all_topics(&topics).unwrap();
+
+        let result = importer.merge_topics("JLPT_N5", "Nouns").unwrap();
+        assert_eq!(result.cards_moved, 1);
+
+        let lookalike = importer.client.find_notes(&deck_search(&importer.subdeck_name("JLPTxN5"))).unwrap();
+        assert_eq!(lookalike.len(), 1);
+        assert!(importer.client.deck_names().unwrap().contains(&importer.subdeck_name("JLPTxN5")));
+    }
+
+    #[test]
+    fn rename_tag_takes_underscores_literally() {
+        let importer = importer("RenameWildcard", spawn_fake_anki());
+        let topics = vec![
+            Topic { name: "JLPT_N5".to_string(), words: vec![Word::new("みず", "water", "水")] },
+            Topic { name: "JLPTxN5".to_string(), words: vec![Word::new("ひ", "fire", "火")] },
+        ];
+        importer.initialise_with_topics(&topics).unwrap();
+        importer.import_all_topics(&topics).unwrap();
+
+        assert_eq!(importer.client.find_notes(&tag_search("JLPT_N5")).unwrap(), vec![1]);
+        assert_eq!(importer.client.rename_tag("JLPT_N5", "N5").unwrap(), 1);
+
+        let tags: Vec<Vec<String>> = importer.client.notes_info(&[1, 2]).unwrap().into_iter().map(|note| note.tags).collect();
+        assert!(tags[0].contains(&"N5".to_string()) && !tags[0].contains(&"JLPT_N5".to_string()));
+        assert!(tags[1].contains(&"JLPTxN5".to_string()) && !tags[1].contains(&"N5".to_string()));
+    }
+
+    #[test]
+    fn prune_leaves_notes_added_by_hand() {
+        let importer = importer("PruneByHand", spawn_fake_anki());
+        assert_eq!(import(&importer), (4, 1, 0));
+
+        let mut by_hand = importer.word_to_note(&Word::new("みず", "water", "水"), "Food").unwrap();
+        by_hand.tags = vec!["mine".to_string()];
+        importer.client.add_notes(vec![by_hand]).unwrap();
+
+        let mut trimmed = topics();
+        trimmed[0].words.remove(1);
+
+        let result = importer.prune(&trimmed).unwrap();
+        assert_eq!(result.notes_deleted, vec![format!("{}: 魚", importer.subdeck_name("Food"))]);
+        assert!(result.decks_removed.is_empty());
+    }
+}
@@ -0,0 +1,28 @@
+use wasm_bindgen::prelude::*;
+
+use crate::preview::{preview, PreviewOptions};
+
+// ============================================================================================
+//                                      Browser Bindings
+// ============================================================================================
+
+// Built with `wasm-pack build --target web --features wasm`, used by web/index.html.
+// Results cross into javascript as JSON so the page doesn't need generated types.
+
+/// Cards a spreadsheet becomes, as a JSON array of `{ name, cards: [{ deck, front, back, tags }], errors }`
+///
+/// `front` and `tags` take the same values as `--front` and `--tags`, empty for the defaults
+#[wasm_bindgen(js_name = previewDeck)]
+pub fn preview_deck(contents: &str, deck_name: &str, front: &str, tags: &str) -> Result<String, JsError> {
+    let options = PreviewOptions {
+        deck_name: deck_name.to_string(),
+        front_strategy: if front.is_empty() { Default::default() } else { front.parse().map_err(|e: String| JsError::new(&e))? },
+        tags: if tags.is_empty() { Default::default() } else { tags.parse().map_err(|e: String| JsError::new(&e))? },
+        flat_deck: false,
+        html: Default::default(),
+    };
+
+    let topics = preview(contents, &options).map_err(|e| JsError::new(&e.to_string()))?;
+
+    serde_json::to_string(&topics).map_err(|e| JsError::new(&e.to_string()))
+}